@@ -1,72 +1,135 @@
 //! CLI orchestrator for the `ballistic` subcommand.
 //!
 //! Walks `Data/*.txt` files, parses each, runs the trajectory simulation
-//! for every projectile, and writes `Ballistic/{vehicle}/{shell}.txt`.
+//! for every projectile, and writes `Ballistic/{vehicle}/{shell}.txt`
+//! (or `Ballistic/{vehicle}.txt` with `--per-vehicle-file`).  With
+//! `--format elevation` the files hold the launch angle of each row instead,
+//! in `--angle-unit` ([`write_elevation_table`]).
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use fcsgen_core::ballistic::{compute_ballistic, should_skip};
+use clap::ValueEnum;
+use fcsgen_core::ballistic::{
+	BallisticConfig,
+	BallisticWarning,
+	RangeSampling,
+	compute_ballistic_both,
+	compute_ballistic_with_warnings,
+	compute_elevation_table,
+	is_zero_penetration,
+	table_header_with_marks,
+	write_elevation_table,
+};
 use fcsgen_core::parser::data::parse_data_file;
 
+use crate::log_file::{LogFile, logln};
+use crate::output::{
+	ShellWriteOptions,
+	WriteOutcome,
+	create_output_dir,
+	exit_if_exists,
+	plan_shell_files,
+	vehicle_section_header,
+	write_output,
+};
+
+/// Range sampling of the written tables (`--sampling`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Sampling {
+	/// One row per scroll step of the sight, as the sight generator expects
+	#[default]
+	Scroll,
+	/// One row every `--step` metres
+	Fixed,
+	/// Both from one simulation: scroll-step tables in the output directory,
+	/// fixed-step tables in `--fixed-output`
+	Both,
+}
+
+/// Shape of the `ballistic` output (`--format`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+	/// One table file per shell, as the sight generator reads them
+	#[default]
+	Tables,
+	/// Every row of every shell in one CSV at `--output-file`, for analysis
+	BulkCsv,
+	/// One `distance\tangle` launch-angle table per shell under `--output`,
+	/// in `--angle-unit`
+	Elevation,
+}
+
+/// Fixed-step tables written next to the scroll-step ones
+/// (`--sampling both`).
+#[derive(Debug, Clone, Copy)]
+pub struct FixedStepOutput<'a> {
+	/// Row spacing in metres.
+	pub step: f64,
+	/// Directory to write `{vehicle}/{shell}.txt` into, e.g. `Penetration/`.
+	pub dir: &'a Path,
+}
+
 /// Run the ballistic computation pipeline.
 ///
 /// # Arguments
 /// * `input`       – Directory containing `Data/*.txt` files (Stage 1 output).
 /// * `output`      – Directory to write `Ballistic/{vehicle}/{shell}.txt` into.
-/// * `sensitivity` – Mouse sensitivity value (0 < s ≤ 1, typically 0.50).
+/// * `ballistic_cfg` – Sensitivity and sampling parameters.
 /// * `filter`      – Optional list of vehicle IDs to process.
+/// * `opts`        – Overwrite, header, collision and zero-penetration handling.
+/// * `fixed`       – With `--sampling both`, where the fixed-step tables go;
+///   not combined with `--per-vehicle-file`.
+#[allow(clippy::too_many_lines)]
 pub fn run_ballistic(
 	input: &Path,
 	output: &Path,
-	sensitivity: f64,
+	ballistic_cfg: &BallisticConfig,
 	filter: Option<&[String]>,
+	opts: ShellWriteOptions<'_>,
+	fixed: Option<FixedStepOutput<'_>>,
 ) {
 	if !input.exists() {
 		eprintln!("Error: input directory not found at {input:?}");
 		std::process::exit(1);
 	}
 
-	if let Err(e) = std::fs::create_dir_all(output) {
-		eprintln!("Error: cannot create output directory: {e}");
-		std::process::exit(1);
+	for dir in std::iter::once(output).chain(fixed.map(|f| f.dir)) {
+		if let Err(e) = create_output_dir(dir) {
+			eprintln!("Error: cannot create output directory {}: {e}", dir.display());
+			std::process::exit(1);
+		}
 	}
+	let fixed_cfg = fixed.map(|f| BallisticConfig {
+		sampling: RangeSampling::FixedStep(f.step),
+		..*ballistic_cfg
+	});
 
-	// Collect *.txt files from input directory
-	let mut files: Vec<_> = std::fs::read_dir(input)
-		.expect("read input directory")
-		.filter_map(|e| e.ok())
-		.filter(|e| e.path().extension().is_some_and(|ext| ext == "txt"))
-		.filter(|e| {
-			if let Some(filter) = filter {
-				let stem = e.path().file_stem().unwrap().to_string_lossy().to_string();
-				filter.iter().any(|f| f == &stem)
-			} else {
-				true
-			}
-		})
-		.collect();
-
-	files.sort_by_key(std::fs::DirEntry::file_name);
-
+	let files = data_files(input, filter);
 	let total = files.len();
 	let mut processed = 0;
 	let mut shells_written = 0;
+	let mut existing_skipped = 0;
+	let mut skipped_zero_pen = 0;
+	let mut unreachable_marks = 0;
+	let mut fixed_written = 0;
 	let mut failed = 0;
 
-	eprintln!("Computing ballistic tables for {total} vehicles (sensitivity={sensitivity})");
+	eprintln!(
+		"Computing ballistic tables for {total} vehicles (sensitivity={})",
+		ballistic_cfg.sensitivity,
+	);
 	eprintln!("Input:  {input:?}");
 	eprintln!("Output: {output:?}");
 	eprintln!();
 
-	for entry in &files {
-		let path = entry.path();
+	for path in &files {
 		let vehicle_id = path
 			.file_stem()
 			.unwrap()
 			.to_str()
 			.unwrap_or("unknown");
 
-		let data = match parse_data_file(&path) {
+		let data = match parse_data_file(path) {
 			Ok(d) => d,
 			Err(e) => {
 				eprintln!("PARSE ERROR {vehicle_id}: {e}");
@@ -77,20 +140,59 @@ pub fn run_ballistic(
 
 		let vehicle_dir = output.join(vehicle_id);
 		let mut any_written = false;
+		let mut combined = String::new();
+		let mut sections = 0;
 
-		for proj in &data.projectiles {
-			if should_skip(&proj.normalized_type) {
-				continue;
-			}
+		let plan = plan_shell_files(
+			vehicle_id,
+			&data.projectiles,
+			ballistic_cfg,
+			opts.on_collision,
+			&LogFile::default(),
+		)
+		.unwrap_or_else(|conflict| conflict.exit(&LogFile::default()));
 
-			if let Some(content) = compute_ballistic(proj, sensitivity) {
+		for (proj, file_stem) in plan {
+			let mut warnings = Vec::new();
+			let tables = match (opts.elevation, fixed) {
+				(Some(unit), _) => compute_elevation_table(proj, ballistic_cfg)
+					.map(|rows| (write_elevation_table(&rows, unit), None)),
+				(None, Some(f)) => {
+					compute_ballistic_both(proj, ballistic_cfg, f.step, &mut warnings)
+						.map(|(scroll, fixed)| (scroll, Some(fixed)))
+				},
+				(None, None) => compute_ballistic_with_warnings(proj, ballistic_cfg, &mut warnings)
+					.map(|t| (t, None)),
+			};
+			print_warnings(&proj.output_name, &warnings, &LogFile::default());
+			if let Some((content, fixed_content)) = tables {
 				if content.is_empty() {
 					continue;
 				}
+				if opts.skip_zero_pen && is_zero_penetration(&content) {
+					skipped_zero_pen += 1;
+					continue;
+				}
+
+				let content = if opts.with_header {
+					let (header, unreachable) =
+						table_header_with_marks(proj, ballistic_cfg, opts.range_marks);
+					unreachable_marks += unreachable;
+					header + &content
+				} else {
+					content
+				};
+
+				if opts.per_vehicle_file {
+					combined.push_str(&vehicle_section_header(&file_stem, proj));
+					combined.push_str(&content);
+					sections += 1;
+					continue;
+				}
 
 				// Ensure vehicle subdirectory exists
 				if !any_written {
-					if let Err(e) = std::fs::create_dir_all(&vehicle_dir) {
+					if let Err(e) = create_output_dir(&vehicle_dir) {
 						eprintln!("DIR ERROR {vehicle_id}: {e}");
 						failed += 1;
 						break;
@@ -98,18 +200,69 @@ pub fn run_ballistic(
 					any_written = true;
 				}
 
-				let filename = format!("{}.txt", proj.output_name);
+				let filename = format!("{file_stem}.txt");
 				let file_path = vehicle_dir.join(&filename);
 
-				if let Err(e) = std::fs::write(&file_path, &content) {
-					eprintln!("WRITE ERROR {vehicle_id}/{filename}: {e}");
-					failed += 1;
+				match write_output(&file_path, &content, opts.overwrite_policy, opts.line_ending) {
+					Ok(WriteOutcome::Written) => shells_written += 1,
+					Ok(WriteOutcome::Skipped) => existing_skipped += 1,
+					Err(e) => {
+						exit_if_exists(&e);
+						eprintln!("WRITE ERROR {vehicle_id}/{filename}: {e}");
+						failed += 1;
+					},
+				}
+
+				let (Some(f), Some(fixed_cfg), Some(table)) = (fixed, &fixed_cfg, fixed_content)
+				else {
+					continue;
+				};
+				let table = if opts.with_header {
+					// Counted once per shell, from the scroll-step header
+					table_header_with_marks(proj, fixed_cfg, opts.range_marks).0 + &table
 				} else {
-					shells_written += 1;
+					table
+				};
+				let fixed_dir = f.dir.join(vehicle_id);
+				let written = create_output_dir(&fixed_dir).and_then(|()| {
+					write_output(
+						&fixed_dir.join(&filename),
+						&table,
+						opts.overwrite_policy,
+						opts.line_ending,
+					)
+				});
+				match written {
+					Ok(WriteOutcome::Written) => fixed_written += 1,
+					Ok(WriteOutcome::Skipped) => existing_skipped += 1,
+					Err(e) => {
+						exit_if_exists(&e);
+						eprintln!("WRITE ERROR {}: {e}", fixed_dir.join(&filename).display());
+						failed += 1;
+					},
 				}
 			}
 		}
 
+		if sections > 0 {
+			any_written = true;
+			let filename = format!("{vehicle_id}.txt");
+			match write_output(
+				&output.join(&filename),
+				&combined,
+				opts.overwrite_policy,
+				opts.line_ending,
+			) {
+				Ok(WriteOutcome::Written) => shells_written += sections,
+				Ok(WriteOutcome::Skipped) => existing_skipped += 1,
+				Err(e) => {
+					exit_if_exists(&e);
+					eprintln!("WRITE ERROR {filename}: {e}");
+					failed += 1;
+				},
+			}
+		}
+
 		if any_written {
 			processed += 1;
 		}
@@ -119,4 +272,200 @@ pub fn run_ballistic(
 	eprintln!(
 		"Done: {processed} vehicles, {shells_written} shell tables written, {failed} errors"
 	);
+	if existing_skipped > 0 {
+		eprintln!("      {existing_skipped} existing files left untouched (--overwrite-policy skip)");
+	}
+	if skipped_zero_pen > 0 {
+		eprintln!("      {skipped_zero_pen} zero-penetration tables skipped (--skip-zero-pen)");
+	}
+	if unreachable_marks > 0 {
+		eprintln!("      {unreachable_marks} range marks beyond reach omitted (--range-marks)");
+	}
+	if let Some(f) = fixed {
+		eprintln!(
+			"      {fixed_written} fixed-step tables written to {} (--sampling both)",
+			f.dir.display(),
+		);
+	}
+}
+
+/// Print the warnings of computing the table of `shell` to `log`.
+pub fn print_warnings(shell: &str, warnings: &[BallisticWarning], log: &LogFile) {
+	for warning in warnings {
+		logln!(log, "warning: {shell}: {warning}");
+	}
+}
+
+/// The `*.txt` files of `input`, sorted, restricted to the vehicles of
+/// `filter` if given.
+pub fn data_files(input: &Path, filter: Option<&[String]>) -> Vec<PathBuf> {
+	let mut files: Vec<PathBuf> = std::fs::read_dir(input)
+		.expect("read input directory")
+		.filter_map(|e| e.ok())
+		.map(|e| e.path())
+		.filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+		.filter(|path| {
+			if let Some(filter) = filter {
+				let stem = path.file_stem().unwrap().to_string_lossy().to_string();
+				filter.iter().any(|f| f == &stem)
+			} else {
+				true
+			}
+		})
+		.collect();
+
+	files.sort();
+	files
+}
+
+#[cfg(test)]
+mod tests {
+	use fcsgen_core::ballistic::AngleUnit;
+
+	use crate::test_util::TempDir;
+	use super::*;
+	use crate::output::{CollisionPolicy, OverwritePolicy};
+
+	/// Two physically different shells that both clean to `pzgr_39`.
+	const COLLIDING_DATA: &str = "\
+WeaponPath:gameData/Weapons/test.blkx
+
+Name:75mm_pzgr_39
+Type:apcbc_tank
+BulletMass:6.8
+BallisticCaliber:0.075
+Speed:740.0
+Cx:0.4
+
+Name:88mm_pzgr_39
+Type:apcbc_tank
+BulletMass:10.2
+BallisticCaliber:0.088
+Speed:773.0
+Cx:0.4
+";
+
+	/// Run the `ballistic` subcommand on a single colliding vehicle and return
+	/// its output directory.
+	fn run_colliding(name: &str, on_collision: CollisionPolicy) -> TempDir {
+		let dir = TempDir::new(&format!("collision-{name}"));
+		let input = dir.join("Data");
+		std::fs::create_dir_all(&input).unwrap();
+		std::fs::write(input.join("test_tank.txt"), COLLIDING_DATA).unwrap();
+
+		let output = dir.join("Ballistic");
+		run_ballistic(
+			&input,
+			&output,
+			&BallisticConfig::default(),
+			None,
+			ShellWriteOptions {
+				overwrite_policy: OverwritePolicy::Overwrite,
+				on_collision,
+				..ShellWriteOptions::default()
+			},
+			None,
+		);
+		dir
+	}
+
+	#[test]
+	fn test_collision_suffix_writes_both_shells() {
+		let dir = run_colliding("suffix", CollisionPolicy::Suffix);
+		let vehicle = dir.join("Ballistic").join("test_tank");
+
+		let first = std::fs::read_to_string(vehicle.join("pzgr_39_2.txt")).unwrap();
+		let last = std::fs::read_to_string(vehicle.join("pzgr_39.txt")).unwrap();
+		assert_ne!(first, last, "distinct physics give distinct tables");
+	}
+
+	#[test]
+	fn test_collision_last_keeps_one_shell() {
+		let dir = run_colliding("last", CollisionPolicy::Last);
+		let vehicle = dir.join("Ballistic").join("test_tank");
+
+		assert!(vehicle.join("pzgr_39.txt").exists());
+		assert!(!vehicle.join("pzgr_39_2.txt").exists());
+	}
+
+	#[test]
+	fn test_sampling_both_writes_scroll_and_fixed_tables() {
+		const DIST_TOL: f64 = 0.01;
+
+		let dir = TempDir::new("sampling-both");
+		let input = dir.join("Data");
+		std::fs::create_dir_all(&input).unwrap();
+		std::fs::write(input.join("test_tank.txt"), COLLIDING_DATA).unwrap();
+
+		let output = dir.join("Ballistic");
+		let fixed_dir = dir.join("Penetration");
+		run_ballistic(
+			&input,
+			&output,
+			&BallisticConfig::default(),
+			None,
+			ShellWriteOptions::default(),
+			Some(FixedStepOutput {
+				step: 100.0,
+				dir: &fixed_dir,
+			}),
+		);
+
+		let read = |root: &Path| {
+			let text = std::fs::read_to_string(root.join("test_tank").join("pzgr_39.txt")).unwrap();
+			fcsgen_core::table::parse_table(&text)
+		};
+		let (scroll, fixed) = (read(&output), read(&fixed_dir));
+		assert!(scroll.len() > fixed.len(), "{} vs {}", scroll.len(), fixed.len());
+		for (i, row) in (0_u32..).zip(&fixed) {
+			let target = 100.0 * f64::from(i);
+			assert!((row.distance - target).abs() < DIST_TOL, "{} vs {target}", row.distance);
+		}
+
+		// Where a scroll-step row lands on the grid, both tables agree
+		let overlapping: Vec<_> = scroll
+			.iter()
+			.filter_map(|s| {
+				let f = fixed.iter().find(|f| (f.distance - s.distance).abs() < DIST_TOL)?;
+				Some((s, f))
+			})
+			.collect();
+		assert!(!overlapping.is_empty(), "the 0 m rows at least");
+		for (s, f) in overlapping {
+			assert!((s.time - f.time).abs() < f64::EPSILON, "{} m", s.distance);
+			assert!((s.penetration - f.penetration).abs() < f64::EPSILON, "{} m", s.distance);
+		}
+	}
+
+	#[test]
+	fn test_elevation_format_writes_angles_in_unit() {
+		let dir = TempDir::new("elevation");
+		let input = dir.join("Data");
+		std::fs::create_dir_all(&input).unwrap();
+		std::fs::write(input.join("test_tank.txt"), COLLIDING_DATA).unwrap();
+
+		let angles = |unit: AngleUnit| -> Vec<(f64, f64)> {
+			let output = dir.join(unit.to_string());
+			let opts = ShellWriteOptions {
+				elevation: Some(unit),
+				..ShellWriteOptions::default()
+			};
+			run_ballistic(&input, &output, &BallisticConfig::default(), None, opts, None);
+			let table = output.join("test_tank").join("pzgr_39.txt");
+			let text = std::fs::read_to_string(table).unwrap();
+			let col = |line: &str, i| line.split('\t').nth(i).unwrap().parse::<f64>().unwrap();
+			text.lines().map(|line| (col(line, 0), col(line, 1))).collect()
+		};
+		let (mils, deg) = (angles(AngleUnit::Mils), angles(AngleUnit::Degrees));
+
+		assert_eq!(mils.len(), deg.len());
+		assert!(mils.len() > 10);
+		// Row 10 is 10 scroll steps (2.8 · 0.5² mrad each) up, in NATO mils
+		let nato_mils = 7.0e-3 * 6400.0 / (2.0 * std::f64::consts::PI);
+		assert!((mils[10].1 - nato_mils).abs() < 1e-3, "{:?}", mils[10]);
+		for (m, d) in mils.iter().zip(&deg).skip(1) {
+			assert!((m.0 - d.0).abs() < f64::EPSILON);
+			assert!((m.1 / (6400.0 / 360.0) - d.1).abs() < 1e-3, "{m:?} vs {d:?}");
+		}
+	}
 }
@@ -0,0 +1,259 @@
+//! Bulk CSV export of every ballistic row (`ballistic --format bulk-csv`).
+//!
+//! One `vehicle,shell,type,caliber_mm,distance,time,penetration` line per
+//! table row of every shell, in a single file, for analysis across the whole
+//! game rather than for sights.  Vehicles are computed in parallel; a writer
+//! thread receives each vehicle's rows over a channel and appends them in
+//! vehicle order, so only the vehicles still waiting for an earlier one are
+//! held in memory, never the whole export.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use fcsgen_core::ballistic::{BallisticConfig, compute_ballistic_with_warnings, is_zero_penetration};
+use fcsgen_core::parser::data::{caliber_mm, parse_data_file};
+use rayon::prelude::*;
+
+use crate::ballistic::{data_files, print_warnings};
+use crate::log_file::LogFile;
+use crate::output::{CollisionPolicy, OverwritePolicy, exit_if_exists, plan_shell_files};
+
+/// First line of the bulk CSV.
+const BULK_CSV_HEADER: &str = "vehicle,shell,type,caliber_mm,distance,time,penetration\n";
+
+/// Vehicles computed ahead of the writer before the workers block.
+const CHANNEL_BOUND: usize = 64;
+
+/// CSV rows of one vehicle, or its parse error.
+type VehicleRows = Result<ShellRows, String>;
+
+/// Rows of the shells of one vehicle.
+#[derive(Debug, Default)]
+struct ShellRows {
+	csv: String,
+	shells: usize,
+	rows: usize,
+}
+
+/// Totals of a bulk export.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct BulkStats {
+	/// Vehicles with at least one row.
+	vehicles: usize,
+	shells: usize,
+	rows: usize,
+	failed: usize,
+}
+
+/// Run `ballistic --format bulk-csv`: write every row of every shell of the
+/// `Data/*.txt` files in `input` to `output_file`.
+///
+/// # Arguments
+/// * `input`         – Directory containing `Data/*.txt` files (Stage 1 output).
+/// * `output_file`   – CSV file to write.
+/// * `ballistic_cfg` – Sensitivity and sampling parameters.
+/// * `filter`        – Optional list of vehicle IDs to process.
+/// * `skip_zero_pen` – Leave out shells that are zero on every row.
+/// * `overwrite_policy` – How to handle an existing `output_file`.
+pub fn run_bulk_csv(
+	input: &Path,
+	output_file: &Path,
+	ballistic_cfg: &BallisticConfig,
+	filter: Option<&[String]>,
+	skip_zero_pen: bool,
+	overwrite_policy: OverwritePolicy,
+) {
+	if !input.exists() {
+		eprintln!("Error: input directory not found at {}", input.display());
+		std::process::exit(1);
+	}
+	if overwrite_policy != OverwritePolicy::Overwrite && output_file.exists() {
+		if overwrite_policy == OverwritePolicy::Skip {
+			eprintln!(
+				"{} exists, left untouched (--overwrite-policy skip)",
+				output_file.display(),
+			);
+			return;
+		}
+		exit_if_exists(&io::Error::new(
+			io::ErrorKind::AlreadyExists,
+			format!("{} already exists", output_file.display()),
+		));
+	}
+
+	let files = data_files(input, filter);
+	eprintln!(
+		"Exporting ballistic rows of {} vehicles (sensitivity={})",
+		files.len(),
+		ballistic_cfg.sensitivity,
+	);
+
+	let written = std::fs::File::create(output_file)
+		.and_then(|file| write_bulk_csv(&files, file, ballistic_cfg, skip_zero_pen));
+	let stats = written.unwrap_or_else(|e| {
+		eprintln!("Error: cannot write {}: {e}", output_file.display());
+		std::process::exit(1);
+	});
+
+	eprintln!(
+		"Done: {} vehicles, {} shells, {} rows, {} errors",
+		stats.vehicles, stats.shells, stats.rows, stats.failed,
+	);
+	eprintln!("Output: {}", output_file.display());
+}
+
+/// Compute the shells of `files` in parallel and stream their rows to `out`
+/// through a writer thread, in file order.
+fn write_bulk_csv(
+	files: &[PathBuf],
+	out: impl Write + Send,
+	ballistic_cfg: &BallisticConfig,
+	skip_zero_pen: bool,
+) -> io::Result<BulkStats> {
+	let (tx, rx) = mpsc::sync_channel::<(usize, VehicleRows)>(CHANNEL_BOUND);
+	std::thread::scope(|scope| {
+		let writer = scope.spawn(move || write_in_order(&rx, out));
+		files.par_iter().enumerate().for_each_with(tx, |tx, (i, path)| {
+			// A failed writer drops the receiver; its error is returned below
+			let _ = tx.send((i, vehicle_rows(path, ballistic_cfg, skip_zero_pen)));
+		});
+		writer.join().expect("bulk CSV writer panicked")
+	})
+}
+
+/// Writer thread: append vehicles to `out` as soon as all earlier ones are
+/// written, until every sender is gone.
+fn write_in_order(
+	rx: &mpsc::Receiver<(usize, VehicleRows)>,
+	out: impl Write,
+) -> io::Result<BulkStats> {
+	let mut out = BufWriter::new(out);
+	out.write_all(BULK_CSV_HEADER.as_bytes())?;
+
+	let mut stats = BulkStats::default();
+	let mut pending = BTreeMap::new();
+	let mut next = 0;
+	for (i, rows) in rx {
+		pending.insert(i, rows);
+		while let Some(rows) = pending.remove(&next) {
+			next += 1;
+			match rows {
+				Ok(rows) => {
+					out.write_all(rows.csv.as_bytes())?;
+					stats.vehicles += usize::from(rows.shells > 0);
+					stats.shells += rows.shells;
+					stats.rows += rows.rows;
+				},
+				Err(e) => {
+					eprintln!("{e}");
+					stats.failed += 1;
+				},
+			}
+		}
+	}
+	out.flush()?;
+	Ok(stats)
+}
+
+/// CSV rows of every computed shell of one `Data/*.txt` file, named as their
+/// ballistic files would be.  Without a penetration column
+/// (`--no-penetration`) the last field is empty.
+fn vehicle_rows(path: &Path, ballistic_cfg: &BallisticConfig, skip_zero_pen: bool) -> VehicleRows {
+	let vehicle_id = path.file_stem().and_then(|s| s.to_str()).unwrap_or("unknown");
+	let data = parse_data_file(path).map_err(|e| format!("PARSE ERROR {vehicle_id}: {e}"))?;
+
+	let mut rows = ShellRows::default();
+	// Suffixed collisions, so that every shell keeps its rows
+	let plan = plan_shell_files(
+		vehicle_id,
+		&data.projectiles,
+		ballistic_cfg,
+		CollisionPolicy::Suffix,
+		&LogFile::default(),
+	)
+	.expect("only the error policy fails");
+	for (proj, shell) in plan {
+		let mut warnings = Vec::new();
+		let table = compute_ballistic_with_warnings(proj, ballistic_cfg, &mut warnings);
+		print_warnings(&proj.output_name, &warnings, &LogFile::default());
+		let Some(table) = table else {
+			continue;
+		};
+		if table.is_empty() || (skip_zero_pen && is_zero_penetration(&table)) {
+			continue;
+		}
+		// Datamine calibers are in metres; 0.00762 m would print 7.619999...
+		let caliber = (caliber_mm(proj) * 100.0).round() / 100.0;
+		for line in table.lines() {
+			let mut cols = line.split('\t');
+			let mut col = || cols.next().unwrap_or("");
+			let (distance, time, pen) = (col(), col(), col());
+			writeln!(
+				rows.csv,
+				"{vehicle_id},{shell},{},{caliber},{distance},{time},{pen}",
+				proj.normalized_type,
+			)
+			.unwrap();
+			rows.rows += 1;
+		}
+		rows.shells += 1;
+	}
+	Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+	use fcsgen_core::ballistic::compute_ballistic_with_config;
+
+	use crate::test_util::TempDir;
+	use super::*;
+
+	#[test]
+	fn test_bulk_csv_rows_of_two_shell_vehicle() {
+		let dir = TempDir::new("bulk-csv");
+		std::fs::write(
+			dir.join("test_tank.txt"),
+			"WeaponPath:gameData/Weapons/test.blkx\n\n\
+			 Name:75mm_pzgr_39\nType:apcbc_tank\nBulletMass:6.8\nBallisticCaliber:0.075\n\
+			 Speed:740.0\nCx:0.4\n\n\
+			 Name:75mm_sprgr_34\nType:he_frag_tank\nBulletMass:5.74\nBallisticCaliber:0.075\n\
+			 Speed:550.0\nCx:0.4\nExplosiveMass:0.686\n",
+		)
+		.unwrap();
+
+		let cfg = BallisticConfig::new(1.0);
+		let files = data_files(&dir, Some(&["test_tank".to_owned()]));
+		let mut csv = Vec::new();
+		let stats = write_bulk_csv(&files, &mut csv, &cfg, false).unwrap();
+		let csv = String::from_utf8(csv).unwrap();
+
+		let data = parse_data_file(&dir.join("test_tank.txt")).unwrap();
+		let table_rows: Vec<usize> = data
+			.projectiles
+			.iter()
+			.map(|proj| compute_ballistic_with_config(proj, &cfg).unwrap().lines().count())
+			.collect();
+		let mut lines = csv.lines();
+		assert_eq!(lines.next(), Some(BULK_CSV_HEADER.trim_end()));
+		let rows: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+		assert_eq!(rows.len(), table_rows.iter().sum::<usize>());
+		assert_eq!(
+			stats,
+			BulkStats {
+				vehicles: 1,
+				shells: 2,
+				rows: rows.len(),
+				failed: 0,
+			}
+		);
+
+		let (pzgr, sprgr) = rows.split_at(table_rows[0]);
+		assert!(pzgr.iter().all(|row| row[..4] == ["test_tank", "pzgr_39", "apcbc", "75"]));
+		assert!(sprgr.iter().all(|row| row[..4] == ["test_tank", "sprgr_34", "he", "75"]));
+		assert!(rows.iter().all(|row| row.len() == 7));
+		assert_eq!(pzgr[0][4..], ["0.000", "0", "124"]);
+	}
+}
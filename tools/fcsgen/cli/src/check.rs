@@ -0,0 +1,188 @@
+//! CLI orchestrator for the `check` subcommand.
+//!
+//! Verifies that `--game-path` points at a War Thunder installation before a
+//! long `run`: `aces.vromfs.bin` and `lang.vromfs.bin` exist and open as
+//! archives, and the game version can be read.  Tankmodels are counted from
+//! the archive's file table without decoding any BLK, as
+//! [`extract::list_vehicle_names`] does.
+
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use wt_blk::vromf::{File as VromfFile, VromfUnpacker};
+
+use crate::extract;
+
+/// Archives `run` cannot do without.  `char.vromfs.bin` only supplies
+/// vehicle ID casing and battle ratings, so its absence is a warning.
+const REQUIRED_ARCHIVES: [&str; 2] = ["aces.vromfs.bin", "lang.vromfs.bin"];
+
+/// Windows `ERROR_SHARING_VIOLATION`: another process holds the file.
+const SHARING_VIOLATION: i32 = 32;
+
+/// What could be read from an archive that opened.
+#[derive(Debug)]
+pub struct ArchiveInfo {
+	/// Game version, or the warning of [`extract::archive_version`].
+	pub version: Result<String, String>,
+	/// Top-level tankmodels in the archive (0 outside `aces.vromfs.bin`).
+	pub tankmodels: usize,
+}
+
+/// Run the `check` subcommand and exit with its status: 0 if the game path
+/// is usable, 1 otherwise.
+pub fn run_check(game_path: &Path) {
+	std::process::exit(check(game_path, open_archive));
+}
+
+/// Check `game_path`, reading archives with `open`, and return the exit code.
+fn check(game_path: &Path, open: impl Fn(&Path) -> Result<ArchiveInfo, String>) -> i32 {
+	eprintln!("Game path: {}", game_path.display());
+	if !game_path.exists() {
+		eprintln!("Error: {} does not exist", game_path.display());
+		return fail(game_path);
+	}
+	if !game_path.is_dir() {
+		eprintln!("Error: {} is a file, not a directory", game_path.display());
+		return fail(game_path);
+	}
+
+	let mut ok = true;
+	for name in REQUIRED_ARCHIVES {
+		let path = game_path.join(name);
+		match check_archive(&path, &open) {
+			Ok(info) if name == "aces.vromfs.bin" => {
+				let version = info.version.unwrap_or_else(|warning| {
+					eprintln!("Warning: {warning}");
+					extract::UNKNOWN_VERSION.to_owned()
+				});
+				eprintln!("  {name}: ok (version {version}, {} tankmodels)", info.tankmodels);
+				if info.tankmodels == 0 {
+					eprintln!("Error: {name} has no tankmodels; the game files may be incomplete");
+					ok = false;
+				}
+			},
+			Ok(_) => eprintln!("  {name}: ok"),
+			Err(e) => {
+				eprintln!("Error: {name}: {e}");
+				ok = false;
+			},
+		}
+	}
+	if !game_path.join("char.vromfs.bin").exists() {
+		eprintln!(
+			"Warning: char.vromfs.bin not found; vehicle ID casing and --min-br/--rank will \
+			 not be available",
+		);
+	}
+
+	if ok {
+		eprintln!("Game path looks good.");
+		0
+	} else {
+		fail(game_path)
+	}
+}
+
+/// Print guidance for a failed check and return the failing exit code.
+fn fail(game_path: &Path) -> i32 {
+	if let Some(nested) = nested_game_dir(game_path) {
+		eprintln!(
+			"Hint: {} looks like the game directory; use it as --game-path",
+			nested.display(),
+		);
+	}
+	eprintln!(
+		"--game-path must be the War Thunder installation directory, the one containing \
+		 aces.vromfs.bin (e.g. `C:\\Program Files (x86)\\Steam\\steamapps\\common\\War Thunder`)",
+	);
+	1
+}
+
+/// Open one archive, telling missing, locked and unreadable files apart.
+fn check_archive(
+	path: &Path,
+	open: impl Fn(&Path) -> Result<ArchiveInfo, String>,
+) -> Result<ArchiveInfo, String> {
+	if let Err(e) = std::fs::File::open(path) {
+		return Err(match e.kind() {
+			ErrorKind::NotFound => format!("not found at {}", path.display()),
+			_ if cfg!(windows) && e.raw_os_error() == Some(SHARING_VIOLATION) => {
+				"locked by another process; close War Thunder and its launcher (an update may \
+				 be running) and retry"
+					.to_owned()
+			},
+			_ => format!("cannot open {}: {e}", path.display()),
+		});
+	}
+	open(path)
+}
+
+/// Open a VROMFS archive and read its version and file table.
+fn open_archive(path: &Path) -> Result<ArchiveInfo, String> {
+	let file = VromfFile::new(path).map_err(|e| format!("failed to read: {e}"))?;
+	let unpacker = VromfUnpacker::from_file(&file, false)
+		.map_err(|e| format!("not a valid archive ({e}); verify the game files"))?;
+	let files = unpacker
+		.unpack_all(None, false)
+		.map_err(|e| format!("failed to list files ({e}); verify the game files"))?;
+	Ok(ArchiveInfo {
+		version: extract::archive_version(unpacker.latest_version()),
+		tankmodels: files.iter().filter_map(|f| extract::tankmodel_stem(f.path())).count(),
+	})
+}
+
+/// A subdirectory of `dir` that holds `aces.vromfs.bin`, for a game path that
+/// stops one level short (e.g. `steamapps/common`).
+fn nested_game_dir(dir: &Path) -> Option<PathBuf> {
+	let mut dirs: Vec<PathBuf> = std::fs::read_dir(dir)
+		.ok()?
+		.filter_map(Result::ok)
+		.map(|entry| entry.path())
+		.filter(|path| path.join("aces.vromfs.bin").is_file())
+		.collect();
+	dirs.sort();
+	dirs.into_iter().next()
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::test_util::TempDir;
+	use super::*;
+
+	#[test]
+	fn test_check_exit_codes() {
+		let stub_archive = |_: &Path| {
+			Ok(ArchiveInfo {
+				version: Ok("2.45.0.1".to_owned()),
+				tankmodels: 3,
+			})
+		};
+		let dir = TempDir::new("check");
+		let game = dir.join("War Thunder");
+		std::fs::create_dir_all(&game).unwrap();
+		for name in REQUIRED_ARCHIVES {
+			std::fs::write(game.join(name), b"stub").unwrap();
+		}
+
+		assert_eq!(check(&game, stub_archive), 0);
+
+		// One level too high: fails, with the game directory as a hint
+		assert_eq!(check(&dir, stub_archive), 1);
+		assert_eq!(nested_game_dir(&dir), Some(game.clone()));
+		assert_eq!(check(&dir.join("missing"), stub_archive), 1);
+
+		// A corrupt archive, and one without vehicles
+		assert_eq!(check(&game, |_| Err("not a valid archive".to_owned())), 1);
+		let empty = |_: &Path| {
+			Ok(ArchiveInfo {
+				version: Err("archive has no version".to_owned()),
+				tankmodels: 0,
+			})
+		};
+		assert_eq!(check(&game, empty), 1);
+
+		std::fs::remove_file(game.join("lang.vromfs.bin")).unwrap();
+		assert_eq!(check(&game, stub_archive), 1);
+	}
+}
@@ -0,0 +1,194 @@
+//! CLI orchestrator for the `clean` subcommand.
+//!
+//! Removes `Data/{vehicle}.txt` files and `Ballistic/{vehicle}/` directories
+//! (also `Ballistic/{set}/{vehicle}/` of `--ammo-set` runs) for vehicles that
+//! are no longer present in the game archives (removed or renamed upstream).
+//! Everything else under the output directory — the `Datamine/` cache, its
+//! version marker, and the lang CSVs — is left alone.
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+
+use crate::extract;
+use crate::run::AmmoSet;
+
+/// Run the `clean` subcommand.
+///
+/// # Arguments
+/// * `game_path`     – War Thunder installation directory (source of the current vehicle list).
+/// * `data_dir`      – `Data/` directory of the output (`--data-subdir`).
+/// * `ballistic_dir` – `Ballistic/` directory of the output (`--ballistic-subdir`).
+/// * `dry_run`       – Only list what would be removed.
+/// * `yes`           – Skip the confirmation prompt.
+pub fn run_clean(
+	game_path: &Path,
+	data_dir: &Path,
+	ballistic_dir: &Path,
+	dry_run: bool,
+	yes: bool,
+) {
+	let current: HashSet<String> = extract::list_vehicle_names(game_path).into_iter().collect();
+	if current.is_empty() {
+		// An empty list almost certainly means a broken archive; deleting
+		// every output would be the wrong response.
+		eprintln!("Error: no vehicles found in the game archive, refusing to clean.");
+		std::process::exit(1);
+	}
+
+	let stale = find_stale(data_dir, ballistic_dir, &current);
+	if stale.is_empty() {
+		eprintln!("Nothing to clean ({} current vehicles).", current.len());
+		return;
+	}
+
+	eprintln!("{} stale entries (vehicles no longer in the game):", stale.len());
+	for path in &stale {
+		eprintln!("  {}", path.display());
+	}
+
+	if dry_run {
+		eprintln!();
+		eprintln!("Dry run: nothing removed.");
+		return;
+	}
+
+	if !yes && !confirm("Remove these entries?") {
+		eprintln!("Aborted.");
+		return;
+	}
+
+	let removed = remove_stale(&stale);
+	eprintln!();
+	eprintln!("Done: {removed} removed, {} errors", stale.len() - removed);
+}
+
+/// Find output entries belonging to vehicles not in `current`.
+///
+/// `current` holds lowercase vehicle stems; output names are compared
+/// case-insensitively since they use unittags casing.  Only `Data/*.txt`
+/// files and vehicle directories of `Ballistic/` are considered: the
+/// `--ammo-set` directories (`Ballistic/stock/`, ...) are not vehicles, but
+/// hold vehicle directories of their own.
+fn find_stale(data_dir: &Path, ballistic_dir: &Path, current: &HashSet<String>) -> Vec<PathBuf> {
+	let is_stale = |path: &Path| {
+		path.file_stem()
+			.is_some_and(|stem| !current.contains(&stem.to_string_lossy().to_lowercase()))
+	};
+	let is_set_dir = |path: &Path| {
+		AmmoSet::value_variants().iter().any(|set| path.ends_with(set.dir_name()))
+	};
+
+	let data = read_dir_sorted(data_dir)
+		.into_iter()
+		.filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "txt"));
+	let (sets, vehicles): (Vec<_>, Vec<_>) = read_dir_sorted(ballistic_dir)
+		.into_iter()
+		.filter(|p| p.is_dir())
+		.partition(|p| is_set_dir(p));
+	let set_vehicles = sets.iter().flat_map(|set| read_dir_sorted(set)).filter(|p| p.is_dir());
+
+	data.chain(vehicles).chain(set_vehicles).filter(|p| is_stale(p)).collect()
+}
+
+/// Delete the given files/directories, returning how many were removed.
+fn remove_stale(stale: &[PathBuf]) -> usize {
+	let mut removed = 0;
+	for path in stale {
+		let result = if path.is_dir() {
+			std::fs::remove_dir_all(path)
+		} else {
+			std::fs::remove_file(path)
+		};
+		match result {
+			Ok(()) => removed += 1,
+			Err(e) => eprintln!("REMOVE ERROR {}: {e}", path.display()),
+		}
+	}
+	removed
+}
+
+/// List a directory's entries in name order (empty if it does not exist).
+fn read_dir_sorted(dir: &Path) -> Vec<PathBuf> {
+	let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+		.map(|entries| entries.filter_map(Result::ok).map(|e| e.path()).collect())
+		.unwrap_or_default();
+	paths.sort();
+	paths
+}
+
+/// Ask a yes/no question on stderr; anything but `y`/`yes` is a no.
+fn confirm(question: &str) -> bool {
+	eprint!("{question} [y/N] ");
+	let _ = std::io::stderr().flush();
+	let mut answer = String::new();
+	if std::io::stdin().read_line(&mut answer).is_err() {
+		return false;
+	}
+	matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::test_util::TempDir;
+	use super::*;
+
+	#[test]
+	fn test_clean_removes_only_stale_vehicles() {
+		let out = TempDir::new("clean");
+
+		let data = out.join("Data");
+		let ballistic = out.join("Ballistic");
+		let datamine = out.join("Datamine");
+		let lang = datamine.join("lang.vromfs.bin_u").join("lang");
+		for dir in [
+			&data,
+			&ballistic.join("germ_pzkpfw_VI_ausf_h1_tiger"),
+			&ballistic.join("us_removed_tank"),
+			&lang,
+		] {
+			std::fs::create_dir_all(dir).unwrap();
+		}
+		std::fs::write(data.join("germ_pzkpfw_VI_ausf_h1_tiger.txt"), "").unwrap();
+		std::fs::write(data.join("us_removed_tank.txt"), "").unwrap();
+		std::fs::write(ballistic.join("us_removed_tank").join("m61.txt"), "").unwrap();
+		std::fs::write(datamine.join(extract::VERSION_MARKER), "2.53.0.19\n0.5").unwrap();
+		std::fs::write(lang.join("units.csv"), "").unwrap();
+
+		let current: HashSet<String> = ["germ_pzkpfw_vi_ausf_h1_tiger".to_owned()].into();
+		let stale = find_stale(&data, &ballistic, &current);
+		assert_eq!(
+			stale,
+			vec![data.join("us_removed_tank.txt"), ballistic.join("us_removed_tank")]
+		);
+
+		assert_eq!(remove_stale(&stale), 2);
+		assert!(!data.join("us_removed_tank.txt").exists());
+		assert!(!ballistic.join("us_removed_tank").exists());
+		assert!(data.join("germ_pzkpfw_VI_ausf_h1_tiger.txt").exists());
+		assert!(ballistic.join("germ_pzkpfw_VI_ausf_h1_tiger").is_dir());
+		assert!(datamine.join(extract::VERSION_MARKER).exists());
+		assert!(lang.join("units.csv").exists());
+	}
+
+	#[test]
+	fn test_clean_keeps_ammo_set_dirs() {
+		let out = TempDir::new("clean-sets");
+
+		let stock = out.join("Ballistic").join(AmmoSet::Stock.dir_name());
+		let researched = out.join("Ballistic").join(AmmoSet::Researched.dir_name());
+		for dir in [
+			&stock.join("germ_pzkpfw_VI_ausf_h1_tiger"),
+			&stock.join("us_removed_tank"),
+			&researched.join("germ_pzkpfw_VI_ausf_h1_tiger"),
+		] {
+			std::fs::create_dir_all(dir).unwrap();
+		}
+
+		let current: HashSet<String> = ["germ_pzkpfw_vi_ausf_h1_tiger".to_owned()].into();
+		let stale = find_stale(&out.join("Data"), &out.join("Ballistic"), &current);
+		assert_eq!(stale, vec![stock.join("us_removed_tank")]);
+	}
+}
@@ -0,0 +1,179 @@
+//! CLI orchestrator for the `compare` subcommand.
+//!
+//! Walks a reference `Ballistic/` tree and fuzzily compares every
+//! `{vehicle}/{shell}.txt` against the same file in a computed tree, using the
+//! tolerance logic of the corpus tests ([`compare_ballistic_fuzzy`]).
+
+use std::path::{Path, PathBuf};
+
+use fcsgen_core::compare::{DeltaStats, Tolerances, compare_ballistic_fuzzy};
+
+/// Outcome of comparing two `Ballistic/` trees.
+#[derive(Debug, Default)]
+struct CompareReport {
+	passed: usize,
+	failed: usize,
+	/// Reference tables with no computed counterpart.
+	missing: usize,
+	/// One line per failed or missing table.
+	failures: Vec<String>,
+	stats: DeltaStats,
+}
+
+/// Run the `compare` subcommand.
+///
+/// Prints pass/fail counts and worst-case deltas, and exits with status 1 if
+/// any table is out of tolerance or missing from `computed`.
+///
+/// # Arguments
+/// * `computed`  – `Ballistic/` tree to check.
+/// * `reference` – `Ballistic/` tree holding the expected tables.
+/// * `tol`       – Per-column tolerances.
+pub fn run_compare(computed: &Path, reference: &Path, tol: &Tolerances) {
+	for dir in [computed, reference] {
+		if !dir.is_dir() {
+			eprintln!("Error: directory not found at {}", dir.display());
+			std::process::exit(1);
+		}
+	}
+
+	let report = compare_trees(computed, reference, tol);
+	let stats = &report.stats;
+
+	for f in &report.failures {
+		eprintln!("  {f}");
+	}
+	if !report.failures.is_empty() {
+		eprintln!();
+	}
+	eprintln!(
+		"Done: {} passed, {} failed, {} missing",
+		report.passed, report.failed, report.missing,
+	);
+	eprintln!("Worst-case deltas:");
+	eprintln!(
+		"  Distance:  {:.4} m  (tol {})  [{}]",
+		stats.max_dist, tol.dist, stats.worst_dist_shell,
+	);
+	eprintln!("  Time:      {:.2} s   (tol {})", stats.max_time, tol.time);
+	eprintln!(
+		"  Pen:       {:.1} mm  (tol {})  [{}]",
+		stats.max_pen, tol.pen, stats.worst_pen_shell,
+	);
+	eprintln!(
+		"  Row count: {}      (tol {})  [{}]",
+		stats.max_row_diff, tol.row_count, stats.worst_row_shell,
+	);
+
+	if report.failed + report.missing > 0 {
+		std::process::exit(1);
+	}
+}
+
+/// Compare every reference table against its computed counterpart.
+fn compare_trees(computed: &Path, reference: &Path, tol: &Tolerances) -> CompareReport {
+	let mut report = CompareReport::default();
+
+	for vehicle_dir in read_dir_sorted(reference) {
+		if !vehicle_dir.is_dir() {
+			continue;
+		}
+		let vehicle = file_stem(&vehicle_dir);
+
+		for expected_path in read_dir_sorted(&vehicle_dir) {
+			if expected_path.extension().is_none_or(|ext| ext != "txt") {
+				continue;
+			}
+			let shell = file_stem(&expected_path);
+
+			let computed_path = computed.join(&vehicle).join(format!("{shell}.txt"));
+			let Ok(actual) = std::fs::read_to_string(&computed_path) else {
+				report.missing += 1;
+				report
+					.failures
+					.push(format!("{vehicle}/{shell}: missing from computed tree"));
+				continue;
+			};
+			let expected = match std::fs::read_to_string(&expected_path) {
+				Ok(e) => e,
+				Err(e) => {
+					report.failed += 1;
+					report.failures.push(format!("{vehicle}/{shell}: {e}"));
+					continue;
+				},
+			};
+
+			match compare_ballistic_fuzzy(
+				&vehicle,
+				&shell,
+				&actual,
+				&expected,
+				tol,
+				&mut report.stats,
+			) {
+				Ok(()) => report.passed += 1,
+				Err(msg) => {
+					report.failed += 1;
+					report.failures.push(msg);
+				},
+			}
+		}
+	}
+
+	report
+}
+
+/// List a directory's entries in name order (empty if it cannot be read).
+fn read_dir_sorted(dir: &Path) -> Vec<PathBuf> {
+	let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)
+		.into_iter()
+		.flatten()
+		.filter_map(|e| e.ok().map(|e| e.path()))
+		.collect();
+	entries.sort();
+	entries
+}
+
+/// File or directory name without extension.
+fn file_stem(path: &Path) -> String {
+	path.file_stem()
+		.unwrap_or_default()
+		.to_string_lossy()
+		.into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::test_util::TempDir;
+	use super::*;
+
+	const TABLE: &str = "0\t0\t250.0\n100\t0.14\t245.2\n200\t0.28\t\u{221E}\n";
+
+	#[test]
+	fn test_small_delta_within_tolerance() {
+		let dir = TempDir::new("compare");
+		let (computed, reference) = (dir.join("computed"), dir.join("reference"));
+		for tree in [&computed, &reference] {
+			std::fs::create_dir_all(tree.join("test_tank")).unwrap();
+		}
+		std::fs::write(reference.join("test_tank").join("pzgr_39.txt"), TABLE).unwrap();
+		std::fs::write(reference.join("test_tank").join("sprgr_34.txt"), TABLE).unwrap();
+		// Penetration off by 0.4 mm on the second row
+		let nudged = TABLE.replace("245.2", "245.6");
+		std::fs::write(computed.join("test_tank").join("pzgr_39.txt"), &nudged).unwrap();
+		std::fs::write(computed.join("test_tank").join("sprgr_34.txt"), TABLE).unwrap();
+
+		let report = compare_trees(&computed, &reference, &Tolerances::default());
+		assert_eq!((report.passed, report.failed, report.missing), (2, 0, 0));
+		assert!((report.stats.max_pen - 0.4).abs() < 1e-9);
+		assert_eq!(report.stats.worst_pen_shell, "test_tank/pzgr_39");
+
+		// The same delta fails a tighter tolerance
+		let tight = Tolerances {
+			pen: 0.1,
+			..Tolerances::default()
+		};
+		let report = compare_trees(&computed, &reference, &tight);
+		assert_eq!((report.passed, report.failed), (1, 1));
+	}
+}
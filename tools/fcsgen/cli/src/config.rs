@@ -0,0 +1,1030 @@
+//! Options for `fcsgen run`, with defaults from an optional TOML config file.
+//!
+//! `fcsgen run --config fcsgen.toml` reads defaults for every `run` option
+//! from the file.  Keys are the long flag names without the leading dashes:
+//!
+//! ```toml
+//! game-path = "C:/Program Files/Steam/steamapps/common/WarThunder"
+//! output = "out"
+//! sensitivity = 0.45
+//! sensitivity-by-nation = { us = 0.5, ussr = 0.4 }
+//! jobs = 8
+//! ```
+//!
+//! Precedence is explicit CLI flag > config file > built-in default.
+//! Relative paths in the file are resolved against the file's directory.
+//! `--print-config` prints the merged result in the same format and exits.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+
+use clap::{ArgAction, Args};
+use clap::parser::{ArgMatches, ValueSource};
+use fcsgen_core::parser::NATIONS;
+use fcsgen_core::{BallisticConfig, PenRounding, PenetrationUnit, RangefinderKind, TimeUnit};
+use serde::{Deserialize, Serialize};
+
+use crate::extract;
+use crate::output::{CollisionPolicy, LineEnding, OverwritePolicy};
+use crate::run::{AmmoSet, DEFAULT_MIN_SPEED};
+
+/// Options of the `run` subcommand.
+#[derive(Args)]
+#[allow(clippy::struct_excessive_bools)] // one per CLI flag
+pub struct RunArgs {
+	/// TOML file providing defaults for the options below
+	#[arg(long)]
+	pub config: Option<PathBuf>,
+
+	/// Print the options in effect after merging `--config` and the command
+	/// line, as a config file, and exit without running
+	#[arg(long, default_value_t = false)]
+	pub print_config: bool,
+
+	/// Path to the War Thunder installation directory
+	#[arg(long)]
+	pub game_path: Option<PathBuf>,
+
+	/// Base output directory (creates Datamine/, Data/, Ballistic/ subdirs);
+	/// defaults to `FCSGenerator/` in the War Thunder user directory
+	#[arg(short, long)]
+	pub output: Option<PathBuf>,
+
+	/// Name of the datamine subdirectory of `--output` (also holds the
+	/// version marker)
+	#[arg(long, default_value = "Datamine", value_parser = parse_subdir)]
+	pub datamine_subdir: String,
+
+	/// Name of the `Data/*.txt` subdirectory of `--output`
+	#[arg(long, default_value = "Data", value_parser = parse_subdir)]
+	pub data_subdir: String,
+
+	/// Name of the ballistic tables subdirectory of `--output`
+	#[arg(long, default_value = "Ballistic", value_parser = parse_subdir)]
+	pub ballistic_subdir: String,
+
+	/// Mouse sensitivity (0 < s ≤ 1); the scroll step is 2.8·s² mrad
+	#[arg(short, long, default_value_t = 0.50, value_parser = parse_sensitivity)]
+	pub sensitivity: f64,
+
+	/// Per-nation sensitivity overrides, e.g. `us=0.5,ussr=0.4`; vehicles of
+	/// other nations use `--sensitivity`
+	#[arg(long, value_delimiter = ',', value_parser = parse_nation_sensitivity)]
+	pub sensitivity_by_nation: Vec<(String, f64)>,
+
+	/// Maximum launch angles simulated per shell (guards very low sensitivities)
+	#[arg(long, default_value_t = BallisticConfig::DEFAULT_MAX_ENTRIES_CAP)]
+	pub max_entries_cap: usize,
+
+	/// Skip shells with a muzzle velocity below this many m/s (placeholder or
+	/// malformed data)
+	#[arg(long, value_name = "M/S", default_value_t = DEFAULT_MIN_SPEED)]
+	pub min_speed: f64,
+
+	/// Gravitational acceleration in m/s² (for modded low-gravity servers)
+	#[arg(long, default_value_t = BallisticConfig::STANDARD_GRAVITY)]
+	pub gravity: f64,
+
+	/// Unit of the penetration column: `mm` or `in` (inches, one decimal)
+	#[arg(long, default_value_t = PenetrationUnit::Millimetres)]
+	pub pen_unit: PenetrationUnit,
+
+	/// Round the penetration column to the nearest multiple of N mm (e.g. 5
+	/// for cleaner sight labels); 1 keeps whole millimetres
+	#[arg(long, value_name = "N", default_value_t = 1, value_parser = parse_pen_round)]
+	pub pen_round: u32,
+
+	/// Rounding of penetration values: `nearest`, `floor` (as some in-game
+	/// readouts) or `ceil`
+	#[arg(long, value_name = "MODE", default_value_t = PenRounding::Nearest)]
+	pub pen_rounding: PenRounding,
+
+	/// Unit of the time column: `s` (one decimal, as the legacy tool) or `ms`
+	/// (whole milliseconds)
+	#[arg(long, default_value_t = TimeUnit::Seconds)]
+	pub time_unit: TimeUnit,
+
+	/// Decimal places of the distance column: 3 as the reference tables,
+	/// fewer to shrink the output of large runs
+	#[arg(
+		long,
+		value_name = "N",
+		default_value_t = BallisticConfig::DEFAULT_DISTANCE_PRECISION,
+		value_parser = parse_distance_precision,
+	)]
+	pub distance_precision: usize,
+
+	/// Whether to write the 0 m muzzle row at the top of each ballistic table
+	/// (`true` or `false`)
+	#[arg(long, value_name = "BOOL", default_value_t = true, action = ArgAction::Set)]
+	pub include_muzzle_row: bool,
+
+	/// Estimate the penetration of APDS-FS rounds missing their armor power
+	/// series from mass, speed and caliber (headers say `pen=estimated`)
+	/// instead of writing zeros
+	#[arg(long, default_value_t = false)]
+	pub estimate_missing_pen: bool,
+
+	/// Write only distance and time, without the penetration column (for
+	/// flight-time and drop analysis; not understood by the sight generator)
+	#[arg(long, default_value_t = false)]
+	pub no_penetration: bool,
+
+	/// Naval preset: extend ballistic tables from 4500 m to 20000 m for ship
+	/// guns (headers say `max_range=20000`)
+	#[arg(long, default_value_t = false)]
+	pub naval: bool,
+
+	/// Give shells without a Cx the legacy 0.38 instead of a per-caliber
+	/// approximation (as in the reference corpus)
+	#[arg(long, default_value_t = false)]
+	pub uniform_cx: bool,
+
+	/// Abandon a vehicle's remaining shells once its ballistic computation has
+	/// run this long (checked between shells)
+	#[arg(long, value_name = "SECS")]
+	pub vehicle_timeout: Option<u64>,
+
+	/// Path to ignore.txt vehicle blacklist file
+	#[arg(long)]
+	pub ignore_file: Option<PathBuf>,
+
+	/// Per-shell physics overrides, one `vehicle:shell:field=value` per line
+	/// (e.g. `us_m1_abrams:m829a2:speed=1700`)
+	#[arg(long, value_name = "FILE")]
+	pub overrides: Option<PathBuf>,
+
+	/// Compute ballistics from an existing `Data/*.txt` tree instead of
+	/// extracting and converting (e.g. after hand edits)
+	#[arg(long, value_name = "DIR", conflicts_with_all = ["skip_extract", "skip_ballistic"])]
+	pub from_data: Option<PathBuf>,
+
+	/// Only process specific vehicle(s) by name (without .blkx extension)
+	#[arg(long)]
+	pub vehicle: Option<Vec<String>>,
+
+	/// Only process the vehicles listed in this file, one per line (same
+	/// format as the ignore file), in addition to any `--vehicle`
+	#[arg(long, value_name = "FILE")]
+	pub vehicle_file: Option<PathBuf>,
+
+	/// Only process vehicles with this kind of rangefinder: `laser`, `scroll`
+	/// (no laser) or `none` (only ATGMs/SAMs/rockets)
+	#[arg(long, value_name = "KIND")]
+	pub rangefinder: Option<RangefinderKind>,
+
+	/// Only process vehicles at or above this realistic battle rating (e.g.
+	/// `10.3`), from the game's wpcost; not available with `--from-data`
+	#[arg(long, value_name = "BR")]
+	pub min_br: Option<f64>,
+
+	/// Only process vehicles of this rank (tier, e.g. `8` for rank VIII); not
+	/// available with `--from-data`
+	#[arg(long, value_name = "N")]
+	pub rank: Option<u32>,
+
+	/// Number of parallel jobs (0 = auto-detect based on CPU count)
+	#[arg(short, long, default_value_t = 0)]
+	pub jobs: usize,
+
+	/// Skip extraction (use existing datamine in Datamine/)
+	#[arg(long, default_value_t = false)]
+	pub skip_extract: bool,
+
+	/// Skip ballistic computation (only extract + convert)
+	#[arg(long, default_value_t = false)]
+	pub skip_ballistic: bool,
+
+	/// Write full datamine .blkx files to disk (for debugging/testing)
+	#[arg(long, default_value_t = false)]
+	pub write_datamine: bool,
+
+	/// Don't extract the lang CSVs (`lang.vromfs.bin` is then not needed);
+	/// shell display names fall back to shell names
+	#[arg(long, default_value_t = false)]
+	pub no_lang: bool,
+
+	/// Free the in-memory datamine as vehicles are converted, at most `--jobs`
+	/// at a time (slower; for machines short on RAM)
+	#[arg(long, default_value_t = false)]
+	pub low_memory: bool,
+
+	/// Benchmark: run the convert and ballistic stages N times on one
+	/// extraction, printing the time and cache reuse of each iteration
+	#[arg(long, value_name = "N", default_value_t = 1, value_parser = parse_repeat)]
+	pub repeat: usize,
+
+	/// Clear the ballistic cache between `--repeat` iterations, so that each
+	/// one starts cold
+	#[arg(long, default_value_t = false)]
+	pub repeat_cold: bool,
+
+	/// Write a JSON summary of the run, listing skipped and unsupported shells
+	#[arg(long, value_name = "FILE")]
+	pub report: Option<PathBuf>,
+
+	/// Also write everything printed to stderr to this file, with timestamps
+	/// (for support requests; `--report` is the machine-readable summary)
+	#[arg(long, value_name = "FILE")]
+	pub log_file: Option<PathBuf>,
+
+	/// Count ballistic tables that are identical across shells and list the
+	/// largest groups (also in `--report`)
+	#[arg(long, default_value_t = false)]
+	pub report_duplicates: bool,
+
+	/// Debug: recompute this fraction (0 < f ≤ 1) of cache hits and report
+	/// any that differ from the cached table (a cache key collision)
+	#[arg(long, value_name = "FRACTION", value_parser = parse_fraction)]
+	pub verify_cache: Option<f64>,
+
+	/// Write every distinct shell (by name and physics) with the vehicles
+	/// carrying it to this file, as CSV for a `.csv` path and JSON otherwise
+	#[arg(long, value_name = "FILE")]
+	pub shell_manifest: Option<PathBuf>,
+
+	/// Print at most N errors of each kind (e.g. `CONVERT ERROR failed to read
+	/// file`), then only count them and print one `N more: ...` line at the end
+	#[arg(long, value_name = "N")]
+	pub quiet_errors: Option<usize>,
+
+	/// Pre-load the ballistic cache from a file written by `--save-cache`
+	#[arg(long, value_name = "FILE")]
+	pub seed_cache_from: Option<PathBuf>,
+
+	/// Write the ballistic cache to a file at the end of the run
+	#[arg(long, value_name = "FILE")]
+	pub save_cache: Option<PathBuf>,
+
+	/// Prepend a `#` metadata line (shell, type, caliber, speed, sensitivity)
+	/// to each ballistic table; not understood by the legacy sight generator
+	#[arg(long, default_value_t = false)]
+	pub with_header: bool,
+
+	/// Sight range marks in metres, ascending (e.g. `200,400,600,800`): adds
+	/// the launch angle each needs to the `--with-header` line
+	#[arg(long, value_name = "M,...", value_delimiter = ',', value_parser = parse_range_mark)]
+	pub range_marks: Option<Vec<f64>>,
+
+	/// What to do when different shells share a ballistic file name
+	#[arg(long, value_enum, default_value_t = CollisionPolicy::Last)]
+	pub on_collision: CollisionPolicy,
+
+	/// Don't write ballistic tables whose penetration is zero throughout (HE, smoke)
+	#[arg(long, default_value_t = false)]
+	pub skip_zero_pen: bool,
+
+	/// Write all of a vehicle's shells to one `Ballistic/{vehicle}.txt`, each
+	/// under a `# {shell}\ttype={type}` line, instead of one file per shell
+	#[arg(long, default_value_t = false)]
+	pub per_vehicle_file: bool,
+
+	/// Write these ammunition sets to `Ballistic/{set}/` (e.g.
+	/// `stock,researched`) instead of all shells to `Ballistic/`
+	#[arg(long, value_enum, value_delimiter = ',', conflicts_with = "from_data")]
+	pub ammo_set: Vec<AmmoSet>,
+
+	/// How to handle output files that already exist
+	#[arg(long, value_enum, default_value_t = OverwritePolicy::Overwrite)]
+	pub overwrite_policy: OverwritePolicy,
+
+	/// Line terminator of written Data and Ballistic files: `lf` or `crlf`
+	/// (for Windows-only consumers)
+	#[arg(long, value_enum, default_value_t = LineEnding::Lf)]
+	pub line_ending: LineEnding,
+}
+
+/// Contents of a `--config` file.  Every key is optional.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct ConfigFile {
+	game_path: Option<PathBuf>,
+	output: Option<PathBuf>,
+	datamine_subdir: Option<String>,
+	data_subdir: Option<String>,
+	ballistic_subdir: Option<String>,
+	sensitivity: Option<f64>,
+	sensitivity_by_nation: Option<BTreeMap<String, f64>>,
+	max_entries_cap: Option<usize>,
+	min_speed: Option<f64>,
+	gravity: Option<f64>,
+	pen_unit: Option<PenetrationUnit>,
+	pen_round: Option<u32>,
+	pen_rounding: Option<PenRounding>,
+	time_unit: Option<TimeUnit>,
+	distance_precision: Option<usize>,
+	include_muzzle_row: Option<bool>,
+	estimate_missing_pen: Option<bool>,
+	no_penetration: Option<bool>,
+	naval: Option<bool>,
+	uniform_cx: Option<bool>,
+	vehicle_timeout: Option<u64>,
+	ignore_file: Option<PathBuf>,
+	overrides: Option<PathBuf>,
+	from_data: Option<PathBuf>,
+	vehicle: Option<Vec<String>>,
+	vehicle_file: Option<PathBuf>,
+	rangefinder: Option<RangefinderKind>,
+	min_br: Option<f64>,
+	rank: Option<u32>,
+	jobs: Option<usize>,
+	skip_extract: Option<bool>,
+	skip_ballistic: Option<bool>,
+	write_datamine: Option<bool>,
+	no_lang: Option<bool>,
+	low_memory: Option<bool>,
+	repeat: Option<usize>,
+	repeat_cold: Option<bool>,
+	report: Option<PathBuf>,
+	log_file: Option<PathBuf>,
+	shell_manifest: Option<PathBuf>,
+	report_duplicates: Option<bool>,
+	verify_cache: Option<f64>,
+	quiet_errors: Option<usize>,
+	seed_cache_from: Option<PathBuf>,
+	save_cache: Option<PathBuf>,
+	with_header: Option<bool>,
+	range_marks: Option<Vec<f64>>,
+	on_collision: Option<CollisionPolicy>,
+	skip_zero_pen: Option<bool>,
+	per_vehicle_file: Option<bool>,
+	ammo_set: Option<Vec<AmmoSet>>,
+	overwrite_policy: Option<OverwritePolicy>,
+	line_ending: Option<LineEnding>,
+
+	/// Anything else; reported as a warning and ignored.
+	#[serde(flatten)]
+	unknown: toml::Table,
+}
+
+impl ConfigFile {
+	/// The options in effect in `args`, every one set.
+	fn from_args(args: &RunArgs) -> Self {
+		Self {
+			game_path: args.game_path.clone(),
+			output: args.output.clone(),
+			datamine_subdir: Some(args.datamine_subdir.clone()),
+			data_subdir: Some(args.data_subdir.clone()),
+			ballistic_subdir: Some(args.ballistic_subdir.clone()),
+			sensitivity: Some(args.sensitivity),
+			sensitivity_by_nation: Some(args.sensitivity_by_nation.iter().cloned().collect()),
+			max_entries_cap: Some(args.max_entries_cap),
+			min_speed: Some(args.min_speed),
+			gravity: Some(args.gravity),
+			pen_unit: Some(args.pen_unit),
+			pen_round: Some(args.pen_round),
+			pen_rounding: Some(args.pen_rounding),
+			time_unit: Some(args.time_unit),
+			distance_precision: Some(args.distance_precision),
+			include_muzzle_row: Some(args.include_muzzle_row),
+			estimate_missing_pen: Some(args.estimate_missing_pen),
+			no_penetration: Some(args.no_penetration),
+			naval: Some(args.naval),
+			uniform_cx: Some(args.uniform_cx),
+			vehicle_timeout: args.vehicle_timeout,
+			ignore_file: args.ignore_file.clone(),
+			overrides: args.overrides.clone(),
+			from_data: args.from_data.clone(),
+			vehicle: args.vehicle.clone(),
+			vehicle_file: args.vehicle_file.clone(),
+			rangefinder: args.rangefinder,
+			min_br: args.min_br,
+			rank: args.rank,
+			jobs: Some(args.jobs),
+			skip_extract: Some(args.skip_extract),
+			skip_ballistic: Some(args.skip_ballistic),
+			write_datamine: Some(args.write_datamine),
+			no_lang: Some(args.no_lang),
+			low_memory: Some(args.low_memory),
+			repeat: Some(args.repeat),
+			repeat_cold: Some(args.repeat_cold),
+			report: args.report.clone(),
+			log_file: args.log_file.clone(),
+			shell_manifest: args.shell_manifest.clone(),
+			report_duplicates: Some(args.report_duplicates),
+			verify_cache: args.verify_cache,
+			quiet_errors: args.quiet_errors,
+			seed_cache_from: args.seed_cache_from.clone(),
+			save_cache: args.save_cache.clone(),
+			with_header: Some(args.with_header),
+			range_marks: args.range_marks.clone(),
+			on_collision: Some(args.on_collision),
+			skip_zero_pen: Some(args.skip_zero_pen),
+			per_vehicle_file: Some(args.per_vehicle_file),
+			ammo_set: Some(args.ammo_set.clone()),
+			overwrite_policy: Some(args.overwrite_policy),
+			line_ending: Some(args.line_ending),
+			unknown: toml::Table::new(),
+		}
+	}
+}
+
+/// Fill in options not given on the command line from `args.config`.
+///
+/// `matches` are the `run` subcommand's matches, used to tell explicit flags
+/// apart from clap defaults.  Exits if the file cannot be read or parsed, or
+/// if `--game-path` ends up unset without `--from-data`.  `--output` defaults
+/// to [`default_output_dir`].  The vehicles of `--vehicle-file` are added to
+/// `--vehicle`; an unreadable file is an error.
+#[allow(clippy::too_many_lines)]
+pub fn resolve_run_args(mut args: RunArgs, matches: &ArgMatches) -> RunArgs {
+	if let Some(path) = &args.config {
+		let file = load_config_file(path);
+		let base = path.parent().unwrap_or(Path::new(""));
+		let explicit = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+		macro_rules! merge {
+			($($field:ident),* $(,)?) => {$(
+				if !explicit(stringify!($field))
+					&& let Some(value) = file.$field
+				{
+					args.$field = value.into();
+				}
+			)*};
+		}
+		merge!(
+			datamine_subdir,
+			data_subdir,
+			ballistic_subdir,
+			sensitivity,
+			max_entries_cap,
+			min_speed,
+			gravity,
+			pen_unit,
+			pen_round,
+			pen_rounding,
+			time_unit,
+			distance_precision,
+			include_muzzle_row,
+			estimate_missing_pen,
+			no_penetration,
+			naval,
+			uniform_cx,
+			vehicle_timeout,
+			vehicle,
+			rangefinder,
+			min_br,
+			rank,
+			jobs,
+			skip_extract,
+			skip_ballistic,
+			write_datamine,
+			no_lang,
+			low_memory,
+			repeat,
+			repeat_cold,
+			report_duplicates,
+			verify_cache,
+			quiet_errors,
+			with_header,
+			range_marks,
+			on_collision,
+			skip_zero_pen,
+			per_vehicle_file,
+			ammo_set,
+			overwrite_policy,
+			line_ending,
+		);
+
+		if !explicit("sensitivity_by_nation")
+			&& let Some(map) = file.sensitivity_by_nation
+		{
+			args.sensitivity_by_nation = map
+				.into_iter()
+				.map(|(nation, sensitivity)| (nation.to_ascii_lowercase(), sensitivity))
+				.collect();
+		}
+
+		// Paths are relative to the config file
+		for (id, arg, value) in [
+			("game_path", &mut args.game_path, file.game_path),
+			("output", &mut args.output, file.output),
+			("ignore_file", &mut args.ignore_file, file.ignore_file),
+			("vehicle_file", &mut args.vehicle_file, file.vehicle_file),
+			("overrides", &mut args.overrides, file.overrides),
+			("from_data", &mut args.from_data, file.from_data),
+			("report", &mut args.report, file.report),
+			("log_file", &mut args.log_file, file.log_file),
+			("shell_manifest", &mut args.shell_manifest, file.shell_manifest),
+			("seed_cache_from", &mut args.seed_cache_from, file.seed_cache_from),
+			("save_cache", &mut args.save_cache, file.save_cache),
+		] {
+			if !explicit(id)
+				&& let Some(value) = value
+			{
+				*arg = Some(base.join(value));
+			}
+		}
+	}
+
+	add_vehicle_file(&mut args);
+
+	args.ammo_set.sort_unstable();
+	args.ammo_set.dedup();
+	if !args.ammo_set.is_empty() && args.from_data.is_some() {
+		eprintln!("Error: --ammo-set needs conversion, so it cannot be used with --from-data");
+		std::process::exit(1);
+	}
+
+	// Config-file values bypass the clap value parsers
+	let checks = [
+		check_sensitivity(args.sensitivity),
+		check_subdir(&args.datamine_subdir),
+		check_subdir(&args.data_subdir),
+		check_subdir(&args.ballistic_subdir),
+		args.verify_cache.map_or(Ok(()), check_fraction),
+		check_pen_round(args.pen_round),
+		check_distance_precision(args.distance_precision),
+		check_range_marks(args.range_marks.as_deref().unwrap_or_default(), args.with_header),
+		check_repeat(&args),
+	];
+	let nation_checks = args
+		.sensitivity_by_nation
+		.iter()
+		.map(|(nation, sensitivity)| check_nation_sensitivity(nation, *sensitivity));
+	if let Some(e) = checks.into_iter().chain(nation_checks).find_map(Result::err) {
+		eprintln!("Error: {e}");
+		std::process::exit(1);
+	}
+
+	if args.game_path.is_none() && args.from_data.is_none() {
+		eprintln!("Error: --game-path is required (on the command line or in --config)");
+		std::process::exit(1);
+	}
+	args.output.get_or_insert_with(default_output_dir);
+
+	args
+}
+
+/// Add the vehicles listed in `args.vehicle_file` to `args.vehicle`.  Exits
+/// if the file cannot be read.
+fn add_vehicle_file(args: &mut RunArgs) {
+	let Some(path) = &args.vehicle_file else {
+		return;
+	};
+	match std::fs::read_to_string(path) {
+		Ok(text) => {
+			let vehicles = args.vehicle.get_or_insert_with(Vec::new);
+			vehicles.extend(extract::parse_vehicle_list(&text));
+			vehicles.sort_unstable();
+			vehicles.dedup();
+		},
+		Err(e) => {
+			eprintln!("Error: cannot read vehicle file {}: {e}", path.display());
+			std::process::exit(1);
+		},
+	}
+}
+
+/// The options of a resolved `args` as a `--config` file, for `run
+/// --print-config`.  Relative paths are printed as given, i.e. relative to
+/// the working directory rather than the file.
+pub fn resolved_config_toml(args: &RunArgs) -> String {
+	toml::to_string(&ConfigFile::from_args(args)).expect("run options serialize as TOML")
+}
+
+/// Operating system family, for [`default_output_dir`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Platform {
+	Windows,
+	MacOs,
+	/// Linux and other Unix-likes.
+	Unix,
+}
+
+impl Platform {
+	const CURRENT: Self = if cfg!(windows) {
+		Self::Windows
+	} else if cfg!(target_os = "macos") {
+		Self::MacOs
+	} else {
+		Self::Unix
+	};
+}
+
+/// Subdirectory of the War Thunder user directory that `run` writes to when
+/// `--output` is not given.
+const DEFAULT_OUTPUT_SUBDIR: &str = "FCSGenerator";
+
+/// Default `--output` of `run`: an `FCSGenerator` folder in the War Thunder
+/// user directory (the one holding `Saves/`), or `./Output` if the home
+/// directory is unknown.
+///
+/// Not `UserSights/` itself: that lives under a per-account user ID, and
+/// `run` writes `Datamine/`, `Data/` and `Ballistic/` rather than sights.
+pub fn default_output_dir() -> PathBuf {
+	wt_user_dir(Platform::CURRENT, |name| std::env::var_os(name))
+		.map_or_else(|| PathBuf::from("Output"), |dir| dir.join(DEFAULT_OUTPUT_SUBDIR))
+}
+
+/// War Thunder user directory on `platform`, with environment variables read
+/// through `env`:
+///
+/// - Windows: `%USERPROFILE%\Documents\My Games\WarThunder`
+/// - macOS: `$HOME/My Games/WarThunder`
+/// - Linux: `$XDG_CONFIG_HOME/WarThunder`, else `$HOME/.config/WarThunder`
+fn wt_user_dir(platform: Platform, env: impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+	let var = |name: &str| env(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+	match platform {
+		Platform::Windows => {
+			Some(var("USERPROFILE")?.join("Documents").join("My Games").join("WarThunder"))
+		},
+		Platform::MacOs => Some(var("HOME")?.join("My Games").join("WarThunder")),
+		Platform::Unix => var("XDG_CONFIG_HOME")
+			.or_else(|| Some(var("HOME")?.join(".config")))
+			.map(|config| config.join("WarThunder")),
+	}
+}
+
+/// Reject sensitivities that yield no scroll step.
+fn check_sensitivity(sensitivity: f64) -> Result<(), String> {
+	if sensitivity > 0.0 {
+		Ok(())
+	} else {
+		Err(format!("sensitivity must be greater than 0 (got {sensitivity})"))
+	}
+}
+
+/// Clap value parser for `--sensitivity`.
+pub fn parse_sensitivity(s: &str) -> Result<f64, String> {
+	let sensitivity: f64 = s.parse().map_err(|e| format!("{e}"))?;
+	check_sensitivity(sensitivity)?;
+	Ok(sensitivity)
+}
+
+/// Reject fractions outside `(0, 1]`.
+fn check_fraction(fraction: f64) -> Result<(), String> {
+	if fraction > 0.0 && fraction <= 1.0 {
+		Ok(())
+	} else {
+		Err(format!("fraction must be in (0, 1] (got {fraction})"))
+	}
+}
+
+/// Clap value parser for `--verify-cache`.
+fn parse_fraction(s: &str) -> Result<f64, String> {
+	let fraction: f64 = s.parse().map_err(|e| format!("{e}"))?;
+	check_fraction(fraction)?;
+	Ok(fraction)
+}
+
+/// Reject a `--pen-round` of 0.
+fn check_pen_round(n: u32) -> Result<(), String> {
+	if n >= 1 {
+		Ok(())
+	} else {
+		Err("penetration rounding must be at least 1 mm".to_owned())
+	}
+}
+
+/// Clap value parser for `--pen-round`.
+pub fn parse_pen_round(s: &str) -> Result<u32, String> {
+	let n: u32 = s.parse().map_err(|e| format!("{e}"))?;
+	check_pen_round(n)?;
+	Ok(n)
+}
+
+/// Largest `--distance-precision`: micrometres, already below any
+/// difference the simulation can tell apart.
+const MAX_DISTANCE_PRECISION: usize = 6;
+
+/// Reject distance precisions finer than the simulation is meaningful for.
+fn check_distance_precision(n: usize) -> Result<(), String> {
+	if n <= MAX_DISTANCE_PRECISION {
+		Ok(())
+	} else {
+		Err(format!(
+			"distance precision must be at most {MAX_DISTANCE_PRECISION} decimals (got {n})"
+		))
+	}
+}
+
+/// Clap value parser for `--distance-precision`.
+pub fn parse_distance_precision(s: &str) -> Result<usize, String> {
+	let n: usize = s.parse().map_err(|e| format!("{e}"))?;
+	check_distance_precision(n)?;
+	Ok(n)
+}
+
+/// Reject range marks out of order, and marks without the header line they
+/// are written to.
+pub fn check_range_marks(marks: &[f64], with_header: bool) -> Result<(), String> {
+	for pair in marks.windows(2) {
+		if let &[earlier, later] = pair
+			&& earlier >= later
+		{
+			return Err(format!(
+				"range marks must be in ascending order ({later} comes after {earlier})"
+			));
+		}
+	}
+	if !marks.is_empty() && !with_header {
+		return Err("--range-marks needs --with-header, whose line they are written to".to_owned());
+	}
+	Ok(())
+}
+
+/// Clap value parser for one `--range-marks` range.
+pub fn parse_range_mark(s: &str) -> Result<f64, String> {
+	let range: f64 = s.trim().parse().map_err(|e| format!("`{s}`: {e}"))?;
+	if range > 0.0 && range.is_finite() {
+		Ok(range)
+	} else {
+		Err(format!("range marks must be positive distances in metres (got {s})"))
+	}
+}
+
+/// Reject a `--repeat` of 0, and repeats that cannot redo a run: with
+/// `--low-memory` the datamine is consumed by the first iteration, and with
+/// `--overwrite-policy error` the second one fails on its own output.
+fn check_repeat(args: &RunArgs) -> Result<(), String> {
+	if args.repeat == 0 {
+		return Err("--repeat must be at least 1".to_owned());
+	}
+	if args.repeat > 1 && args.low_memory {
+		return Err("--repeat cannot be used with --low-memory".to_owned());
+	}
+	if args.repeat > 1 && args.overwrite_policy == OverwritePolicy::Error {
+		return Err("--repeat cannot be used with --overwrite-policy error".to_owned());
+	}
+	Ok(())
+}
+
+/// Clap value parser for `--repeat`.
+fn parse_repeat(s: &str) -> Result<usize, String> {
+	let n: usize = s.parse().map_err(|e| format!("{e}"))?;
+	if n == 0 {
+		return Err("--repeat must be at least 1".to_owned());
+	}
+	Ok(n)
+}
+
+/// Reject unknown nations and invalid sensitivities in
+/// `--sensitivity-by-nation`.
+fn check_nation_sensitivity(nation: &str, sensitivity: f64) -> Result<(), String> {
+	if !NATIONS.contains(&nation) {
+		return Err(format!(
+			"unknown nation `{nation}` (expected one of {})",
+			NATIONS.join(", "),
+		));
+	}
+	check_sensitivity(sensitivity).map_err(|e| format!("{nation}: {e}"))
+}
+
+/// Clap value parser for one `nation=sensitivity` pair of
+/// `--sensitivity-by-nation`.
+pub fn parse_nation_sensitivity(s: &str) -> Result<(String, f64), String> {
+	let (nation, sensitivity) = s
+		.split_once('=')
+		.ok_or_else(|| format!("expected `nation=sensitivity`, got `{s}`"))?;
+	let nation = nation.trim().to_ascii_lowercase();
+	let sensitivity: f64 = sensitivity.trim().parse().map_err(|e| format!("{nation}: {e}"))?;
+	check_nation_sensitivity(&nation, sensitivity)?;
+	Ok((nation, sensitivity))
+}
+
+/// Reject subdirectory names that are not a single plain path component,
+/// so outputs cannot escape `--output`.
+fn check_subdir(name: &str) -> Result<(), String> {
+	let mut components = Path::new(name).components();
+	let single_normal = matches!(components.next(), Some(Component::Normal(_)))
+		&& components.next().is_none();
+	if single_normal && !name.contains(['/', '\\']) {
+		Ok(())
+	} else {
+		Err(format!("subdirectory name `{name}` must be a single directory name"))
+	}
+}
+
+/// Clap value parser for the `--*-subdir` options.
+pub fn parse_subdir(s: &str) -> Result<String, String> {
+	check_subdir(s)?;
+	Ok(s.to_owned())
+}
+
+/// Warning for a sensitivity above 1, which is accepted but coarse: the
+/// scroll step grows as 2.8·s² mrad, so `2.0` leaves under a hundred rows
+/// per table where the default `0.5` gives ~1500.
+pub fn sensitivity_warning(sensitivity: f64) -> Option<String> {
+	(sensitivity > 1.0).then(|| {
+		format!(
+			"sensitivity {sensitivity} is above 1; the scroll step is {:.1} mrad and \
+			 tables will be coarse",
+			2.8 * sensitivity * sensitivity,
+		)
+	})
+}
+
+/// Read and parse a config file, warning about unknown keys.
+fn load_config_file(path: &Path) -> ConfigFile {
+	let text = std::fs::read_to_string(path).unwrap_or_else(|e| {
+		eprintln!("Error: cannot read config file {}: {e}", path.display());
+		std::process::exit(1);
+	});
+	let file: ConfigFile = toml::from_str(&text).unwrap_or_else(|e| {
+		eprintln!("Error: invalid config file {}: {e}", path.display());
+		std::process::exit(1);
+	});
+
+	for key in file.unknown.keys() {
+		eprintln!(
+			"Warning: unknown key `{key}` in {} (ignored)",
+			path.display()
+		);
+	}
+
+	file
+}
+
+#[cfg(test)]
+mod tests {
+	use clap::{Command, FromArgMatches};
+
+	use crate::test_util::TempDir;
+	use super::*;
+
+	#[test]
+	fn test_cli_flags_override_config_file() {
+		let dir = TempDir::new("config");
+		let config = dir.join("fcsgen.toml");
+		std::fs::write(
+			&config,
+			r#"
+game-path = "/games/WarThunder"
+output = "out"
+sensitivity = 0.3
+jobs = 4
+ignore-file = "ignore.txt"
+on-collision = "suffix"
+"#,
+		)
+		.unwrap();
+
+		let matches = RunArgs::augment_args(Command::new("run"))
+			.try_get_matches_from([
+				"run",
+				"--config",
+				config.to_str().unwrap(),
+				"--sensitivity",
+				"0.7",
+				"--output",
+				"elsewhere",
+			])
+			.unwrap();
+		let args = RunArgs::from_arg_matches(&matches).unwrap();
+		let args = resolve_run_args(args, &matches);
+
+		// Explicit flags win
+		assert!((args.sensitivity - 0.7).abs() < f64::EPSILON);
+		assert_eq!(args.output.as_deref(), Some(Path::new("elsewhere")));
+		// Config fills the rest, relative paths anchored at the file
+		assert_eq!(args.game_path.as_deref(), Some(Path::new("/games/WarThunder")));
+		assert_eq!(args.ignore_file, Some(dir.join("ignore.txt")));
+		assert_eq!(args.jobs, 4);
+		assert_eq!(args.on_collision, CollisionPolicy::Suffix);
+		// Untouched options keep their clap defaults
+		assert_eq!(args.max_entries_cap, BallisticConfig::DEFAULT_MAX_ENTRIES_CAP);
+		assert_eq!(args.overwrite_policy, OverwritePolicy::Overwrite);
+	}
+
+	#[test]
+	fn test_print_config_shows_merged_options() {
+		let dir = TempDir::new("print-config");
+		let config = dir.join("fcsgen.toml");
+		std::fs::write(
+			&config,
+			r#"
+game-path = "/games/WarThunder"
+sensitivity = 0.3
+sensitivity-by-nation = { US = 0.5 }
+jobs = 4
+naval = true
+"#,
+		)
+		.unwrap();
+
+		let matches = RunArgs::augment_args(Command::new("run"))
+			.try_get_matches_from([
+				"run",
+				"--config",
+				config.to_str().unwrap(),
+				"--print-config",
+				"--sensitivity",
+				"0.7",
+				"--pen-round",
+				"5",
+				"--output",
+				"out",
+			])
+			.unwrap();
+		let args = resolve_run_args(RunArgs::from_arg_matches(&matches).unwrap(), &matches);
+		assert!(args.print_config);
+		let printed = resolved_config_toml(&args);
+
+		// The output is itself a valid config file
+		let file: ConfigFile = toml::from_str(&printed).unwrap();
+		assert!(file.unknown.is_empty(), "{printed}");
+		assert_eq!(file.sensitivity, Some(0.7));
+		assert_eq!(file.pen_round, Some(5));
+		assert_eq!(file.output.as_deref(), Some(Path::new("out")));
+		assert_eq!(file.game_path.as_deref(), Some(Path::new("/games/WarThunder")));
+		assert_eq!(file.jobs, Some(4));
+		assert_eq!(file.naval, Some(true));
+		assert_eq!(file.sensitivity_by_nation, Some(BTreeMap::from([("us".to_owned(), 0.5)])));
+		// Defaults are spelled out too
+		assert_eq!(file.gravity, Some(BallisticConfig::STANDARD_GRAVITY));
+		assert_eq!(file.on_collision, Some(CollisionPolicy::Last));
+		assert!(printed.contains("line-ending = \"lf\"\n"), "{printed}");
+	}
+
+	#[test]
+	fn test_wt_user_dir_per_platform() {
+		let env = |vars: &'static [(&str, &str)]| {
+			move |name: &str| {
+				vars.iter().find(|(k, _)| *k == name).map(|(_, v)| OsString::from(v))
+			}
+		};
+		let home = &[("HOME", "/home/me"), ("USERPROFILE", "C:/Users/me")];
+
+		assert_eq!(
+			wt_user_dir(Platform::Windows, env(home)),
+			Some(PathBuf::from("C:/Users/me/Documents/My Games/WarThunder")),
+		);
+		assert_eq!(
+			wt_user_dir(Platform::MacOs, env(home)),
+			Some(PathBuf::from("/home/me/My Games/WarThunder")),
+		);
+		assert_eq!(
+			wt_user_dir(Platform::Unix, env(home)),
+			Some(PathBuf::from("/home/me/.config/WarThunder")),
+		);
+		assert_eq!(
+			wt_user_dir(Platform::Unix, env(&[("HOME", "/home/me"), ("XDG_CONFIG_HOME", "/cfg")])),
+			Some(PathBuf::from("/cfg/WarThunder")),
+		);
+
+		// No (or an empty) home directory: no default
+		for platform in [Platform::Windows, Platform::MacOs, Platform::Unix] {
+			assert_eq!(wt_user_dir(platform, env(&[])), None);
+			assert_eq!(wt_user_dir(platform, env(&[("HOME", ""), ("USERPROFILE", "")])), None);
+		}
+	}
+
+	#[test]
+	fn test_sensitivity_validation() {
+		let parse = |value: &str| {
+			RunArgs::augment_args(Command::new("run"))
+				.try_get_matches_from(["run", "--sensitivity", value])
+				.map(|m| RunArgs::from_arg_matches(&m).unwrap().sensitivity)
+		};
+
+		let err = parse("0").unwrap_err().to_string();
+		assert!(err.contains("sensitivity must be greater than 0"), "{err}");
+		assert!(parse("-0.5").is_err());
+
+		// Above 1 is accepted, with a warning
+		assert!((parse("2.0").unwrap() - 2.0).abs() < f64::EPSILON);
+		assert!(sensitivity_warning(2.0).is_some_and(|w| w.contains("coarse")));
+		assert!(sensitivity_warning(1.0).is_none());
+	}
+
+	#[test]
+	fn test_range_marks_validation() {
+		let parse = |value: &str| {
+			RunArgs::augment_args(Command::new("run"))
+				.try_get_matches_from(["run", "--range-marks", value])
+				.map(|m| RunArgs::from_arg_matches(&m).unwrap().range_marks.unwrap())
+		};
+
+		let marks = parse("200,400,600").unwrap();
+		assert!(marks.iter().copied().eq([200.0, 400.0, 600.0]));
+		assert!(check_range_marks(&marks, true).is_ok());
+		assert!(check_range_marks(&marks, false).is_err(), "needs --with-header");
+		assert!(parse("0,400").is_err());
+		assert!(parse("200,-400").is_err());
+
+		let err = check_range_marks(&[400.0, 200.0], true).unwrap_err();
+		assert!(err.contains("ascending"), "{err}");
+		assert!(check_range_marks(&[400.0, 400.0], true).is_err());
+	}
+
+	#[test]
+	fn test_subdir_names_must_stay_under_output() {
+		for ok in ["Data", "Ballistic_0.45", "my data"] {
+			assert_eq!(parse_subdir(ok).as_deref(), Ok(ok));
+		}
+		for bad in ["", ".", "..", "../Data", "a/b", "a\\b", "/abs"] {
+			assert!(parse_subdir(bad).is_err(), "{bad:?} accepted");
+		}
+	}
+
+	#[test]
+	fn test_parse_nation_sensitivity() {
+		assert_eq!(parse_nation_sensitivity("us=0.5"), Ok(("us".to_owned(), 0.5)));
+		assert_eq!(parse_nation_sensitivity(" USSR = 0.4"), Ok(("ussr".to_owned(), 0.4)));
+		for bad in ["us", "us=", "us=0", "usa=0.5", "us=fast"] {
+			assert!(parse_nation_sensitivity(bad).is_err(), "{bad:?} accepted");
+		}
+	}
+}
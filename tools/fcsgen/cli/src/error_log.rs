@@ -0,0 +1,127 @@
+//! Per-vehicle error output (`run --quiet-errors <N>`).
+//!
+//! A broken extraction can fail the same way for every vehicle, burying the
+//! useful lines under thousands of copies.  Errors are grouped into
+//! categories by their tag and the stable start of their message (see
+//! [`category`]); once `N` errors of a category have been printed, further
+//! ones are only counted, and [`ErrorLog::summary`] gives one
+//! `"{n} more: {category}"` line per collapsed category.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::Mutex;
+
+use crate::log_file::{LogFile, logln};
+
+/// Shared by all work units of a run; errors arrive from rayon threads.
+#[derive(Debug, Default)]
+pub struct ErrorLog {
+	/// Errors printed per category; `None` prints all of them.
+	limit: Option<usize>,
+	/// Errors seen per category, printed or not.
+	counts: Mutex<HashMap<String, usize>>,
+}
+
+impl ErrorLog {
+	pub fn new(limit: Option<usize>) -> Self {
+		Self {
+			limit,
+			counts: Mutex::default(),
+		}
+	}
+
+	/// Print `{tag} {subject}: {message}` to `log`, unless `limit` errors of
+	/// the same category were printed already.
+	pub fn report(&self, log: &LogFile, tag: &str, subject: &str, message: &dyn Display) {
+		let message = message.to_string();
+		if self.admit(&category(tag, &message)) {
+			logln!(log, "{tag} {subject}: {message}");
+		}
+	}
+
+	/// Count an error of `category`, returning whether it should be printed.
+	fn admit(&self, category: &str) -> bool {
+		let mut counts = self.counts.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+		let count = counts.entry(category.to_owned()).or_default();
+		*count += 1;
+		self.limit.is_none_or(|limit| *count <= limit)
+	}
+
+	/// `"{n} more: {category}"` for every category with unprinted errors,
+	/// largest first.
+	pub fn summary(&self) -> Vec<String> {
+		let Some(limit) = self.limit else {
+			return Vec::new();
+		};
+		let counts = self.counts.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+		let mut collapsed: Vec<_> = counts
+			.iter()
+			.filter(|&(_, &count)| count > limit)
+			.map(|(category, &count)| (count - limit, category))
+			.collect();
+		collapsed.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+		collapsed.into_iter().map(|(n, category)| format!("{n} more: {category}")).collect()
+	}
+}
+
+/// `tag` plus the leading purely alphabetic words of `message`, which stop
+/// at the first path, name, number or quote (`failed to read file
+/// Datamine/...` → `failed to read file`).
+fn category(tag: &str, message: &str) -> String {
+	let words: Vec<_> = message
+		.split(' ')
+		.take_while(|word| !word.is_empty() && word.chars().all(|c| c.is_ascii_alphabetic()))
+		.collect();
+	if words.is_empty() {
+		tag.to_owned()
+	} else {
+		format!("{tag} {}", words.join(" "))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_category_stops_at_variable_part() {
+		assert_eq!(
+			category("CONVERT ERROR", "failed to read file Datamine/a.blkx: not found"),
+			"CONVERT ERROR failed to read file",
+		);
+		assert_eq!(
+			category("CONVERT ERROR", "weapon module weapons/30mm_he: bad data"),
+			"CONVERT ERROR weapon module",
+		);
+		assert_eq!(
+			category("WRITE ERROR", "Permission denied (os error 13)"),
+			"WRITE ERROR Permission denied",
+		);
+		assert_eq!(category("PARSE ERROR", "'Speed' is not a number"), "PARSE ERROR");
+		assert_eq!(
+			category("CONVERT ERROR", "not found in datamine"),
+			"CONVERT ERROR not found in datamine",
+		);
+	}
+
+	#[test]
+	fn test_repeated_category_collapsed_with_accurate_count() {
+		let log = ErrorLog::new(Some(3));
+		let printed: Vec<_> = (0..1500)
+			.map(|i| {
+				log.admit(&category(
+					"CONVERT ERROR",
+					&format!("failed to read file Datamine/tank_{i}.blkx: missing"),
+				))
+			})
+			.collect();
+		assert_eq!(printed.iter().filter(|&&p| p).count(), 3);
+		assert!(printed[..3].iter().all(|&p| p));
+
+		// Another category has its own budget
+		assert!(log.admit(&category("WRITE ERROR", "disk full")));
+
+		assert_eq!(log.summary(), ["1497 more: CONVERT ERROR failed to read file"]);
+		assert!(ErrorLog::new(None).summary().is_empty());
+	}
+}
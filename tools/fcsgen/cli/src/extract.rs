@@ -1,25 +1,71 @@
 //! Datamine extraction from War Thunder VROMFS archives.
 //!
 //! Uses the `wt_blk` crate to open `aces.vromfs.bin`, `char.vromfs.bin`, and `lang.vromfs.bin`,
-//! unpack the files we need (tank models, weapons, unittags, localization CSVs), and
+//! unpack the files we need (tank models, weapons, unittags, wpcost, localization CSVs), and
 //! either return them in memory or write them to disk.
 //!
 //! The default mode (`run_extract_in_memory`) keeps aces files in memory
 //! and only writes lang CSVs to disk, avoiding the 150 MB intermediate dump.
 //! Use `--write-datamine` to also persist the full aces extraction.
+//!
+//! [`run_extract_in_memory_with_progress`] reports [`ExtractProgress`] updates
+//! through a callback so a GUI can show progress during the long unpack.
+//!
+//! With `--write-datamine`, digests of the raw archive entries are saved next
+//! to the datamine ([`ENTRY_DIGESTS`]).  The next extraction only BLK-decodes
+//! the tankmodels and weapons whose digest changed and reads the others back
+//! from disk; see [`decode_incremental`].
 
-use std::collections::{HashMap, HashSet};
-use std::io::BufRead;
-use std::path::Path;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
 
-use fcsgen_core::{Datamine, UnittagsMap, parse_unittags_str};
+use fcsgen_core::{
+	Datamine,
+	RankMap,
+	UnittagsMap,
+	datamine_key,
+	parse_unittags_str,
+	parse_wpcost_str,
+};
+use serde::{Deserialize, Serialize};
 use wt_blk::vromf::{BlkOutputFormat, File as VromfFile, VromfUnpacker};
 
+use crate::log_file::{LogFile, logln};
+
 /// Marker filename written to the extraction output directory after a
 /// successful pipeline run.  Contains the WT version string and sensitivity
 /// so we can skip re-processing when nothing has changed.
 pub const VERSION_MARKER: &str = ".fcsgen-version";
 
+/// Directory of the extracted lang CSVs, under the datamine directory.
+pub const LANG_DIR: &str = "lang.vromfs.bin_u";
+
+/// File in the extraction output directory holding the [`EntryDigests`] of
+/// the datamine written with `--write-datamine`.
+pub const ENTRY_DIGESTS: &str = ".fcsgen-entries.json";
+
+/// Shown in place of an archive version that could not be read.
+pub const UNKNOWN_VERSION: &str = "unknown";
+
+/// Version string from [`VromfUnpacker::latest_version`], or the warning to
+/// print when there is none.
+///
+/// Without a version the outputs cannot be matched against the
+/// [`VERSION_MARKER`], so every run re-extracts; the warning says so rather
+/// than letting a placeholder silently mismatch the marker each time.
+pub fn archive_version<V: fmt::Display, E: fmt::Display>(
+	version: Result<Option<V>, E>,
+) -> Result<String, String> {
+	const DISABLED: &str = "version-based freshness caching is disabled and every run re-extracts";
+	match version {
+		Ok(Some(version)) => Ok(version.to_string()),
+		Ok(None) => Err(format!("archive has no version; {DISABLED}")),
+		Err(e) => Err(format!("could not read version from archive ({e}); {DISABLED}")),
+	}
+}
+
 /// Result of an in-memory extraction.
 pub struct ExtractionResult {
 	/// In-memory aces files: normalized path → JSON string.
@@ -30,18 +76,221 @@ pub struct ExtractionResult {
 	/// Sorted list of vehicle stems (without .blkx extension).
 	pub vehicle_names: Vec<String>,
 
-	/// War Thunder version string extracted from the archive metadata.
-	pub version: String,
+	/// War Thunder version string extracted from the archive metadata, or
+	/// `None` if it could not be read (see [`archive_version`]).
+	pub version: Option<String>,
 
 	/// Vehicle ID lookup map: lowercase ID → correctly-cased ID.
 	/// Built from unittags.blkx in char.vromfs.bin.
 	pub unittags: UnittagsMap,
+
+	/// Rank and battle rating by lowercase vehicle ID, from wpcost.blkx in
+	/// char.vromfs.bin.
+	pub ranks: RankMap,
+}
+
+/// Digests of the raw (undecoded) archive entries behind an on-disk datamine.
+///
+/// `wt_blk` exposes no per-entry hashes, so these are computed from the bytes
+/// of [`VromfUnpacker::unpack_all`] without BLK decoding.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct EntryDigests {
+	/// Digest of the shared name map and dictionaries: slim BLK entries
+	/// decode differently when it changes, even if their own bytes do not.
+	shared: u64,
+	/// Digest of each tankmodel and weapon entry, by archive path.
+	entries: BTreeMap<String, u64>,
+}
+
+impl EntryDigests {
+	/// Digests of the `(archive path, raw contents)` pairs in `raw`.
+	fn of(raw: &[(&Path, &[u8])]) -> Self {
+		let mut digests = Self {
+			shared: FNV_OFFSET,
+			..Self::default()
+		};
+		for &(path, buf) in raw {
+			if is_shared_entry(path) {
+				let path = path.to_string_lossy();
+				digests.shared = fnv1a(fnv1a(digests.shared, path.as_bytes()), buf);
+			} else if is_datamine_entry(path) {
+				let digest = fnv1a(FNV_OFFSET, buf);
+				digests.entries.insert(path.to_string_lossy().into_owned(), digest);
+			}
+		}
+		digests
+	}
+}
+
+pub(crate) const FNV_OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+
+/// 64-bit FNV-1a of `bytes`, continuing from `hash`.  Unlike
+/// `DefaultHasher`, stable across Rust versions, so digests can be saved.
+pub(crate) fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+	bytes.iter().fold(hash, |hash, &b| (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01B3))
+}
+
+/// Archive entries that every slim BLK entry depends on.
+fn is_shared_entry(path: &Path) -> bool {
+	path.file_name().is_some_and(|name| name == "nm")
+		|| path.extension().is_some_and(|ext| ext == "dict")
+}
+
+/// Entries kept in the datamine: top-level tankmodels and weapons.
+fn is_datamine_entry(path: &Path) -> bool {
+	tankmodel_stem(path).is_some() || path.starts_with(WEAPONS_PREFIX)
+}
+
+/// Path of a datamine entry under `aces.vromfs.bin_u`: `.blk` becomes `.blkx`.
+fn blkx_path(path: &Path) -> PathBuf {
+	if path.extension().is_some_and(|ext| ext == "blk") {
+		path.with_extension("blkx")
+	} else {
+		path.to_path_buf()
+	}
+}
+
+/// Entries of [`decode_incremental`], decoded or read from the datamine.
+struct IncrementalDecode {
+	/// `(archive path, decoded contents)` of every datamine entry.
+	files: Vec<(PathBuf, Vec<u8>)>,
+	/// Entries that had to be decoded.
+	decoded: usize,
+}
+
+/// Decode the datamine entries of `raw`, taking those whose digest in
+/// `current` matches `previous` from `cached` (the datamine on disk) and
+/// decoding the rest with `decode`.
+///
+/// The caller must check that the shared digests match: otherwise every
+/// entry has to be decoded, which is faster with a single full unpack.
+fn decode_incremental(
+	raw: &[(&Path, &[u8])],
+	current: &EntryDigests,
+	previous: &EntryDigests,
+	cached: impl Fn(&Path) -> Option<Vec<u8>>,
+	decode: impl Fn(&Path) -> Result<Vec<u8>, String>,
+) -> Result<IncrementalDecode, String> {
+	let mut result = IncrementalDecode {
+		files: Vec::new(),
+		decoded: 0,
+	};
+	for &(path, _) in raw {
+		let key = path.to_string_lossy();
+		let Some(digest) = current.entries.get(key.as_ref()) else {
+			continue;
+		};
+		let unchanged = previous.entries.get(key.as_ref()) == Some(digest);
+		let buf = if let Some(buf) = unchanged.then(|| cached(path)).flatten() {
+			buf
+		} else {
+			result.decoded += 1;
+			decode(path).map_err(|e| format!("{key}: {e}"))?
+		};
+		result.files.push((path.to_path_buf(), buf));
+	}
+	Ok(result)
+}
+
+/// Aces archive entries decoded to JSON by [`unpack_aces`].
+struct UnpackedAces {
+	/// Every archive file, from a full unpack (empty if incremental).
+	full: Vec<VromfFile>,
+	/// Datamine entries only, from [`decode_incremental`].
+	incremental: Option<IncrementalDecode>,
+	/// Digests of the raw entries (`write_datamine` only).
+	digests: Option<EntryDigests>,
+}
+
+impl UnpackedAces {
+	/// `(archive path, decoded contents)` pairs, in archive order.
+	fn files(&self) -> Vec<(&Path, &[u8])> {
+		match &self.incremental {
+			Some(incremental) => {
+				let files = incremental.files.iter();
+				files.map(|(path, buf)| (path.as_path(), buf.as_slice())).collect()
+			},
+			None => self.full.iter().map(|file| (file.path(), file.buf())).collect(),
+		}
+	}
+}
+
+/// Load the [`EntryDigests`] saved by the previous extraction, if any.
+fn load_entry_digests(path: &Path, log: &LogFile) -> Option<EntryDigests> {
+	let text = std::fs::read_to_string(path).ok()?;
+	serde_json::from_str(&text)
+		.inspect_err(|e| logln!(log, "Warning: ignoring {}: {e}", path.display()))
+		.ok()
+}
+
+/// Stage of an in-memory extraction, reported via [`ExtractProgress`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtractPhase {
+	/// Opening and parsing `aces.vromfs.bin`.
+	Opening,
+	/// Decompressing and BLK-decoding the aces archive.
+	Unpacking,
+	/// Selecting tankmodel and weapon files from the unpacked archive.
+	Filtering,
+	/// Extracting localization CSVs from `lang.vromfs.bin`.
+	Lang,
+}
+
+/// Progress update passed to the [`run_extract_in_memory_with_progress`]
+/// callback.
+///
+/// Each phase starts at `processed == 0` and ends at `processed == total`.
+/// Single-step phases (opening, unpacking) use a `total` of 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtractProgress {
+	pub phase: ExtractPhase,
+	pub processed: usize,
+	pub total: usize,
+}
+
+impl fmt::Display for ExtractProgress {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let phase = match self.phase {
+			ExtractPhase::Opening => "opening",
+			ExtractPhase::Unpacking => "unpacking",
+			ExtractPhase::Filtering => "filtering",
+			ExtractPhase::Lang => "lang",
+		};
+		write!(f, "{phase} {}/{}", self.processed, self.total)
+	}
+}
+
+/// Aces files kept by [`collect_aces_files`].
+#[derive(Default)]
+struct AcesSelection {
+	datamine: Datamine,
+	/// Vehicle stems in archive order.
+	vehicle_names: Vec<String>,
+	/// Tankmodel `.blkx` filenames written to disk (`write_datamine` only).
+	written_tankmodels: HashSet<String>,
+	weapon_count: u32,
+	/// Keys of files stored with invalid UTF-8 replaced; see [`decode_lossy`].
+	lossy_files: Vec<String>,
+}
+
+impl AcesSelection {
+	/// Store `buf` under `key`, warning if it is not valid UTF-8.
+	fn insert(&mut self, key: String, buf: &[u8], log: &LogFile) {
+		let (content, lossy) = decode_lossy(buf);
+		if lossy {
+			logln!(log, "Warning: {key}: invalid UTF-8 replaced with U+FFFD (corrupt extraction?)");
+			self.lossy_files.push(key.clone());
+		}
+		self.datamine.insert(key, content);
+	}
 }
 
 /// Extract datamine into memory, only writing lang CSVs to disk.
 ///
 /// If `write_datamine` is true, also writes all aces .blkx files to disk
-/// (matching the old behaviour for debugging/testing).
+/// (matching the old behaviour for debugging/testing).  If `lang` is false
+/// (`--no-lang`), `lang.vromfs.bin` is neither required nor opened and no
+/// CSVs are written.
 ///
 /// Returns an [`ExtractionResult`] with all aces files in memory.
 pub fn run_extract_in_memory(
@@ -49,67 +298,287 @@ pub fn run_extract_in_memory(
 	output: &Path,
 	ignore_file: Option<&Path>,
 	write_datamine: bool,
+	lang: bool,
+	log: &LogFile,
+) -> ExtractionResult {
+	run_extract_in_memory_with_progress(
+		game_path,
+		output,
+		ignore_file,
+		write_datamine,
+		lang,
+		log,
+		|_| {},
+	)
+}
+
+/// Same as [`run_extract_in_memory`], calling `progress` as each
+/// [`ExtractPhase`] advances.
+pub fn run_extract_in_memory_with_progress(
+	game_path: &Path,
+	output: &Path,
+	ignore_file: Option<&Path>,
+	write_datamine: bool,
+	lang: bool,
+	log: &LogFile,
+	mut progress: impl FnMut(ExtractProgress),
 ) -> ExtractionResult {
 	// --- Validate archive paths ---
 	let aces_bin = game_path.join("aces.vromfs.bin");
-	let lang_bin = game_path.join("lang.vromfs.bin");
 
-	if !aces_bin.exists() {
-		eprintln!("Error: aces.vromfs.bin not found at {aces_bin:?}");
-		eprintln!("Make sure the path points to the War Thunder installation directory.");
-		std::process::exit(1);
-	}
-	if !lang_bin.exists() {
-		eprintln!("Error: lang.vromfs.bin not found at {lang_bin:?}");
-		eprintln!("Make sure the path points to the War Thunder installation directory.");
+	if let Some(missing) = missing_archive(game_path, lang) {
+		logln!(log, "Error: {} not found", missing.display());
+		logln!(log, "Make sure the path points to the War Thunder installation directory.");
 		std::process::exit(1);
 	}
 
 	// --- Open aces archive ---
+	progress(ExtractProgress {
+		phase: ExtractPhase::Opening,
+		processed: 0,
+		total: 1,
+	});
 	let aces_file = VromfFile::new(&aces_bin).unwrap_or_else(|e| {
-		eprintln!("Error: failed to read {aces_bin:?}: {e}");
+		logln!(log, "Error: failed to read {aces_bin:?}: {e}");
 		std::process::exit(1);
 	});
 	let aces_unpacker = VromfUnpacker::from_file(&aces_file, false).unwrap_or_else(|e| {
-		eprintln!("Error: failed to parse {aces_bin:?}: {e}");
+		logln!(log, "Error: failed to parse {aces_bin:?}: {e}");
 		std::process::exit(1);
 	});
 
 	// --- Version check ---
-	let version = aces_unpacker.latest_version().unwrap_or_else(|e| {
-		eprintln!("Warning: could not read version from archive: {e}");
-		None
-	});
+	let version = archive_version(aces_unpacker.latest_version())
+		.inspect_err(|warning| logln!(log, "Warning: {warning}"))
+		.ok();
+	let version_str = version.as_deref().unwrap_or(UNKNOWN_VERSION);
 
-	let version_str = version.map_or_else(|| "unknown".to_owned(), |v| v.to_string());
+	progress(ExtractProgress {
+		phase: ExtractPhase::Opening,
+		processed: 1,
+		total: 1,
+	});
 
-	eprintln!("Extracting datamine (version {version_str})...");
+	logln!(log, "Extracting datamine (version {version_str})...");
 
 	// --- Load ignore list ---
-	let ignore_set = ignore_file.map_or_else(HashSet::new, load_ignore_list);
+	let ignore_set = ignore_file.map_or_else(HashSet::new, |path| load_ignore_list(path, log));
 
 	// --- Unpack aces archive ---
-	let aces_files = aces_unpacker
-		.unpack_all(Some(BlkOutputFormat::Json), false)
-		.unwrap_or_else(|e| {
-			eprintln!("Error: failed to unpack {aces_bin:?}: {e}");
-			std::process::exit(1);
-		});
+	progress(ExtractProgress {
+		phase: ExtractPhase::Unpacking,
+		processed: 0,
+		total: 1,
+	});
+	let unpacked = unpack_aces(&aces_unpacker, &aces_bin, output, write_datamine, log);
+	progress(ExtractProgress {
+		phase: ExtractPhase::Unpacking,
+		processed: 1,
+		total: 1,
+	});
 
 	// --- Filter and collect aces files ---
 	let aces_root = output.join("aces.vromfs.bin_u");
+	let tankmodels_prefix = Path::new(TANKMODELS_PREFIX);
 
-	let tankmodels_prefix = Path::new("gamedata/units/tankmodels");
-	let weapons_prefix = Path::new("gamedata/weapons/groundmodels_weapons");
+	let AcesSelection {
+		datamine,
+		mut vehicle_names,
+		written_tankmodels,
+		weapon_count,
+		lossy_files,
+	} = collect_aces_files(
+		unpacked.files().into_iter(),
+		&ignore_set,
+		write_datamine.then_some(aces_root.as_path()),
+		log,
+		&mut progress,
+	);
+	let tankmodel_count = vehicle_names.len();
 
-	let mut datamine: Datamine = HashMap::new();
-	let mut vehicle_names: Vec<String> = Vec::new();
-	let mut written_tankmodels: HashSet<String> = HashSet::new();
-	let mut tankmodel_count: u32 = 0;
-	let mut weapon_count: u32 = 0;
+	// Delete stale tankmodel files on disk when writing
+	if write_datamine {
+		let tankmodels_dir = aces_root.join(tankmodels_prefix);
+		if tankmodels_dir.is_dir()
+			&& let Ok(entries) = std::fs::read_dir(&tankmodels_dir)
+		{
+			for entry in entries.filter_map(Result::ok) {
+				let name = entry.file_name().to_string_lossy().into_owned();
+				if name.ends_with(".blkx") && !written_tankmodels.contains(&name) {
+					let _ = std::fs::remove_file(entry.path());
+				}
+			}
+		}
 
-	for file in &aces_files {
-		let file_path = file.path();
+		// Saved only once the datamine matches them
+		save_entry_digests(&output.join(ENTRY_DIGESTS), unpacked.digests.as_ref(), log);
+	}
+
+	// Sort vehicle names for deterministic processing order
+	vehicle_names.sort();
+
+	// --- Extract char archive for unittags and ranks ---
+	let CharConfigs { unittags, ranks } = extract_char(game_path, log);
+
+	// --- Extract lang archive ---
+	if lang {
+		extract_lang(game_path, output, log, &mut progress);
+	} else {
+		logln!(log, "Skipping lang CSVs (--no-lang)");
+	}
+
+	logln!(
+		log,
+		"Extracted {tankmodel_count} tankmodels, {weapon_count} weapons (version {version_str})"
+	);
+	if !lossy_files.is_empty() {
+		logln!(
+			log,
+			"Warning: {} extracted files were not valid UTF-8 (listed above)",
+			lossy_files.len(),
+		);
+	}
+
+	ExtractionResult {
+		datamine,
+		vehicle_names,
+		version,
+		unittags,
+		ranks,
+	}
+}
+
+/// The first archive an extraction needs that is missing from `game_path`:
+/// `aces.vromfs.bin`, then `lang.vromfs.bin` unless `lang` is false.
+fn missing_archive(game_path: &Path, lang: bool) -> Option<PathBuf> {
+	let archives: &[&str] = if lang {
+		&["aces.vromfs.bin", "lang.vromfs.bin"]
+	} else {
+		&["aces.vromfs.bin"]
+	};
+	archives.iter().map(|name| game_path.join(name)).find(|path| !path.exists())
+}
+
+/// Unpack the aces archive, BLK-decoding to JSON.
+///
+/// With `write_datamine`, digests of the raw entries tell which ones a patch
+/// changed since the datamine in `output` was written, and only those are
+/// decoded.  Without saved digests, or if the name map changed or an entry
+/// fails to decode alone, the whole archive is decoded.
+fn unpack_aces(
+	unpacker: &VromfUnpacker,
+	aces_bin: &Path,
+	output: &Path,
+	write_datamine: bool,
+	log: &LogFile,
+) -> UnpackedAces {
+	let raw_files = if write_datamine {
+		unpacker
+			.unpack_all(None, false)
+			.inspect_err(|e| {
+				logln!(log, "Warning: cannot list raw entries of {}: {e}", aces_bin.display());
+			})
+			.ok()
+	} else {
+		None
+	};
+	let raw: Option<Vec<(&Path, &[u8])>> =
+		raw_files.as_ref().map(|files| files.iter().map(|f| (f.path(), f.buf())).collect());
+	let digests = raw.as_deref().map(EntryDigests::of);
+
+	let aces_root = output.join("aces.vromfs.bin_u");
+	let mut incremental = None;
+	if let (Some(raw), Some(current)) = (&raw, &digests)
+		&& let Some(previous) = load_entry_digests(&output.join(ENTRY_DIGESTS), log)
+		&& previous.shared == current.shared
+	{
+		incremental = decode_incremental(
+			raw,
+			current,
+			&previous,
+			|path| std::fs::read(aces_root.join(blkx_path(path))).ok(),
+			|path| {
+				unpacker
+					.unpack_one(path, Some(BlkOutputFormat::Json), false)
+					.map_err(|e| e.to_string())
+			},
+		)
+		.inspect_err(|e| {
+			logln!(log, "Warning: incremental extraction failed ({e}); decoding all entries");
+		})
+		.ok();
+	}
+
+	let full = if let Some(incremental) = &incremental {
+		logln!(
+			log,
+			"Decoded {} of {} datamine entries; the rest are unchanged since the last extraction",
+			incremental.decoded,
+			incremental.files.len(),
+		);
+		Vec::new()
+	} else {
+		unpacker.unpack_all(Some(BlkOutputFormat::Json), false).unwrap_or_else(|e| {
+			logln!(log, "Error: failed to unpack {aces_bin:?}: {e}");
+			std::process::exit(1);
+		})
+	};
+	UnpackedAces {
+		full,
+		incremental,
+		digests,
+	}
+}
+
+/// Save `digests` for the next extraction, or remove stale ones if there are
+/// none: the next extraction then decodes everything.
+fn save_entry_digests(path: &Path, digests: Option<&EntryDigests>, log: &LogFile) {
+	let Some(digests) = digests else {
+		let _ = std::fs::remove_file(path);
+		return;
+	};
+	let saved = serde_json::to_string(digests)
+		.map_err(std::io::Error::other)
+		.and_then(|json| std::fs::write(path, json));
+	if let Err(e) = saved {
+		logln!(log, "Warning: cannot write {}: {e}", path.display());
+	}
+}
+
+/// Archive directory of the top-level tankmodels.
+const TANKMODELS_PREFIX: &str = "gamedata/units/tankmodels";
+
+/// Archive directory of ground vehicle weapons.
+const WEAPONS_PREFIX: &str = "gamedata/weapons/groundmodels_weapons";
+
+/// Select tankmodel and weapon files from the unpacked aces archive.
+///
+/// `files` yields `(archive path, contents)` pairs.  When `disk_root` is set,
+/// every kept file is also written under it.
+fn collect_aces_files<'a>(
+	files: impl ExactSizeIterator<Item = (&'a Path, &'a [u8])>,
+	ignore_set: &HashSet<String>,
+	disk_root: Option<&Path>,
+	log: &LogFile,
+	progress: &mut dyn FnMut(ExtractProgress),
+) -> AcesSelection {
+	let tankmodels_prefix = Path::new(TANKMODELS_PREFIX);
+	let weapons_prefix = Path::new(WEAPONS_PREFIX);
+
+	let mut selection = AcesSelection::default();
+	let total = files.len();
+	progress(ExtractProgress {
+		phase: ExtractPhase::Filtering,
+		processed: 0,
+		total,
+	});
+
+	for (i, (file_path, buf)) in files.enumerate() {
+		progress(ExtractProgress {
+			phase: ExtractPhase::Filtering,
+			processed: i + 1,
+			total,
+		});
 
 		// tankmodels: top-level .blk files only (no subdirectories)
 		if let Ok(rel) = file_path.strip_prefix(tankmodels_prefix) {
@@ -137,93 +606,53 @@ pub fn run_extract_in_memory(
 			);
 
 			// Store in memory
-			let content = String::from_utf8_lossy(file.buf()).into_owned();
-			datamine.insert(key, content);
-			vehicle_names.push(stem.to_string());
+			selection.insert(key, buf, log);
+			selection.vehicle_names.push(stem.to_string());
 
 			// Optionally write to disk
-			if write_datamine {
-				let dest = aces_root.join(tankmodels_prefix).join(&blkx_filename);
-				write_file(&dest, file.buf());
-				written_tankmodels.insert(blkx_filename);
+			if let Some(root) = disk_root {
+				let dest = root.join(tankmodels_prefix).join(&blkx_filename);
+				write_file(&dest, buf, log);
+				selection.written_tankmodels.insert(blkx_filename);
 			}
 
-			tankmodel_count += 1;
 			continue;
 		}
 
 		// weapons: all files under groundmodels_weapons
 		if file_path.starts_with(weapons_prefix) {
-			// Normalized key: lowercase path with .blkx extension
-			let key_path = if file_path.extension().is_some_and(|ext| ext == "blk") {
-				file_path.with_extension("blkx")
-			} else {
-				file_path.to_path_buf()
-			};
-			let key = key_path
-				.to_string_lossy()
-				.replace('\\', "/")
-				.to_lowercase();
+			// Canonical key (see `datamine_key`) with .blkx extension
+			let key_path = blkx_path(file_path);
+			let key = datamine_key(&key_path.to_string_lossy());
 
 			// Store in memory
-			let content = String::from_utf8_lossy(file.buf()).into_owned();
-			datamine.insert(key, content);
+			selection.insert(key, buf, log);
 
 			// Optionally write to disk
-			if write_datamine {
-				let dest = aces_root.join(&key_path);
-				write_file(&dest, file.buf());
+			if let Some(root) = disk_root {
+				let dest = root.join(&key_path);
+				write_file(&dest, buf, log);
 			}
 
-			weapon_count += 1;
+			selection.weapon_count += 1;
 		}
 	}
 
-	// Delete stale tankmodel files on disk when writing
-	if write_datamine {
-		let tankmodels_dir = aces_root.join(tankmodels_prefix);
-		if tankmodels_dir.is_dir()
-			&& let Ok(entries) = std::fs::read_dir(&tankmodels_dir)
-		{
-			for entry in entries.filter_map(Result::ok) {
-				let name = entry.file_name().to_string_lossy().into_owned();
-				if name.ends_with(".blkx") && !written_tankmodels.contains(&name) {
-					let _ = std::fs::remove_file(entry.path());
-				}
-			}
-		}
-	}
-
-	// Sort vehicle names for deterministic processing order
-	vehicle_names.sort();
-
-	// --- Extract char archive for unittags ---
-	let unittags = extract_unittags(game_path);
-
-	// --- Extract lang archive ---
-	extract_lang(game_path, output);
-
-	eprintln!(
-		"Extracted {tankmodel_count} tankmodels, {weapon_count} weapons (version {version_str})"
-	);
-
-	ExtractionResult {
-		datamine,
-		vehicle_names,
-		version: version_str,
-		unittags,
-	}
+	selection
 }
 
 /// Run the full extraction pipeline, writing all files to disk.
 ///
 /// This is the legacy behaviour used by the standalone `extract` subcommand
-/// and `--write-datamine` mode.
+/// and `--write-datamine` mode.  The version marker covers the aces archive
+/// only: an up-to-date datamine missing the lang CSVs that `lang` asks for
+/// (e.g. after `--no-lang`) gets just those extracted.
 pub fn run_extract(
 	game_path: &Path,
 	output: &Path,
 	ignore_file: Option<&Path>,
 	force: bool,
+	lang: bool,
 ) {
 	// --- Validate archive paths ---
 	let aces_bin = game_path.join("aces.vromfs.bin");
@@ -244,37 +673,86 @@ pub fn run_extract(
 		std::process::exit(1);
 	});
 
-	let version = aces_unpacker.latest_version().unwrap_or_else(|e| {
-		eprintln!("Warning: could not read version from archive: {e}");
-		None
-	});
-
-	let version_str = version.map_or_else(|| "unknown".to_owned(), |v| v.to_string());
-
+	// Without a version there is nothing to compare; the extraction below
+	// warns about it
 	let marker_path = output.join(VERSION_MARKER);
-	if !force {
+	if !force && let Ok(version) = archive_version(aces_unpacker.latest_version()) {
 		if let Ok(cached) = std::fs::read_to_string(&marker_path) {
-			if cached.trim() == version_str {
-				eprintln!("Already up-to-date (version {version_str})");
+			if cached.trim() == version {
+				eprintln!("Already up-to-date (version {version})");
+				if lang && !output.join(LANG_DIR).is_dir() {
+					extract_lang(game_path, output, &LogFile::default(), &mut |_| {});
+				}
 				return;
 			}
 		}
 	}
 
 	// Full extraction with disk writes
-	run_extract_in_memory(game_path, output, ignore_file, true);
+	run_extract_in_memory(game_path, output, ignore_file, true, lang, &LogFile::default());
+}
+
+/// List the vehicle stems currently present in `aces.vromfs.bin`.
+///
+/// Only the archive's file table is read (no BLK decoding), so this is much
+/// cheaper than a full extraction.  Stems are returned lowercase and sorted;
+/// the ignore list is not applied.
+pub fn list_vehicle_names(game_path: &Path) -> Vec<String> {
+	let aces_bin = game_path.join("aces.vromfs.bin");
+
+	if !aces_bin.exists() {
+		eprintln!("Error: aces.vromfs.bin not found at {}", aces_bin.display());
+		eprintln!("Make sure the path points to the War Thunder installation directory.");
+		std::process::exit(1);
+	}
+
+	let aces_file = VromfFile::new(&aces_bin).unwrap_or_else(|e| {
+		eprintln!("Error: failed to read {}: {e}", aces_bin.display());
+		std::process::exit(1);
+	});
+	let aces_unpacker = VromfUnpacker::from_file(&aces_file, false).unwrap_or_else(|e| {
+		eprintln!("Error: failed to parse {}: {e}", aces_bin.display());
+		std::process::exit(1);
+	});
+	let aces_files = aces_unpacker.unpack_all(None, false).unwrap_or_else(|e| {
+		eprintln!("Error: failed to unpack {}: {e}", aces_bin.display());
+		std::process::exit(1);
+	});
+
+	let mut names: Vec<String> =
+		aces_files.iter().filter_map(|file| tankmodel_stem(file.path())).collect();
+
+	names.sort();
+	names
+}
+
+/// Lowercase vehicle stem of an archive path under `gamedata/units/tankmodels`.
+///
+/// Top-level `.blk` files only (same rule as the full extraction).
+pub fn tankmodel_stem(path: &Path) -> Option<String> {
+	let rel = path.strip_prefix(TANKMODELS_PREFIX).ok()?;
+	if rel.parent().is_some_and(|p| p != Path::new("")) {
+		return None;
+	}
+	let filename = rel.to_string_lossy();
+	filename.strip_suffix(".blk").map(str::to_lowercase)
 }
 
 /// Extract lang CSVs from lang.vromfs.bin.
-fn extract_lang(game_path: &Path, output: &Path) {
+fn extract_lang(
+	game_path: &Path,
+	output: &Path,
+	log: &LogFile,
+	progress: &mut dyn FnMut(ExtractProgress),
+) {
 	let lang_bin = game_path.join("lang.vromfs.bin");
 
 	let lang_file = VromfFile::new(&lang_bin).unwrap_or_else(|e| {
-		eprintln!("Error: failed to read {lang_bin:?}: {e}");
+		logln!(log, "Error: failed to read {lang_bin:?}: {e}");
 		std::process::exit(1);
 	});
 	let lang_unpacker = VromfUnpacker::from_file(&lang_file, false).unwrap_or_else(|e| {
-		eprintln!("Error: failed to parse {lang_bin:?}: {e}");
+		logln!(log, "Error: failed to parse {lang_bin:?}: {e}");
 		std::process::exit(1);
 	});
 
@@ -282,15 +760,26 @@ fn extract_lang(game_path: &Path, output: &Path) {
 	let lang_files = lang_unpacker
 		.unpack_all(None, false)
 		.unwrap_or_else(|e| {
-			eprintln!("Error: failed to unpack {lang_bin:?}: {e}");
+			logln!(log, "Error: failed to unpack {lang_bin:?}: {e}");
 			std::process::exit(1);
 		});
 
 	let mut lang_count: u32 = 0;
 	let lang_targets: [&str; 2] = ["lang/units.csv", "lang/units_weaponry.csv"];
-	let lang_root = output.join("lang.vromfs.bin_u");
+	let lang_root = output.join(LANG_DIR);
+	let total = lang_files.len();
+	progress(ExtractProgress {
+		phase: ExtractPhase::Lang,
+		processed: 0,
+		total,
+	});
 
-	for file in &lang_files {
+	for (i, file) in lang_files.iter().enumerate() {
+		progress(ExtractProgress {
+			phase: ExtractPhase::Lang,
+			processed: i + 1,
+			total,
+		});
 		let file_path = file.path();
 		let path_str = file_path.to_string_lossy();
 
@@ -300,13 +789,22 @@ fn extract_lang(game_path: &Path, output: &Path) {
 		for target in &lang_targets {
 			if normalized == *target {
 				let dest = lang_root.join(target);
-				write_file(&dest, file.buf());
+				write_file(&dest, file.buf(), log);
 				lang_count += 1;
 			}
 		}
 	}
 
-	eprintln!("Extracted {lang_count} lang files");
+	logln!(log, "Extracted {lang_count} lang files");
+}
+
+/// Configs read from char.vromfs.bin by [`extract_char`].
+#[derive(Default)]
+pub struct CharConfigs {
+	/// Vehicle ID lookup map: lowercase ID → correctly-cased ID.
+	pub unittags: UnittagsMap,
+	/// Rank and battle rating by lowercase vehicle ID, from wpcost.blkx.
+	pub ranks: RankMap,
 }
 
 /// Extract unittags.blkx from char.vromfs.bin and build vehicle ID lookup map.
@@ -314,27 +812,35 @@ fn extract_lang(game_path: &Path, output: &Path) {
 /// The unittags file contains all vehicle IDs with their correct casing,
 /// which is required for War Thunder's case-sensitive UserSights folder matching.
 pub fn extract_unittags(game_path: &Path) -> UnittagsMap {
+	extract_char(game_path, &LogFile::default()).unittags
+}
+
+/// Extract unittags.blkx and wpcost.blkx from char.vromfs.bin.
+///
+/// Either map is left empty, with a warning, if its file is missing or
+/// invalid.
+pub fn extract_char(game_path: &Path, log: &LogFile) -> CharConfigs {
 	let char_bin = game_path.join("char.vromfs.bin");
 
 	if !char_bin.exists() {
-		eprintln!("Warning: char.vromfs.bin not found at {char_bin:?}");
-		eprintln!("Vehicle ID casing may be incorrect.");
-		return UnittagsMap::new();
+		logln!(log, "Warning: char.vromfs.bin not found at {char_bin:?}");
+		logln!(log, "Vehicle ID casing may be incorrect.");
+		return CharConfigs::default();
 	}
 
 	let char_file = match VromfFile::new(&char_bin) {
 		Ok(f) => f,
 		Err(e) => {
-			eprintln!("Warning: failed to read {char_bin:?}: {e}");
-			return UnittagsMap::new();
+			logln!(log, "Warning: failed to read {char_bin:?}: {e}");
+			return CharConfigs::default();
 		},
 	};
 
 	let char_unpacker = match VromfUnpacker::from_file(&char_file, false) {
 		Ok(u) => u,
 		Err(e) => {
-			eprintln!("Warning: failed to parse {char_bin:?}: {e}");
-			return UnittagsMap::new();
+			logln!(log, "Warning: failed to parse {char_bin:?}: {e}");
+			return CharConfigs::default();
 		},
 	};
 
@@ -342,77 +848,242 @@ pub fn extract_unittags(game_path: &Path) -> UnittagsMap {
 	let char_files = match char_unpacker.unpack_all(Some(BlkOutputFormat::Json), false) {
 		Ok(files) => files,
 		Err(e) => {
-			eprintln!("Warning: failed to unpack {char_bin:?}: {e}");
-			return UnittagsMap::new();
+			logln!(log, "Warning: failed to unpack {char_bin:?}: {e}");
+			return CharConfigs::default();
 		},
 	};
 
-	// Find and parse config/unittags.blk
-	let target_path = "config/unittags.blk";
-	for file in &char_files {
-		let file_path = file.path();
-		let path_str = file_path.to_string_lossy().replace('\\', "/");
+	let read = |target_path: &str| {
+		let file = char_files
+			.iter()
+			.find(|file| file.path().to_string_lossy().replace('\\', "/") == target_path);
+		let Some(file) = file else {
+			logln!(log, "Warning: {target_path} not found in char.vromfs.bin");
+			return None;
+		};
+		let (content, lossy) = decode_lossy(file.buf());
+		if lossy {
+			logln!(log, "Warning: {target_path}: invalid UTF-8 replaced with U+FFFD");
+		}
+		Some(content)
+	};
 
-		if path_str == target_path {
-			let content = String::from_utf8_lossy(file.buf());
-			if let Some(map) = parse_unittags_str(&content) {
-				eprintln!("Loaded {} vehicle IDs from unittags", map.len());
-				return map;
-			} else {
-				eprintln!("Warning: failed to parse unittags.blk");
-				return UnittagsMap::new();
-			}
+	let mut configs = CharConfigs::default();
+	if let Some(content) = read("config/unittags.blk") {
+		if let Some(map) = parse_unittags_str(&content) {
+			logln!(log, "Loaded {} vehicle IDs from unittags", map.len());
+			configs.unittags = map;
+		} else {
+			logln!(log, "Warning: failed to parse unittags.blk");
 		}
 	}
+	if let Some(content) = read("config/wpcost.blk") {
+		if let Some(map) = parse_wpcost_str(&content) {
+			configs.ranks = map;
+		} else {
+			logln!(log, "Warning: failed to parse wpcost.blk");
+		}
+	}
+	configs
+}
 
-	eprintln!("Warning: config/unittags.blk not found in char.vromfs.bin");
-	UnittagsMap::new()
+/// Decode datamine bytes as UTF-8, replacing invalid sequences with U+FFFD.
+///
+/// Also returns whether anything was replaced: a mangled shell name would
+/// otherwise pass silently into output filenames.
+fn decode_lossy(buf: &[u8]) -> (String, bool) {
+	match String::from_utf8_lossy(buf) {
+		Cow::Borrowed(content) => (content.to_owned(), false),
+		Cow::Owned(content) => (content, true),
+	}
 }
 
 /// Write `data` to `path`, creating parent directories as needed.
-fn write_file(path: &Path, data: &[u8]) {
+fn write_file(path: &Path, data: &[u8], log: &LogFile) {
     if let Some(parent) = path.parent() {
         if let Err(e) = std::fs::create_dir_all(parent) {
-            eprintln!("Error: cannot create directory {parent:?}: {e}");
+            logln!(log, "Error: cannot create directory {parent:?}: {e}");
             std::process::exit(1);
         }
     }
     if let Err(e) = std::fs::write(path, data) {
-        eprintln!("Error: cannot write {path:?}: {e}");
+        logln!(log, "Error: cannot write {path:?}: {e}");
         std::process::exit(1);
     }
 }
 
-/// Load a vehicle ignore list from a file.
+/// Load a vehicle ignore list from a file (see [`parse_vehicle_list`]).
+fn load_ignore_list(path: &Path, log: &LogFile) -> HashSet<String> {
+	match std::fs::read_to_string(path) {
+		Ok(text) => parse_vehicle_list(&text).collect(),
+		Err(e) => {
+			logln!(log, "Warning: cannot read ignore file {path:?}: {e}");
+			HashSet::new()
+		},
+	}
+}
+
+/// Parse a vehicle list (`--ignore-file`, `--vehicle-file`).
 ///
 /// Each line is a vehicle ID (optionally quoted, with `#` comment lines).
-/// Returns a set of vehicle IDs (lowercased, quotes and `.blkx` extension stripped).
-fn load_ignore_list(path: &Path) -> HashSet<String> {
-    let file = match std::fs::File::open(path) {
-        Ok(f) => f,
-        Err(e) => {
-            eprintln!("Warning: cannot read ignore file {path:?}: {e}");
-            return HashSet::new();
-        },
-    };
-
-    let reader = std::io::BufReader::new(file);
-    reader
-        .lines()
-        .filter_map(Result::ok)
-        .map(|line| line.trim().to_owned())
-        .filter(|line| !line.is_empty() && !line.starts_with('#'))
-        .map(|line| {
-            // Strip surrounding quotes (legacy format compat)
-            if line.starts_with('"') && line.ends_with('"') && line.len() >= 2 {
-                line[1..line.len() - 1].to_owned()
-            } else {
-                line
-            }
-        })
-        // Strip .blkx extension if present (legacy format compat)
-        .map(|s| s.strip_suffix(".blkx").unwrap_or(&s).to_owned())
-        // Lowercase for case-insensitive matching (archive names are lowercase)
-        .map(|s| s.to_lowercase())
-        .collect()
+/// Yields vehicle IDs lowercased, quotes and `.blkx` extension stripped.
+pub fn parse_vehicle_list(text: &str) -> impl Iterator<Item = String> + '_ {
+	text.lines()
+		.map(str::trim)
+		.filter(|line| !line.is_empty() && !line.starts_with('#'))
+		.map(|line| {
+			// Strip surrounding quotes (legacy format compat)
+			if line.starts_with('"') && line.ends_with('"') && line.len() >= 2 {
+				&line[1..line.len() - 1]
+			} else {
+				line
+			}
+		})
+		// Strip .blkx extension if present (legacy format compat)
+		.map(|s| s.strip_suffix(".blkx").unwrap_or(s))
+		// Lowercase for case-insensitive matching (archive names are lowercase)
+		.map(str::to_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::test_util::TempDir;
+	use super::*;
+
+	#[test]
+	fn test_filtering_progress_reaches_total() {
+		let files: Vec<(&Path, &[u8])> = vec![
+			(Path::new("gamedata/units/tankmodels/us_m1_abrams.blk"), b"{}"),
+			(Path::new("gamedata/units/tankmodels/dm/us_m1_abrams.blk"), b"{}"),
+			(Path::new("gamedata/weapons/groundmodels_weapons/105mm_m68.blk"), b"{}"),
+			(Path::new("gamedata/units/tankmodels/ussr_t_34_1941.blk"), b"{}"),
+			(Path::new("config/unittags.blk"), b"{}"),
+		];
+
+		let mut updates = Vec::new();
+		let selection = collect_aces_files(
+			files.iter().copied(),
+			&HashSet::from(["ussr_t_34_1941".to_owned()]),
+			None,
+			&LogFile::default(),
+			&mut |p| updates.push(p),
+		);
+
+		assert!(
+			updates
+				.iter()
+				.all(|p| p.phase == ExtractPhase::Filtering && p.total == files.len())
+		);
+		let counts: Vec<usize> = updates.iter().map(|p| p.processed).collect();
+		assert_eq!(counts, (0..=files.len()).collect::<Vec<_>>());
+
+		assert_eq!(selection.vehicle_names, ["us_m1_abrams"]);
+		assert_eq!(selection.weapon_count, 1);
+		assert_eq!(selection.datamine.len(), 2);
+	}
+
+	#[test]
+	fn test_missing_archive_version_disables_caching() {
+		// What a stubbed unpacker without version metadata returns
+		let none: Result<Option<&str>, &str> = Ok(None);
+		let warning = archive_version(none).unwrap_err();
+		assert!(warning.contains("freshness caching is disabled"), "{warning}");
+
+		let broken: Result<Option<&str>, &str> = Err("bad header");
+		assert!(archive_version(broken).unwrap_err().contains("bad header"));
+		assert_eq!(archive_version(Ok::<_, &str>(Some("2.53.0.19"))).unwrap(), "2.53.0.19");
+	}
+
+	#[test]
+	fn test_no_lang_needs_only_aces() {
+		let game = TempDir::new("no-lang");
+		std::fs::write(game.join("aces.vromfs.bin"), b"stub").unwrap();
+
+		assert_eq!(missing_archive(&game, true), Some(game.join("lang.vromfs.bin")));
+		assert_eq!(missing_archive(&game, false), None);
+		std::fs::remove_file(game.join("aces.vromfs.bin")).unwrap();
+		assert_eq!(missing_archive(&game, false), Some(game.join("aces.vromfs.bin")));
+	}
+
+	#[test]
+	fn test_invalid_utf8_stored_lossily_and_flagged() {
+		let files: Vec<(&Path, &[u8])> = vec![
+			(Path::new("gamedata/units/tankmodels/us_m1_abrams.blk"), b"{}"),
+			(
+				Path::new("gamedata/weapons/groundmodels_weapons/105mm_m68.blk"),
+				b"{\"bullet\": \"105mm_m\xff735\"}",
+			),
+		];
+
+		let selection = collect_aces_files(
+			files.iter().copied(),
+			&HashSet::new(),
+			None,
+			&LogFile::default(),
+			&mut |_| {},
+		);
+
+		let key = "gamedata/weapons/groundmodels_weapons/105mm_m68.blkx";
+		assert_eq!(selection.lossy_files, [key]);
+		assert_eq!(selection.datamine.get(key).unwrap(), "{\"bullet\": \"105mm_m\u{fffd}735\"}");
+		assert_eq!(selection.datamine.len(), 2);
+	}
+
+	#[test]
+	fn test_incremental_decode_only_changed_entries() {
+		let old: Vec<(&Path, &[u8])> = vec![
+			(Path::new("nm"), b"names"),
+			(Path::new("gamedata/units/tankmodels/us_m1_abrams.blk"), b"abrams"),
+			(Path::new("gamedata/units/tankmodels/ussr_t_34_1941.blk"), b"t34"),
+			(Path::new("gamedata/weapons/groundmodels_weapons/105mm_m68.blk"), b"m68"),
+			(Path::new("config/unittags.blk"), b"tags"),
+		];
+		let mut new = old.clone();
+		new[2].1 = b"t34 patched";
+		new[4].1 = b"tags patched";
+
+		let previous = EntryDigests::of(&old);
+		let current = EntryDigests::of(&new);
+		assert_eq!(current.shared, previous.shared);
+		assert_eq!(current.entries.len(), 3);
+
+		let decoded = std::cell::RefCell::new(Vec::new());
+		let result = decode_incremental(
+			&new,
+			&current,
+			&previous,
+			|path| Some(format!("cached {}", path.display()).into_bytes()),
+			|path| {
+				decoded.borrow_mut().push(path.to_path_buf());
+				Ok(b"decoded".to_vec())
+			},
+		)
+		.unwrap();
+
+		let t34 = Path::new("gamedata/units/tankmodels/ussr_t_34_1941.blk");
+		assert_eq!(decoded.into_inner(), [t34]);
+		assert_eq!(result.decoded, 1);
+		let files: Vec<(&Path, &str)> = result
+			.files
+			.iter()
+			.map(|(path, buf)| (path.as_path(), std::str::from_utf8(buf).unwrap()))
+			.collect();
+		assert_eq!(
+			files,
+			[
+				(new[1].0, "cached gamedata/units/tankmodels/us_m1_abrams.blk"),
+				(t34, "decoded"),
+				(new[3].0, "cached gamedata/weapons/groundmodels_weapons/105mm_m68.blk"),
+			]
+		);
+
+		// An entry missing from the datamine on disk is decoded too
+		let result =
+			decode_incremental(&new, &current, &previous, |_| None, |_| Ok(Vec::new())).unwrap();
+		assert_eq!(result.decoded, 3);
+
+		// A new name map changes how every entry decodes
+		new[0].1 = b"names patched";
+		assert_ne!(EntryDigests::of(&new).shared, previous.shared);
+	}
 }
@@ -0,0 +1,72 @@
+//! Copy of the diagnostics of a run in a file (`run --log-file <file>`).
+//!
+//! Stderr is gone once the terminal closes, but support requests need the
+//! whole output: step banners, per-vehicle errors, warnings and the final
+//! summary.  The run prints them with [`logln!`] on its [`LogFile`] (see
+//! `PipelineConfig::log`), which prints to stderr and, if the run was given
+//! a file, appends the same text with the time since the file was created.
+//! One lock covers both writes, so lines from rayon workers are never
+//! interleaved, and are in the same order in the file as on the terminal.
+//! Code shared with other subcommands takes the handle as a parameter;
+//! they pass a [`LogFile::default`], which only prints.
+//!
+//! Unlike the JSON report (`--report`), the log is for people: it is the
+//! terminal output, not a summary.
+
+use std::fmt::{self, Write as _};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use std::time::Instant;
+
+/// Where a run's diagnostics go: stderr, plus the log file if one was
+/// created.
+#[derive(Debug, Default)]
+pub struct LogFile {
+	/// The open file and when it was created; `None` after a failed write.
+	file: Mutex<Option<(File, Instant)>>,
+}
+
+impl LogFile {
+	/// Copy diagnostics to `path`, replacing its contents.
+	pub fn create(path: &Path) -> io::Result<Self> {
+		let file = File::create(path)?;
+		Ok(Self {
+			file: Mutex::new(Some((file, Instant::now()))),
+		})
+	}
+
+	/// Print `args` to stderr and append it to the file, each line prefixed
+	/// with the seconds since [`create`](Self::create) (`[  12.345s] ...`).
+	pub fn line(&self, args: fmt::Arguments<'_>) {
+		let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+		let text = args.to_string();
+		eprintln!("{text}");
+
+		let Some((out, start)) = file.as_mut() else {
+			return;
+		};
+		let elapsed = start.elapsed().as_secs_f64();
+		let mut stamped = String::new();
+		for line in text.split('\n') {
+			writeln!(stamped, "[{elapsed:8.3}s] {line}").unwrap();
+		}
+		// A full disk must not abort the run; stderr still has everything
+		if out.write_all(stamped.as_bytes()).is_err() {
+			file.take();
+			eprintln!("Warning: cannot write the log file; logging stopped");
+		}
+	}
+}
+
+/// `eprintln!` that also appends to a [`LogFile`]: `logln!(log, "...")`.
+macro_rules! logln {
+	($log:expr) => {
+		$log.line(format_args!(""))
+	};
+	($log:expr, $($arg:tt)*) => {
+		$log.line(format_args!($($arg)*))
+	};
+}
+pub(crate) use logln;
@@ -5,16 +5,63 @@
 //! pipeline (no text roundtrip between conversion and ballistic stages).
 //!
 //! Legacy subcommands (`convert`, `extract`, `ballistic`) are retained
-//! for debugging and development workflows.
+//! for debugging and development workflows; `compare` checks a `Ballistic/`
+//! tree against a reference.
 
 mod ballistic;
+mod bulk;
+mod check;
+mod clean;
+mod compare;
+mod config;
+mod error_log;
 mod extract;
+mod log_file;
+mod manifest;
+mod output;
+mod overrides;
+mod quick;
+mod reference;
+mod report;
 mod run;
+mod summary;
+#[cfg(test)]
+mod test_util;
 
 use std::path::{Path, PathBuf};
-
-use clap::{Parser, Subcommand};
-use fcsgen_core::{VERSION, convert_vehicle, emit_legacy_txt, lookup_vehicle_id};
+use std::time::Duration;
+
+use clap::{ArgAction, CommandFactory, FromArgMatches, Parser, Subcommand};
+use fcsgen_core::{
+	AngleUnit,
+	BallisticConfig,
+	DefaultCx,
+	PenRounding,
+	PenetrationUnit,
+	RangeSampling,
+	TargetMaterial,
+	TimeUnit,
+	Tolerances,
+	VERSION,
+	convert_vehicle_with_warnings,
+	emit_legacy_txt,
+	fill_default_cx,
+	lookup_vehicle_id,
+};
+use ballistic::{OutputFormat, Sampling};
+use error_log::ErrorLog;
+use log_file::{LogFile, logln};
+use output::{
+	CollisionPolicy,
+	LineEnding,
+	OverwritePolicy,
+	ShellWriteOptions,
+	WriteOutcome,
+	create_output_dir,
+	exit_if_exists,
+	write_output,
+};
+use overrides::Overrides;
 
 #[derive(Parser)]
 #[command(name = "fcsgen", version = VERSION, about = "War Thunder FCS generation tool")]
@@ -26,43 +73,7 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
 	/// Run the full pipeline: extract → convert → ballistic
-	Run {
-		/// Path to the War Thunder installation directory
-		#[arg(long)]
-		game_path: PathBuf,
-
-		/// Base output directory (creates Datamine/, Data/, Ballistic/ subdirs)
-		#[arg(short, long)]
-		output: PathBuf,
-
-		/// Mouse sensitivity (0 < s ≤ 1)
-		#[arg(short, long, default_value_t = 0.50)]
-		sensitivity: f64,
-
-		/// Path to ignore.txt vehicle blacklist file
-		#[arg(long)]
-		ignore_file: Option<PathBuf>,
-
-		/// Only process specific vehicle(s) by name (without .blkx extension)
-		#[arg(long)]
-		vehicle: Option<Vec<String>>,
-
-		/// Number of parallel jobs (0 = auto-detect based on CPU count)
-		#[arg(short, long, default_value_t = 0)]
-		jobs: usize,
-
-		/// Skip extraction (use existing datamine in Datamine/)
-		#[arg(long, default_value_t = false)]
-		skip_extract: bool,
-
-		/// Skip ballistic computation (only extract + convert)
-		#[arg(long, default_value_t = false)]
-		skip_ballistic: bool,
-
-		/// Write full datamine .blkx files to disk (for debugging/testing)
-		#[arg(long, default_value_t = false)]
-		write_datamine: bool,
-	},
+	Run(Box<config::RunArgs>),
 
 	/// Convert datamine to Data/*.txt format (legacy, prefer `run`)
 	Convert {
@@ -81,6 +92,20 @@ enum Commands {
 		/// Only convert specific vehicle(s) by name (without .blkx extension)
 		#[arg(long)]
 		vehicle: Option<Vec<String>>,
+
+		/// Give shells without a Cx the legacy 0.38 instead of a per-caliber
+		/// approximation (as in the reference corpus)
+		#[arg(long, default_value_t = false)]
+		uniform_cx: bool,
+
+		/// How to handle output files that already exist
+		#[arg(long, value_enum, default_value_t = OverwritePolicy::Overwrite)]
+		overwrite_policy: OverwritePolicy,
+
+		/// Line terminator of written files: `lf` or `crlf`
+		/// (for Windows-only consumers)
+		#[arg(long, value_enum, default_value_t = LineEnding::Lf)]
+		line_ending: LineEnding,
 	},
 
 	/// Extract datamine from War Thunder VROMFS archives (standalone)
@@ -100,6 +125,10 @@ enum Commands {
 		/// Force re-extraction even if version matches cached marker
 		#[arg(long, default_value_t = false)]
 		force: bool,
+
+		/// Don't extract the lang CSVs (`lang.vromfs.bin` is then not needed)
+		#[arg(long, default_value_t = false)]
+		no_lang: bool,
 	},
 
 	/// Compute ballistic tables from Data/*.txt (legacy, prefer `run`)
@@ -109,44 +138,364 @@ enum Commands {
 		input: PathBuf,
 
 		/// Output directory for Ballistic/{vehicle}/{shell}.txt files
+		#[arg(short, long, required_unless_present = "output_file")]
+		output: Option<PathBuf>,
+
+		/// `tables` (one file per shell under --output), `bulk-csv` (every
+		/// row of every shell in one CSV at --output-file) or `elevation` (the
+		/// launch angle of every row, one file per shell under --output)
+		#[arg(long, value_enum, default_value_t = OutputFormat::Tables)]
+		format: OutputFormat,
+
+		/// Angle unit of `--format elevation`: `mils` (NATO, 6400 per circle;
+		/// default) or `deg`
+		#[arg(long)]
+		angle_unit: Option<AngleUnit>,
+
+		/// CSV file of `--format bulk-csv`
+		/// (`vehicle,shell,type,caliber_mm,distance,time,penetration`)
+		#[arg(long, value_name = "FILE")]
+		output_file: Option<PathBuf>,
+
+		/// Mouse sensitivity (0 < s ≤ 1); the scroll step is 2.8·s² mrad
+		#[arg(short, long, default_value_t = 0.50, value_parser = config::parse_sensitivity)]
+		sensitivity: f64,
+
+		/// Maximum launch angles simulated per shell (guards very low sensitivities)
+		#[arg(long, default_value_t = BallisticConfig::DEFAULT_MAX_ENTRIES_CAP)]
+		max_entries_cap: usize,
+
+		/// Gravitational acceleration in m/s² (for modded low-gravity servers)
+		#[arg(long, default_value_t = BallisticConfig::STANDARD_GRAVITY)]
+		gravity: f64,
+
+		/// Unit of the penetration column: `mm` or `in` (inches, one decimal)
+		#[arg(long, default_value_t = PenetrationUnit::Millimetres)]
+		pen_unit: PenetrationUnit,
+
+		/// Round the penetration column to the nearest multiple of N mm (e.g. 5
+		/// for cleaner sight labels); 1 keeps whole millimetres
+		#[arg(long, value_name = "N", default_value_t = 1, value_parser = config::parse_pen_round)]
+		pen_round: u32,
+
+		/// Rounding of penetration values: `nearest`, `floor` (as some in-game
+		/// readouts) or `ceil`
+		#[arg(long, value_name = "MODE", default_value_t = PenRounding::Nearest)]
+		pen_rounding: PenRounding,
+
+		/// Unit of the time column: `s` (one decimal, as the legacy tool) or `ms`
+		/// (whole milliseconds)
+		#[arg(long, default_value_t = TimeUnit::Seconds)]
+		time_unit: TimeUnit,
+
+		/// Decimal places of the distance column: 3 as the reference tables,
+		/// fewer to shrink the output
+		#[arg(
+			long,
+			value_name = "N",
+			default_value_t = BallisticConfig::DEFAULT_DISTANCE_PRECISION,
+			value_parser = config::parse_distance_precision,
+		)]
+		distance_precision: usize,
+
+		/// Whether to write the 0 m muzzle row at the top of each ballistic table
+		/// (`true` or `false`)
+		#[arg(long, value_name = "BOOL", default_value_t = true, action = ArgAction::Set)]
+		include_muzzle_row: bool,
+
+		/// Estimate the penetration of APDS-FS rounds missing their armor power
+		/// series from mass, speed and caliber (headers say `pen=estimated`)
+		/// instead of writing zeros
+		#[arg(long, default_value_t = false)]
+		estimate_missing_pen: bool,
+
+		/// Write only distance and time, without the penetration column (for
+		/// flight-time and drop analysis; not understood by the sight generator)
+		#[arg(long, default_value_t = false)]
+		no_penetration: bool,
+
+		/// Naval preset: extend ballistic tables from 4500 m to 20000 m for ship
+		/// guns (headers say `max_range=20000`)
+		#[arg(long, default_value_t = false)]
+		naval: bool,
+
+		/// Rows per scroll step (`scroll`), every `--step` metres (`fixed`), or
+		/// both from one simulation (`both`, fixed-step tables in `--fixed-output`)
+		#[arg(long, value_enum, default_value_t = Sampling::Scroll)]
+		sampling: Sampling,
+
+		/// Row spacing in metres of fixed-step tables
+		#[arg(long, value_name = "M", default_value_t = 100.0)]
+		step: f64,
+
+		/// Output directory for the fixed-step tables of `--sampling both`
+		/// [default: `Penetration` next to --output]
+		#[arg(long)]
+		fixed_output: Option<PathBuf>,
+
+		/// Only process specific vehicle(s) by name (without .txt extension)
+		#[arg(long)]
+		vehicle: Option<Vec<String>>,
+
+		/// Prepend a `#` metadata line (shell, type, caliber, speed, sensitivity)
+		/// to each ballistic table; not understood by the legacy sight generator
+		#[arg(long, default_value_t = false)]
+		with_header: bool,
+
+		/// Sight range marks in metres, ascending (e.g. `200,400,600,800`):
+		/// adds the launch angle each needs to the `--with-header` line
+		#[arg(
+			long,
+			value_name = "M,...",
+			value_delimiter = ',',
+			value_parser = config::parse_range_mark,
+		)]
+		range_marks: Option<Vec<f64>>,
+
+		/// What to do when different shells share a ballistic file name
+		#[arg(long, value_enum, default_value_t = CollisionPolicy::Last)]
+		on_collision: CollisionPolicy,
+
+		/// Don't write tables whose penetration is zero throughout (HE, smoke)
+		#[arg(long, default_value_t = false)]
+		skip_zero_pen: bool,
+
+		/// Write all of a vehicle's shells to one `{vehicle}.txt`, each under a
+		/// `# {shell}\ttype={type}` line, instead of one file per shell
+		#[arg(long, default_value_t = false)]
+		per_vehicle_file: bool,
+
+		/// How to handle output files that already exist
+		#[arg(long, value_enum, default_value_t = OverwritePolicy::Overwrite)]
+		overwrite_policy: OverwritePolicy,
+
+		/// Line terminator of written files: `lf` or `crlf`
+		/// (for Windows-only consumers)
+		#[arg(long, value_enum, default_value_t = LineEnding::Lf)]
+		line_ending: LineEnding,
+	},
+
+	/// Write one TSV line per shell with its direct-fire reach and penetration there
+	Summary {
+		/// Input directory containing Data/*.txt files (Stage 1 output)
+		#[arg(short, long)]
+		input: PathBuf,
+
+		/// TSV file to write (`vehicle`, `caliber`, `shell`, `type`, `distance`, `penetration`)
 		#[arg(short, long)]
 		output: PathBuf,
 
-		/// Mouse sensitivity (0 < s ≤ 1)
-		#[arg(short, long, default_value_t = 0.50)]
+		/// Mouse sensitivity (0 < s ≤ 1); the flattest shot is one 2.8·s² mrad step
+		#[arg(short, long, default_value_t = 0.50, value_parser = config::parse_sensitivity)]
 		sensitivity: f64,
 
+		/// Gravitational acceleration in m/s² (for modded low-gravity servers)
+		#[arg(long, default_value_t = BallisticConfig::STANDARD_GRAVITY)]
+		gravity: f64,
+
 		/// Only process specific vehicle(s) by name (without .txt extension)
 		#[arg(long)]
 		vehicle: Option<Vec<String>>,
+
+		/// Add a `zero` column: the suggested sight zero in metres (one full
+		/// elevation sweep per shell)
+		#[arg(long)]
+		with_zero: bool,
+
+		/// Add an `effective_penetration` column against `rha`, `cast` or
+		/// `structural` armor (the `penetration` column stays RHA)
+		#[arg(long, value_name = "MATERIAL")]
+		target_material: Option<TargetMaterial>,
+
+		/// How to handle an existing output file
+		#[arg(long, value_enum, default_value_t = OverwritePolicy::Overwrite)]
+		overwrite_policy: OverwritePolicy,
+	},
+
+	/// Print the ballistic table of one projectile described by flags
+	Quick(quick::QuickArgs),
+
+	/// Compare two Ballistic/ trees within numeric tolerances (regression check)
+	Compare {
+		/// Ballistic/ tree to check
+		#[arg(long)]
+		computed: PathBuf,
+
+		/// Ballistic/ tree holding the expected tables
+		#[arg(long)]
+		reference: PathBuf,
+
+		/// Maximum distance delta (metres)
+		#[arg(long, default_value_t = Tolerances::default().dist)]
+		dist_tol: f64,
+
+		/// Maximum time delta (seconds)
+		#[arg(long, default_value_t = Tolerances::default().time)]
+		time_tol: f64,
+
+		/// Maximum penetration delta (mm)
+		#[arg(long, default_value_t = Tolerances::default().pen)]
+		pen_tol: f64,
+
+		/// Maximum row-count difference per table
+		#[arg(long, default_value_t = Tolerances::default().row_count)]
+		row_tol: usize,
+
+		/// Decimal places of the computed distances; below 3, `--dist-tol` is
+		/// widened by the rounding
+		#[arg(
+			long,
+			value_name = "N",
+			default_value_t = BallisticConfig::DEFAULT_DISTANCE_PRECISION,
+			value_parser = config::parse_distance_precision,
+		)]
+		distance_precision: usize,
+	},
+
+	/// Regenerate the test reference corpus (data/ + ballistic/) from a game install
+	#[command(hide = true)]
+	GenReference {
+		/// Path to the War Thunder installation directory
+		#[arg(long)]
+		game_path: PathBuf,
+
+		/// Corpus root, e.g. test_data/expected (data/ and ballistic/ are replaced)
+		#[arg(long)]
+		out: PathBuf,
+
+		/// Path to ignore.txt vehicle blacklist file
+		#[arg(long)]
+		ignore_file: Option<PathBuf>,
+
+		/// Required: acknowledge that the existing reference trees are deleted
+		#[arg(long, default_value_t = false)]
+		confirm: bool,
+	},
+
+	/// Verify that a game path holds readable archives and print the game version
+	Check {
+		/// Path to the War Thunder installation directory
+		#[arg(long)]
+		game_path: PathBuf,
+	},
+
+	/// Remove Data/ and Ballistic/ outputs for vehicles no longer in the game
+	Clean {
+		/// Path to the War Thunder installation directory
+		#[arg(long)]
+		game_path: PathBuf,
+
+		/// Base output directory (contains Data/ and Ballistic/ subdirs)
+		#[arg(short, long)]
+		output: PathBuf,
+
+		/// Name of the `Data/*.txt` subdirectory of `--output`, as given to `run`
+		#[arg(long, default_value = "Data", value_parser = config::parse_subdir)]
+		data_subdir: String,
+
+		/// Name of the ballistic tables subdirectory of `--output`, as given to `run`
+		#[arg(long, default_value = "Ballistic", value_parser = config::parse_subdir)]
+		ballistic_subdir: String,
+
+		/// List stale entries without removing anything
+		#[arg(long, default_value_t = false)]
+		dry_run: bool,
+
+		/// Remove without asking for confirmation
+		#[arg(short, long, default_value_t = false)]
+		yes: bool,
 	},
 }
 
 fn main() {
-	let cli = Cli::parse();
+	// Parse via `ArgMatches` so `run --config` can tell explicit flags from defaults
+	let matches = Cli::command().get_matches();
+	let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
 	match cli.command {
-		Commands::Run {
-			game_path,
-			output,
-			sensitivity,
-			ignore_file,
-			vehicle,
-			jobs,
-			skip_extract,
-			skip_ballistic,
-			write_datamine,
-		} => {
+		Commands::Run(args) => {
+			let run_matches = matches.subcommand_matches("run").expect("run subcommand");
+			let args = config::resolve_run_args(*args, run_matches);
+			if args.print_config {
+				print!("{}", config::resolved_config_toml(&args));
+				return;
+			}
+			let log = args.log_file.as_deref().map_or_else(LogFile::default, |path| {
+				LogFile::create(path).unwrap_or_else(|e| {
+					eprintln!("Error: cannot create log file {}: {e}", path.display());
+					std::process::exit(1);
+				})
+			});
+			let output = args.output.as_deref().expect("resolved");
+			logln!(log, "Output: {}", output.display());
+			let sensitivities = args.sensitivity_by_nation.iter().map(|&(_, s)| s);
+			for sensitivity in std::iter::once(args.sensitivity).chain(sensitivities) {
+				if let Some(warning) = config::sensitivity_warning(sensitivity) {
+					logln!(log, "Warning: {warning}");
+				}
+			}
+			let overrides = args.overrides.as_deref().map(|path| {
+				Overrides::load(path, &log).unwrap_or_else(|e| {
+					logln!(log, "Error: cannot read overrides file {}: {e}", path.display());
+					std::process::exit(1);
+				})
+			});
+			let ballistic_cfg = BallisticConfig {
+				max_entries_cap: args.max_entries_cap,
+				gravity: args.gravity,
+				pen_unit: args.pen_unit,
+				pen_round: args.pen_round,
+				pen_rounding: args.pen_rounding,
+				time_unit: args.time_unit,
+				distance_precision: args.distance_precision,
+				include_muzzle_row: args.include_muzzle_row,
+				estimate_missing_pen: args.estimate_missing_pen,
+				trajectory_only: args.no_penetration,
+				..BallisticConfig::new(args.sensitivity)
+			};
 			run::run_pipeline(&run::PipelineConfig {
-				game_path: &game_path,
-				output: &output,
-				sensitivity,
-				ignore_file: ignore_file.as_deref(),
-				filter: vehicle.as_deref(),
-				jobs,
-				skip_extract,
-				skip_ballistic,
-				write_datamine,
+				// Not needed (and possibly unset) with --from-data
+				game_path: args.game_path.as_deref().unwrap_or(Path::new("")),
+				output,
+				datamine_subdir: &args.datamine_subdir,
+				data_subdir: &args.data_subdir,
+				ballistic_subdir: &args.ballistic_subdir,
+				ballistic: if args.naval { ballistic_cfg.naval() } else { ballistic_cfg },
+				sensitivity_by_nation: &args.sensitivity_by_nation,
+				min_speed: args.min_speed,
+				uniform_cx: args.uniform_cx,
+				vehicle_timeout: args.vehicle_timeout.map(Duration::from_secs),
+				ignore_file: args.ignore_file.as_deref(),
+				from_data: args.from_data.as_deref(),
+				report: args.report.as_deref(),
+				seed_cache_from: args.seed_cache_from.as_deref(),
+				save_cache: args.save_cache.as_deref(),
+				filter: args.vehicle.as_deref(),
+				rangefinder: args.rangefinder,
+				min_br: args.min_br,
+				rank: args.rank,
+				overrides: overrides.as_ref(),
+				jobs: args.jobs,
+				skip_extract: args.skip_extract,
+				skip_ballistic: args.skip_ballistic,
+				write_datamine: args.write_datamine,
+				no_lang: args.no_lang,
+				overwrite_policy: args.overwrite_policy,
+				line_ending: args.line_ending,
+				with_header: args.with_header,
+				range_marks: args.range_marks.as_deref().unwrap_or_default(),
+				on_collision: args.on_collision,
+				skip_zero_pen: args.skip_zero_pen,
+				per_vehicle_file: args.per_vehicle_file,
+				ammo_sets: &args.ammo_set,
+				report_duplicates: args.report_duplicates,
+				verify_cache: args.verify_cache,
+				shell_manifest: args.shell_manifest.as_deref(),
+				low_memory: args.low_memory,
+				repeat: args.repeat,
+				repeat_cold: args.repeat_cold,
+				errors: ErrorLog::new(args.quiet_errors),
+				log,
 			});
 		},
 		Commands::Convert {
@@ -154,39 +503,244 @@ fn main() {
 			output,
 			game_path,
 			vehicle,
+			uniform_cx,
+			overwrite_policy,
+			line_ending,
 		} => {
-			run_convert(&input, &output, game_path.as_deref(), vehicle.as_deref());
+			run_convert(
+				&input,
+				&output,
+				game_path.as_deref(),
+				vehicle.as_deref(),
+				uniform_cx,
+				overwrite_policy,
+				line_ending,
+			);
 		},
 		Commands::Extract {
 			game_path,
 			output,
 			ignore_file,
 			force,
+			no_lang,
 		} => {
 			extract::run_extract(
 				&game_path,
 				&output,
 				ignore_file.as_deref(),
 				force,
+				!no_lang,
 			);
 		},
 		Commands::Ballistic {
 			input,
 			output,
+			format,
+			angle_unit,
+			output_file,
 			sensitivity,
+			max_entries_cap,
+			gravity,
+			pen_unit,
+			pen_round,
+			pen_rounding,
+			time_unit,
+			distance_precision,
+			include_muzzle_row,
+			estimate_missing_pen,
+			no_penetration,
+			naval,
+			sampling,
+			step,
+			fixed_output,
 			vehicle,
+			overwrite_policy,
+			with_header,
+			range_marks,
+			on_collision,
+			skip_zero_pen,
+			per_vehicle_file,
+			line_ending,
 		} => {
+			if let Some(warning) = config::sensitivity_warning(sensitivity) {
+				eprintln!("Warning: {warning}");
+			}
+			let range_marks = range_marks.unwrap_or_default();
+			if let Err(e) = config::check_range_marks(&range_marks, with_header) {
+				eprintln!("Error: {e}");
+				std::process::exit(1);
+			}
+			if sampling != Sampling::Scroll && !(step > 0.0 && step.is_finite()) {
+				eprintln!("Error: --step must be a positive number of metres (got {step})");
+				std::process::exit(1);
+			}
+			if sampling == Sampling::Both && per_vehicle_file {
+				eprintln!("Error: --sampling both cannot be used with --per-vehicle-file");
+				std::process::exit(1);
+			}
+			let bulk_csv = format == OutputFormat::BulkCsv;
+			if bulk_csv && (sampling == Sampling::Both || per_vehicle_file) {
+				eprintln!(
+					"Error: --format bulk-csv cannot be used with --sampling both or \
+					 --per-vehicle-file",
+				);
+				std::process::exit(1);
+			}
+			let elevation =
+				(format == OutputFormat::Elevation).then(|| angle_unit.unwrap_or_default());
+			if elevation.is_none() && angle_unit.is_some() {
+				eprintln!("Error: --angle-unit is only used with --format elevation");
+				std::process::exit(1);
+			}
+			if elevation.is_some() && (sampling == Sampling::Both || with_header || skip_zero_pen) {
+				eprintln!(
+					"Error: --format elevation cannot be used with --sampling both, --with-header \
+					 or --skip-zero-pen",
+				);
+				std::process::exit(1);
+			}
+			let output = match (bulk_csv, output, &output_file) {
+				(true, _, None) => {
+					eprintln!("Error: --format bulk-csv needs --output-file");
+					std::process::exit(1);
+				},
+				(false, _, Some(_)) => {
+					eprintln!("Error: --output-file is only used with --format bulk-csv");
+					std::process::exit(1);
+				},
+				// clap requires --output without --output-file
+				(_, output, _) => output.unwrap_or_default(),
+			};
+			let fixed_dir = (sampling == Sampling::Both)
+				.then(|| fixed_output.unwrap_or_else(|| output.with_file_name("Penetration")));
+			let ballistic_cfg = BallisticConfig {
+				sampling: if sampling == Sampling::Fixed {
+					RangeSampling::FixedStep(step)
+				} else {
+					RangeSampling::ScrollStep
+				},
+				max_entries_cap,
+				gravity,
+				pen_unit,
+				pen_round,
+				pen_rounding,
+				time_unit,
+				distance_precision,
+				include_muzzle_row,
+				estimate_missing_pen,
+				trajectory_only: no_penetration,
+				..BallisticConfig::new(sensitivity)
+			};
+			let ballistic_cfg = if naval { ballistic_cfg.naval() } else { ballistic_cfg };
+			if let Some(output_file) = output_file {
+				bulk::run_bulk_csv(
+					&input,
+					&output_file,
+					&ballistic_cfg,
+					vehicle.as_deref(),
+					skip_zero_pen,
+					overwrite_policy,
+				);
+				return;
+			}
 			ballistic::run_ballistic(
 				&input,
 				&output,
-				sensitivity,
+				&ballistic_cfg,
+				vehicle.as_deref(),
+				ShellWriteOptions {
+					overwrite_policy,
+					with_header,
+					range_marks: &range_marks,
+					on_collision,
+					skip_zero_pen,
+					per_vehicle_file,
+					line_ending,
+					elevation,
+				},
+				fixed_dir.as_deref().map(|dir| ballistic::FixedStepOutput { step, dir }),
+			);
+		},
+		Commands::Summary {
+			input,
+			output,
+			sensitivity,
+			gravity,
+			vehicle,
+			with_zero,
+			target_material,
+			overwrite_policy,
+		} => {
+			let ballistic_cfg = BallisticConfig {
+				gravity,
+				..BallisticConfig::new(sensitivity)
+			};
+			summary::run_summary(
+				&input,
+				&output,
+				&ballistic_cfg,
 				vehicle.as_deref(),
+				with_zero,
+				target_material,
+				overwrite_policy,
 			);
 		},
+		Commands::Quick(args) => quick::run_quick(&args),
+		Commands::Compare {
+			computed,
+			reference,
+			dist_tol,
+			time_tol,
+			pen_tol,
+			row_tol,
+			distance_precision,
+		} => {
+			let tolerances = Tolerances {
+				dist: dist_tol,
+				time: time_tol,
+				pen: pen_tol,
+				row_count: row_tol,
+			};
+			let tolerances = if distance_precision < BallisticConfig::DEFAULT_DISTANCE_PRECISION {
+				tolerances.for_distance_precision(distance_precision)
+			} else {
+				tolerances
+			};
+			compare::run_compare(&computed, &reference, &tolerances);
+		},
+		Commands::GenReference {
+			game_path,
+			out,
+			ignore_file,
+			confirm,
+		} => {
+			reference::run_gen_reference(&game_path, &out, ignore_file.as_deref(), confirm);
+		},
+		Commands::Check { game_path } => check::run_check(&game_path),
+		Commands::Clean {
+			game_path,
+			output,
+			data_subdir,
+			ballistic_subdir,
+			dry_run,
+			yes,
+		} => {
+			let data_dir = output.join(data_subdir);
+			let ballistic_dir = output.join(ballistic_subdir);
+			clean::run_clean(&game_path, &data_dir, &ballistic_dir, dry_run, yes);
+		},
 	}
 }
 
-fn run_convert(input: &PathBuf, output: &PathBuf, game_path: Option<&Path>, filter: Option<&[String]>) {
+fn run_convert(
+	input: &PathBuf,
+	output: &PathBuf,
+	game_path: Option<&Path>,
+	filter: Option<&[String]>,
+	uniform_cx: bool,
+	overwrite_policy: OverwritePolicy,
+	line_ending: LineEnding,
+) {
 	// Input should be the aces.vromfs.bin_u directory itself
 	let tankmodels = input.join("gamedata").join("units").join("tankmodels");
 
@@ -210,7 +764,7 @@ fn run_convert(input: &PathBuf, output: &PathBuf, game_path: Option<&Path>, filt
 	);
 
 	// Create output directory
-	if let Err(e) = std::fs::create_dir_all(output) {
+	if let Err(e) = create_output_dir(output) {
 		eprintln!("Error: cannot create output directory: {e}");
 		std::process::exit(1);
 	}
@@ -233,6 +787,7 @@ fn run_convert(input: &PathBuf, output: &PathBuf, game_path: Option<&Path>, filt
 	let total = vehicles.len();
 	let mut converted = 0;
 	let mut skipped = 0;
+	let mut existing_skipped = 0;
 	let mut failed = 0;
 
 	eprintln!("Converting {total} vehicles from {tankmodels:?}");
@@ -243,17 +798,28 @@ fn run_convert(input: &PathBuf, output: &PathBuf, game_path: Option<&Path>, filt
 		let path = entry.path();
 		let name = path.file_stem().unwrap().to_string_lossy();
 
-		match convert_vehicle(&path, datamine_root) {
-			Ok(data) if data.is_armed() => {
+		let mut warnings = Vec::new();
+		let result = convert_vehicle_with_warnings(&path, datamine_root, &mut warnings);
+		for warning in &warnings {
+			eprintln!("CONVERT WARNING {name}: {warning}");
+		}
+		match result {
+			Ok(mut data) if data.is_armed() => {
+				if uniform_cx {
+					fill_default_cx(&mut data.projectiles, DefaultCx::Uniform);
+				}
 				let txt = emit_legacy_txt(&data);
 				let vehicle_id = lookup_vehicle_id(&unittags, &name);
 				let out_path = output.join(format!("{vehicle_id}.txt"));
 
-				if let Err(e) = std::fs::write(&out_path, &txt) {
-					eprintln!("WRITE ERROR {name}: {e}");
-					failed += 1;
-				} else {
-					converted += 1;
+				match write_output(&out_path, &txt, overwrite_policy, line_ending) {
+					Ok(WriteOutcome::Written) => converted += 1,
+					Ok(WriteOutcome::Skipped) => existing_skipped += 1,
+					Err(e) => {
+						exit_if_exists(&e);
+						eprintln!("WRITE ERROR {name}: {e}");
+						failed += 1;
+					},
 				}
 			},
 			Ok(_) => {
@@ -269,4 +835,7 @@ fn run_convert(input: &PathBuf, output: &PathBuf, game_path: Option<&Path>, filt
 
 	eprintln!();
 	eprintln!("Done: {converted} converted, {skipped} skipped (unarmed), {failed} failed");
+	if existing_skipped > 0 {
+		eprintln!("      {existing_skipped} existing files left untouched (--overwrite-policy skip)");
+	}
 }
@@ -0,0 +1,148 @@
+//! Deduplicated list of every shell in a `run` (`--shell-manifest <file>`).
+//!
+//! Most shells are carried by several vehicles with identical physics, the
+//! same observation that makes the ballistic cache effective.  The manifest
+//! lists each distinct shell once, by name and [`BallisticKey`], with the
+//! vehicles that carry it and its in-game name from `units_weaponry.csv`
+//! (see [`LangTable::shell_display_name`]).  A `.csv` path gets CSV
+//! (vehicles separated by `;`), anything else pretty-printed JSON.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::fmt::Write;
+use std::path::Path;
+
+use fcsgen_core::{BallisticKey, LangTable};
+use fcsgen_core::ballistic::BallisticConfig;
+use fcsgen_core::parser::data::{DataProjectile, is_hull_break_capable};
+use serde::Serialize;
+
+/// One distinct shell and its carriers.
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestShell {
+	/// Full projectile name (e.g. `75mm_pzgr_39`).
+	pub name: String,
+	/// In-game name (e.g. `PzGr 39`), or the output name if not localized.
+	pub display_name: String,
+	/// Ballistic file stem (e.g. `pzgr_39`), the display name fallback.
+	#[serde(skip)]
+	output_name: String,
+	/// Raw type string (e.g. `apcbc_tank`).
+	#[serde(rename = "type")]
+	pub bullet_type: String,
+	pub caliber: f64,
+	pub mass: f64,
+	pub speed: f64,
+	/// Likely to hull-break light vehicles (heuristic); see
+	/// [`is_hull_break_capable`].
+	pub hull_break: bool,
+	/// Vehicles carrying the shell, sorted.
+	pub vehicles: Vec<String>,
+}
+
+/// Shells seen during a run, keyed by name and physics.
+#[derive(Default)]
+pub struct ShellManifest {
+	shells: HashMap<(String, BallisticKey), ManifestShell>,
+}
+
+impl ShellManifest {
+	/// Record the projectiles of `vehicle`.
+	///
+	/// The key is computed with the default [`BallisticConfig`], so that
+	/// per-nation sensitivities do not split a shell in two.
+	pub fn add(&mut self, vehicle: &str, projectiles: &[DataProjectile]) {
+		let cfg = BallisticConfig::default();
+		for proj in projectiles {
+			let key = (proj.name.clone(), BallisticKey::with_config(proj, &cfg));
+			let shell = self.shells.entry(key).or_insert_with(|| ManifestShell {
+				name: proj.name.clone(),
+				display_name: String::new(),
+				output_name: proj.output_name.clone(),
+				bullet_type: proj.bullet_type.clone(),
+				caliber: proj.ballistic_caliber,
+				mass: proj.mass,
+				speed: proj.speed,
+				hull_break: is_hull_break_capable(
+					&proj.bullet_type,
+					proj.ballistic_caliber,
+					proj.explosive_mass,
+				),
+				vehicles: Vec::new(),
+			});
+			shell.vehicles.push(vehicle.to_owned());
+		}
+	}
+
+	pub fn merge(&mut self, other: Self) {
+		for (key, shell) in other.shells {
+			match self.shells.entry(key) {
+				Entry::Occupied(mut entry) => {
+					entry.get_mut().vehicles.extend(shell.vehicles);
+				},
+				Entry::Vacant(entry) => {
+					entry.insert(shell);
+				},
+			}
+		}
+	}
+
+	/// Number of distinct shells.
+	pub fn len(&self) -> usize {
+		self.shells.len()
+	}
+
+	/// Shells sorted by name (then speed, for variants sharing a name), with
+	/// sorted vehicle lists free of repeats (a shell often appears in several
+	/// belts of the same vehicle) and display names from `lang`.
+	fn into_sorted(self, lang: &LangTable) -> Vec<ManifestShell> {
+		let mut shells: Vec<_> = self
+			.shells
+			.into_values()
+			.map(|mut shell| {
+				shell.vehicles.sort();
+				shell.vehicles.dedup();
+				shell.display_name =
+					lang.shell_display_name(&shell.name, &shell.output_name).to_owned();
+				shell
+			})
+			.collect();
+		shells.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.speed.total_cmp(&b.speed)));
+		shells
+	}
+}
+
+/// Write `manifest` to `path`, as CSV for a `.csv` extension and as JSON
+/// otherwise.  Display names come from `lang` (`units_weaponry.csv`); an
+/// empty table gives every shell its output name.
+pub fn write_manifest(
+	path: &Path,
+	manifest: ShellManifest,
+	lang: &LangTable,
+) -> std::io::Result<()> {
+	let shells = manifest.into_sorted(lang);
+	let text = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv")) {
+		let mut csv =
+			String::from("name,display_name,type,caliber,mass,speed,hull_break,vehicles\n");
+		for s in &shells {
+			// Display names may contain commas (`12,8/8,8 Pzgr.TS`)
+			writeln!(
+				csv,
+				"{},\"{}\",{},{},{},{},{},{}",
+				s.name,
+				s.display_name.replace('"', "\"\""),
+				s.bullet_type,
+				s.caliber,
+				s.mass,
+				s.speed,
+				s.hull_break,
+				s.vehicles.join(";"),
+			)
+			.unwrap();
+		}
+		csv
+	} else {
+		serde_json::to_string_pretty(&shells).map_err(std::io::Error::other)? + "\n"
+	};
+	std::fs::write(path, text)
+}
@@ -0,0 +1,423 @@
+//! Shared output-file writing for the `Data/` and `Ballistic/` trees.
+//!
+//! All generated files go through [`write_output`], which applies the
+//! user-selected [`OverwritePolicy`] when the target already exists and the
+//! [`LineEnding`] of the written text.  Their directories are made by
+//! [`create_output_dir`], which follows symlinks (output is often linked into
+//! the game's sights folder) and rejects dangling ones.
+//! Ballistic file names are assigned by [`plan_shell_files`], which applies
+//! the [`CollisionPolicy`] when distinct shells share a name.  With
+//! `--per-vehicle-file` the tables are instead concatenated into one file per
+//! vehicle, each under a [`vehicle_section_header`].
+//! Under the `error` policies both report an [`OutputConflict`], which stops
+//! the run.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::{fmt, io};
+use std::path::Path;
+
+use clap::ValueEnum;
+use fcsgen_core::ballistic::{AngleUnit, BallisticConfig, BallisticKey, should_skip};
+use fcsgen_core::parser::data::DataProjectile;
+use serde::{Deserialize, Serialize};
+
+use crate::log_file::{LogFile, logln};
+
+/// What to do when an output file already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverwritePolicy {
+	/// Replace existing files (default).
+	#[default]
+	Overwrite,
+	/// Leave existing files untouched.
+	Skip,
+	/// Abort the run if any target file already exists.
+	Error,
+}
+
+/// Line terminator of written text files.  Emitters always produce `\n`;
+/// the terminator is applied by [`write_output`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LineEnding {
+	/// `\n` (default).
+	#[default]
+	Lf,
+	/// `\r\n`, for Windows-only consumers.
+	Crlf,
+}
+
+impl LineEnding {
+	/// `content` (with `\n` line ends) converted to this line ending.
+	pub fn apply(self, content: &str) -> Cow<'_, str> {
+		match self {
+			Self::Lf => Cow::Borrowed(content),
+			Self::Crlf => Cow::Owned(content.replace('\n', "\r\n")),
+		}
+	}
+}
+
+/// What to do when physically different shells clean to the same
+/// ballistic file name (e.g. `75mm_pzgr_39` and `76mm_pzgr_39`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CollisionPolicy {
+	/// Write every distinct shell; all but the last get `_2`, `_3`, ... suffixes.
+	Suffix,
+	/// Keep only the last shell per name, with a warning (default, legacy).
+	#[default]
+	Last,
+	/// Abort the run.
+	Error,
+}
+
+/// How the `ballistic` subcommand names and writes shell tables.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShellWriteOptions<'a> {
+	/// What to do with shell tables that already exist.
+	pub overwrite_policy: OverwritePolicy,
+	/// Prepend a `#` metadata line to each shell table.
+	pub with_header: bool,
+	/// Sight range marks of that line; see
+	/// [`fcsgen_core::ballistic::table_header_with_marks`].
+	pub range_marks: &'a [f64],
+	/// What to do when distinct shells share a file name.
+	pub on_collision: CollisionPolicy,
+	/// Don't write tables whose penetration is zero on every row.
+	pub skip_zero_pen: bool,
+	/// Write one `{vehicle}.txt` with a section per shell instead of a
+	/// `{vehicle}/` directory.
+	pub per_vehicle_file: bool,
+	/// Line terminator of the written tables.
+	pub line_ending: LineEnding,
+	/// Write elevation tables with angles in this unit instead of ballistic
+	/// tables (`--format elevation`).
+	pub elevation: Option<AngleUnit>,
+}
+
+/// Outcome of a successful [`write_output`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteOutcome {
+	/// The file was written.
+	Written,
+	/// The file already existed and was left untouched (`skip` policy).
+	Skipped,
+}
+
+/// Write `content` to `path`, honouring `policy` if the file already exists.
+///
+/// Under [`OverwritePolicy::Error`] an existing target yields an
+/// [`io::ErrorKind::AlreadyExists`] error; callers should treat that as
+/// fatal (see [`exit_if_exists`]).  A symlinked `path` is written through to
+/// its target, unless the link is dangling.
+pub fn write_output(
+	path: &Path,
+	content: &str,
+	policy: OverwritePolicy,
+	line_ending: LineEnding,
+) -> io::Result<WriteOutcome> {
+	check_not_dangling(path)?;
+	if policy != OverwritePolicy::Overwrite && path.exists() {
+		return match policy {
+			OverwritePolicy::Skip => Ok(WriteOutcome::Skipped),
+			_ => Err(already_exists(path)),
+		};
+	}
+
+	std::fs::write(path, line_ending.apply(content).as_bytes())?;
+	Ok(WriteOutcome::Written)
+}
+
+/// Fail as [`write_output`] would under [`OverwritePolicy::Error`], without
+/// writing anything.
+pub fn check_writable(path: &Path) -> io::Result<()> {
+	check_not_dangling(path)?;
+	if path.exists() {
+		return Err(already_exists(path));
+	}
+	Ok(())
+}
+
+/// The error of an existing target under [`OverwritePolicy::Error`].
+fn already_exists(path: &Path) -> io::Error {
+	io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", path.display()))
+}
+
+/// Create `dir` and its missing parents, like [`std::fs::create_dir_all`].
+///
+/// Symlinks on the way are followed, so an existing link is never replaced
+/// by a real directory.  A dangling link fails with an error naming the
+/// link and its missing target, where `create_dir_all` would only report
+/// "File exists".
+pub fn create_output_dir(dir: &Path) -> io::Result<()> {
+	// Only the deepest existing ancestor matters: everything above it
+	// resolved already
+	if let Some(existing) = dir.ancestors().find(|a| std::fs::symlink_metadata(a).is_ok()) {
+		check_not_dangling(existing)?;
+	}
+	std::fs::create_dir_all(dir)
+}
+
+/// Fail if `path` is a symlink whose target does not exist.
+fn check_not_dangling(path: &Path) -> io::Result<()> {
+	let is_link = std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink());
+	if is_link && !path.exists() {
+		let target = std::fs::read_link(path)?;
+		return Err(io::Error::new(
+			io::ErrorKind::NotFound,
+			format!(
+				"{} is a symlink to {}, which does not exist",
+				path.display(),
+				target.display(),
+			),
+		));
+	}
+	Ok(())
+}
+
+/// A conflict that stops the run under an `error` policy: a target file
+/// that already exists, or distinct shells sharing a file name.
+///
+/// Parallel workers return it rather than exiting, so that no vehicle is
+/// started after it; the main thread then reports it with [`Self::exit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputConflict(String);
+
+impl OutputConflict {
+	/// Print the conflict to `log` and exit with status 1.
+	pub fn exit(&self, log: &LogFile) -> ! {
+		logln!(log, "Error: {self}");
+		std::process::exit(1);
+	}
+}
+
+impl fmt::Display for OutputConflict {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
+/// Fail with an [`OutputConflict`] if `err` was raised by the `error`
+/// overwrite policy.
+///
+/// Other I/O errors are left for the caller to report per file.
+pub fn check_exists(err: &io::Error) -> Result<(), OutputConflict> {
+	if err.kind() == io::ErrorKind::AlreadyExists {
+		return Err(OutputConflict(format!("{err} (--overwrite-policy error)")));
+	}
+	Ok(())
+}
+
+/// Like [`check_exists`], but exit at once; for sequential writers.
+pub fn exit_if_exists(err: &io::Error) {
+	if let Err(conflict) = check_exists(err) {
+		conflict.exit(&LogFile::default());
+	}
+}
+
+/// Section delimiter preceding each shell table in a `--per-vehicle-file`
+/// output: `# {file_stem}\ttype={normalized_type}`.
+pub fn vehicle_section_header(file_stem: &str, proj: &DataProjectile) -> String {
+	format!("# {file_stem}\ttype={}\n", proj.normalized_type)
+}
+
+/// Pick the shells of one vehicle to compute and the file stem each one is
+/// written to.
+///
+/// Skipped types are dropped.  Shells sharing an `output_name` with identical
+/// physics (same [`BallisticKey`]) collapse to the last one, as in the legacy
+/// tool; genuinely different shells are resolved by `policy`.  The last shell
+/// always keeps the plain name, so the legacy sight generator finds it.
+///
+/// Only [`CollisionPolicy::Error`] fails, before any file is written; the
+/// warning of [`CollisionPolicy::Last`] goes to `log`.
+pub fn plan_shell_files<'a>(
+	vehicle: &str,
+	projectiles: &'a [DataProjectile],
+	ballistic_cfg: &BallisticConfig,
+	policy: CollisionPolicy,
+	log: &LogFile,
+) -> Result<Vec<(&'a DataProjectile, String)>, OutputConflict> {
+	// Group by output name in first-seen order
+	let mut groups: Vec<(&str, Vec<&DataProjectile>)> = Vec::new();
+	let mut group_of: HashMap<&str, usize> = HashMap::new();
+	for proj in projectiles {
+		if should_skip(&proj.normalized_type) {
+			continue;
+		}
+		let idx = *group_of.entry(&proj.output_name).or_insert_with(|| {
+			groups.push((&proj.output_name, Vec::new()));
+			groups.len() - 1
+		});
+		groups[idx].1.push(proj);
+	}
+
+	let mut plan = Vec::new();
+	for (name, shells) in groups {
+		// Last occurrence of each distinct physics, in file order
+		let keys: Vec<BallisticKey> = shells
+			.iter()
+			.map(|p| BallisticKey::with_config(p, ballistic_cfg))
+			.collect();
+		let distinct: Vec<&DataProjectile> = shells
+			.iter()
+			.enumerate()
+			.filter(|&(i, _)| !keys[i + 1..].contains(&keys[i]))
+			.map(|(_, &p)| p)
+			.collect();
+		let (last, earlier) = distinct.split_last().expect("groups are non-empty");
+
+		if !earlier.is_empty() {
+			match policy {
+				CollisionPolicy::Suffix => {
+					for (n, &proj) in (2..).zip(earlier) {
+						plan.push((proj, format!("{name}_{n}")));
+					}
+				},
+				CollisionPolicy::Last => logln!(
+					log,
+					"Warning: {vehicle}: {} different shells share the name {name}; keeping the last \
+					 (--on-collision)",
+					distinct.len(),
+				),
+				CollisionPolicy::Error => {
+					return Err(OutputConflict(format!(
+						"{vehicle}: {} different shells share the name {name} \
+						 (--on-collision error)",
+						distinct.len(),
+					)));
+				},
+			}
+		}
+		plan.push((*last, name.to_owned()));
+	}
+
+	Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::PathBuf;
+
+	use fcsgen_core::ballistic::compute_ballistic_with_config;
+	use fcsgen_core::parser::data::parse_data_text;
+	use fcsgen_core::parse_table;
+
+	use crate::test_util::TempDir;
+	use super::*;
+
+	/// Create a fresh directory under the system temp dir with one
+	/// pre-existing file, returning `(dir, existing_file)`.
+	fn seeded_dir(name: &str) -> (TempDir, PathBuf) {
+		let dir = TempDir::new(&format!("output-{name}"));
+		let existing = dir.join("existing.txt");
+		std::fs::write(&existing, "curated").unwrap();
+		(dir, existing)
+	}
+
+	#[test]
+	fn test_overwrite_replaces_existing() {
+		let (_dir, existing) = seeded_dir("overwrite");
+
+		let outcome =
+			write_output(&existing, "new", OverwritePolicy::Overwrite, LineEnding::Lf).unwrap();
+		assert_eq!(outcome, WriteOutcome::Written);
+		assert_eq!(std::fs::read_to_string(&existing).unwrap(), "new");
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn test_writes_through_symlinked_dir() {
+		use std::os::unix::fs::symlink;
+
+		let (dir, _) = seeded_dir("symlink");
+		let sights = dir.join("UserSights");
+		std::fs::create_dir(&sights).unwrap();
+		let link = dir.join("output");
+		symlink(&sights, &link).unwrap();
+
+		let vehicle_dir = link.join("Ballistic").join("test_tank");
+		create_output_dir(&vehicle_dir).unwrap();
+		write_output(
+			&vehicle_dir.join("pzgr_39.txt"),
+			"table",
+			OverwritePolicy::Overwrite,
+			LineEnding::Lf,
+		)
+		.unwrap();
+
+		// Written at the target; the link itself is still a link
+		let target = sights.join("Ballistic").join("test_tank").join("pzgr_39.txt");
+		assert_eq!(std::fs::read_to_string(target).unwrap(), "table");
+		assert!(std::fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+
+		// A dangling link is reported, not followed or replaced
+		let dangling = dir.join("dangling");
+		symlink(dir.join("missing"), &dangling).unwrap();
+		let err = create_output_dir(&dangling.join("Ballistic")).unwrap_err();
+		assert!(err.to_string().contains("does not exist"), "{err}");
+		let err = write_output(&dangling, "table", OverwritePolicy::Overwrite, LineEnding::Lf)
+			.unwrap_err();
+		assert_eq!(err.kind(), io::ErrorKind::NotFound);
+		assert!(!dir.join("missing").exists());
+	}
+
+	#[test]
+	fn test_skip_leaves_existing() {
+		let (dir, existing) = seeded_dir("skip");
+
+		let outcome =
+			write_output(&existing, "new", OverwritePolicy::Skip, LineEnding::Lf).unwrap();
+		assert_eq!(outcome, WriteOutcome::Skipped);
+		assert_eq!(std::fs::read_to_string(&existing).unwrap(), "curated");
+
+		// New files are still written
+		let fresh = dir.join("fresh.txt");
+		let outcome = write_output(&fresh, "new", OverwritePolicy::Skip, LineEnding::Lf).unwrap();
+		assert_eq!(outcome, WriteOutcome::Written);
+		assert_eq!(std::fs::read_to_string(&fresh).unwrap(), "new");
+	}
+
+	#[test]
+	fn test_error_rejects_existing() {
+		let (dir, existing) = seeded_dir("error");
+
+		let err =
+			write_output(&existing, "new", OverwritePolicy::Error, LineEnding::Lf).unwrap_err();
+		assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+		assert_eq!(std::fs::read_to_string(&existing).unwrap(), "curated");
+
+		let fresh = dir.join("fresh.txt");
+		let outcome = write_output(&fresh, "new", OverwritePolicy::Error, LineEnding::Lf).unwrap();
+		assert_eq!(outcome, WriteOutcome::Written);
+	}
+
+	#[test]
+	fn test_crlf_output_round_trips() {
+		let (dir, _) = seeded_dir("crlf");
+		let data = "WeaponPath:gameData/Weapons/test.blkx\nZoomIn:4.5\n\n\
+			Name:75mm_pzgr_39\nType:apcbc_tank\nBulletMass:6.8\nBallisticCaliber:0.075\n\
+			Speed:740.0\nCx:0.4\n";
+		let data_path = dir.join("data.txt");
+		write_output(&data_path, data, OverwritePolicy::Overwrite, LineEnding::Crlf).unwrap();
+
+		let written = std::fs::read_to_string(&data_path).unwrap();
+		assert_eq!(written.matches("\r\n").count(), data.matches('\n').count());
+		assert_eq!(written.matches('\n').count(), data.matches('\n').count());
+		let parsed = parse_data_text(&written, "test_tank");
+		assert_eq!(parsed.weapon_path.as_deref(), Some("gameData/Weapons/test.blkx"));
+		assert_eq!(parsed.zoom_in, Some(4.5));
+		assert_eq!(parsed.projectiles.len(), 1);
+		assert_eq!(parsed.projectiles[0].name, "75mm_pzgr_39");
+
+		let cfg = BallisticConfig::new(1.0);
+		let table = compute_ballistic_with_config(&parsed.projectiles[0], &cfg).unwrap();
+		let table_path = dir.join("pzgr_39.txt");
+		write_output(&table_path, &table, OverwritePolicy::Overwrite, LineEnding::Crlf).unwrap();
+		let written = std::fs::read_to_string(&table_path).unwrap();
+		assert!(written.split_terminator('\n').all(|line| line.ends_with('\r')));
+		assert_eq!(parse_table(&written), parse_table(&table));
+	}
+}
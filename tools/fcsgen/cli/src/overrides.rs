@@ -0,0 +1,189 @@
+//! Per-shell physics overrides (`run --overrides <file>`).
+//!
+//! Each line is `vehicle:shell:field=value`, e.g.
+//! `us_m1_abrams:m829a2:speed=1700`, and replaces one field of that shell's
+//! [`DataProjectile`] before its ballistic table is computed.  The vehicle
+//! is matched case-insensitively; the shell by its output name (`m829a2`)
+//! or full name (`120mm_m829a2`).  Blank lines and `#` comments are
+//! ignored.
+//!
+//! Overrides change the projectile itself, so they are part of its
+//! [`BallisticKey`](fcsgen_core::BallisticKey) and never mix with cached
+//! tables of the unmodified shell.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use fcsgen_core::parser::data::DataProjectile;
+
+use crate::log_file::{LogFile, logln};
+
+/// Overridable fields, by the name used in the file.
+#[allow(clippy::type_complexity)]
+const FIELDS: [(&str, fn(&mut DataProjectile) -> &mut f64); 11] = [
+	("mass", |p| &mut p.mass),
+	("speed", |p| &mut p.speed),
+	("cx", |p| &mut p.cx),
+	("ballistic_caliber", |p| &mut p.ballistic_caliber),
+	("explosive_mass", |p| &mut p.explosive_mass),
+	("damage_mass", |p| &mut p.damage_mass),
+	("damage_caliber", |p| &mut p.damage_caliber),
+	("demarre_k", |p| &mut p.demarre_k),
+	("demarre_speed_pow", |p| &mut p.demarre_speed_pow),
+	("demarre_mass_pow", |p| &mut p.demarre_mass_pow),
+	("demarre_caliber_pow", |p| &mut p.demarre_caliber_pow),
+];
+
+/// One `shell:field=value` line for a vehicle.
+#[derive(Debug, Clone)]
+struct Override {
+	shell: String,
+	/// Index into [`FIELDS`].
+	field: usize,
+	value: f64,
+}
+
+/// Parsed overrides, by lowercase vehicle name.  Sorted, so that the
+/// `Debug` output of equal overrides is equal (see the run's version marker).
+#[derive(Debug, Default)]
+pub struct Overrides {
+	by_vehicle: BTreeMap<String, Vec<Override>>,
+}
+
+impl Overrides {
+	/// Parse an overrides file, returning a warning per line that was
+	/// skipped (malformed, unknown field or non-numeric value).
+	pub fn parse(text: &str) -> (Self, Vec<String>) {
+		let mut overrides = Self::default();
+		let mut warnings = Vec::new();
+
+		for (i, line) in text.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			let line_no = i + 1;
+
+			let Some((vehicle, shell, field, value)) = split_line(line) else {
+				warnings.push(format!(
+					"line {line_no}: expected `vehicle:shell:field=value`, got `{line}`"
+				));
+				continue;
+			};
+
+			let field = field.trim();
+			let Some(index) = FIELDS.iter().position(|(name, _)| *name == field) else {
+				let known: Vec<_> = FIELDS.iter().map(|(name, _)| *name).collect();
+				warnings.push(format!(
+					"line {line_no}: unknown field `{field}` (expected one of {})",
+					known.join(", "),
+				));
+				continue;
+			};
+			let Ok(value) = value.trim().parse::<f64>() else {
+				warnings.push(format!("line {line_no}: `{}` is not a number", value.trim()));
+				continue;
+			};
+
+			overrides
+				.by_vehicle
+				.entry(vehicle.trim().to_lowercase())
+				.or_default()
+				.push(Override {
+					shell: shell.trim().to_owned(),
+					field: index,
+					value,
+				});
+		}
+
+		(overrides, warnings)
+	}
+
+	/// Read and parse an overrides file, printing a warning per skipped line.
+	pub fn load(path: &Path, log: &LogFile) -> std::io::Result<Self> {
+		let (overrides, warnings) = Self::parse(&std::fs::read_to_string(path)?);
+		for warning in warnings {
+			logln!(log, "Warning: {}: {warning}", path.display());
+		}
+		Ok(overrides)
+	}
+
+	/// Apply the overrides for `vehicle` to its projectiles, in file order.
+	pub fn apply(&self, vehicle: &str, projectiles: &mut [DataProjectile]) {
+		let Some(overrides) = self.by_vehicle.get(&vehicle.to_lowercase()) else {
+			return;
+		};
+		for o in overrides {
+			for proj in projectiles
+				.iter_mut()
+				.filter(|p| p.output_name == o.shell || p.name == o.shell)
+			{
+				*(FIELDS[o.field].1)(proj) = o.value;
+			}
+		}
+	}
+}
+
+/// Split `vehicle:shell:field=value` into its four parts.
+fn split_line(line: &str) -> Option<(&str, &str, &str, &str)> {
+	let (lhs, value) = line.split_once('=')?;
+	let mut parts = lhs.splitn(3, ':');
+	Some((parts.next()?, parts.next()?, parts.next()?, value))
+}
+
+#[cfg(test)]
+mod tests {
+	use fcsgen_core::ballistic::{BallisticConfig, BallisticKey, compute_ballistic_with_config};
+	use fcsgen_core::parser::data::parse_data_text;
+
+	use super::*;
+
+	const DATA: &str = "\
+WeaponPath:gameData/Weapons/test.blkx
+
+Name:75mm_pzgr_39
+Type:apcbc_tank
+BulletMass:6.8
+BallisticCaliber:0.075
+Speed:740.0
+Cx:0.4
+";
+
+	#[test]
+	fn test_speed_override_changes_table() {
+		let (overrides, warnings) = Overrides::parse(
+			"# faster Pzgr\n\nTest_Tank:pzgr_39:speed=900\nother_tank:pzgr_39:speed=100\n",
+		);
+		assert!(warnings.is_empty(), "{warnings:?}");
+
+		let original = parse_data_text(DATA, "test_tank").projectiles;
+		let mut modified = original.clone();
+		overrides.apply("test_tank", &mut modified);
+		assert!((modified[0].speed - 900.0).abs() < f64::EPSILON);
+
+		let cfg = BallisticConfig::new(1.0);
+		assert_ne!(
+			compute_ballistic_with_config(&original[0], &cfg),
+			compute_ballistic_with_config(&modified[0], &cfg),
+		);
+		assert!(
+			BallisticKey::with_config(&original[0], &cfg)
+				!= BallisticKey::with_config(&modified[0], &cfg)
+		);
+	}
+
+	#[test]
+	fn test_bad_lines_warned_and_skipped() {
+		let (overrides, warnings) = Overrides::parse(
+			"test_tank:pzgr_39:velocity=900\ntest_tank:pzgr_39:mass=heavy\ntest_tank=1\n\
+			 test_tank:75mm_pzgr_39:cx=0.3\n",
+		);
+		assert_eq!(warnings.len(), 3, "{warnings:?}");
+		assert!(warnings[0].contains("unknown field `velocity`"));
+
+		let mut projectiles = parse_data_text(DATA, "test_tank").projectiles;
+		overrides.apply("test_tank", &mut projectiles);
+		assert!((projectiles[0].cx - 0.3).abs() < f64::EPSILON);
+		assert!((projectiles[0].mass - 6.8).abs() < f64::EPSILON);
+	}
+}
@@ -0,0 +1,215 @@
+//! CLI orchestrator for the `quick` subcommand.
+//!
+//! Builds a single [`DataProjectile`] from command-line flags and prints its
+//! ballistic table to stdout, without a datamine or `Data/` tree.  For quick
+//! experiments with the engine and for support questions ("what does a
+//! 740 m/s 75 mm APCBC do at 2 km?").
+
+use clap::Args;
+use fcsgen_core::ballistic::{
+	BallisticConfig,
+	compute_ballistic_with_warnings,
+	is_unsupported_type,
+	should_skip,
+};
+use fcsgen_core::parser::data::{
+	DataProjectile,
+	DefaultCx,
+	caliber_m_from_mm,
+	is_incendiary_type,
+	is_tracer_type,
+	normalize_shell_type,
+};
+
+use crate::ballistic::print_warnings;
+use crate::config;
+use crate::log_file::LogFile;
+
+/// Flags of the `quick` subcommand.
+#[derive(Args, Debug)]
+pub struct QuickArgs {
+	/// Shell type as in the datamine (e.g. `apcbc`, `apds_fs`, `he_frag_tank`)
+	#[arg(long = "type", value_name = "TYPE")]
+	pub bullet_type: String,
+
+	/// Projectile mass in kg
+	#[arg(long)]
+	pub mass: f64,
+
+	/// Ballistic caliber in mm
+	#[arg(long)]
+	pub caliber: f64,
+
+	/// Muzzle velocity in m/s
+	#[arg(long)]
+	pub speed: f64,
+
+	/// Drag coefficient; defaults to a per-caliber approximation
+	#[arg(long)]
+	pub cx: Option<f64>,
+
+	/// Explosive filler mass in kg (APHE penalty)
+	#[arg(long, default_value_t = 0.0)]
+	pub explosive_mass: f64,
+
+	/// Penetrator core mass in kg (required for `apcr` and `apds`)
+	#[arg(long)]
+	pub damage_mass: Option<f64>,
+
+	/// Penetrator core caliber in mm (required for `apcr` and `apds`)
+	#[arg(long)]
+	pub damage_caliber: Option<f64>,
+
+	/// Armor power series as `distance:penetration` pairs in m and mm, e.g.
+	/// `0:500,1000:480` (required for `apds_fs`)
+	#[arg(long, value_delimiter = ',', value_parser = parse_armor_power)]
+	pub armor_power: Vec<(f64, f64)>,
+
+	/// Mouse sensitivity (0 < s ≤ 1); the scroll step is 2.8·s² mrad
+	#[arg(short, long, default_value_t = 0.50, value_parser = config::parse_sensitivity)]
+	pub sensitivity: f64,
+
+	/// Gravitational acceleration in m/s² (for modded low-gravity servers)
+	#[arg(long, default_value_t = BallisticConfig::STANDARD_GRAVITY)]
+	pub gravity: f64,
+}
+
+/// Run the `quick` subcommand: print the table, or exit on invalid flags.
+pub fn run_quick(args: &QuickArgs) {
+	match quick_table(args) {
+		Ok(table) => print!("{table}"),
+		Err(e) => {
+			eprintln!("Error: {e}");
+			std::process::exit(1);
+		},
+	}
+}
+
+/// The ballistic TSV of the projectile described by `args`.
+fn quick_table(args: &QuickArgs) -> Result<String, String> {
+	let proj = quick_projectile(args)?;
+	if is_unsupported_type(&proj.normalized_type) {
+		eprintln!(
+			"Warning: type `{}` has no penetration model; penetration is 0",
+			proj.bullet_type,
+		);
+	}
+	let cfg = BallisticConfig {
+		gravity: args.gravity,
+		..BallisticConfig::new(args.sensitivity)
+	};
+	let mut warnings = Vec::new();
+	let table = compute_ballistic_with_warnings(&proj, &cfg, &mut warnings);
+	print_warnings(&proj.output_name, &warnings, &LogFile::default());
+	table.ok_or_else(|| format!("type `{}` is not computed", args.bullet_type))
+}
+
+/// Build the projectile, checking the fields its type needs.
+fn quick_projectile(args: &QuickArgs) -> Result<DataProjectile, String> {
+	for (flag, value) in [("mass", args.mass), ("caliber", args.caliber), ("speed", args.speed)] {
+		if !(value > 0.0 && value.is_finite()) {
+			return Err(format!("--{flag} must be a positive number (got {value})"));
+		}
+	}
+
+	let normalized_type = normalize_shell_type(&args.bullet_type);
+	if should_skip(&normalized_type) {
+		return Err(format!(
+			"type `{}` is a guided or rocket type, which has no ballistic table",
+			args.bullet_type,
+		));
+	}
+	let (damage_mass, damage_caliber) = match normalized_type.as_str() {
+		"apcr" | "apds" => match (args.damage_mass, args.damage_caliber) {
+			(Some(mass), Some(caliber)) if mass > 0.0 && caliber > 0.0 => {
+				(mass, caliber_m_from_mm(caliber))
+			},
+			_ => {
+				return Err(format!(
+					"subcaliber type `{}` needs a positive --damage-mass and --damage-caliber",
+					args.bullet_type,
+				));
+			},
+		},
+		"apds_fs" if args.armor_power.is_empty() => {
+			return Err(format!("type `{}` needs an --armor-power series", args.bullet_type));
+		},
+		_ => (0.0, 0.0),
+	};
+
+	let mut armor_power_table = args.armor_power.clone();
+	armor_power_table.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+	let ballistic_caliber = caliber_m_from_mm(args.caliber);
+	let name = format!("{}mm_quick", args.caliber);
+	Ok(DataProjectile {
+		name,
+		bullet_type: args.bullet_type.clone(),
+		is_tracer: is_tracer_type(&args.bullet_type),
+		is_incendiary: is_incendiary_type(&args.bullet_type),
+		normalized_type,
+		mass: args.mass,
+		ballistic_caliber,
+		speed: args.speed,
+		cx: args.cx.unwrap_or_else(|| DefaultCx::ByCaliber.cx(Some(ballistic_caliber))),
+		explosive_mass: args.explosive_mass,
+		explosive_type: None,
+		blast_radius: None,
+		fuze_delay_m: None,
+		fuze_sensitivity: None,
+		damage_mass,
+		damage_caliber,
+		demarre_k: 0.0,
+		demarre_speed_pow: 0.0,
+		demarre_mass_pow: 0.0,
+		demarre_caliber_pow: 0.0,
+		armor_power_table,
+		output_name: "quick".to_owned(),
+	})
+}
+
+/// Clap value parser for one `distance:penetration` pair of `--armor-power`.
+fn parse_armor_power(s: &str) -> Result<(f64, f64), String> {
+	let (distance, pen) = s
+		.split_once(':')
+		.ok_or_else(|| format!("expected `distance:penetration`, got `{s}`"))?;
+	let number = |v: &str| v.trim().parse::<f64>().map_err(|e| format!("`{v}`: {e}"));
+	Ok((number(distance)?, number(pen)?))
+}
+
+#[cfg(test)]
+mod tests {
+	use clap::{Command, FromArgMatches};
+
+	use super::*;
+
+	fn parse(args: &[&str]) -> QuickArgs {
+		let matches = QuickArgs::augment_args(Command::new("quick"))
+			.try_get_matches_from(std::iter::once("quick").chain(args.iter().copied()))
+			.unwrap();
+		QuickArgs::from_arg_matches(&matches).unwrap()
+	}
+
+	#[test]
+	fn test_quick_pzgr_39_first_and_last_rows() {
+		let args = parse(&[
+			"--type", "apcbc", "--mass", "6.8", "--caliber", "75", "--speed", "740", "--cx", "0.4",
+			"--sensitivity", "0.5",
+		]);
+		let table = quick_table(&args).unwrap();
+		let rows: Vec<_> = table.lines().collect();
+		// distance, time of flight, penetration
+		assert_eq!(rows[0], "0.000\t0\t124");
+		assert_eq!(rows[rows.len() - 1], "4473.443\t8.8\t45");
+	}
+
+	#[test]
+	fn test_quick_subcaliber_needs_core() {
+		let args = parse(&["--type", "apcr", "--mass", "4.1", "--caliber", "75", "--speed", "930"]);
+		let err = quick_projectile(&args).unwrap_err();
+		assert!(err.contains("--damage-mass"), "{err}");
+
+		let args = parse(&["--type", "atgm", "--mass", "20", "--caliber", "152", "--speed", "300"]);
+		assert!(quick_projectile(&args).is_err());
+	}
+}
@@ -0,0 +1,221 @@
+//! CLI orchestrator for the hidden `gen-reference` subcommand.
+//!
+//! Regenerates the integration-test reference corpus
+//! (`test_data/expected/{data,ballistic}/`) from a game install, using the
+//! in-memory pipeline at the canonical sensitivity.  This is the only
+//! supported way to refresh the corpus, so hand edits do not drift from what
+//! the pipeline produces.
+//!
+//! Both trees are keyed on the lowercase `.blkx` stem (not the unittags
+//! casing `run` uses): the corpus tests look up `data/{stem}.txt`,
+//! `ballistic/{stem}/` and `tankmodels/{stem}.blkx` by the same name.
+
+use std::io;
+use std::path::Path;
+
+use fcsgen_core::ballistic::{
+	BallisticCache,
+	BallisticConfig,
+	compute_ballistic_cached_with_warnings,
+};
+use fcsgen_core::parser::data::{DefaultCx, fill_default_cx, from_projectile};
+use fcsgen_core::{VehicleData, convert_vehicle_in_memory, emit_legacy_txt};
+use rayon::prelude::*;
+
+use crate::ballistic::print_warnings;
+use crate::extract;
+use crate::log_file::LogFile;
+use crate::output::{CollisionPolicy, plan_shell_files};
+
+/// Sensitivity the reference corpus is generated (and tested) at.
+const REFERENCE_SENSITIVITY: f64 = 0.50;
+
+/// Run the `gen-reference` subcommand.
+///
+/// # Arguments
+/// * `game_path`   – War Thunder installation directory.
+/// * `out`         – Corpus root; `data/` and `ballistic/` are replaced.
+/// * `ignore_file` – Optional vehicle blacklist, as for `run`.
+/// * `confirm`     – Must be set; the existing trees are deleted.
+pub fn run_gen_reference(game_path: &Path, out: &Path, ignore_file: Option<&Path>, confirm: bool) {
+	if !confirm {
+		eprintln!(
+			"Error: gen-reference replaces {} and {}; pass --confirm to proceed",
+			out.join("data").display(),
+			out.join("ballistic").display(),
+		);
+		std::process::exit(1);
+	}
+
+	// Lang CSVs are not part of the corpus; nothing is written to the scratch
+	// directory without them, but keep extraction out of `out` regardless
+	let scratch = std::env::temp_dir().join(format!("fcsgen-gen-reference-{}", std::process::id()));
+	let extraction = extract::run_extract_in_memory(
+		game_path,
+		&scratch,
+		ignore_file,
+		false,
+		false,
+		&LogFile::default(),
+	);
+	let _ = std::fs::remove_dir_all(&scratch);
+
+	// Start from empty trees so vehicles removed from the game don't linger
+	for dir in [out.join("data"), out.join("ballistic")] {
+		let _ = std::fs::remove_dir_all(&dir);
+		if let Err(e) = std::fs::create_dir_all(&dir) {
+			eprintln!("Error: cannot create directory {}: {e}", dir.display());
+			std::process::exit(1);
+		}
+	}
+
+	let ballistic_cfg = BallisticConfig::new(REFERENCE_SENSITIVITY);
+	let cache = BallisticCache::new();
+	let tankmodels_prefix = "gamedata/units/tankmodels";
+
+	let written = extraction
+		.vehicle_names
+		.par_iter()
+		.filter(|name| {
+			let key = format!("{tankmodels_prefix}/{name}.blkx");
+			let Some(content) = extraction.datamine.get(&key) else {
+				return false;
+			};
+			let mut data = match convert_vehicle_in_memory(name, content, &extraction.datamine) {
+				Ok(d) => d,
+				Err(e) => {
+					eprintln!("CONVERT ERROR {name}: {e}");
+					return false;
+				},
+			};
+			// The corpus keeps the legacy default Cx
+			fill_default_cx(&mut data.projectiles, DefaultCx::Uniform);
+			write_vehicle_reference(out, name, &data, &ballistic_cfg, &cache).unwrap_or_else(|e| {
+				eprintln!("WRITE ERROR {name}: {e}");
+				false
+			})
+		})
+		.count();
+
+	eprintln!(
+		"Done: {written} vehicles written to {} (version {}, sensitivity {REFERENCE_SENSITIVITY})",
+		out.display(),
+		extraction.version.as_deref().unwrap_or(extract::UNKNOWN_VERSION),
+	);
+}
+
+/// Write `data/{stem}.txt` and `ballistic/{stem}/{shell}.txt` for one vehicle.
+///
+/// Returns `Ok(false)` for unarmed vehicles, which get no files.  Shells
+/// sharing a file name keep the last one, as in the legacy tool.
+fn write_vehicle_reference(
+	out: &Path,
+	stem: &str,
+	data: &VehicleData,
+	ballistic_cfg: &BallisticConfig,
+	cache: &BallisticCache,
+) -> io::Result<bool> {
+	if !data.is_armed() {
+		return Ok(false);
+	}
+
+	std::fs::write(
+		out.join("data").join(format!("{stem}.txt")),
+		emit_legacy_txt(data),
+	)?;
+
+	let data_projectiles: Vec<_> = data.projectiles.iter().map(from_projectile).collect();
+	let vehicle_dir = out.join("ballistic").join(stem);
+	for (dp, file_stem) in plan_shell_files(
+		stem,
+		&data_projectiles,
+		ballistic_cfg,
+		CollisionPolicy::Last,
+		&LogFile::default(),
+	)
+	.expect("only the error policy fails")
+	{
+		let mut warnings = Vec::new();
+		let (table, _) =
+			compute_ballistic_cached_with_warnings(dp, ballistic_cfg, cache, &mut warnings);
+		print_warnings(&dp.output_name, &warnings, &LogFile::default());
+		if let Some(table) = table.filter(|t| !t.is_empty()) {
+			std::fs::create_dir_all(&vehicle_dir)?;
+			std::fs::write(vehicle_dir.join(format!("{file_stem}.txt")), table)?;
+		}
+	}
+
+	Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeSet;
+	use std::path::PathBuf;
+
+	use fcsgen_core::convert_vehicle;
+
+	use crate::test_util::TempDir;
+	use super::*;
+
+	/// Get the path to the `test_data` directory.
+	fn test_data_dir() -> PathBuf {
+		PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+			.parent()
+			.unwrap()
+			.join("test_data")
+	}
+
+	/// File names in `dir`, sorted.
+	fn file_names(dir: &Path) -> BTreeSet<String> {
+		std::fs::read_dir(dir)
+			.unwrap()
+			.map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+			.collect()
+	}
+
+	/// The generated layout is the one the corpus tests read: `data/{stem}.txt`
+	/// plus one `ballistic/{stem}/{shell}.txt` per reference shell.
+	#[test]
+	fn test_reference_layout_matches_corpus() {
+		let datamine = test_data_dir().join("datamine");
+		let vehicle_path = datamine
+			.join("aces.vromfs.bin_u")
+			.join("gamedata")
+			.join("units")
+			.join("tankmodels")
+			.join("cn_al_khalid_1.blkx");
+		let expected = test_data_dir().join("expected");
+		if !vehicle_path.exists() || !expected.join("ballistic").join("cn_al_khalid_1").exists() {
+			eprintln!("Skipping reference layout test: corpus not present");
+			return;
+		}
+
+		let out = TempDir::new("reference");
+		std::fs::create_dir_all(out.join("data")).unwrap();
+
+		let data = convert_vehicle(&vehicle_path, &datamine).unwrap();
+		let written = write_vehicle_reference(
+			&out,
+			"cn_al_khalid_1",
+			&data,
+			&BallisticConfig::new(REFERENCE_SENSITIVITY),
+			&BallisticCache::new(),
+		)
+		.unwrap();
+
+		assert!(written);
+		assert_eq!(
+			file_names(&out.join("data")),
+			BTreeSet::from(["cn_al_khalid_1.txt".to_owned()])
+		);
+		assert_eq!(
+			file_names(&out.join("ballistic")),
+			BTreeSet::from(["cn_al_khalid_1".to_owned()])
+		);
+		assert_eq!(
+			file_names(&out.join("ballistic").join("cn_al_khalid_1")),
+			file_names(&expected.join("ballistic").join("cn_al_khalid_1")),
+		);
+	}
+}
@@ -0,0 +1,199 @@
+//! Machine-readable summary of a `run` (`--report <file>`).
+//!
+//! Alongside the totals printed to stderr, the report lists every shell that
+//! did not get a usable ballistic table, grouped by reason, so missing
+//! support can be prioritised across the whole datamine.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use fcsgen_core::BallisticKey;
+use fcsgen_core::parser::data::DataProjectile;
+use serde::Serialize;
+
+/// A shell of one vehicle, as listed in the report.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ShellRef {
+	pub vehicle: String,
+	/// Full projectile name (e.g. `75mm_sprgr_34`).
+	pub shell: String,
+	/// Raw type string (e.g. `he_frag_tank`).
+	#[serde(rename = "type")]
+	pub bullet_type: String,
+}
+
+impl ShellRef {
+	pub fn new(vehicle: &str, proj: &DataProjectile) -> Self {
+		Self {
+			vehicle: vehicle.to_owned(),
+			shell: proj.name.clone(),
+			bullet_type: proj.bullet_type.clone(),
+		}
+	}
+}
+
+/// A vehicle whose processing failed, with the error message.
+#[derive(Debug, Clone, Serialize)]
+pub struct VehicleError {
+	pub vehicle: String,
+	pub error: String,
+}
+
+/// Shells without a usable table, by reason.  Each shell is listed under the
+/// first reason that applies, in field order.
+#[derive(Debug, Default, Serialize)]
+pub struct SkippedShells {
+	/// SAM/ATGM/rocket/AAM types, never computed.
+	pub skipped_type: Vec<ShellRef>,
+	/// Shells slower than `--min-speed`, never computed.
+	pub low_speed: Vec<ShellRef>,
+	/// Types the penetration model does not know; written with 0 penetration.
+	pub unsupported_type: Vec<ShellRef>,
+	/// APDS-FS rounds without an armor power table; written with 0 penetration.
+	pub empty_armor_table: Vec<ShellRef>,
+	/// The same rounds with `--estimate-missing-pen`; written with an
+	/// estimated penetration.
+	pub estimated_pen: Vec<ShellRef>,
+	/// All-zero tables dropped by `--skip-zero-pen`.
+	pub zero_pen: Vec<ShellRef>,
+}
+
+impl SkippedShells {
+	pub fn merge(&mut self, other: Self) {
+		self.skipped_type.extend(other.skipped_type);
+		self.low_speed.extend(other.low_speed);
+		self.unsupported_type.extend(other.unsupported_type);
+		self.empty_armor_table.extend(other.empty_armor_table);
+		self.estimated_pen.extend(other.estimated_pen);
+		self.zero_pen.extend(other.zero_pen);
+	}
+
+	/// Sort each list and drop repeats (a shell often appears in several
+	/// belts of the same vehicle).
+	fn normalize(&mut self) {
+		for list in [
+			&mut self.skipped_type,
+			&mut self.low_speed,
+			&mut self.unsupported_type,
+			&mut self.empty_armor_table,
+			&mut self.estimated_pen,
+			&mut self.zero_pen,
+		] {
+			list.sort();
+			list.dedup();
+		}
+	}
+}
+
+/// Totals of a `run`, mirroring the stderr summary.
+#[derive(Debug, Default, Serialize)]
+pub struct RunReport {
+	pub converted: usize,
+	pub skipped_unarmed: usize,
+	pub convert_errors: usize,
+	pub shells_written: usize,
+	pub ballistic_errors: usize,
+	pub existing_skipped: usize,
+	pub timed_out: usize,
+	/// Cache hits whose recomputed table differed, with `--verify-cache`.
+	pub cache_mismatches: usize,
+	/// Shells skipped by `--min-speed` (listed in `skipped_shells.low_speed`).
+	pub skipped_low_speed: usize,
+	/// `--range-marks` beyond a shell's reach, left out of table headers.
+	pub unreachable_marks: usize,
+	/// Vehicles of another kind than `--rangefinder`, not converted.
+	pub skipped_wrong_rangefinder: usize,
+	/// Vehicles below `--min-br` or of another `--rank`, not converted.
+	pub skipped_low_br: usize,
+	/// Armed vehicles without a single computed shell type (ATGM/SAM
+	/// carriers), as opposed to `skipped_unarmed`.
+	pub shells_all_skipped: usize,
+	pub skipped_shells: SkippedShells,
+	/// Vehicles whose processing panicked (counted in `convert_errors`).
+	pub panicked: Vec<VehicleError>,
+	/// Identical tables across shells, with `--report-duplicates`.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub duplicates: Option<DuplicateReport>,
+	/// Timing and cache use of each iteration, with `--repeat`; the totals
+	/// above are those of the last one.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub iterations: Vec<IterationReport>,
+}
+
+/// One iteration of a `--repeat` benchmark.
+#[derive(Debug, Clone, Serialize)]
+pub struct IterationReport {
+	/// 1-based iteration number.
+	pub iteration: usize,
+	/// Wall-clock time of the convert and ballistic stages.
+	pub seconds: f64,
+	pub cache_hits: usize,
+	pub cache_misses: usize,
+}
+
+/// Number of groups kept in [`DuplicateReport::largest_groups`].
+const DUPLICATE_GROUPS_LISTED: usize = 20;
+
+/// How much of the ballistic output is the same table written for several
+/// shells (`--report-duplicates`), measured by [`BallisticKey`]: shells with
+/// equal keys get byte-identical tables.
+#[derive(Debug, Default, Serialize)]
+pub struct DuplicateReport {
+	/// Tables produced, one per shell (sections of `--per-vehicle-file`
+	/// files count individually).
+	pub shell_writes: usize,
+	/// Distinct keys among them, i.e. tables that would remain if identical
+	/// ones were shared.
+	pub distinct_tables: usize,
+	/// The largest groups of shells sharing a key, largest first.
+	pub largest_groups: Vec<DuplicateGroup>,
+}
+
+/// Shells whose tables are identical.
+#[derive(Debug, Serialize)]
+pub struct DuplicateGroup {
+	pub count: usize,
+	pub shells: Vec<ShellRef>,
+}
+
+impl DuplicateReport {
+	/// Group written shells by the cache key of their table.  Only keys
+	/// shared by more than one shell form a group.
+	pub fn new(writes: Vec<(BallisticKey, ShellRef)>) -> Self {
+		let shell_writes = writes.len();
+		let mut by_key: HashMap<BallisticKey, Vec<ShellRef>> = HashMap::new();
+		for (key, shell) in writes {
+			by_key.entry(key).or_default().push(shell);
+		}
+		let distinct_tables = by_key.len();
+
+		let mut groups: Vec<DuplicateGroup> = by_key
+			.into_values()
+			.filter(|shells| shells.len() > 1)
+			.map(|mut shells| {
+				shells.sort();
+				DuplicateGroup {
+					count: shells.len(),
+					shells,
+				}
+			})
+			.collect();
+		// Ties in a stable order, so reports of the same tree are comparable
+		groups.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.shells.cmp(&b.shells)));
+		groups.truncate(DUPLICATE_GROUPS_LISTED);
+
+		Self {
+			shell_writes,
+			distinct_tables,
+			largest_groups: groups,
+		}
+	}
+}
+
+/// Write `report` to `path` as pretty-printed JSON.
+pub fn write_report(path: &Path, mut report: RunReport) -> std::io::Result<()> {
+	report.skipped_shells.normalize();
+	report.panicked.sort_by(|a, b| a.vehicle.cmp(&b.vehicle));
+	let json = serde_json::to_string_pretty(&report).map_err(std::io::Error::other)?;
+	std::fs::write(path, json + "\n")
+}
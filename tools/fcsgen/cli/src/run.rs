@@ -9,33 +9,209 @@
 //!
 //! Vehicles are processed in parallel via [`rayon`], with a shared
 //! [`BallisticCache`] (backed by `DashMap`) for cross-vehicle shell
-//! deduplication.
+//! deduplication.  `--verify-cache` recomputes a sample of cache hits to
+//! catch key collisions; see [`verify_cache_hit`].
+//!
+//! `--from-data` skips extraction and conversion altogether and feeds an
+//! existing `Data/*.txt` tree (e.g. hand-edited) straight into the parallel
+//! ballistic stage.
 
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{BufReader, BufWriter, Write};
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
+use clap::ValueEnum;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use wt_blk::vromf::{File as VromfFile, VromfUnpacker};
 
-use fcsgen_core::ballistic::{BallisticCache, compute_ballistic_cached, should_skip};
-use fcsgen_core::parser::data::from_projectile;
-use fcsgen_core::{convert_vehicle, convert_vehicle_in_memory, emit_legacy_txt, lookup_vehicle_id};
+use fcsgen_core::ballistic::{
+	BallisticCache,
+	BallisticConfig,
+	BallisticKey,
+	compute_ballistic_cached_with_warnings,
+	compute_ballistic_with_config,
+	estimates_penetration,
+	is_unsupported_type,
+	is_zero_penetration,
+	seed_cache,
+	seed_cache_from_reader,
+	should_skip,
+	table_header_with_marks,
+	write_cache,
+	write_cache_to,
+};
+use fcsgen_core::parser::data::{
+	DataProjectile,
+	DefaultCx,
+	fill_default_cx,
+	from_projectile,
+	parse_data_file,
+};
+use fcsgen_core::{
+	Datamine,
+	LangTable,
+	ParseError,
+	Projectile,
+	RangefinderKind,
+	VehicleRank,
+	convert_vehicle_in_memory_with_warnings,
+	convert_vehicle_with_warnings,
+	emit_legacy_txt,
+	lookup_vehicle_id,
+	vehicle_module_keys,
+	vehicle_module_paths,
+	vehicle_nation,
+};
 
+use crate::ballistic::print_warnings;
+use crate::error_log::ErrorLog;
+use crate::log_file::{LogFile, logln};
 use crate::extract;
+use crate::manifest::{ShellManifest, write_manifest};
+use crate::overrides::Overrides;
+use crate::report::{
+	DuplicateReport,
+	IterationReport,
+	RunReport,
+	ShellRef,
+	SkippedShells,
+	VehicleError,
+	write_report,
+};
+use crate::output::{
+	CollisionPolicy,
+	LineEnding,
+	OverwritePolicy,
+	OutputConflict,
+	WriteOutcome,
+	check_exists,
+	check_writable,
+	create_output_dir,
+	plan_shell_files,
+	vehicle_section_header,
+	write_output,
+};
+
+/// Ammunition subset written to its own `Ballistic/{set}/` tree
+/// (`--ammo-set`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AmmoSet {
+	/// Shells available without researching modifications.
+	Stock,
+	/// Every shell, as on a fully researched vehicle.
+	Researched,
+}
+
+impl AmmoSet {
+	/// Subdirectory of `Ballistic/` holding this set.
+	pub fn dir_name(self) -> &'static str {
+		match self {
+			Self::Stock => "stock",
+			Self::Researched => "researched",
+		}
+	}
+
+	fn includes(self, proj: &Projectile) -> bool {
+		match self {
+			Self::Stock => proj.unlocked_by_default,
+			Self::Researched => true,
+		}
+	}
+}
 
 /// Configuration for the unified pipeline.
+#[allow(clippy::struct_excessive_bools)] // one per CLI flag
 pub struct PipelineConfig<'a> {
 	pub game_path: &'a Path,
 	pub output: &'a Path,
-	pub sensitivity: f64,
+	/// Subdirectory names under `output` (defaults `Datamine`, `Data`,
+	/// `Ballistic`); validated as single path components.
+	pub datamine_subdir: &'a str,
+	pub data_subdir: &'a str,
+	pub ballistic_subdir: &'a str,
+	/// Table settings of every shell, `--naval` included.
+	pub ballistic: BallisticConfig,
+	/// `(nation, sensitivity)` overrides of the `ballistic` sensitivity, by
+	/// vehicle ID prefix; see [`vehicle_ballistic_config`].
+	pub sensitivity_by_nation: &'a [(String, f64)],
+	/// Muzzle velocity (m/s) below which a shell is skipped; see
+	/// [`process_ballistic`].
+	pub min_speed: f64,
+	/// Fill missing Cx with the legacy 0.38; see [`DefaultCx`].
+	pub uniform_cx: bool,
+	/// Per-vehicle ballistic budget; see [`process_ballistic`].
+	pub vehicle_timeout: Option<Duration>,
 	pub ignore_file: Option<&'a Path>,
+	/// Existing `Data/` tree to compute ballistics from; bypasses extraction
+	/// and conversion (`game_path` is then unused).
+	pub from_data: Option<&'a Path>,
+	/// JSON summary written at the end of the run; see [`crate::report`].
+	pub report: Option<&'a Path>,
+	/// Ballistic cache file loaded before the first shell is computed.
+	pub seed_cache_from: Option<&'a Path>,
+	/// Ballistic cache file written at the end of the run.
+	pub save_cache: Option<&'a Path>,
 	pub filter: Option<&'a [String]>,
+	/// Skip vehicles with another kind of rangefinder (after conversion).
+	pub rangefinder: Option<RangefinderKind>,
+	/// Skip vehicles below this realistic battle rating; see
+	/// [`rank_matches`].
+	pub min_br: Option<f64>,
+	/// Skip vehicles of another rank (tier).
+	pub rank: Option<u32>,
+	/// Physics overrides applied before ballistic computation.
+	pub overrides: Option<&'a Overrides>,
 	pub jobs: usize,
 	pub skip_extract: bool,
 	pub skip_ballistic: bool,
 	pub write_datamine: bool,
+	/// Skip the lang CSVs (`--no-lang`); see [`load_weaponry_lang`].
+	pub no_lang: bool,
+	pub overwrite_policy: OverwritePolicy,
+	pub line_ending: LineEnding,
+	pub with_header: bool,
+	/// Sight range marks of the header line; see
+	/// [`table_header_with_marks`].
+	pub range_marks: &'a [f64],
+	pub on_collision: CollisionPolicy,
+	pub skip_zero_pen: bool,
+	pub per_vehicle_file: bool,
+	/// Write each set to `Ballistic/{set}/` instead of all shells to
+	/// `Ballistic/`; see [`process_vehicle_ballistic`].
+	pub ammo_sets: &'a [AmmoSet],
+	/// Measure identical tables across shells; see [`DuplicateReport`].
+	pub report_duplicates: bool,
+	/// Fraction of cache hits to recompute; see [`verify_cache_hit`].
+	pub verify_cache: Option<f64>,
+	/// Distinct shells written at the end of the run; see
+	/// [`crate::manifest`].
+	pub shell_manifest: Option<&'a Path>,
+	/// Consume the in-memory datamine in batches; see
+	/// [`run_pipeline_in_memory`].
+	pub low_memory: bool,
+	/// Number of times to run the convert and ballistic stages (`--repeat`
+	/// benchmark); extraction happens once.
+	pub repeat: usize,
+	/// Clear the ballistic cache between repeat iterations.
+	pub repeat_cold: bool,
+	/// Per-vehicle error output, collapsed per category with
+	/// `--quiet-errors`.
+	pub errors: ErrorLog,
+	/// Stderr, copied to the `--log-file` if any.
+	pub log: LogFile,
 }
 
+/// Default `--min-speed`: only shells that do not move at all are skipped.
+pub const DEFAULT_MIN_SPEED: f64 = 1.0;
+
 /// Per-vehicle statistics returned from each parallel work unit.
 ///
 /// Accumulated via `merge` in a rayon `reduce` step — no shared mutable
@@ -49,6 +225,31 @@ struct VehicleStats {
 	ballistic_errors: usize,
 	cache_hits: usize,
 	cache_misses: usize,
+	/// Cache hits whose recomputed table differed, with `--verify-cache`.
+	cache_mismatches: usize,
+	existing_skipped: usize,
+	timed_out: usize,
+	skipped_zero_pen: usize,
+	skipped_low_speed: usize,
+	/// `--range-marks` beyond a shell's reach, left out of its header.
+	unreachable_marks: usize,
+	/// Vehicles dropped by `--rangefinder`; see [`rangefinder_matches`].
+	skipped_wrong_rangefinder: usize,
+	/// Vehicles dropped by `--min-br` / `--rank`; see [`rank_matches`].
+	skipped_low_br: usize,
+	/// Armed vehicles whose shells are all skipped types; see
+	/// [`count_all_skipped`].
+	shells_all_skipped: usize,
+	skipped_shells: SkippedShells,
+	/// Vehicles whose work unit panicked; see [`catch_vehicle_panic`].
+	panicked: Vec<VehicleError>,
+	/// Cache key of every table produced, with `--report-duplicates`.
+	table_keys: Vec<(BallisticKey, ShellRef)>,
+	/// Shells of every vehicle, with `--shell-manifest`.
+	shells: ShellManifest,
+	/// Files held back under `--overwrite-policy error`; see
+	/// [`write_or_stage`].
+	staged: Vec<StagedFile>,
 }
 
 impl VehicleStats {
@@ -60,16 +261,189 @@ impl VehicleStats {
 		self.ballistic_errors += other.ballistic_errors;
 		self.cache_hits += other.cache_hits;
 		self.cache_misses += other.cache_misses;
+		self.cache_mismatches += other.cache_mismatches;
+		self.existing_skipped += other.existing_skipped;
+		self.timed_out += other.timed_out;
+		self.skipped_zero_pen += other.skipped_zero_pen;
+		self.skipped_low_speed += other.skipped_low_speed;
+		self.unreachable_marks += other.unreachable_marks;
+		self.skipped_wrong_rangefinder += other.skipped_wrong_rangefinder;
+		self.skipped_low_br += other.skipped_low_br;
+		self.shells_all_skipped += other.shells_all_skipped;
+		self.skipped_shells.merge(other.skipped_shells);
+		self.panicked.extend(other.panicked);
+		self.table_keys.extend(other.table_keys);
+		self.shells.merge(other.shells);
+		self.staged.extend(other.staged);
 		self
 	}
 }
 
+/// An output file held back until every target of the run is checked.
+struct StagedFile {
+	path: PathBuf,
+	content: String,
+	/// Vehicle or file named in a write error.
+	label: String,
+	/// Tables in the file, 0 for a `Data/` file.
+	shells: usize,
+}
+
+/// Write a vehicle's output file, honouring `cfg.overwrite_policy`.
+///
+/// Under [`OverwritePolicy::Error`] the file is only checked and staged in
+/// `vs`: the run must stop before anything is written if any target
+/// exists, and targets are only known once each vehicle is converted.  The
+/// staged files are written by [`write_staged`] after the parallel phase.
+fn write_or_stage(
+	path: &Path,
+	content: &str,
+	label: &str,
+	shells: usize,
+	cfg: &PipelineConfig<'_>,
+	vs: &mut VehicleStats,
+) -> std::io::Result<WriteOutcome> {
+	if cfg.overwrite_policy != OverwritePolicy::Error {
+		return write_output(path, content, cfg.overwrite_policy, cfg.line_ending);
+	}
+	check_writable(path)?;
+	vs.staged.push(StagedFile {
+		path: path.to_owned(),
+		content: content.to_owned(),
+		label: label.to_owned(),
+		shells,
+	});
+	Ok(WriteOutcome::Written)
+}
+
+/// Write the files staged by [`write_or_stage`], once all vehicles are done
+/// and none of their targets existed.
+///
+/// A target planned twice (e.g. two vehicles with the same ID) is the same
+/// conflict as a target that exists.  A file that fails to write is
+/// reported and taken back out of the counts.
+fn write_staged(stats: &mut VehicleStats, cfg: &PipelineConfig<'_>) -> Result<(), OutputConflict> {
+	let staged = std::mem::take(&mut stats.staged);
+	let mut paths: Vec<&Path> = staged.iter().map(|file| file.path.as_path()).collect();
+	paths.sort_unstable();
+	if let Some(twice) = paths.windows(2).find(|pair| pair[0] == pair[1]) {
+		let err = std::io::Error::new(
+			std::io::ErrorKind::AlreadyExists,
+			format!("{} already exists", twice[0].display()),
+		);
+		check_exists(&err)?;
+	}
+
+	let failed: Vec<&StagedFile> = staged
+		.par_iter()
+		.filter(|file| {
+			let written =
+				write_output(&file.path, &file.content, cfg.overwrite_policy, cfg.line_ending);
+			if let Err(e) = &written {
+				cfg.errors.report(&cfg.log, "WRITE ERROR", &file.label, e);
+			}
+			written.is_err()
+		})
+		.collect();
+	for file in failed {
+		if file.shells == 0 {
+			stats.converted -= 1;
+			stats.convert_failed += 1;
+		} else {
+			stats.shells_written -= file.shells;
+			stats.ballistic_errors += 1;
+		}
+	}
+	Ok(())
+}
+
+/// Run one vehicle's work unit, turning a panic into a convert error.
+///
+/// Rayon would otherwise re-raise the panic on the calling thread and abort
+/// the whole run.  Anything the vehicle wrote before panicking is kept, but
+/// its counts and staged files are lost; the vehicle is reported as one
+/// convert error and listed in [`VehicleStats::panicked`].  An
+/// [`OutputConflict`] is passed through, to stop the run.
+fn catch_vehicle_panic(
+	name: &str,
+	log: &LogFile,
+	work: impl FnOnce() -> Result<VehicleStats, OutputConflict>,
+) -> Result<VehicleStats, OutputConflict> {
+	match std::panic::catch_unwind(AssertUnwindSafe(work)) {
+		Ok(result) => result,
+		Err(payload) => {
+			let message = payload
+				.downcast_ref::<&str>()
+				.map(|s| (*s).to_owned())
+				.or_else(|| payload.downcast_ref::<String>().cloned())
+				.unwrap_or_else(|| "unknown panic".to_owned());
+			logln!(log, "PANIC {name}: {message}");
+			Ok(VehicleStats {
+				convert_failed: 1,
+				panicked: vec![VehicleError {
+					vehicle: name.to_owned(),
+					error: message,
+				}],
+				..VehicleStats::default()
+			})
+		},
+	}
+}
+
+/// Digest of the options that change which files a run writes, or what is
+/// in them, other than the sensitivity; recorded in the version marker.
+///
+/// Hashes their `Debug` output with FNV-1a (see [`extract::fnv1a`]), which
+/// unlike `DefaultHasher` is stable across builds.  A renamed option or
+/// variant only costs one recomputation.  `--no-lang` is left out: the lang
+/// CSVs are not part of the marker.
+fn output_fingerprint(cfg: &PipelineConfig<'_>) -> u64 {
+	let selection = (cfg.ignore_file, cfg.filter, cfg.rangefinder, cfg.min_br, cfg.rank);
+	let tables = (
+		cfg.ballistic,
+		cfg.sensitivity_by_nation,
+		cfg.min_speed,
+		cfg.uniform_cx,
+		cfg.overrides,
+		cfg.skip_zero_pen,
+	);
+	let files = (
+		cfg.line_ending,
+		cfg.with_header,
+		cfg.range_marks,
+		cfg.on_collision,
+		cfg.per_vehicle_file,
+		cfg.ammo_sets,
+	);
+	let options = format!("{selection:?}{tables:?}{files:?}");
+	extract::fnv1a(extract::FNV_OFFSET, options.as_bytes())
+}
+
+/// Report the weapon module entries dropped while converting `vehicle`, as
+/// `CONVERT WARNING` lines collapsed like errors by `--quiet-errors`.
+fn report_convert_warnings(cfg: &PipelineConfig<'_>, vehicle: &str, warnings: &[ParseError]) {
+	for warning in warnings {
+		cfg.errors.report(&cfg.log, "CONVERT WARNING", vehicle, warning);
+	}
+}
+
+/// Whether a run may be skipped as up to date (see [`check_up_to_date`]).
+///
+/// Only runs that extract are: the marker describes an extraction.  A run
+/// asked for a `--report`, `--shell-manifest` or `--save-cache` always goes
+/// through, since none of them is kept from the last run.
+fn checks_freshness(cfg: &PipelineConfig<'_>) -> bool {
+	!cfg.skip_extract
+		&& cfg.from_data.is_none()
+		&& cfg.report.is_none()
+		&& cfg.shell_manifest.is_none()
+		&& cfg.save_cache.is_none()
+}
+
 /// Check whether the pipeline output is already up-to-date.
 ///
-/// Reads the version marker from `datamine_dir` (two-line format:
-/// `version\nsensitivity`) and compares against the current archive version
-/// and requested sensitivity.  Also verifies that `data_dir` contains at
-/// least one `.txt` file and `ballistic_dir` exists.
+/// Compares the cached outputs (see [`cached_version`]) against the current
+/// archive version.
 ///
 /// Returns the cached version string if up-to-date, `None` otherwise.
 fn check_up_to_date(
@@ -77,18 +451,68 @@ fn check_up_to_date(
 	datamine_dir: &Path,
 	data_dir: &Path,
 	ballistic_dir: &Path,
+	marker: &Marker,
+	skip_ballistic: bool,
+) -> Option<String> {
+	let cached_version =
+		cached_version(datamine_dir, data_dir, ballistic_dir, marker, skip_ballistic)?;
+
+	// Read archive version without unpacking
+	let aces_bin = game_path.join("aces.vromfs.bin");
+	let aces_file = VromfFile::new(&aces_bin).ok()?;
+	let aces_unpacker = VromfUnpacker::from_file(&aces_file, true).ok()?;
+	let version = extract::archive_version(aces_unpacker.latest_version()).ok()?;
+
+	if cached_version == version {
+		Some(version)
+	} else {
+		None
+	}
+}
+
+/// Settings of a run recorded in the version marker, after the version.
+#[derive(Debug, Clone, Copy)]
+struct Marker {
 	sensitivity: f64,
+	/// See [`output_fingerprint`].
+	fingerprint: u64,
+}
+
+impl Marker {
+	fn of(cfg: &PipelineConfig<'_>) -> Self {
+		Self {
+			sensitivity: cfg.ballistic.sensitivity,
+			fingerprint: output_fingerprint(cfg),
+		}
+	}
+}
+
+/// Version recorded by the last complete run, if its outputs are usable.
+///
+/// Reads the version marker from `datamine_dir` (three-line format:
+/// `version\nsensitivity\nfingerprint`) and checks the requested `marker`
+/// against it; markers of older releases have no fingerprint and never
+/// match.  Also verifies that `data_dir` contains at least one `.txt` file
+/// and `ballistic_dir` exists.
+fn cached_version(
+	datamine_dir: &Path,
+	data_dir: &Path,
+	ballistic_dir: &Path,
+	marker: &Marker,
 	skip_ballistic: bool,
 ) -> Option<String> {
-	// Read marker file (two-line format: "version\nsensitivity")
+	// Read marker file (three-line format: "version\nsensitivity\nfingerprint")
 	let marker_path = datamine_dir.join(extract::VERSION_MARKER);
 	let marker_content = std::fs::read_to_string(&marker_path).ok()?;
 	let mut lines = marker_content.lines();
-	let cached_version = lines.next()?.trim();
+	let cached_version = lines.next()?.trim().to_owned();
 	let cached_sensitivity: f64 = lines.next()?.trim().parse().ok()?;
+	let cached_fingerprint = u64::from_str_radix(lines.next()?.trim(), 16).ok()?;
 
-	// Compare sensitivity
-	if (cached_sensitivity - sensitivity).abs() > f64::EPSILON {
+	// Compare sensitivity and the other output options
+	if (cached_sensitivity - marker.sensitivity).abs() > f64::EPSILON
+		|| cached_fingerprint != marker.fingerprint
+	{
 		return None;
 	}
 
@@ -110,63 +534,83 @@ fn check_up_to_date(
 		return None;
 	}
 
-	// Read archive version without unpacking
-	let aces_bin = game_path.join("aces.vromfs.bin");
-	let aces_file = VromfFile::new(&aces_bin).ok()?;
-	let aces_unpacker = VromfUnpacker::from_file(&aces_file, true).ok()?;
-	let version = aces_unpacker.latest_version().ok()??;
-	let version_str = version.to_string();
+	Some(cached_version)
+}
 
-	if cached_version == version_str {
-		Some(version_str)
+/// Whether a `--save-cache` / `--seed-cache-from` file is gzip-compressed,
+/// by its `.gz` extension.  Other files are plain JSON, for easy inspection.
+fn is_gzip(path: &Path) -> bool {
+	path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
+
+/// [`seed_cache`], decompressing a `.gz` file as it is parsed.
+fn load_cache(cache: &BallisticCache, path: &Path) -> std::io::Result<usize> {
+	if is_gzip(path) {
+		let decoder = GzDecoder::new(std::fs::File::open(path)?);
+		seed_cache_from_reader(cache, BufReader::new(decoder))
 	} else {
-		None
+		seed_cache(cache, path)
+	}
+}
+
+/// [`write_cache`], compressing as it is serialized for a `.gz` path.
+fn save_cache(cache: &BallisticCache, path: &Path) -> std::io::Result<()> {
+	if is_gzip(path) {
+		let file = BufWriter::new(std::fs::File::create(path)?);
+		let mut encoder = GzEncoder::new(file, Compression::default());
+		write_cache_to(cache, &mut encoder)?;
+		encoder.finish()?.flush()
+	} else {
+		write_cache(cache, path)
 	}
 }
 
-/// Write the version+sensitivity marker after a successful pipeline run.
-fn write_marker(datamine_dir: &Path, version: &str, sensitivity: f64) {
+/// Write the version marker after a successful pipeline run.
+fn write_marker(datamine_dir: &Path, version: &str, marker: &Marker, log: &LogFile) {
 	if let Err(e) = std::fs::create_dir_all(datamine_dir) {
-		eprintln!("Warning: cannot create Datamine dir for marker: {e}");
+		logln!(log, "Warning: cannot create Datamine dir for marker: {e}");
 		return;
 	}
 	let marker_path = datamine_dir.join(extract::VERSION_MARKER);
 	let content = format!(
-		"{version}\n{sensitivity}",
+		"{version}\n{}\n{:016x}",
+		marker.sensitivity, marker.fingerprint,
 	);
 	if let Err(e) = std::fs::write(&marker_path, content) {
-		eprintln!("Warning: failed to write version marker: {e}");
+		logln!(log, "Warning: failed to write version marker: {e}");
 	}
 }
 
 /// Run the full pipeline: extract → convert → ballistic.
 #[allow(clippy::too_many_lines)]
 pub fn run_pipeline(cfg: &PipelineConfig<'_>) {
-	let datamine_dir = cfg.output.join("Datamine");
-	let data_dir = cfg.output.join("Data");
-	let ballistic_dir = cfg.output.join("Ballistic");
+	let datamine_dir = cfg.output.join(cfg.datamine_subdir);
+	let data_dir = cfg.output.join(cfg.data_subdir);
+	let ballistic_dir = cfg.output.join(cfg.ballistic_subdir);
 
 	// Create output directories
-	for dir in [&data_dir, &ballistic_dir] {
-		if let Err(e) = std::fs::create_dir_all(dir) {
-			eprintln!("Error: cannot create directory {}: {e}", dir.display());
+	let set_dirs = cfg.ammo_sets.iter().map(|set| ballistic_dir.join(set.dir_name()));
+	for dir in [data_dir.clone(), ballistic_dir.clone()].into_iter().chain(set_dirs) {
+		if let Err(e) = create_output_dir(&dir) {
+			logln!(cfg.log, "Error: cannot create directory {}: {e}", dir.display());
 			std::process::exit(1);
 		}
 	}
 
-	// ── Freshness check: skip if version+sensitivity unchanged ─────────
-	if !cfg.skip_extract {
+	// ── Freshness check: skip if version and options unchanged ─────────
+	if checks_freshness(cfg) {
 		if let Some(ver) = check_up_to_date(
 			cfg.game_path,
 			&datamine_dir,
 			&data_dir,
 			&ballistic_dir,
-			cfg.sensitivity,
+			&Marker::of(cfg),
 			cfg.skip_ballistic,
 		) {
-			eprintln!(
+			logln!(
+				cfg.log,
 				"Already up-to-date (version {ver}, sensitivity {})",
-				cfg.sensitivity,
+				cfg.ballistic.sensitivity,
 			);
 			return;
 		}
@@ -190,60 +634,129 @@ pub fn run_pipeline(cfg: &PipelineConfig<'_>) {
 
 	// Cross-vehicle ballistic cache
 	let ballistic_cache: BallisticCache = BallisticCache::new();
+	if let Some(path) = cfg.seed_cache_from {
+		match load_cache(&ballistic_cache, path) {
+			Ok(count) => logln!(cfg.log, "Seeded ballistic cache with {count} entries"),
+			Err(e) => {
+				logln!(cfg.log, "Warning: ignoring cache file {}: {e}", path.display());
+			},
+		}
+	}
 
-	let sensitivity = cfg.sensitivity;
+	let ballistic_cfg = cfg.ballistic;
 	let skip_ballistic = cfg.skip_ballistic;
 
-	// ── Branch: in-memory vs disk-based extraction ─────────────────────
-	if cfg.skip_extract {
+	// ── Branch: existing Data/ tree vs in-memory vs disk-based extraction ─
+	let mut extraction = None;
+	if cfg.from_data.is_some() {
+		logln!(cfg.log, "Steps 1-2/3: Skipping extraction and conversion (--from-data)");
+		if cfg.min_br.is_some() || cfg.rank.is_some() {
+			// Battle ratings come from char.vromfs.bin, which is not read
+			logln!(cfg.log, "Warning: --min-br and --rank are ignored with --from-data");
+		}
+	} else if cfg.skip_extract {
 		// Disk-based path: read .blkx files from a previous extraction
-		eprintln!("Step 1/3: Skipping extraction (--skip-extract)");
-		run_pipeline_from_disk(
-			cfg,
-			&datamine_dir,
-			&data_dir,
-			&ballistic_dir,
-			&ballistic_cache,
-			sensitivity,
-			skip_ballistic,
-			thread_count,
-		);
+		logln!(cfg.log, "Step 1/3: Skipping extraction (--skip-extract)");
 	} else {
 		// In-memory path: extract → convert → ballistic without writing .blkx
-		eprintln!("Step 1/3: Extracting datamine...");
-		let extraction = extract::run_extract_in_memory(
+		logln!(cfg.log, "Step 1/3: Extracting datamine...");
+		extraction = Some(extract::run_extract_in_memory(
 			cfg.game_path,
 			&datamine_dir,
 			cfg.ignore_file,
 			cfg.write_datamine,
-		);
-		run_pipeline_in_memory(
-			cfg,
-			&extraction,
-			&data_dir,
-			&ballistic_dir,
-			&ballistic_cache,
-			sensitivity,
-			skip_ballistic,
-			thread_count,
-		);
+			!cfg.no_lang,
+			&cfg.log,
+		));
+	}
+
+	// `--repeat` reruns the stages after extraction on the same datamine
+	let mut stats = VehicleStats::default();
+	let mut iterations = Vec::new();
+	for iteration in 1..=cfg.repeat {
+		if iteration > 1 && cfg.repeat_cold {
+			ballistic_cache.clear();
+		}
+		let start = Instant::now();
+		stats = if let Some(from_data) = cfg.from_data {
+			run_pipeline_from_data(
+				cfg,
+				from_data,
+				&ballistic_dir,
+				&ballistic_cache,
+				&ballistic_cfg,
+				thread_count,
+			)
+		} else if let Some(extraction) = &mut extraction {
+			run_pipeline_in_memory(
+				cfg,
+				extraction,
+				&data_dir,
+				&ballistic_dir,
+				&ballistic_cache,
+				&ballistic_cfg,
+				skip_ballistic,
+				thread_count,
+			)
+		} else {
+			run_pipeline_from_disk(
+				cfg,
+				&datamine_dir,
+				&data_dir,
+				&ballistic_dir,
+				&ballistic_cache,
+				&ballistic_cfg,
+				skip_ballistic,
+				thread_count,
+			)
+		}
+		// Raised in a worker; rayon has stopped starting vehicles by now
+		.and_then(|mut stats| write_staged(&mut stats, cfg).map(|()| stats))
+		.unwrap_or_else(|conflict| conflict.exit(&cfg.log));
+		if cfg.repeat > 1 {
+			let report = IterationReport {
+				iteration,
+				seconds: start.elapsed().as_secs_f64(),
+				cache_hits: stats.cache_hits,
+				cache_misses: stats.cache_misses,
+			};
+			print_iteration(&report, cfg.repeat, &cfg.log);
+			iterations.push(report);
+		}
+	}
+	// The Data/ tree is never converted, so there is always a ballistic stage
+	finish_run(cfg, stats, skip_ballistic && cfg.from_data.is_none(), iterations);
+
+	// Write the version marker on success.  Without a version there
+	// is no marker, so the next run extracts again (as warned).
+	if let Some(version) = extraction.as_ref().and_then(|e| e.version.as_ref()) {
+		write_marker(&datamine_dir, version, &Marker::of(cfg), &cfg.log);
+	}
 
-		// Write version+sensitivity marker on success
-		write_marker(&datamine_dir, &extraction.version, sensitivity);
+	if let Some(path) = cfg.save_cache
+		&& let Err(e) = save_cache(&ballistic_cache, path)
+	{
+		logln!(cfg.log, "Warning: failed to write cache file {}: {e}", path.display());
 	}
 }
 
 /// Pipeline branch: process vehicles from in-memory datamine.
+///
+/// With `--low-memory`, the datamine is consumed as the run goes: vehicles
+/// are converted in batches of `thread_count`, and each batch drops its
+/// tankmodels and the weapon modules no later batch reads (see
+/// [`last_reads`]).  Files no selected vehicle reads are dropped up front.
+#[allow(clippy::too_many_lines)]
 fn run_pipeline_in_memory(
 	cfg: &PipelineConfig<'_>,
-	extraction: &extract::ExtractionResult,
+	extraction: &mut extract::ExtractionResult,
 	data_dir: &Path,
 	ballistic_dir: &Path,
 	ballistic_cache: &BallisticCache,
-	sensitivity: f64,
+	ballistic_cfg: &BallisticConfig,
 	skip_ballistic: bool,
 	thread_count: usize,
-) {
+) -> Result<VehicleStats, OutputConflict> {
 	// Apply vehicle filter
 	let vehicle_names: Vec<&String> = extraction
 		.vehicle_names
@@ -258,75 +771,163 @@ fn run_pipeline_in_memory(
 		.collect();
 
 	let total = vehicle_names.len();
-	let tankmodels_prefix = "gamedata/units/tankmodels";
 
-	eprintln!(
+	logln!(
+		cfg.log,
 		"Step 2/3: Converting {total} vehicles (+ ballistic, sensitivity={}, jobs={thread_count})",
-		cfg.sensitivity,
+		cfg.ballistic.sensitivity,
 	);
-	eprintln!("  Data:      {}", data_dir.display());
+	logln!(cfg.log, "  Data:      {}", data_dir.display());
 	if !skip_ballistic {
-		eprintln!("  Ballistic: {}", ballistic_dir.display());
+		logln!(cfg.log, "  Ballistic: {}", ballistic_dir.display());
 	}
-	eprintln!();
+	logln!(cfg.log);
 
-	let stats = vehicle_names
-		.par_iter()
-		.map(|name| {
+	let unittags = &extraction.unittags;
+	let ranks = &extraction.ranks;
+	let process = |name: &str, vehicle_content: Option<&str>, datamine: &Datamine| {
+		catch_vehicle_panic(name, &cfg.log, || {
 			let mut vs = VehicleStats::default();
 
-			// Look up vehicle content from in-memory datamine
-			let key = format!("{tankmodels_prefix}/{name}.blkx");
-			let vehicle_content = match extraction.datamine.get(&key) {
-				Some(content) => content,
-				None => {
-					eprintln!("CONVERT ERROR {name}: not found in datamine");
-					vs.convert_failed += 1;
-					return vs;
-				},
+			let Some(vehicle_content) = vehicle_content else {
+				cfg.errors.report(&cfg.log, "CONVERT ERROR", name, &"not found in datamine");
+				vs.convert_failed += 1;
+				return Ok(vs);
 			};
 
 			// Convert vehicle from in-memory data
-			let data = match convert_vehicle_in_memory(name, vehicle_content, &extraction.datamine)
-			{
+			let mut warnings = Vec::new();
+			let converted = convert_vehicle_in_memory_with_warnings(
+				name,
+				vehicle_content,
+				datamine,
+				&mut warnings,
+			);
+			report_convert_warnings(cfg, name, &warnings);
+			let mut data = match converted {
 				Ok(d) => d,
 				Err(e) => {
-					eprintln!("CONVERT ERROR {name}: {e}");
+					cfg.errors.report(&cfg.log, "CONVERT ERROR", name, &e);
 					vs.convert_failed += 1;
-					return vs;
+					return Ok(vs);
 				},
 			};
 
 			if !data.is_armed() {
 				vs.skipped += 1;
-				return vs;
+				return Ok(vs);
+			}
+			if !rangefinder_matches(cfg, data.rangefinder_kind(), &mut vs) {
+				return Ok(vs);
+			}
+			if !rank_matches(cfg, ranks.get(&name.to_lowercase()), &mut vs) {
+				return Ok(vs);
+			}
+
+			if cfg.uniform_cx {
+				fill_default_cx(&mut data.projectiles, DefaultCx::Uniform);
 			}
 
 			// Look up correct-casing vehicle ID from unittags
-			let vehicle_id = lookup_vehicle_id(&extraction.unittags, name);
+			let vehicle_id = lookup_vehicle_id(unittags, name);
 
 			// Write Data/{vehicle}.txt (needed by C# sight generator)
 			let txt = emit_legacy_txt(&data);
 			let data_path = data_dir.join(format!("{vehicle_id}.txt"));
-			if let Err(e) = std::fs::write(&data_path, &txt) {
-				eprintln!("WRITE ERROR {name}: {e}");
-				vs.convert_failed += 1;
-				return vs;
+			match write_or_stage(&data_path, &txt, name, 0, cfg, &mut vs) {
+				Ok(WriteOutcome::Written) => {},
+				Ok(WriteOutcome::Skipped) => vs.existing_skipped += 1,
+				Err(e) => {
+					check_exists(&e)?;
+					cfg.errors.report(&cfg.log, "WRITE ERROR", name, &e);
+					vs.convert_failed += 1;
+					return Ok(vs);
+				},
 			}
 
 			vs.converted += 1;
 
 			// Ballistic computation
 			if skip_ballistic {
-				return vs;
+				return Ok(vs);
 			}
 
-			process_ballistic(&data, vehicle_id, ballistic_dir, sensitivity, ballistic_cache, &mut vs);
-			vs
+			process_vehicle_ballistic(
+				&data.projectiles,
+				vehicle_id,
+				ballistic_dir,
+				ballistic_cfg,
+				ballistic_cache,
+				cfg,
+				&mut vs,
+			)?;
+			Ok(vs)
 		})
-		.reduce(VehicleStats::default, VehicleStats::merge);
+	};
+
+	if cfg.low_memory {
+		let mut datamine = std::mem::take(&mut extraction.datamine);
+		let batches: Vec<&[&String]> = vehicle_names.chunks(thread_count.max(1)).collect();
+		let drops = last_reads(&batches, &datamine);
+		let needed: HashSet<&String> = drops.iter().flatten().collect();
+		datamine.retain(|key, _| needed.contains(key));
+
+		let mut stats = VehicleStats::default();
+		for (batch, drop) in batches.iter().zip(&drops) {
+			let contents: Vec<Option<String>> =
+				batch.iter().map(|name| datamine.remove(&tankmodel_key(name))).collect();
+			stats = batch
+				.par_iter()
+				.zip(&contents)
+				.map(|(name, content)| process(name, content.as_deref(), &datamine))
+				.try_reduce(VehicleStats::default, |a, b| Ok(a.merge(b)))?
+				.merge(stats);
+			for key in drop {
+				datamine.remove(key);
+			}
+		}
+		Ok(stats)
+	} else {
+		let datamine = &extraction.datamine;
+		vehicle_names
+			.par_iter()
+			.map(|name| {
+				let content = datamine.get(&tankmodel_key(name)).map(String::as_str);
+				process(name, content, datamine)
+			})
+			.try_reduce(VehicleStats::default, |a, b| Ok(a.merge(b)))
+	}
+}
+
+/// [`Datamine`] key of a vehicle's tankmodel file.
+fn tankmodel_key(name: &str) -> String {
+	format!("gamedata/units/tankmodels/{name}.blkx")
+}
+
+/// For each batch of `--low-memory` vehicles, the datamine keys no later
+/// batch reads: the batch's own tankmodels and the weapon modules last used
+/// by one of its vehicles.  Together they are every file the run needs.
+fn last_reads(batches: &[&[&String]], datamine: &Datamine) -> Vec<Vec<String>> {
+	let mut last_batch: HashMap<String, usize> = HashMap::new();
+	for (i, batch) in batches.iter().enumerate() {
+		for name in *batch {
+			let key = tankmodel_key(name);
+			// Unparseable vehicles are reported by the conversion itself
+			let modules = datamine
+				.get(&key)
+				.and_then(|content| vehicle_module_keys(content).ok())
+				.unwrap_or_default();
+			for key in modules.into_iter().chain([key]) {
+				last_batch.insert(key, i);
+			}
+		}
+	}
 
-	print_stats(&stats, skip_ballistic);
+	let mut drops = vec![Vec::new(); batches.len()];
+	for (key, i) in last_batch {
+		drops[i].push(key);
+	}
+	drops
 }
 
 /// Pipeline branch: process vehicles from disk-based datamine.
@@ -336,24 +937,25 @@ fn run_pipeline_from_disk(
 	data_dir: &Path,
 	ballistic_dir: &Path,
 	ballistic_cache: &BallisticCache,
-	sensitivity: f64,
+	ballistic_cfg: &BallisticConfig,
 	skip_ballistic: bool,
 	thread_count: usize,
-) {
+) -> Result<VehicleStats, OutputConflict> {
 	let aces_root = datamine_dir.join("aces.vromfs.bin_u");
 	let tankmodels = aces_root.join("gamedata").join("units").join("tankmodels");
 
 	if !tankmodels.exists() {
-		eprintln!(
+		logln!(
+			cfg.log,
 			"Error: tankmodels directory not found at {}",
 			tankmodels.display()
 		);
-		eprintln!("Run without --skip-extract to populate the datamine first.");
+		logln!(cfg.log, "Run without --skip-extract to populate the datamine first.");
 		std::process::exit(1);
 	}
 
-	// Load unittags for vehicle ID casing lookup
-	let unittags = extract::extract_unittags(cfg.game_path);
+	// Load unittags for vehicle ID casing lookup, wpcost for `--min-br`
+	let extract::CharConfigs { unittags, ranks } = extract::extract_char(cfg.game_path, &cfg.log);
 
 	// Collect vehicle files
 	let mut vehicles: Vec<_> = std::fs::read_dir(&tankmodels)
@@ -373,147 +975,1953 @@ fn run_pipeline_from_disk(
 	vehicles.sort_by_key(std::fs::DirEntry::file_name);
 	let total = vehicles.len();
 
-	eprintln!(
+	let vehicle_paths: Vec<_> = vehicles.iter().map(std::fs::DirEntry::path).collect();
+	if let Some(warning) = audit_weapon_modules(&vehicle_paths, datamine_dir) {
+		logln!(cfg.log, "Warning: {warning}");
+		logln!(cfg.log, "Run without --skip-extract to re-extract the datamine.");
+	}
+
+	logln!(
+		cfg.log,
 		"Step 2/3: Converting {total} vehicles (+ ballistic, sensitivity={}, jobs={thread_count})",
-		cfg.sensitivity,
+		cfg.ballistic.sensitivity,
 	);
-	eprintln!("  Data:      {}", data_dir.display());
+	logln!(cfg.log, "  Data:      {}", data_dir.display());
 	if !skip_ballistic {
-		eprintln!("  Ballistic: {}", ballistic_dir.display());
+		logln!(cfg.log, "  Ballistic: {}", ballistic_dir.display());
 	}
-	eprintln!();
+	logln!(cfg.log);
 
-	let stats = vehicles
+	vehicles
 		.par_iter()
 		.map(|entry| {
-			let mut vs = VehicleStats::default();
 			let path = entry.path();
 			let name = path.file_stem().unwrap().to_string_lossy().to_string();
+			catch_vehicle_panic(&name, &cfg.log, || {
+				let mut vs = VehicleStats::default();
 
-			// Convert vehicle from disk
-			let data = match convert_vehicle(&path, datamine_dir) {
-				Ok(d) => d,
-				Err(e) => {
-					eprintln!("CONVERT ERROR {name}: {e}");
-					vs.convert_failed += 1;
-					return vs;
-				},
-			};
+				// Convert vehicle from disk
+				let mut warnings = Vec::new();
+				let converted = convert_vehicle_with_warnings(&path, datamine_dir, &mut warnings);
+				report_convert_warnings(cfg, &name, &warnings);
+				let mut data = match converted {
+					Ok(d) => d,
+					Err(e) => {
+						cfg.errors.report(&cfg.log, "CONVERT ERROR", &name, &e);
+						vs.convert_failed += 1;
+						return Ok(vs);
+					},
+				};
 
-			if !data.is_armed() {
-				vs.skipped += 1;
-				return vs;
-			}
+				if !data.is_armed() {
+					vs.skipped += 1;
+					return Ok(vs);
+				}
+				if !rangefinder_matches(cfg, data.rangefinder_kind(), &mut vs) {
+					return Ok(vs);
+				}
+				if !rank_matches(cfg, ranks.get(&name.to_lowercase()), &mut vs) {
+					return Ok(vs);
+				}
 
-			// Look up correct-casing vehicle ID from unittags
-			let vehicle_id = lookup_vehicle_id(&unittags, &name);
+				if cfg.uniform_cx {
+					fill_default_cx(&mut data.projectiles, DefaultCx::Uniform);
+				}
 
-			// Write Data/{vehicle}.txt using correct casing from unittags
-			let txt = emit_legacy_txt(&data);
-			let data_path = data_dir.join(format!("{vehicle_id}.txt"));
-			if let Err(e) = std::fs::write(&data_path, &txt) {
-				eprintln!("WRITE ERROR {name}: {e}");
-				vs.convert_failed += 1;
-				return vs;
-			}
+				// Look up correct-casing vehicle ID from unittags
+				let vehicle_id = lookup_vehicle_id(&unittags, &name);
 
-			vs.converted += 1;
+				// Write Data/{vehicle}.txt using correct casing from unittags
+				let txt = emit_legacy_txt(&data);
+				let data_path = data_dir.join(format!("{vehicle_id}.txt"));
+				match write_or_stage(&data_path, &txt, &name, 0, cfg, &mut vs) {
+					Ok(WriteOutcome::Written) => {},
+					Ok(WriteOutcome::Skipped) => vs.existing_skipped += 1,
+					Err(e) => {
+						check_exists(&e)?;
+						cfg.errors.report(&cfg.log, "WRITE ERROR", &name, &e);
+						vs.convert_failed += 1;
+						return Ok(vs);
+					},
+				}
 
-			if skip_ballistic {
-				return vs;
-			}
+				vs.converted += 1;
+
+				if skip_ballistic {
+					return Ok(vs);
+				}
 
-			// Use unittags lookup for ballistic folder names (correct casing)
-			process_ballistic(&data, vehicle_id, ballistic_dir, sensitivity, ballistic_cache, &mut vs);
-			vs
+				// Use unittags lookup for ballistic folder names (correct casing)
+				process_vehicle_ballistic(
+					&data.projectiles,
+					vehicle_id,
+					ballistic_dir,
+					ballistic_cfg,
+					ballistic_cache,
+					cfg,
+					&mut vs,
+				)?;
+				Ok(vs)
+			})
 		})
-		.reduce(VehicleStats::default, VehicleStats::merge);
+		.try_reduce(VehicleStats::default, |a, b| Ok(a.merge(b)))
+}
+
+/// Warning for a datamine that lacks weapon modules referenced by `vehicles`
+/// (e.g. an interrupted extraction), or `None` if all are present.
+///
+/// Checked before conversion so that one line stands in for a "weapon
+/// module not found" error per vehicle.  Vehicle files that fail to parse are
+/// left to the conversion step to report.
+fn audit_weapon_modules(vehicles: &[PathBuf], datamine_dir: &Path) -> Option<String> {
+	let modules: BTreeSet<PathBuf> = vehicles
+		.par_iter()
+		.flat_map_iter(|path| vehicle_module_paths(path, datamine_dir).unwrap_or_default())
+		.collect();
+	let found = modules.iter().filter(|path| path.exists()).count();
+	if found == modules.len() {
+		return None;
+	}
 
-	print_stats(&stats, skip_ballistic);
+	let weapons = datamine_dir.join("aces.vromfs.bin_u").join("gamedata").join("weapons");
+	let no_weapons_dir = if weapons.exists() {
+		String::new()
+	} else {
+		format!(" (no weapons directory at {})", weapons.display())
+	};
+	Some(format!(
+		"{found} of {} weapon modules found — extraction incomplete{no_weapons_dir}",
+		modules.len(),
+	))
 }
 
-/// Compute and write ballistic tables for a single vehicle's projectiles.
-fn process_ballistic(
-	data: &fcsgen_core::VehicleData,
-	name: &str,
+/// Pipeline branch: compute ballistics from an existing `Data/*.txt` tree.
+///
+/// Vehicles are named after the file stems, as in the `ballistic`
+/// subcommand; a file that fails to parse counts as a convert error.
+fn run_pipeline_from_data(
+	cfg: &PipelineConfig<'_>,
+	data_dir: &Path,
 	ballistic_dir: &Path,
-	sensitivity: f64,
 	ballistic_cache: &BallisticCache,
-	vs: &mut VehicleStats,
-) {
-	let data_projectiles: Vec<_> = data.projectiles.iter().map(from_projectile).collect();
-
-	// Deduplicate by output_name
-	let mut last_by_name: HashMap<String, usize> = HashMap::new();
-	for (idx, dp) in data_projectiles.iter().enumerate() {
-		if !should_skip(&dp.normalized_type) {
-			last_by_name.insert(dp.output_name.clone(), idx);
-		}
-	}
+	ballistic_cfg: &BallisticConfig,
+	thread_count: usize,
+) -> Result<VehicleStats, OutputConflict> {
+	let entries = match std::fs::read_dir(data_dir) {
+		Ok(entries) => entries,
+		Err(e) => {
+			logln!(cfg.log, "Error: cannot read Data directory {}: {e}", data_dir.display());
+			std::process::exit(1);
+		},
+	};
 
-	let vehicle_dir = ballistic_dir.join(name);
-	let mut dir_created = false;
+	let mut files: Vec<_> = entries
+		.filter_map(Result::ok)
+		.filter(|e| e.path().extension().is_some_and(|ext| ext == "txt"))
+		.filter(|e| {
+			if let Some(filter) = cfg.filter {
+				let stem = e.path().file_stem().unwrap().to_string_lossy().to_string();
+				filter.iter().any(|f| f == &stem)
+			} else {
+				true
+			}
+		})
+		.collect();
 
-	for &idx in last_by_name.values() {
-		let dp = &data_projectiles[idx];
+	files.sort_by_key(std::fs::DirEntry::file_name);
+	let total = files.len();
 
-		let (result, hit) = compute_ballistic_cached(dp, sensitivity, ballistic_cache);
-		if hit {
-			vs.cache_hits += 1;
-		} else {
-			vs.cache_misses += 1;
-		}
+	logln!(
+		cfg.log,
+		"Step 3/3: Computing ballistics for {total} vehicles (sensitivity={}, jobs={thread_count})",
+		cfg.ballistic.sensitivity,
+	);
+	logln!(cfg.log, "  Data:      {}", data_dir.display());
+	logln!(cfg.log, "  Ballistic: {}", ballistic_dir.display());
+	logln!(cfg.log);
 
-		if let Some(content) = result {
-			if content.is_empty() {
-				continue;
-			}
+	files
+		.par_iter()
+		.map(|entry| {
+			let path = entry.path();
+			let name = path.file_stem().unwrap().to_string_lossy().to_string();
+			catch_vehicle_panic(&name, &cfg.log, || {
+				let mut vs = VehicleStats::default();
 
-			if !dir_created {
-				if let Err(e) = std::fs::create_dir_all(&vehicle_dir) {
-					eprintln!("DIR ERROR {name}: {e}");
-					vs.ballistic_errors += 1;
-					break;
+				let data = match parse_data_file(&path) {
+					Ok(d) => d,
+					Err(e) => {
+						cfg.errors.report(&cfg.log, "PARSE ERROR", &name, &e);
+						vs.convert_failed += 1;
+						return Ok(vs);
+					},
+				};
+				if !rangefinder_matches(cfg, data.rangefinder_kind(), &mut vs) {
+					return Ok(vs);
 				}
-				dir_created = true;
-			}
-
-			let filename = format!("{}.txt", dp.output_name);
-			let file_path = vehicle_dir.join(&filename);
+				vs.converted += 1;
+				count_all_skipped(&data.projectiles, &mut vs);
 
-			if let Err(e) = std::fs::write(&file_path, &content) {
-				eprintln!("WRITE ERROR {name}/{filename}: {e}");
-				vs.ballistic_errors += 1;
-			} else {
-				vs.shells_written += 1;
-			}
-		}
-	}
+				process_ballistic(
+					&data.projectiles,
+					&name,
+					ballistic_dir,
+					ballistic_cfg,
+					ballistic_cache,
+					cfg,
+					&mut vs,
+				)?;
+				Ok(vs)
+			})
+		})
+		.try_reduce(VehicleStats::default, |a, b| Ok(a.merge(b)))
 }
 
-/// Print final pipeline statistics.
-fn print_stats(stats: &VehicleStats, skip_ballistic: bool) {
-	eprintln!();
-	eprintln!(
-		"Done: {} converted, {} skipped (unarmed), {} convert errors",
-		stats.converted, stats.skipped, stats.convert_failed,
-	);
-	if !skip_ballistic {
-		let total_lookups = stats.cache_hits + stats.cache_misses;
-		eprintln!(
-			"      {} ballistic tables written, {} ballistic errors",
-			stats.shells_written, stats.ballistic_errors,
-		);
-		eprintln!(
-			"      Cache: {} unique / {total_lookups} total ({} hits, {:.0}% reuse)",
-			stats.cache_misses,
-			stats.cache_hits,
-			if total_lookups > 0 {
-				100.0 * stats.cache_hits as f64 / total_lookups as f64
-			} else {
-				0.0
-			},
+/// Compute and write a converted vehicle's ballistic tables: all shells
+/// into `ballistic_dir`, or with `--ammo-set` each set's shells into
+/// `ballistic_dir/{set}`.
+///
+/// The sets share `ballistic_cache`, so a shell present in several is only
+/// simulated once.
+fn process_vehicle_ballistic(
+	projectiles: &[Projectile],
+	name: &str,
+	ballistic_dir: &Path,
+	ballistic_cfg: &BallisticConfig,
+	ballistic_cache: &BallisticCache,
+	cfg: &PipelineConfig<'_>,
+	vs: &mut VehicleStats,
+) -> Result<(), OutputConflict> {
+	let data_projectiles: Vec<_> = projectiles.iter().map(from_projectile).collect();
+	count_all_skipped(&data_projectiles, vs);
+
+	if cfg.ammo_sets.is_empty() {
+		return process_ballistic(
+			&data_projectiles,
+			name,
+			ballistic_dir,
+			ballistic_cfg,
+			ballistic_cache,
+			cfg,
+			vs,
 		);
 	}
+
+	for &set in cfg.ammo_sets {
+		let set_projectiles: Vec<_> = projectiles
+			.iter()
+			.zip(&data_projectiles)
+			.filter(|(proj, _)| set.includes(proj))
+			.map(|(_, dp)| dp.clone())
+			.collect();
+		process_ballistic(
+			&set_projectiles,
+			name,
+			&ballistic_dir.join(set.dir_name()),
+			ballistic_cfg,
+			ballistic_cache,
+			cfg,
+			vs,
+		)?;
+	}
+	Ok(())
+}
+
+/// Whether a vehicle with rangefinder `kind` passes `--rangefinder`; a
+/// vehicle that does not is counted in
+/// [`VehicleStats::skipped_wrong_rangefinder`].
+fn rangefinder_matches(
+	cfg: &PipelineConfig<'_>,
+	kind: RangefinderKind,
+	vs: &mut VehicleStats,
+) -> bool {
+	if cfg.rangefinder.is_some_and(|wanted| wanted != kind) {
+		vs.skipped_wrong_rangefinder += 1;
+		return false;
+	}
+	true
 }
 
+/// Whether a vehicle with wpcost entry `rank` passes `--min-br` and
+/// `--rank`; a vehicle that does not, or has no entry, is counted in
+/// [`VehicleStats::skipped_low_br`].
+fn rank_matches(
+	cfg: &PipelineConfig<'_>,
+	rank: Option<&VehicleRank>,
+	vs: &mut VehicleStats,
+) -> bool {
+	if cfg.min_br.is_none() && cfg.rank.is_none() {
+		return true;
+	}
+	let matches = rank.is_some_and(|r| {
+		cfg.min_br.is_none_or(|min| r.battle_rating >= min)
+			&& cfg.rank.is_none_or(|wanted| r.rank == wanted)
+	});
+	if !matches {
+		vs.skipped_low_br += 1;
+	}
+	matches
+}
+
+/// Count a vehicle whose shells are all of skipped types (ATGM/SAM
+/// carriers): it is armed, so it gets a `Data/` file, but never a ballistic
+/// table.  Kept apart from unarmed vehicles so the summary can say why.
+fn count_all_skipped(data_projectiles: &[DataProjectile], vs: &mut VehicleStats) {
+	if !data_projectiles.is_empty()
+		&& data_projectiles.iter().all(|dp| should_skip(&dp.normalized_type))
+	{
+		vs.shells_all_skipped += 1;
+	}
+}
+
+/// `ballistic_cfg` with the `--sensitivity-by-nation` override for the
+/// nation of vehicle `name`, if any.  The sensitivity is part of the
+/// [`BallisticKey`](fcsgen_core::BallisticKey), so vehicles of different
+/// nations never share cached tables.
+fn vehicle_ballistic_config(
+	name: &str,
+	ballistic_cfg: &BallisticConfig,
+	sensitivity_by_nation: &[(String, f64)],
+) -> BallisticConfig {
+	let sensitivity = vehicle_nation(name)
+		.and_then(|nation| sensitivity_by_nation.iter().rev().find(|(n, _)| n == nation))
+		.map_or(ballistic_cfg.sensitivity, |&(_, sensitivity)| sensitivity);
+	BallisticConfig {
+		sensitivity,
+		..*ballistic_cfg
+	}
+}
+
+/// Compute and write ballistic tables for a single vehicle's projectiles.
+///
+/// Shells slower than `cfg.min_speed` (speed 0 in placeholder data) would
+/// only yield degenerate tables; they are skipped and counted separately
+/// from the skipped types.
+///
+/// Rayon tasks cannot be cancelled, so `cfg.vehicle_timeout` is checked
+/// between shells: once exceeded, the remaining shells are abandoned (tables
+/// already written are kept) and the vehicle is counted as timed out.
+///
+/// An [`OutputConflict`] stops the vehicle at once and is returned, to stop
+/// the run.
+#[allow(clippy::too_many_lines)]
+fn process_ballistic(
+	data_projectiles: &[DataProjectile],
+	name: &str,
+	ballistic_dir: &Path,
+	ballistic_cfg: &BallisticConfig,
+	ballistic_cache: &BallisticCache,
+	cfg: &PipelineConfig<'_>,
+	vs: &mut VehicleStats,
+) -> Result<(), OutputConflict> {
+	let started = Instant::now();
+	let ballistic_cfg = &vehicle_ballistic_config(name, ballistic_cfg, cfg.sensitivity_by_nation);
+
+	for dp in data_projectiles.iter().filter(|dp| should_skip(&dp.normalized_type)) {
+		vs.skipped_shells.skipped_type.push(ShellRef::new(name, dp));
+	}
+
+	let mut data_projectiles = data_projectiles.to_vec();
+	if let Some(overrides) = cfg.overrides {
+		overrides.apply(name, &mut data_projectiles);
+	}
+	if cfg.shell_manifest.is_some() {
+		vs.shells.add(name, &data_projectiles);
+	}
+
+	let (low_speed, data_projectiles): (Vec<_>, Vec<_>) =
+		data_projectiles.into_iter().partition(|dp| {
+			!should_skip(&dp.normalized_type) && (dp.speed < cfg.min_speed || dp.speed.is_nan())
+		});
+	vs.skipped_low_speed += low_speed.len();
+	for dp in &low_speed {
+		vs.skipped_shells.low_speed.push(ShellRef::new(name, dp));
+	}
+
+	// Deduplicate by output_name, resolving distinct-physics collisions
+	let plan = plan_shell_files(
+		name,
+		&data_projectiles,
+		ballistic_cfg,
+		cfg.on_collision,
+		&cfg.log,
+	)?;
+	let total = plan.len();
+
+	let vehicle_dir = ballistic_dir.join(name);
+	let mut dir_created = false;
+	// `--per-vehicle-file`: sections collected here, written after the loop
+	let mut combined = String::new();
+	let mut sections = 0;
+
+	for (done, (dp, file_stem)) in plan.into_iter().enumerate() {
+		if let Some(budget) = cfg.vehicle_timeout
+			&& started.elapsed() > budget
+		{
+			logln!(
+				cfg.log,
+				"TIMEOUT {name}: abandoned after {:.1}s with {done}/{total} shells computed \
+				 (--vehicle-timeout)",
+				started.elapsed().as_secs_f64(),
+			);
+			vs.timed_out += 1;
+			break;
+		}
+
+		let mut warnings = Vec::new();
+		let (mut result, hit) = compute_ballistic_cached_with_warnings(
+			dp,
+			ballistic_cfg,
+			ballistic_cache,
+			&mut warnings,
+		);
+		print_warnings(&dp.output_name, &warnings, &cfg.log);
+		if hit {
+			vs.cache_hits += 1;
+			if let Some(fraction) = cfg.verify_cache
+				&& verify_cache_hit(
+					dp,
+					ballistic_cfg,
+					&mut result,
+					fraction,
+					name,
+					&file_stem,
+					&cfg.log,
+				)
+			{
+				vs.cache_mismatches += 1;
+			}
+		} else {
+			vs.cache_misses += 1;
+		}
+
+		if let Some(content) = result {
+			if content.is_empty() {
+				continue;
+			}
+
+			// Tables computed without a real penetration model
+			let with_pen = !ballistic_cfg.trajectory_only;
+			let unsupported = with_pen && is_unsupported_type(&dp.normalized_type);
+			let no_armor_table = with_pen
+				&& dp.normalized_type == "apds_fs"
+				&& dp.armor_power_table.is_empty();
+			if unsupported {
+				vs.skipped_shells.unsupported_type.push(ShellRef::new(name, dp));
+			} else if estimates_penetration(dp, ballistic_cfg) {
+				vs.skipped_shells.estimated_pen.push(ShellRef::new(name, dp));
+			} else if no_armor_table {
+				vs.skipped_shells.empty_armor_table.push(ShellRef::new(name, dp));
+			}
+
+			if cfg.skip_zero_pen && is_zero_penetration(&content) {
+				vs.skipped_zero_pen += 1;
+				if !unsupported && !no_armor_table {
+					vs.skipped_shells.zero_pen.push(ShellRef::new(name, dp));
+				}
+				continue;
+			}
+
+			if cfg.report_duplicates {
+				vs.table_keys
+					.push((BallisticKey::with_config(dp, ballistic_cfg), ShellRef::new(name, dp)));
+			}
+
+			let content = if cfg.with_header {
+				let (header, unreachable) =
+					table_header_with_marks(dp, ballistic_cfg, cfg.range_marks);
+				vs.unreachable_marks += unreachable;
+				header + &content
+			} else {
+				content
+			};
+
+			if cfg.per_vehicle_file {
+				combined.push_str(&vehicle_section_header(&file_stem, dp));
+				combined.push_str(&content);
+				sections += 1;
+				continue;
+			}
+
+			if !dir_created {
+				if let Err(e) = create_output_dir(&vehicle_dir) {
+					cfg.errors.report(&cfg.log, "DIR ERROR", name, &e);
+					vs.ballistic_errors += 1;
+					break;
+				}
+				dir_created = true;
+			}
+
+			let filename = format!("{file_stem}.txt");
+			let file_path = vehicle_dir.join(&filename);
+			let label = format!("{name}/{filename}");
+
+			match write_or_stage(&file_path, &content, &label, 1, cfg, vs) {
+				Ok(WriteOutcome::Written) => vs.shells_written += 1,
+				Ok(WriteOutcome::Skipped) => vs.existing_skipped += 1,
+				Err(e) => {
+					check_exists(&e)?;
+					cfg.errors.report(&cfg.log, "WRITE ERROR", &label, &e);
+					vs.ballistic_errors += 1;
+				},
+			}
+		}
+	}
+
+	if sections > 0 {
+		let filename = format!("{name}.txt");
+		match write_or_stage(
+			&ballistic_dir.join(&filename),
+			&combined,
+			&filename,
+			sections,
+			cfg,
+			vs,
+		) {
+			Ok(WriteOutcome::Written) => vs.shells_written += sections,
+			Ok(WriteOutcome::Skipped) => vs.existing_skipped += 1,
+			Err(e) => {
+				check_exists(&e)?;
+				cfg.errors.report(&cfg.log, "WRITE ERROR", &filename, &e);
+				vs.ballistic_errors += 1;
+			},
+		}
+	}
+	Ok(())
+}
+
+/// Print final pipeline statistics and write the `--report` file, if any.
+fn finish_run(
+	cfg: &PipelineConfig<'_>,
+	mut stats: VehicleStats,
+	skip_ballistic: bool,
+	iterations: Vec<IterationReport>,
+) {
+	print_stats(&stats, skip_ballistic, &cfg.log);
+	for line in cfg.errors.summary() {
+		logln!(cfg.log, "      {line} (--quiet-errors)");
+	}
+
+	let duplicates = (cfg.report_duplicates && !skip_ballistic)
+		.then(|| DuplicateReport::new(std::mem::take(&mut stats.table_keys)));
+	if let Some(duplicates) = &duplicates {
+		print_duplicates(duplicates, &cfg.log);
+	}
+
+	if let Some(path) = cfg.shell_manifest
+		&& !skip_ballistic
+	{
+		let shells = std::mem::take(&mut stats.shells);
+		logln!(cfg.log, "      {} distinct shells (--shell-manifest)", shells.len());
+		if let Err(e) = write_manifest(path, shells, &load_weaponry_lang(cfg)) {
+			logln!(cfg.log, "Warning: failed to write shell manifest {}: {e}", path.display());
+		}
+	}
+
+	let Some(path) = cfg.report else {
+		return;
+	};
+	let report = RunReport {
+		converted: stats.converted,
+		skipped_unarmed: stats.skipped,
+		convert_errors: stats.convert_failed,
+		shells_written: stats.shells_written,
+		ballistic_errors: stats.ballistic_errors,
+		existing_skipped: stats.existing_skipped,
+		timed_out: stats.timed_out,
+		cache_mismatches: stats.cache_mismatches,
+		skipped_low_speed: stats.skipped_low_speed,
+		unreachable_marks: stats.unreachable_marks,
+		skipped_wrong_rangefinder: stats.skipped_wrong_rangefinder,
+		skipped_low_br: stats.skipped_low_br,
+		shells_all_skipped: stats.shells_all_skipped,
+		skipped_shells: stats.skipped_shells,
+		panicked: stats.panicked,
+		duplicates,
+		iterations,
+	};
+	if let Err(e) = write_report(path, report) {
+		logln!(cfg.log, "Warning: failed to write report {}: {e}", path.display());
+	}
+}
+
+/// `units_weaponry.csv` of the extracted lang CSVs, for shell display names;
+/// empty if there is none (e.g. `--from-data` without a previous
+/// extraction) or with `--no-lang`, which ignores CSVs of earlier runs too.
+fn load_weaponry_lang(cfg: &PipelineConfig<'_>) -> LangTable {
+	if cfg.no_lang {
+		logln!(cfg.log, "Warning: no lang CSVs with --no-lang; display names are shell names");
+		return LangTable::default();
+	}
+	let path = cfg
+		.output
+		.join(cfg.datamine_subdir)
+		.join(extract::LANG_DIR)
+		.join("lang/units_weaponry.csv");
+	std::fs::read_to_string(path)
+		.map_or_else(|_| LangTable::default(), |text| LangTable::parse(&text))
+}
+
+/// `--verify-cache`: recompute the table of a sampled cache hit and compare
+/// it with the cached one.
+///
+/// A difference means two physically different shells share a
+/// [`BallisticKey`], i.e. a field that affects the output is missing from the
+/// key.  It is printed with the key, `cached` is replaced by the fresh table
+/// and `true` returned.  A `fraction` of hits is sampled by hashing vehicle
+/// and shell, so reruns check the same shells.
+fn verify_cache_hit(
+	dp: &DataProjectile,
+	ballistic_cfg: &BallisticConfig,
+	cached: &mut Option<String>,
+	fraction: f64,
+	vehicle: &str,
+	file_stem: &str,
+	log: &LogFile,
+) -> bool {
+	let mut hasher = DefaultHasher::new();
+	(vehicle, file_stem).hash(&mut hasher);
+	#[allow(clippy::cast_precision_loss)]
+	let sample = hasher.finish() as f64 / u64::MAX as f64;
+	if sample >= fraction {
+		return false;
+	}
+
+	let fresh = compute_ballistic_with_config(dp, ballistic_cfg);
+	if fresh == *cached {
+		return false;
+	}
+	let key = BallisticKey::with_config(dp, ballistic_cfg);
+	logln!(
+		log,
+		"CACHE MISMATCH {vehicle}/{file_stem}: cached table differs from a fresh computation \
+		 (key {})",
+		serde_json::to_string(&key).unwrap_or_default(),
+	);
+	*cached = fresh;
+	true
+}
+
+/// Number of groups printed by [`print_duplicates`]; the report lists more.
+const DUPLICATE_GROUPS_PRINTED: usize = 5;
+
+/// Print the `--report-duplicates` summary and its largest groups.
+fn print_duplicates(duplicates: &DuplicateReport, log: &LogFile) {
+	logln!(
+		log,
+		"      Duplicates: {} tables, {} distinct ({} identical to another)",
+		duplicates.shell_writes,
+		duplicates.distinct_tables,
+		duplicates.shell_writes - duplicates.distinct_tables,
+	);
+	for group in duplicates.largest_groups.iter().take(DUPLICATE_GROUPS_PRINTED) {
+		let first = &group.shells[0];
+		logln!(
+			log,
+			"        {} × {} ({}), e.g. {}",
+			group.count,
+			first.shell,
+			first.bullet_type,
+			group
+				.shells
+				.iter()
+				.take(3)
+				.map(|s| s.vehicle.as_str())
+				.collect::<Vec<_>>()
+				.join(", "),
+		);
+	}
+}
+
+/// Print the timing and cache reuse of a `--repeat` iteration.
+#[allow(clippy::cast_precision_loss)]
+fn print_iteration(report: &IterationReport, repeat: usize, log: &LogFile) {
+	let total_lookups = report.cache_hits + report.cache_misses;
+	logln!(
+		log,
+		"Iteration {}/{repeat}: {:.2}s, {} cache hits / {total_lookups} lookups ({:.0}% reuse)",
+		report.iteration,
+		report.seconds,
+		report.cache_hits,
+		if total_lookups > 0 {
+			100.0 * report.cache_hits as f64 / total_lookups as f64
+		} else {
+			0.0
+		},
+	);
+}
+
+/// Print final pipeline statistics.
+fn print_stats(stats: &VehicleStats, skip_ballistic: bool, log: &LogFile) {
+	logln!(log);
+	logln!(
+		log,
+		"Done: {} converted, {} skipped (unarmed), {} convert errors",
+		stats.converted, stats.skipped, stats.convert_failed,
+	);
+	if !stats.panicked.is_empty() {
+		logln!(
+			log,
+			"      {} of them panicked (see PANIC lines above)",
+			stats.panicked.len(),
+		);
+	}
+	if stats.skipped_wrong_rangefinder > 0 {
+		logln!(
+			log,
+			"      {} vehicles with another rangefinder skipped (--rangefinder)",
+			stats.skipped_wrong_rangefinder,
+		);
+	}
+	if stats.skipped_low_br > 0 {
+		logln!(
+			log,
+			"      {} vehicles below --min-br or of another --rank skipped",
+			stats.skipped_low_br,
+		);
+	}
+	if stats.existing_skipped > 0 {
+		logln!(
+			log,
+			"      {} existing files left untouched (--overwrite-policy skip)",
+			stats.existing_skipped,
+		);
+	}
+	if !skip_ballistic {
+		let total_lookups = stats.cache_hits + stats.cache_misses;
+		logln!(
+			log,
+			"      {} ballistic tables written, {} ballistic errors",
+			stats.shells_written, stats.ballistic_errors,
+		);
+		if stats.skipped_zero_pen > 0 {
+			logln!(
+				log,
+				"      {} zero-penetration tables skipped (--skip-zero-pen)",
+				stats.skipped_zero_pen,
+			);
+		}
+		if stats.shells_all_skipped > 0 {
+			logln!(
+				log,
+				"      {} vehicles armed only with ATGMs/SAMs/rockets (no ballistic tables)",
+				stats.shells_all_skipped,
+			);
+		}
+		if !stats.skipped_shells.estimated_pen.is_empty() {
+			logln!(
+				log,
+				"      {} APDS-FS tables with estimated penetration (--estimate-missing-pen)",
+				stats.skipped_shells.estimated_pen.len(),
+			);
+		}
+		if stats.skipped_low_speed > 0 {
+			logln!(
+				log,
+				"      {} shells below --min-speed skipped",
+				stats.skipped_low_speed,
+			);
+		}
+		if stats.unreachable_marks > 0 {
+			logln!(
+				log,
+				"      {} range marks beyond reach omitted (--range-marks)",
+				stats.unreachable_marks,
+			);
+		}
+		if stats.timed_out > 0 {
+			logln!(
+				log,
+				"      {} vehicles timed out (--vehicle-timeout)",
+				stats.timed_out,
+			);
+		}
+		if stats.cache_mismatches > 0 {
+			logln!(
+				log,
+				"      {} cache hits differed from a fresh computation (--verify-cache)",
+				stats.cache_mismatches,
+			);
+		}
+		logln!(
+			log,
+			"      Cache: {} unique / {total_lookups} total ({} hits, {:.0}% reuse)",
+			stats.cache_misses,
+			stats.cache_hits,
+			if total_lookups > 0 {
+				100.0 * stats.cache_hits as f64 / total_lookups as f64
+			} else {
+				0.0
+			},
+		);
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+
+	use fcsgen_core::ballistic::PenetrationUnit;
+	use fcsgen_core::parser::data::parse_data_text;
+
+	use crate::test_util::TempDir;
+	use super::*;
+
+	/// Three distinct shells, each slow to sweep at a tiny sensitivity.
+	const SLOW_DATA: &str = "\
+WeaponPath:gameData/Weapons/test.blkx
+
+Name:75mm_pzgr_39
+Type:apcbc_tank
+BulletMass:6.8
+BallisticCaliber:0.075
+Speed:740.0
+Cx:0.4
+
+Name:75mm_sprgr_34
+Type:he_frag_tank
+BulletMass:5.74
+BallisticCaliber:0.075
+Speed:550.0
+Cx:0.4
+
+Name:75mm_pzgr_40
+Type:apcr_tank
+BulletMass:4.1
+BallisticCaliber:0.075
+Speed:930.0
+Cx:0.4
+";
+
+	/// A pipeline writing to `output` with `ballistic` and every option at its
+	/// CLI default; tests override only the fields they exercise.
+	fn pipeline_config(output: &Path, ballistic: BallisticConfig) -> PipelineConfig<'_> {
+		PipelineConfig {
+			game_path: Path::new(""),
+			output,
+			datamine_subdir: "Datamine",
+			data_subdir: "Data",
+			ballistic_subdir: "Ballistic",
+			ballistic,
+			sensitivity_by_nation: &[],
+			min_speed: DEFAULT_MIN_SPEED,
+			uniform_cx: false,
+			vehicle_timeout: None,
+			ignore_file: None,
+			from_data: None,
+			report: None,
+			seed_cache_from: None,
+			save_cache: None,
+			filter: None,
+			rangefinder: None,
+			min_br: None,
+			rank: None,
+			overrides: None,
+			jobs: 0,
+			skip_extract: false,
+			skip_ballistic: false,
+			write_datamine: false,
+			no_lang: false,
+			overwrite_policy: OverwritePolicy::Overwrite,
+			line_ending: LineEnding::Lf,
+			with_header: false,
+			range_marks: &[],
+			on_collision: CollisionPolicy::Last,
+			skip_zero_pen: false,
+			per_vehicle_file: false,
+			ammo_sets: &[],
+			report_duplicates: false,
+			verify_cache: None,
+			shell_manifest: None,
+			low_memory: false,
+			repeat: 1,
+			repeat_cold: false,
+			errors: ErrorLog::default(),
+			log: LogFile::default(),
+		}
+	}
+
+	#[test]
+	fn test_gzipped_cache_roundtrips_like_plain() {
+		let dir = TempDir::new("gz-cache");
+		let ballistic_cfg = BallisticConfig::new(1.0);
+		let cache = BallisticCache::new();
+		for proj in &parse_data_text(SLOW_DATA, "test_tank").projectiles {
+			cache.insert(
+				BallisticKey::with_config(proj, &ballistic_cfg),
+				compute_ballistic_with_config(proj, &ballistic_cfg),
+			);
+		}
+
+		let reload = |name: &str| {
+			let path = dir.join(name);
+			save_cache(&cache, &path).unwrap();
+			let reloaded = BallisticCache::new();
+			assert_eq!(load_cache(&reloaded, &path).unwrap(), cache.len());
+			let entries: HashMap<_, _> = reloaded.into_iter().collect();
+			(entries, std::fs::read(&path).unwrap())
+		};
+		let (plain, plain_bytes) = reload("cache.json");
+		let (gzipped, gz_bytes) = reload("cache.json.gz");
+
+		assert!(plain == gzipped);
+		assert_eq!(plain.len(), 3);
+		assert_eq!(gz_bytes[..2], [0x1f, 0x8b], "gzip magic");
+		assert!(gz_bytes.len() < plain_bytes.len() / 2);
+		assert_eq!(plain_bytes[0], b'{', "uncompressed JSON stays readable");
+	}
+
+	#[test]
+	fn test_verify_cache_catches_key_collision() {
+		let dir = TempDir::new("verify-cache");
+		let projectiles = parse_data_text(SLOW_DATA, "test_tank").projectiles;
+		let (pzgr_39, pzgr_40) = (&projectiles[0], &projectiles[2]);
+
+		// A key too weak to tell the two shells apart: Pzgr 40 hits Pzgr 39's table
+		let ballistic_cfg = BallisticConfig::new(1.0);
+		let cache = BallisticCache::new();
+		cache.insert(
+			BallisticKey::with_config(pzgr_40, &ballistic_cfg),
+			compute_ballistic_with_config(pzgr_39, &ballistic_cfg),
+		);
+
+		let cfg = PipelineConfig {
+			verify_cache: Some(1.0),
+			..pipeline_config(&dir, ballistic_cfg)
+		};
+
+		let mut vs = VehicleStats::default();
+		process_ballistic(&projectiles, "test_tank", &dir, &ballistic_cfg, &cache, &cfg, &mut vs)
+			.unwrap();
+
+		assert_eq!((vs.cache_hits, vs.cache_mismatches), (1, 1));
+		let written = std::fs::read_to_string(dir.join("test_tank").join("pzgr_40.txt")).unwrap();
+		assert_eq!(Some(written), compute_ballistic_with_config(pzgr_40, &ballistic_cfg));
+	}
+
+	#[test]
+	fn test_vehicle_timeout_abandons_remaining_shells() {
+		let dir = TempDir::new("timeout");
+		let projectiles = parse_data_text(SLOW_DATA, "test_tank").projectiles;
+
+		// ~7500 launch angles per shell: far more than a 1 ms budget allows
+		let ballistic_cfg = BallisticConfig::new(0.1);
+		let cfg = PipelineConfig {
+			vehicle_timeout: Some(Duration::from_millis(1)),
+			..pipeline_config(&dir, ballistic_cfg)
+		};
+
+		let mut vs = VehicleStats::default();
+		process_ballistic(
+			&projectiles,
+			"test_tank",
+			&dir,
+			&ballistic_cfg,
+			&BallisticCache::new(),
+			&cfg,
+			&mut vs,
+		)
+		.unwrap();
+
+		// The first shell alone blows the budget; the rest are skipped
+		assert_eq!(vs.timed_out, 1);
+		assert_eq!(vs.shells_written, 1);
+		assert_eq!(vs.ballistic_errors, 0);
+		assert_eq!(std::fs::read_dir(dir.join("test_tank")).unwrap().count(), 1);
+	}
+
+	#[test]
+	fn test_skip_zero_pen_drops_only_all_zero_tables() {
+		let dir = TempDir::new("zero-pen");
+		let projectiles = parse_data_text(SLOW_DATA, "test_tank").projectiles;
+
+		let ballistic_cfg = BallisticConfig::default();
+		let cfg = PipelineConfig {
+			skip_zero_pen: true,
+			..pipeline_config(&dir, ballistic_cfg)
+		};
+
+		let mut vs = VehicleStats::default();
+		process_ballistic(
+			&projectiles,
+			"test_tank",
+			&dir,
+			&ballistic_cfg,
+			&BallisticCache::new(),
+			&cfg,
+			&mut vs,
+		)
+		.unwrap();
+
+		// The HE shell is all zeros; the AP shells are not
+		assert_eq!(vs.skipped_zero_pen, 1);
+		assert_eq!(vs.shells_written, 2);
+		let vehicle = dir.join("test_tank");
+		assert!(!vehicle.join("sprgr_34.txt").exists());
+		assert!(vehicle.join("pzgr_39.txt").exists());
+		assert!(vehicle.join("pzgr_40.txt").exists());
+	}
+
+	#[test]
+	fn test_min_speed_skips_zero_speed_shells() {
+		let dir = TempDir::new("min-speed");
+		let data = "\
+WeaponPath:gameData/Weapons/test.blkx
+
+Name:75mm_pzgr_39
+Type:apcbc_tank
+BulletMass:6.8
+BallisticCaliber:0.075
+Speed:740.0
+Cx:0.4
+
+Name:75mm_dummy
+Type:apcbc_tank
+BulletMass:6.8
+BallisticCaliber:0.075
+Speed:0.0
+Cx:0.4
+";
+		let projectiles = parse_data_text(data, "test_tank").projectiles;
+
+		let ballistic_cfg = BallisticConfig::default();
+		let cfg = PipelineConfig {
+			..pipeline_config(&dir, ballistic_cfg)
+		};
+
+		let mut vs = VehicleStats::default();
+		process_ballistic(
+			&projectiles,
+			"test_tank",
+			&dir,
+			&ballistic_cfg,
+			&BallisticCache::new(),
+			&cfg,
+			&mut vs,
+		)
+		.unwrap();
+
+		assert_eq!(vs.skipped_low_speed, 1);
+		assert_eq!(vs.shells_written, 1);
+		assert!(vs.skipped_shells.skipped_type.is_empty());
+		assert_eq!(vs.skipped_shells.low_speed[0].shell, "75mm_dummy");
+		assert!(!dir.join("test_tank/dummy.txt").exists());
+	}
+
+	#[test]
+	fn test_audit_warns_when_weapons_missing() {
+		let dir = TempDir::new("audit");
+		let aces_root = dir.join("aces.vromfs.bin_u");
+		let tankmodels = aces_root.join("gamedata/units/tankmodels");
+		std::fs::create_dir_all(&tankmodels).unwrap();
+
+		// Tankmodels only, as left by an interrupted extraction
+		let mut vehicles = Vec::new();
+		let guns = [("tank_a", "75mm_kwk40"), ("tank_b", "75mm_kwk40"), ("tank_c", "88mm_kwk36")];
+		for (name, gun) in guns {
+			let path = tankmodels.join(format!("{name}.blkx"));
+			let blk = format!("gameData/Weapons/groundModels_weapons/{gun}.blk");
+			let vehicle = serde_json::json!({ "commonWeapons": { "Weapon": { "blk": blk } } });
+			std::fs::write(&path, vehicle.to_string()).unwrap();
+			vehicles.push(path);
+		}
+
+		let warning = audit_weapon_modules(&vehicles, &dir).unwrap();
+		assert!(warning.starts_with("0 of 2 weapon modules found"), "{warning}");
+		assert!(warning.contains("no weapons directory"), "{warning}");
+
+		let weapons = aces_root.join("gamedata/weapons/groundmodels_weapons");
+		std::fs::create_dir_all(&weapons).unwrap();
+		std::fs::write(weapons.join("75mm_kwk40.blkx"), "{}").unwrap();
+		let warning = audit_weapon_modules(&vehicles, &dir).unwrap();
+		assert!(warning.starts_with("1 of 2 weapon modules found"), "{warning}");
+		assert!(!warning.contains("no weapons directory"), "{warning}");
+
+		std::fs::write(weapons.join("88mm_kwk36.blkx"), "{}").unwrap();
+		assert_eq!(audit_weapon_modules(&vehicles, &dir), None);
+	}
+
+	#[test]
+	fn test_from_data_writes_ballistic_tree() {
+		let dir = TempDir::new("from-data");
+		let data_dir = dir.join("EditedData");
+		std::fs::create_dir_all(&data_dir).unwrap();
+		std::fs::write(data_dir.join("test_tank.txt"), SLOW_DATA).unwrap();
+		let output = dir.join("out");
+
+		let ballistic_cfg = BallisticConfig::default();
+		run_pipeline(&PipelineConfig {
+			from_data: Some(&data_dir),
+			..pipeline_config(&output, ballistic_cfg)
+		});
+
+		let vehicle = output.join("Ballistic").join("test_tank");
+		for shell in ["pzgr_39", "sprgr_34", "pzgr_40"] {
+			assert!(vehicle.join(format!("{shell}.txt")).exists(), "{shell} missing");
+		}
+		// Nothing is extracted or converted
+		assert!(!output.join("Datamine").exists());
+		assert_eq!(std::fs::read_dir(output.join("Data")).unwrap().count(), 0);
+	}
+
+	#[test]
+	fn test_per_vehicle_file_has_one_section_per_shell() {
+		let dir = TempDir::new("per-vehicle");
+		let projectiles = parse_data_text(SLOW_DATA, "test_tank").projectiles;
+
+		let ballistic_cfg = BallisticConfig::default();
+		let cfg = PipelineConfig {
+			per_vehicle_file: true,
+			..pipeline_config(&dir, ballistic_cfg)
+		};
+
+		let mut vs = VehicleStats::default();
+		process_ballistic(
+			&projectiles[..2],
+			"test_tank",
+			&dir,
+			&ballistic_cfg,
+			&BallisticCache::new(),
+			&cfg,
+			&mut vs,
+		)
+		.unwrap();
+
+		assert_eq!(vs.shells_written, 2);
+		assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1, "one file, no directory");
+		let combined = std::fs::read_to_string(dir.join("test_tank.txt")).unwrap();
+		let sections: Vec<&str> = combined.lines().filter(|l| l.starts_with('#')).collect();
+		assert_eq!(sections, ["# pzgr_39\ttype=apcbc", "# sprgr_34\ttype=he"]);
+		assert!(combined.starts_with("# pzgr_39\ttype=apcbc\n0.000\t"));
+	}
+
+	#[test]
+	#[allow(clippy::too_many_lines)]
+	fn test_report_lists_skipped_shells_by_reason() {
+		let dir = TempDir::new("report");
+		let data_dir = dir.join("Data");
+		std::fs::create_dir_all(&data_dir).unwrap();
+		std::fs::write(
+			data_dir.join("test_tank.txt"),
+			"\
+WeaponPath:gameData/Weapons/test.blkx
+
+Name:75mm_nb_39
+Type:smoke_tank
+BulletMass:6.8
+BallisticCaliber:0.075
+Speed:420.0
+Cx:0.4
+
+Name:75mm_snowball
+Type:snowball_tank
+BulletMass:0.5
+BallisticCaliber:0.075
+Speed:100.0
+Cx:0.4
+
+Name:120mm_milan
+Type:atgm_tandem_tank
+BulletMass:6.7
+BallisticCaliber:0.12
+Speed:200.0
+
+Name:75mm_pzgr_39
+Type:apcbc_tank
+BulletMass:6.8
+BallisticCaliber:0.075
+Speed:740.0
+Cx:0.4
+",
+		)
+		.unwrap();
+		let output = dir.join("out");
+		let report_path = dir.join("report.json");
+
+		let ballistic_cfg = BallisticConfig::default();
+		run_pipeline(&PipelineConfig {
+			from_data: Some(&data_dir),
+			report: Some(&report_path),
+			skip_zero_pen: true,
+			..pipeline_config(&output, ballistic_cfg)
+		});
+
+		let report: serde_json::Value =
+			serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+		let shells = |reason: &str| -> Vec<String> {
+			report["skipped_shells"][reason]
+				.as_array()
+				.unwrap()
+				.iter()
+				.map(|s| format!("{}/{}", s["vehicle"], s["shell"]).replace('"', ""))
+				.collect()
+		};
+
+		assert_eq!(shells("skipped_type"), ["test_tank/120mm_milan"]);
+		assert_eq!(shells("unsupported_type"), ["test_tank/75mm_snowball"]);
+		assert_eq!(shells("zero_pen"), ["test_tank/75mm_nb_39"]);
+		assert!(shells("empty_armor_table").is_empty());
+		assert_eq!(report["shells_written"], 1);
+	}
+
+	#[test]
+	#[allow(clippy::too_many_lines)]
+	fn test_vehicle_file_selects_exactly_listed_vehicles() {
+		use clap::{Args, Command, FromArgMatches};
+
+		use crate::config::{RunArgs, resolve_run_args};
+
+		let dir = TempDir::new("vehicle-file");
+		let data_dir = dir.join("Data");
+		std::fs::create_dir_all(&data_dir).unwrap();
+		for vehicle in ["test_a", "test_b", "test_c", "test_d", "test_e"] {
+			std::fs::write(data_dir.join(format!("{vehicle}.txt")), SLOW_DATA).unwrap();
+		}
+		let list = dir.join("vehicles.txt");
+		std::fs::write(&list, "# selection\ntest_a\n\n\"test_c\"\nTest_E.blkx\n").unwrap();
+		let output = dir.join("out");
+
+		let matches = RunArgs::augment_args(Command::new("run"))
+			.try_get_matches_from([
+				"run",
+				"--from-data",
+				data_dir.to_str().unwrap(),
+				"--output",
+				output.to_str().unwrap(),
+				"--vehicle-file",
+				list.to_str().unwrap(),
+			])
+			.unwrap();
+		let args = resolve_run_args(RunArgs::from_arg_matches(&matches).unwrap(), &matches);
+		let selection = args.vehicle.unwrap_or_default();
+		assert_eq!(selection, ["test_a", "test_c", "test_e"]);
+
+		let ballistic_cfg = BallisticConfig::new(1.0);
+		run_pipeline(&PipelineConfig {
+			from_data: Some(&data_dir),
+			filter: Some(&selection),
+			..pipeline_config(&output, ballistic_cfg)
+		});
+
+		let mut processed: Vec<_> = std::fs::read_dir(output.join("Ballistic"))
+			.unwrap()
+			.map(|e| e.unwrap().file_name().into_string().unwrap())
+			.collect();
+		processed.sort();
+		assert_eq!(processed, ["test_a", "test_c", "test_e"]);
+	}
+
+	#[test]
+	fn test_report_duplicates_groups_identical_shells() {
+		let dir = TempDir::new("duplicates");
+		let data_dir = dir.join("Data");
+		std::fs::create_dir_all(&data_dir).unwrap();
+		let shell = |speed: f64| {
+			format!(
+				"WeaponPath:gameData/Weapons/test.blkx\n\nName:75mm_pzgr_39\nType:apcbc_tank\n\
+				 BulletMass:6.8\nBallisticCaliber:0.075\nSpeed:{speed}\nCx:0.4\n"
+			)
+		};
+		// Three vehicles share the gun, a fourth has a faster variant
+		for vehicle in ["test_a", "test_b", "test_c"] {
+			std::fs::write(data_dir.join(format!("{vehicle}.txt")), shell(740.0)).unwrap();
+		}
+		std::fs::write(data_dir.join("test_d.txt"), shell(790.0)).unwrap();
+		let output = dir.join("out");
+		let report_path = dir.join("report.json");
+
+		let ballistic_cfg = BallisticConfig::new(1.0);
+		run_pipeline(&PipelineConfig {
+			from_data: Some(&data_dir),
+			report: Some(&report_path),
+			report_duplicates: true,
+			..pipeline_config(&output, ballistic_cfg)
+		});
+
+		let report: serde_json::Value =
+			serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+		let duplicates = &report["duplicates"];
+		assert_eq!(duplicates["shell_writes"], 4);
+		assert_eq!(duplicates["distinct_tables"], 2);
+
+		// Only the shared shell forms a group
+		let groups = duplicates["largest_groups"].as_array().unwrap();
+		assert_eq!(groups.len(), 1);
+		assert_eq!(groups[0]["count"], 3);
+		let vehicles: Vec<_> = groups[0]["shells"]
+			.as_array()
+			.unwrap()
+			.iter()
+			.map(|s| s["vehicle"].as_str().unwrap())
+			.collect();
+		assert_eq!(vehicles, ["test_a", "test_b", "test_c"]);
+	}
+
+	#[test]
+	fn test_shell_manifest_lists_shared_shell_once() {
+		let dir = TempDir::new("manifest");
+		let data_dir = dir.join("Data");
+		std::fs::create_dir_all(&data_dir).unwrap();
+		let pzgr_39 = "Name:75mm_pzgr_39\nType:apcbc_tank\nBulletMass:6.8\n\
+			BallisticCaliber:0.075\nSpeed:740.0\nCx:0.4\n";
+		let sprgr_34 = "Name:75mm_sprgr_34\nType:he_frag_tank\nBulletMass:5.74\n\
+			BallisticCaliber:0.075\nSpeed:550.0\nCx:0.4\n";
+		let weapon = "WeaponPath:gameData/Weapons/test.blkx\n\n";
+		std::fs::write(data_dir.join("test_a.txt"), format!("{weapon}{pzgr_39}")).unwrap();
+		std::fs::write(data_dir.join("test_b.txt"), format!("{weapon}{pzgr_39}\n{sprgr_34}"))
+			.unwrap();
+		let output = dir.join("out");
+		let manifest_path = dir.join("shells.json");
+		// Only the AP shell is localized
+		let lang_dir = output.join("Datamine/lang.vromfs.bin_u/lang");
+		std::fs::create_dir_all(&lang_dir).unwrap();
+		std::fs::write(
+			lang_dir.join("units_weaponry.csv"),
+			"\"<ID|readonly|noverify>\";\"<English>\"\n\"75mm_pzgr_39\";\"PzGr 39\"\n",
+		)
+		.unwrap();
+
+		let ballistic_cfg = BallisticConfig::new(1.0);
+		run_pipeline(&PipelineConfig {
+			from_data: Some(&data_dir),
+			shell_manifest: Some(&manifest_path),
+			..pipeline_config(&output, ballistic_cfg)
+		});
+
+		let manifest: serde_json::Value =
+			serde_json::from_str(&std::fs::read_to_string(&manifest_path).unwrap()).unwrap();
+		let shells = manifest.as_array().unwrap();
+		assert_eq!(shells.len(), 2, "{manifest}");
+		assert_eq!(shells[0]["name"], "75mm_pzgr_39");
+		assert_eq!(shells[0]["display_name"], "PzGr 39");
+		assert_eq!(shells[0]["type"], "apcbc_tank");
+		assert_eq!(shells[0]["hull_break"], false);
+		assert_eq!(shells[0]["vehicles"], serde_json::json!(["test_a", "test_b"]));
+		assert_eq!(shells[1]["name"], "75mm_sprgr_34");
+		assert_eq!(shells[1]["display_name"], "sprgr_34");
+		assert_eq!(shells[1]["vehicles"], serde_json::json!(["test_b"]));
+	}
+
+	#[test]
+	fn test_rangefinder_filter_keeps_only_laser_tanks() {
+		let dir = TempDir::new("rangefinder");
+		let data_dir = dir.join("Data");
+		std::fs::create_dir_all(&data_dir).unwrap();
+		let laser_data = SLOW_DATA.replacen("\n\n", "\nHasLaser\n\n", 1);
+		std::fs::write(data_dir.join("laser_tank.txt"), laser_data).unwrap();
+		std::fs::write(data_dir.join("scroll_tank.txt"), SLOW_DATA).unwrap();
+		let output = dir.join("out");
+		let report_path = dir.join("report.json");
+
+		let ballistic_cfg = BallisticConfig::new(1.0);
+		run_pipeline(&PipelineConfig {
+			from_data: Some(&data_dir),
+			report: Some(&report_path),
+			rangefinder: Some(RangefinderKind::Laser),
+			..pipeline_config(&output, ballistic_cfg)
+		});
+
+		let ballistic = output.join("Ballistic");
+		assert!(ballistic.join("laser_tank").join("pzgr_39.txt").exists());
+		assert!(!ballistic.join("scroll_tank").exists());
+
+		let report: serde_json::Value =
+			serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+		assert_eq!(report["converted"], 1);
+		assert_eq!(report["skipped_wrong_rangefinder"], 1);
+	}
+
+	#[test]
+	fn test_min_br_keeps_only_higher_vehicle() {
+		let ranks = fcsgen_core::parse_wpcost(&serde_json::json!({
+			"us_m1_abrams": { "rank": 7, "economicRankHistorical": 33 },
+			"us_m4_sherman": { "rank": 2, "economicRankHistorical": 9 },
+		}));
+		let names = ["us_M1_Abrams", "us_m4_sherman", "us_not_in_wpcost"];
+
+		let ballistic_cfg = BallisticConfig::new(1.0);
+		let cfg = PipelineConfig {
+			min_br: Some(8.0),
+			..pipeline_config(Path::new(""), ballistic_cfg)
+		};
+		let mut vs = VehicleStats::default();
+		let kept: Vec<_> = names
+			.into_iter()
+			.filter(|name| rank_matches(&cfg, ranks.get(&name.to_lowercase()), &mut vs))
+			.collect();
+		// 11.0 passes; 4.0 and the vehicle without a BR do not
+		assert_eq!(kept, ["us_M1_Abrams"]);
+		assert_eq!(vs.skipped_low_br, 2);
+
+		let cfg = PipelineConfig {
+			min_br: None,
+			rank: Some(2),
+			..cfg
+		};
+		let kept: Vec<_> = names
+			.into_iter()
+			.filter(|name| rank_matches(&cfg, ranks.get(&name.to_lowercase()), &mut vs))
+			.collect();
+		assert_eq!(kept, ["us_m4_sherman"]);
+	}
+
+	/// Every file under `dir`, as relative path → contents.
+	fn read_tree(dir: &Path) -> BTreeMap<PathBuf, Vec<u8>> {
+		let mut files = BTreeMap::new();
+		let mut pending = vec![dir.to_path_buf()];
+		while let Some(current) = pending.pop() {
+			for entry in std::fs::read_dir(&current).unwrap() {
+				let path = entry.unwrap().path();
+				if path.is_dir() {
+					pending.push(path);
+				} else {
+					let contents = std::fs::read(&path).unwrap();
+					files.insert(path.strip_prefix(dir).unwrap().to_path_buf(), contents);
+				}
+			}
+		}
+		files
+	}
+
+	#[test]
+	#[allow(clippy::too_many_lines)]
+	fn test_low_memory_matches_default_output() {
+		let aces = Path::new(env!("CARGO_MANIFEST_DIR"))
+			.parent()
+			.unwrap()
+			.join("test_data/datamine/aces.vromfs.bin_u");
+		let vehicles = ["cn_al_khalid_1", "cn_bt_5", "cn_cm11", "cn_hj_9"];
+		let tankmodels = aces.join("gamedata/units/tankmodels");
+		if vehicles.iter().any(|v| !tankmodels.join(format!("{v}.blkx")).exists()) {
+			eprintln!("Skipping low-memory test: corpus not present");
+			return;
+		}
+
+		// A small in-memory datamine: the vehicles, their modules and one
+		// module nobody reads
+		let mut datamine = Datamine::new();
+		let add = |datamine: &mut Datamine, path: &Path| {
+			let key = path.strip_prefix(&aces).unwrap().to_string_lossy().replace('\\', "/");
+			datamine.insert(key, std::fs::read_to_string(path).unwrap());
+		};
+		for vehicle in vehicles {
+			let path = tankmodels.join(format!("{vehicle}.blkx"));
+			add(&mut datamine, &path);
+			for module in vehicle_module_paths(&path, aces.parent().unwrap()).unwrap() {
+				add(&mut datamine, &module);
+			}
+		}
+		let unused = std::fs::read_dir(aces.join("gamedata/weapons/groundmodels_weapons"))
+			.unwrap()
+			.map(|e| e.unwrap().path())
+			.find(|p| !datamine.contains_key(&*p.strip_prefix(&aces).unwrap().to_string_lossy()))
+			.unwrap();
+		add(&mut datamine, &unused);
+
+		let dir = TempDir::new("low-memory");
+		let ballistic_cfg = BallisticConfig::new(1.0);
+		let run = |low_memory: bool| {
+			let output = dir.join(if low_memory { "low" } else { "default" });
+			let mut names: Vec<String> = vehicles.iter().map(|&v| v.to_owned()).collect();
+			names.push("missing_tank".to_owned());
+			let mut extraction = extract::ExtractionResult {
+				datamine: datamine.clone(),
+				vehicle_names: names,
+				version: None,
+				unittags: fcsgen_core::UnittagsMap::new(),
+				ranks: fcsgen_core::RankMap::new(),
+			};
+			let cfg = PipelineConfig {
+				low_memory,
+				..pipeline_config(&output, ballistic_cfg)
+			};
+			let data_dir = output.join("Data");
+			let ballistic_dir = output.join("Ballistic");
+			std::fs::create_dir_all(&data_dir).unwrap();
+			std::fs::create_dir_all(&ballistic_dir).unwrap();
+			run_pipeline_in_memory(
+				&cfg,
+				&mut extraction,
+				&data_dir,
+				&ballistic_dir,
+				&BallisticCache::new(),
+				&ballistic_cfg,
+				false,
+				2,
+			)
+			.unwrap();
+			// Low-memory mode consumes the datamine
+			assert_eq!(extraction.datamine.is_empty(), low_memory);
+			read_tree(&output)
+		};
+
+		let default = run(false);
+		assert!(default.len() > vehicles.len(), "tables written");
+		assert!(default == run(true), "outputs differ");
+	}
+
+	#[test]
+	fn test_atgm_only_vehicle_counted_as_all_skipped() {
+		let dir = TempDir::new("atgm-only");
+		let data_dir = dir.join("Data");
+		std::fs::create_dir_all(&data_dir).unwrap();
+		std::fs::write(data_dir.join("test_tank.txt"), SLOW_DATA).unwrap();
+		std::fs::write(
+			data_dir.join("atgm_carrier.txt"),
+			"\
+RocketPath:gameData/Weapons/groundModels_weapons/9m113_launcher.blkx
+
+Name:9m113
+Type:atgm_tandem_tank
+BulletMass:17.0
+BallisticCaliber:0.135
+Speed:200.0
+",
+		)
+		.unwrap();
+		let output = dir.join("out");
+		let report_path = dir.join("report.json");
+
+		let ballistic_cfg = BallisticConfig::default();
+		run_pipeline(&PipelineConfig {
+			from_data: Some(&data_dir),
+			report: Some(&report_path),
+			..pipeline_config(&output, ballistic_cfg)
+		});
+
+		let report: serde_json::Value =
+			serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+		assert_eq!(report["shells_all_skipped"], 1);
+		assert_eq!(report["skipped_unarmed"], 0);
+		assert_eq!(report["shells_written"], 3, "only test_tank has tables");
+		assert!(!output.join("Ballistic").join("atgm_carrier").exists());
+	}
+
+	#[test]
+	fn test_renamed_subdirs_used_for_output_and_freshness() {
+		let dir = TempDir::new("subdirs");
+		let edited = dir.join("Edited");
+		std::fs::create_dir_all(&edited).unwrap();
+		std::fs::write(edited.join("test_tank.txt"), SLOW_DATA).unwrap();
+		let output = dir.join("out");
+
+		let ballistic_cfg = BallisticConfig::default();
+		let cfg = PipelineConfig {
+			datamine_subdir: "Cache",
+			data_subdir: "Txt",
+			ballistic_subdir: "Tables_0.5",
+			from_data: Some(&edited),
+			..pipeline_config(&output, ballistic_cfg)
+		};
+		run_pipeline(&cfg);
+
+		assert!(output.join("Tables_0.5").join("test_tank").join("pzgr_39.txt").exists());
+		assert!(output.join("Txt").is_dir());
+		assert!(!output.join("Ballistic").exists());
+		assert!(!output.join("Data").exists());
+
+		// The marker is read from, and outputs checked in, the renamed dirs
+		std::fs::copy(edited.join("test_tank.txt"), output.join("Txt").join("test_tank.txt"))
+			.unwrap();
+		write_marker(&output.join("Cache"), "2.45.0.1", &Marker::of(&cfg), &cfg.log);
+		let cached = |datamine: &str, data: &str, ballistic: &str| {
+			cached_version(
+				&output.join(datamine),
+				&output.join(data),
+				&output.join(ballistic),
+				&Marker::of(&cfg),
+				false,
+			)
+		};
+		assert_eq!(cached("Cache", "Txt", "Tables_0.5").as_deref(), Some("2.45.0.1"));
+		assert_eq!(cached("Datamine", "Data", "Ballistic"), None);
+	}
+
+	#[test]
+	fn test_marker_of_other_pen_unit_is_stale() {
+		let dir = TempDir::new("marker");
+		let edited = dir.join("Edited");
+		std::fs::create_dir_all(&edited).unwrap();
+		std::fs::write(edited.join("test_tank.txt"), SLOW_DATA).unwrap();
+		let output = dir.join("out");
+		let table = output.join("Ballistic").join("test_tank").join("pzgr_39.txt");
+
+		// First run in millimetres, marked as a complete run of 2.45.0.1
+		let mm = PipelineConfig {
+			from_data: Some(&edited),
+			..pipeline_config(&output, BallisticConfig::default())
+		};
+		run_pipeline(&mm);
+		std::fs::copy(edited.join("test_tank.txt"), output.join("Data").join("test_tank.txt"))
+			.unwrap();
+		write_marker(&output.join("Datamine"), "2.45.0.1", &Marker::of(&mm), &mm.log);
+		let mm_table = std::fs::read_to_string(&table).unwrap();
+
+		let cached = |cfg: &PipelineConfig<'_>| {
+			cached_version(
+				&output.join("Datamine"),
+				&output.join("Data"),
+				&output.join("Ballistic"),
+				&Marker::of(cfg),
+				false,
+			)
+		};
+		assert_eq!(cached(&mm).as_deref(), Some("2.45.0.1"));
+
+		// Same version and sensitivity in inches: not up to date, so the
+		// second run rewrites the tables
+		let inches = pipeline_config(
+			&output,
+			BallisticConfig {
+				pen_unit: PenetrationUnit::Inches,
+				..BallisticConfig::default()
+			},
+		);
+		assert_eq!(cached(&inches), None);
+		run_pipeline(&PipelineConfig {
+			from_data: Some(&edited),
+			..inches
+		});
+		assert_ne!(std::fs::read_to_string(&table).unwrap(), mm_table);
+	}
+
+	#[test]
+	fn test_requested_outputs_bypass_freshness_check() {
+		let output = Path::new("out");
+		let cfg = pipeline_config(output, BallisticConfig::default());
+		assert!(checks_freshness(&cfg));
+
+		let report = Path::new("report.json");
+		assert!(!checks_freshness(&PipelineConfig {
+			report: Some(report),
+			..pipeline_config(output, BallisticConfig::default())
+		}));
+
+		let manifest = Path::new("shells.json");
+		assert!(!checks_freshness(&PipelineConfig {
+			shell_manifest: Some(manifest),
+			..pipeline_config(output, BallisticConfig::default())
+		}));
+
+		let cache = Path::new("cache.json.gz");
+		assert!(!checks_freshness(&PipelineConfig {
+			save_cache: Some(cache),
+			..pipeline_config(output, BallisticConfig::default())
+		}));
+	}
+
+	#[test]
+	fn test_sensitivity_by_nation_per_vehicle() {
+		let dir = TempDir::new("nation");
+		let data_dir = dir.join("EditedData");
+		std::fs::create_dir_all(&data_dir).unwrap();
+		for vehicle in ["us_test_tank", "ussr_test_tank", "germ_test_tank"] {
+			std::fs::write(data_dir.join(format!("{vehicle}.txt")), SLOW_DATA).unwrap();
+		}
+		let output = dir.join("out");
+
+		let ballistic_cfg = BallisticConfig::new(1.0);
+		let overrides = [("us".to_owned(), 0.9), ("ussr".to_owned(), 0.8)];
+		run_pipeline(&PipelineConfig {
+			sensitivity_by_nation: &overrides,
+			from_data: Some(&data_dir),
+			with_header: true,
+			..pipeline_config(&output, ballistic_cfg)
+		});
+
+		let table = |vehicle: &str| {
+			let path = output.join("Ballistic").join(vehicle).join("pzgr_39.txt");
+			std::fs::read_to_string(path).unwrap()
+		};
+		let sensitivity = |table: &str| {
+			let header = table.lines().next().unwrap();
+			header
+				.split('\t')
+				.find_map(|field| field.strip_prefix("sensitivity="))
+				.unwrap()
+				.to_owned()
+		};
+		assert_eq!(sensitivity(&table("us_test_tank")), "0.9");
+		assert_eq!(sensitivity(&table("ussr_test_tank")), "0.8");
+		assert_eq!(sensitivity(&table("germ_test_tank")), "1", "no override: global value");
+
+		// A finer step gives more rows for the same shell
+		let rows = |vehicle: &str| table(vehicle).lines().count();
+		assert!(rows("ussr_test_tank") > rows("us_test_tank"));
+		assert!(rows("us_test_tank") > rows("germ_test_tank"));
+	}
+
+	#[test]
+	#[allow(clippy::too_many_lines)]
+	fn test_ammo_sets_split_stock_and_researched() {
+		let dir = TempDir::new("ammo-set");
+
+		let weapon = serde_json::json!({
+			"bullet": {
+				"bulletName": "75mm_pzgr_39",
+				"bulletType": "apcbc_tank",
+				"mass": 6.8,
+				"caliber": 0.075,
+				"speed": 740.0,
+				"Cx": 0.4
+			},
+			"75mm_ger_kwk40_APCR": {
+				"bullet": {
+					"bulletName": "75mm_pzgr_40",
+					"bulletType": "apcr_tank",
+					"mass": 4.1,
+					"caliber": 0.075,
+					"speed": 930.0,
+					"Cx": 0.4,
+					"damageMass": 0.9,
+					"damageCaliber": 0.028
+				}
+			}
+		});
+		let vehicle = serde_json::json!({"modifications": {"75mm_ger_kwk40_APCR": {}}});
+		let projectiles = fcsgen_core::parse_weapon_module(&weapon, Some(&vehicle)).unwrap();
+		assert!(!projectiles[1].unlocked_by_default, "APCR belt is modification-gated");
+
+		let ballistic_cfg = BallisticConfig::new(1.0);
+		let sets = [AmmoSet::Stock, AmmoSet::Researched];
+		let cfg = PipelineConfig {
+			ammo_sets: &sets,
+			..pipeline_config(&dir, ballistic_cfg)
+		};
+
+		let mut vs = VehicleStats::default();
+		process_vehicle_ballistic(
+			&projectiles,
+			"test_tank",
+			&dir,
+			&ballistic_cfg,
+			&BallisticCache::new(),
+			&cfg,
+			&mut vs,
+		)
+		.unwrap();
+
+		let stock = dir.join("stock").join("test_tank");
+		let researched = dir.join("researched").join("test_tank");
+		assert!(stock.join("pzgr_39.txt").exists());
+		assert!(!stock.join("pzgr_40.txt").exists(), "gated round is not stock");
+		assert!(researched.join("pzgr_39.txt").exists());
+		assert!(researched.join("pzgr_40.txt").exists());
+		assert!(!dir.join("test_tank").exists(), "nothing outside the set trees");
+
+		// The shared shell is simulated once
+		assert_eq!((vs.shells_written, vs.cache_misses, vs.cache_hits), (3, 2, 1));
+	}
+
+	#[test]
+	fn test_output_conflicts_are_returned_to_the_caller() {
+		let dir = TempDir::new("conflict");
+		let data_dir = dir.join("Data");
+		let ballistic_dir = dir.join("Ballistic");
+		std::fs::create_dir_all(&data_dir).unwrap();
+		std::fs::create_dir_all(ballistic_dir.join("test_tank")).unwrap();
+		std::fs::write(data_dir.join("test_tank.txt"), SLOW_DATA).unwrap();
+		std::fs::write(ballistic_dir.join("test_tank").join("pzgr_40.txt"), "old").unwrap();
+		// Two physically different shells cleaning to `pzgr_39`
+		std::fs::write(
+			data_dir.join("collide_tank.txt"),
+			SLOW_DATA.replace("75mm_pzgr_40", "76mm_pzgr_39"),
+		)
+		.unwrap();
+
+		let ballistic_cfg = BallisticConfig::new(1.0);
+		let run = |cfg: &PipelineConfig<'_>| {
+			run_pipeline_from_data(
+				cfg,
+				&data_dir,
+				&ballistic_dir,
+				&BallisticCache::new(),
+				&ballistic_cfg,
+				1,
+			)
+		};
+
+		let only_test_tank = ["test_tank".to_owned()];
+		let Err(conflict) = run(&PipelineConfig {
+			filter: Some(&only_test_tank),
+			overwrite_policy: OverwritePolicy::Error,
+			..pipeline_config(&dir, ballistic_cfg)
+		}) else {
+			panic!("the existing table did not stop the run");
+		};
+		assert!(conflict.to_string().contains("pzgr_40.txt already exists"), "{conflict}");
+		let existing = ballistic_dir.join("test_tank").join("pzgr_40.txt");
+		assert_eq!(std::fs::read_to_string(existing).unwrap(), "old");
+		// The shell planned before the existing one is not written either
+		assert!(!ballistic_dir.join("test_tank").join("pzgr_39.txt").exists());
+
+		let only_collide_tank = ["collide_tank".to_owned()];
+		let Err(conflict) = run(&PipelineConfig {
+			filter: Some(&only_collide_tank),
+			on_collision: CollisionPolicy::Error,
+			..pipeline_config(&dir, ballistic_cfg)
+		}) else {
+			panic!("the name collision did not stop the run");
+		};
+		assert!(conflict.to_string().contains("share the name pzgr_39"), "{conflict}");
+		// The collision is found before any of the vehicle's tables is written
+		assert!(!ballistic_dir.join("collide_tank").exists());
+	}
+
+	#[test]
+	fn test_error_policy_writes_nothing_until_all_targets_are_checked() {
+		let dir = TempDir::new("staged");
+		let data_dir = dir.join("Data");
+		let ballistic_dir = dir.join("Ballistic");
+		std::fs::create_dir_all(&data_dir).unwrap();
+		std::fs::create_dir_all(ballistic_dir.join("b_tank")).unwrap();
+		std::fs::write(data_dir.join("a_tank.txt"), SLOW_DATA).unwrap();
+		std::fs::write(data_dir.join("b_tank.txt"), SLOW_DATA).unwrap();
+		let existing = ballistic_dir.join("b_tank").join("pzgr_40.txt");
+		std::fs::write(&existing, "old").unwrap();
+
+		let ballistic_cfg = BallisticConfig::new(1.0);
+		let cfg = PipelineConfig {
+			overwrite_policy: OverwritePolicy::Error,
+			..pipeline_config(&dir, ballistic_cfg)
+		};
+		let run = || {
+			run_pipeline_from_data(
+				&cfg,
+				&data_dir,
+				&ballistic_dir,
+				&BallisticCache::new(),
+				&ballistic_cfg,
+				1,
+			)
+		};
+
+		assert!(run().is_err(), "the existing table did not stop the run");
+		// Neither the vehicle without a conflict nor its neighbour was written
+		for vehicle in ["a_tank", "b_tank"] {
+			assert!(!ballistic_dir.join(vehicle).join("pzgr_39.txt").exists(), "{vehicle}");
+		}
+		assert!(!ballistic_dir.join("a_tank").join("pzgr_40.txt").exists());
+
+		std::fs::remove_file(existing).unwrap();
+		let mut stats = run().unwrap();
+		assert_eq!((stats.staged.len(), stats.shells_written), (6, 6));
+		assert!(!ballistic_dir.join("a_tank").join("pzgr_39.txt").exists(), "only staged");
+		write_staged(&mut stats, &cfg).unwrap();
+		for vehicle in ["a_tank", "b_tank"] {
+			for shell in ["pzgr_39.txt", "sprgr_34.txt", "pzgr_40.txt"] {
+				assert!(ballistic_dir.join(vehicle).join(shell).exists(), "{vehicle}/{shell}");
+			}
+		}
+		assert_eq!((stats.shells_written, stats.ballistic_errors), (6, 0));
+	}
+
+	#[test]
+	fn test_panicking_vehicle_does_not_abort_batch() {
+		let dir = TempDir::new("panic");
+		let ballistic_dir = dir.join("Ballistic");
+		std::fs::create_dir_all(&ballistic_dir).unwrap();
+		let data = parse_data_text(SLOW_DATA, "test_tank");
+
+		// The work unit of each vehicle of a from-data run, with a panic
+		// injected into one of them
+		let ballistic_cfg = BallisticConfig::new(1.0);
+		let cfg = pipeline_config(&dir, ballistic_cfg);
+		let cache = BallisticCache::new();
+		let stats = ["good_a", "panicking_tank", "good_b"]
+			.par_iter()
+			.map(|&name| {
+				catch_vehicle_panic(name, &cfg.log, || {
+					assert_ne!(name, "panicking_tank", "injected panic");
+					let mut vs = VehicleStats {
+						converted: 1,
+						..VehicleStats::default()
+					};
+					process_ballistic(
+						&data.projectiles,
+						name,
+						&ballistic_dir,
+						&ballistic_cfg,
+						&cache,
+						&cfg,
+						&mut vs,
+					)?;
+					Ok(vs)
+				})
+			})
+			.try_reduce(VehicleStats::default, |a, b| Ok(a.merge(b)))
+			.unwrap();
+
+		assert_eq!((stats.converted, stats.convert_failed), (2, 1));
+		let panicked: Vec<_> = stats.panicked.iter().map(|e| e.vehicle.as_str()).collect();
+		assert_eq!(panicked, ["panicking_tank"]);
+		assert!(stats.panicked[0].error.contains("injected panic"), "{}", stats.panicked[0].error);
+		for name in ["good_a", "good_b"] {
+			for shell in ["pzgr_39", "sprgr_34", "pzgr_40"] {
+				let table = ballistic_dir.join(name).join(format!("{shell}.txt"));
+				assert!(table.exists(), "{} missing", table.display());
+			}
+		}
+	}
+
+	#[test]
+	fn test_repeat_reuses_cache_in_second_iteration() {
+		let dir = TempDir::new("repeat");
+		let data_dir = dir.join("EditedData");
+		std::fs::create_dir_all(&data_dir).unwrap();
+		for vehicle in ["test_tank_a", "test_tank_b"] {
+			std::fs::write(data_dir.join(format!("{vehicle}.txt")), SLOW_DATA).unwrap();
+		}
+		let output = dir.join("out");
+		let report_path = dir.join("report.json");
+
+		let ballistic_cfg = BallisticConfig::new(1.0);
+		run_pipeline(&PipelineConfig {
+			from_data: Some(&data_dir),
+			report: Some(&report_path),
+			repeat: 2,
+			..pipeline_config(&output, ballistic_cfg)
+		});
+
+		let report: serde_json::Value =
+			serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+		let iterations = report["iterations"].as_array().unwrap();
+		assert_eq!(iterations.len(), 2);
+		let hits = |i: usize| iterations[i]["cache_hits"].as_u64().unwrap();
+		assert!(hits(1) > hits(0), "{iterations:?}");
+		assert_eq!(iterations[1]["cache_misses"], 0, "warm cache: every table reused");
+		// The totals are those of the last iteration
+		assert_eq!(report["converted"], 2);
+	}
+
+	#[test]
+	fn test_log_file_has_step_banners_and_warnings() {
+		let dir = TempDir::new("log-file");
+		let data_dir = dir.join("EditedData");
+		std::fs::create_dir_all(&data_dir).unwrap();
+		std::fs::write(data_dir.join("test_tank.txt"), SLOW_DATA).unwrap();
+		// Not UTF-8: the vehicle fails to parse
+		std::fs::write(data_dir.join("test_broken.txt"), [0xff, 0xfe, 0x00]).unwrap();
+		let output = dir.join("out");
+		let missing_cache = dir.join("missing-cache.json");
+		let log_path = dir.join("fcsgen.log");
+
+		let ballistic_cfg = BallisticConfig::new(1.0);
+		let cfg = PipelineConfig {
+			from_data: Some(&data_dir),
+			seed_cache_from: Some(&missing_cache),
+			log: LogFile::create(&log_path).unwrap(),
+			..pipeline_config(&output, ballistic_cfg)
+		};
+		run_pipeline(&cfg);
+		drop(cfg);
+
+		let log = std::fs::read_to_string(&log_path).unwrap();
+		assert!(log.contains("Steps 1-2/3: Skipping extraction"), "{log}");
+		assert!(log.contains("Step 3/3: Computing ballistics for 2 vehicles"), "{log}");
+		assert!(log.contains("Warning: ignoring cache file"), "{log}");
+		assert!(log.contains("PARSE ERROR test_broken"), "{log}");
+		assert!(log.lines().all(|line| line.starts_with('[') && line.contains("s] ")), "{log}");
+	}
+}
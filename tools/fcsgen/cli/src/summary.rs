@@ -0,0 +1,346 @@
+//! CLI orchestrator for the `summary` subcommand.
+//!
+//! Walks `Data/*.txt` files and writes a single TSV with one line per shell:
+//! how far its flattest scroll-step shot reaches and the penetration there
+//! ([`compute_max_direct_range`]).  One trajectory per shell instead of a full
+//! sweep, for a quick overview across all vehicles.  Within a vehicle, shells
+//! are grouped by caliber, largest first, so main gun rounds come before
+//! autocannon or MG belts.  `--with-zero` adds a suggested sight zero per
+//! shell ([`suggest_zero_distance`]), at the cost of a full elevation sweep.
+//! `--target-material` adds the penetration against cast armor or structural
+//! steel ([`TargetMaterial`]); the RHA column is unchanged.
+
+use std::cmp::Reverse;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use fcsgen_core::ballistic::{
+	BallisticConfig,
+	TargetMaterial,
+	compute_elevation_table,
+	compute_max_direct_range,
+};
+use fcsgen_core::parser::data::{DataProjectile, caliber_mm, parse_data_file};
+use fcsgen_core::table::suggest_zero_distance;
+
+use crate::log_file::LogFile;
+use crate::output::{
+	CollisionPolicy,
+	LineEnding,
+	OverwritePolicy,
+	WriteOutcome,
+	exit_if_exists,
+	plan_shell_files,
+	write_output,
+};
+
+/// Column names of the summary TSV.
+const SUMMARY_HEADER: &str = "vehicle\tcaliber\tshell\ttype\tdistance\tpenetration\n";
+
+/// Run the `summary` subcommand.
+///
+/// # Arguments
+/// * `input`         – Directory containing `Data/*.txt` files (Stage 1 output).
+/// * `output`        – TSV file to write.
+/// * `ballistic_cfg` – Sensitivity and gravity of the simulated shot.
+/// * `filter`        – Optional list of vehicle IDs to process.
+/// * `with_zero`     – Add a `zero` column with the suggested sight zero.
+/// * `material`      – Add an `effective_penetration` column against this armor.
+/// * `overwrite_policy` – How to handle an existing `output`.
+pub fn run_summary(
+	input: &Path,
+	output: &Path,
+	ballistic_cfg: &BallisticConfig,
+	filter: Option<&[String]>,
+	with_zero: bool,
+	material: Option<TargetMaterial>,
+	overwrite_policy: OverwritePolicy,
+) {
+	if !input.exists() {
+		eprintln!("Error: input directory not found at {input:?}");
+		std::process::exit(1);
+	}
+
+	let mut files: Vec<_> = std::fs::read_dir(input)
+		.expect("read input directory")
+		.filter_map(|e| e.ok())
+		.map(|e| e.path())
+		.filter(|p| p.extension().is_some_and(|ext| ext == "txt"))
+		.filter(|p| {
+			let stem = p.file_stem().unwrap().to_string_lossy();
+			filter.is_none_or(|filter| filter.iter().any(|f| *f == stem))
+		})
+		.collect();
+	files.sort();
+
+	eprintln!(
+		"Summarizing direct-fire range for {} vehicles (sensitivity={})",
+		files.len(),
+		ballistic_cfg.sensitivity,
+	);
+
+	let mut tsv = SUMMARY_HEADER.to_owned();
+	if material.is_some() {
+		tsv = tsv.replace('\n', "\teffective_penetration\n");
+	}
+	if with_zero {
+		tsv = tsv.replace('\n', "\tzero\n");
+	}
+	let mut shells = 0;
+	let mut failed = 0;
+
+	for path in &files {
+		let vehicle_id = path
+			.file_stem()
+			.unwrap()
+			.to_str()
+			.unwrap_or("unknown");
+
+		match parse_data_file(path) {
+			Ok(data) => {
+				shells += summarize_vehicle(
+					&mut tsv,
+					vehicle_id,
+					&data.projectiles,
+					ballistic_cfg,
+					with_zero,
+					material,
+				);
+			},
+			Err(e) => {
+				eprintln!("PARSE ERROR {vehicle_id}: {e}");
+				failed += 1;
+			},
+		}
+	}
+
+	match write_output(output, &tsv, overwrite_policy, LineEnding::Lf) {
+		Ok(WriteOutcome::Written) => {},
+		Ok(WriteOutcome::Skipped) => {
+			eprintln!("{output:?} exists, left untouched (--overwrite-policy skip)");
+		},
+		Err(e) => {
+			exit_if_exists(&e);
+			eprintln!("Error: cannot write {output:?}: {e}");
+			std::process::exit(1);
+		},
+	}
+
+	eprintln!("Done: {shells} shells summarized, {failed} errors");
+	eprintln!("Output: {output:?}");
+}
+
+/// Append one `vehicle\tcaliber\tshell\ttype\tdistance\tpenetration` line
+/// per shell of a vehicle to `tsv`, naming shells as their ballistic files
+/// would be, plus `\teffective_penetration` against `material` if given and
+/// `\tzero` if `with_zero`.  Returns the number of lines written.
+fn summarize_vehicle(
+	tsv: &mut String,
+	vehicle_id: &str,
+	projectiles: &[DataProjectile],
+	ballistic_cfg: &BallisticConfig,
+	with_zero: bool,
+	material: Option<TargetMaterial>,
+) -> usize {
+	let plan = plan_shell_files(
+		vehicle_id,
+		projectiles,
+		ballistic_cfg,
+		CollisionPolicy::Suffix,
+		&LogFile::default(),
+	)
+	.expect("only the error policy fails");
+	let mut lines = 0;
+	for (caliber_mm, shells) in group_by_caliber(plan) {
+		for (proj, file_stem) in shells {
+			let Some((distance, pen)) = compute_max_direct_range(proj, ballistic_cfg) else {
+				continue;
+			};
+			let fmt_pen = |pen: f64| {
+				if pen.is_finite() {
+					format!("{pen}")
+				} else {
+					"\u{221E}".to_owned()
+				}
+			};
+			write!(
+				tsv,
+				"{vehicle_id}\t{caliber_mm}\t{file_stem}\t{}\t{distance:.3}\t{}",
+				proj.normalized_type,
+				fmt_pen(pen),
+			)
+			.unwrap();
+			if let Some(material) = material {
+				write!(tsv, "\t{}", fmt_pen(material.effective_penetration(pen).round())).unwrap();
+			}
+			tsv.push('\n');
+			if with_zero {
+				let rows = compute_elevation_table(proj, ballistic_cfg).unwrap_or_default();
+				tsv.pop();
+				writeln!(tsv, "\t{}", suggest_zero_distance(&rows)).unwrap();
+			}
+			lines += 1;
+		}
+	}
+	lines
+}
+
+/// Group planned shells by `ballistic_caliber` rounded to whole millimetres,
+/// largest caliber first.  Shells keep their Data order within a group.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn group_by_caliber(
+	shells: Vec<(&DataProjectile, String)>,
+) -> Vec<(u32, Vec<(&DataProjectile, String)>)> {
+	let mut groups: Vec<(u32, Vec<_>)> = Vec::new();
+	for shell in shells {
+		let caliber_mm = caliber_mm(shell.0).round().max(0.0) as u32;
+		match groups.iter_mut().find(|(mm, _)| *mm == caliber_mm) {
+			Some((_, group)) => group.push(shell),
+			None => groups.push((caliber_mm, vec![shell])),
+		}
+	}
+	groups.sort_by_key(|&(mm, _)| Reverse(mm));
+	groups
+}
+
+#[cfg(test)]
+mod tests {
+	use fcsgen_core::parser::data::parse_data_text;
+
+	use super::*;
+
+	const DATA: &str = "\
+WeaponPath:gameData/Weapons/test.blkx
+
+Name:75mm_pzgr_39
+Type:apcbc_tank
+BulletMass:6.8
+BallisticCaliber:0.075
+Speed:740.0
+Cx:0.4
+
+Name:75mm_atgm
+Type:atgm_tank
+BulletMass:20.0
+BallisticCaliber:0.15
+Speed:300.0
+Cx:0.4
+";
+
+	/// Coaxial MG, main gun and autocannon belts, interleaved.
+	const MULTI_CALIBER_DATA: &str = "\
+WeaponPath:gameData/Weapons/test.blkx
+
+Name:7.62mm_ball
+Type:ball
+BulletMass:0.0096
+BallisticCaliber:0.00762
+Speed:830.0
+Cx:0.3
+
+Name:105mm_m456
+Type:heat_fs
+BulletMass:10.5
+BallisticCaliber:0.105
+Speed:1174.0
+Cx:0.3
+
+Name:20mm_api
+Type:ap_i
+BulletMass:0.11
+BallisticCaliber:0.02
+Speed:1050.0
+Cx:0.35
+
+Name:105mm_m392
+Type:apds
+BulletMass:5.8
+BallisticCaliber:0.1049
+Speed:1478.0
+Cx:0.3
+
+Name:20mm_hei
+Type:he_i
+BulletMass:0.1
+BallisticCaliber:0.02
+Speed:1050.0
+Cx:0.35
+";
+
+	#[test]
+	fn test_one_line_per_computed_shell() {
+		let data = parse_data_text(DATA, "test_tank");
+		let cfg = BallisticConfig::default();
+		let mut tsv = String::new();
+
+		let lines = summarize_vehicle(&mut tsv, "test_tank", &data.projectiles, &cfg, false, None);
+		assert_eq!(lines, 1);
+		let cols: Vec<&str> = tsv.trim_end().split('\t').collect();
+		assert_eq!(cols[..4], ["test_tank", "75", "pzgr_39", "apcbc"]);
+
+		let (distance, pen) = compute_max_direct_range(&data.projectiles[0], &cfg).unwrap();
+		assert_eq!(cols[4], format!("{distance:.3}"));
+		assert_eq!(cols[5], format!("{pen}"));
+		assert_eq!(cols.len(), 6);
+
+		tsv.clear();
+		summarize_vehicle(&mut tsv, "test_tank", &data.projectiles, &cfg, true, None);
+		let zero: f64 = tsv.trim_end().split('\t').nth(6).unwrap().parse().unwrap();
+		assert!(zero > 0.0);
+	}
+
+	#[test]
+	fn test_shells_grouped_by_caliber_largest_first() {
+		let data = parse_data_text(MULTI_CALIBER_DATA, "test_tank");
+		let cfg = BallisticConfig::default();
+		let plan = plan_shell_files(
+			"test_tank",
+			&data.projectiles,
+			&cfg,
+			CollisionPolicy::Suffix,
+			&LogFile::default(),
+		)
+		.unwrap();
+
+		let groups: Vec<(u32, Vec<&str>)> = group_by_caliber(plan)
+			.into_iter()
+			.map(|(mm, shells)| (mm, shells.iter().map(|(p, _)| p.name.as_str()).collect()))
+			.collect();
+		assert_eq!(
+			groups,
+			[
+				(105, vec!["105mm_m456", "105mm_m392"]),
+				(20, vec!["20mm_api", "20mm_hei"]),
+				(8, vec!["7.62mm_ball"]),
+			]
+		);
+
+		let mut tsv = String::new();
+		let lines = summarize_vehicle(&mut tsv, "test_tank", &data.projectiles, &cfg, false, None);
+		assert_eq!(lines, 5);
+		let calibers: Vec<&str> = tsv.lines().map(|l| l.split('\t').nth(1).unwrap()).collect();
+		assert_eq!(calibers, ["105", "105", "20", "20", "8"]);
+	}
+
+	#[test]
+	fn test_softer_material_raises_effective_penetration() {
+		let data = parse_data_text(DATA, "test_tank");
+		let cfg = BallisticConfig::default();
+		let effective_pen = |material| {
+			let mut tsv = String::new();
+			let projectiles = &data.projectiles;
+			summarize_vehicle(&mut tsv, "test_tank", projectiles, &cfg, false, Some(material));
+			let cols: Vec<String> = tsv.trim_end().split('\t').map(str::to_owned).collect();
+			(cols[5].clone(), cols[6].parse::<f64>().unwrap())
+		};
+
+		let (rha_pen, rha) = effective_pen(TargetMaterial::Rha);
+		let (cast_pen, cast) = effective_pen(TargetMaterial::Cast);
+		let (structural_pen, structural) = effective_pen(TargetMaterial::Structural);
+		// The RHA penetration column is the same whatever the material
+		assert_eq!(rha_pen, cast_pen);
+		assert_eq!(rha_pen, structural_pen);
+		assert!((rha - rha_pen.parse::<f64>().unwrap()).abs() <= 0.5);
+		assert!(rha < cast && cast < structural, "{rha} {cast} {structural}");
+	}
+}
@@ -0,0 +1,38 @@
+//! Helpers shared by the unit tests.
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// A scratch directory under the system temp dir, removed with its contents
+/// when dropped, so a failing test does not leave it behind.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+	/// Create `fcsgen-{name}-{pid}`, emptied first if an aborted run left it.
+	pub fn new(name: &str) -> Self {
+		let path = std::env::temp_dir().join(format!("fcsgen-{name}-{}", std::process::id()));
+		let _ = std::fs::remove_dir_all(&path);
+		std::fs::create_dir_all(&path).unwrap();
+		Self(path)
+	}
+}
+
+impl Deref for TempDir {
+	type Target = Path;
+
+	fn deref(&self) -> &Path {
+		&self.0
+	}
+}
+
+impl AsRef<Path> for TempDir {
+	fn as_ref(&self) -> &Path {
+		&self.0
+	}
+}
+
+impl Drop for TempDir {
+	fn drop(&mut self) {
+		let _ = std::fs::remove_dir_all(&self.0);
+	}
+}
@@ -4,13 +4,18 @@
 //! formula, matching the C# `Ballistic()` method in Form1.cs.
 
 use std::f64::consts::PI;
-use std::fmt::Write;
+use std::fmt::{self, Write};
 use std::hash::{Hash, Hasher};
+use std::io::{self, BufReader, BufWriter, Read, Write as _};
+use std::path::Path;
+use std::str::FromStr;
 use std::sync::LazyLock;
 
 use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
 
 use crate::parser::data::DataProjectile;
+use crate::table::drops_at_standard_ranges;
 
 // ── Physics constants ──────────────────────────────────────────────────────
 const G: f64 = 9.806_65;
@@ -22,7 +27,17 @@ const R_GAS: f64 = 8.314_46;
 const LAPSE_RATE: f64 = 0.0065;
 const T_STD: f64 = 288.15;
 const DEMARRE_REF_V: f64 = 1900.0;
-const MAX_RANGE: f64 = 4500.0;
+const MM_PER_INCH: f64 = 25.4;
+
+// ── Fixed-step range solver ────────────────────────────────────────────────
+/// Acceptable distance error (metres) when solving for a fixed-step row.
+const FIXED_STEP_TOL: f64 = 1e-3;
+/// Initial launch-angle increment (radians) used to bracket a target range.
+const ANGLE_BRACKET_STEP: f64 = 0.01;
+/// Highest launch angle (radians) considered, matching the 60° scroll sweep.
+const MAX_ANGLE: f64 = PI / 3.0;
+/// Bisection iteration cap; far more than needed to reach `FIXED_STEP_TOL`.
+const MAX_BISECT_ITERS: usize = 100;
 
 // ── Atmospheric density lookup table ───────────────────────────────────────
 /// Resolution of the pre-baked density table (metres per entry).
@@ -47,6 +62,32 @@ static DENSITY_TABLE: LazyLock<Vec<f64>> = LazyLock::new(|| {
 		.collect()
 });
 
+/// Resolution of the coarser density table for long-range configurations
+/// (metres per entry).
+const HIGH_DENSITY_STEP: f64 = 1.0;
+
+/// Precomputed density for [`BallisticConfig::NAVAL_MAX_RANGE`]: shells
+/// fired that far climb several kilometres, well past [`DENSITY_TABLE`],
+/// and would otherwise take the `powf` fallback on most steps.  Covers
+/// altitudes up to the range itself, at [`HIGH_DENSITY_STEP`] resolution.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_precision_loss)]
+static HIGH_DENSITY_TABLE: LazyLock<Vec<f64>> = LazyLock::new(|| {
+	let len = (BallisticConfig::NAVAL_MAX_RANGE / HIGH_DENSITY_STEP) as usize + 1;
+	(0..len)
+		.map(|i| barometric_density(i as f64 * HIGH_DENSITY_STEP))
+		.collect()
+});
+
+/// Air density (kg/m³) at altitude `alt` m, by the barometric formula.
+///
+/// The formula reaches zero pressure at ~44 km; the base is clamped there so
+/// that a shell lobbed that high sees vacuum instead of `NaN`.
+fn barometric_density(alt: f64) -> f64 {
+	let rho_base = P_ATM * M_AIR / R_GAS / (T_GROUND + 273.15);
+	let baro_exp = G * M_AIR / R_GAS / LAPSE_RATE - 1.0;
+	rho_base * (1.0 - LAPSE_RATE * alt / T_STD).max(0.0).powf(baro_exp)
+}
+
 // ── DeMarre defaults (applied when the parsed value is zero) ───────────────
 const DEFAULT_K: f64 = 0.9;
 const DEFAULT_SPEED_POW: f64 = 1.43;
@@ -60,6 +101,9 @@ const AP_TYPES: &[&str] = &[
 ];
 const APHE_TYPES: &[&str] = &["aphe", "aphebc", "ac", "sapcbc", "sap", "sapi"];
 const SKIP_TYPES: &[&str] = &["sam", "atgm", "rocket", "aam"];
+/// Types with no kinetic penetration model that correctly report 0.
+const NON_PENETRATING_TYPES: &[&str] =
+	&["he", "heat", "hesh", "frag", "shrapnel", "ahead", "smoke"];
 
 // ── APHE explosive-filler penalty table (ratio threshold → multiplier) ─────
 const PEN_BY_EXPL: [(f64, f64); 5] = [
@@ -80,17 +124,487 @@ const PEN_BY_SUBCALIBER: [(f64, f64); 4] = [
 
 /// Intermediate row produced by the trajectory simulation.
 struct Row {
+	/// Launch angle (rad).
+	angle: f64,
 	distance: f64,
 	time: f64,
 	penetration: f64,
 }
 
+/// Something worth telling the user about a computed (or skipped) table;
+/// collected by [`compute_ballistic_with_warnings`] rather than printed, so
+/// the caller decides where it goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BallisticWarning {
+	/// No `BallisticCaliber`: skipped, since it would fly without drag.
+	NoBallisticCaliber,
+	/// APCR/APDS round without `DamageCaliber`, computed on its ballistic
+	/// caliber (see [`PenetrationKind::Subcaliber`]).
+	NoDamageCaliber,
+	/// APDS-FS round without an armor power series, penetration estimated
+	/// (see [`estimates_penetration`]).
+	EstimatedPenetration,
+	/// The scroll-step sweep stopped at
+	/// [`BallisticConfig::max_entries_cap`] rows short of the max range.
+	Truncated { max_entries_cap: usize },
+}
+
+impl fmt::Display for BallisticWarning {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::NoBallisticCaliber => {
+				f.write_str("no BallisticCaliber, skipped (it would fly without drag)")
+			},
+			Self::NoDamageCaliber => {
+				f.write_str("subcaliber round has no DamageCaliber, using BallisticCaliber")
+			},
+			Self::EstimatedPenetration => {
+				f.write_str("APDS-FS round has no armor power series, penetration estimated")
+			},
+			Self::Truncated { max_entries_cap } => {
+				write!(
+					f,
+					"ballistic table truncated at {max_entries_cap} entries (max_entries_cap)",
+				)
+			},
+		}
+	}
+}
+
 /// Returns `true` if this shell type should be skipped entirely.
 #[must_use]
 pub fn should_skip(normalized_type: &str) -> bool {
 	SKIP_TYPES.contains(&normalized_type)
 }
 
+/// Whether `proj` gets a table: not a [skipped](should_skip) type, and a
+/// positive ballistic caliber.
+///
+/// A caliber of 0 (missing from the Data file) zeroes the drag cross-section
+/// and gives a drag-free trajectory, so such a shell is skipped with a
+/// [`BallisticWarning::NoBallisticCaliber`] instead.
+fn is_computed(proj: &DataProjectile, warnings: &mut Vec<BallisticWarning>) -> bool {
+	if should_skip(&proj.normalized_type) {
+		return false;
+	}
+	if !(proj.ballistic_caliber > 0.0 && proj.ballistic_caliber.is_finite()) {
+		warnings.push(BallisticWarning::NoBallisticCaliber);
+		return false;
+	}
+	true
+}
+
+/// Returns `true` if the penetration model does not recognise this type.
+///
+/// Such shells are still computed, but fall through to 0 penetration like
+/// HE does, so their tables are probably wrong.  Skipped types (see
+/// [`should_skip`]) and known non-penetrating types are not unsupported.
+#[must_use]
+pub fn is_unsupported_type(normalized_type: &str) -> bool {
+	!AP_TYPES.contains(&normalized_type)
+		&& !["apcr", "apds", "apds_fs"].contains(&normalized_type)
+		&& !NON_PENETRATING_TYPES.contains(&normalized_type)
+		&& !should_skip(normalized_type)
+}
+
+// ── Ballistic configuration ────────────────────────────────────────────────
+
+/// How the rows of a ballistic table are placed along the range axis.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RangeSampling {
+	/// Sweep launch angles in increments of the scroll step derived from the
+	/// mouse sensitivity.  This is what scroll-wheel sights expect.
+	#[default]
+	ScrollStep,
+
+	/// Emit one row every `n` metres (0, n, 2n, …), solving for the launch
+	/// angle that lands at each range.  Independent of sensitivity.
+	FixedStep(f64),
+}
+
+/// Unit of the penetration column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum PenetrationUnit {
+	/// Whole millimetres of RHA, as the legacy tool writes them.
+	#[default]
+	#[serde(rename = "mm")]
+	Millimetres,
+
+	/// Inches of RHA with one decimal.
+	#[serde(rename = "in")]
+	Inches,
+}
+
+impl FromStr for PenetrationUnit {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"mm" => Ok(Self::Millimetres),
+			"in" => Ok(Self::Inches),
+			_ => Err(format!("unknown penetration unit `{s}` (expected `mm` or `in`)")),
+		}
+	}
+}
+
+impl fmt::Display for PenetrationUnit {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::Millimetres => "mm",
+			Self::Inches => "in",
+		})
+	}
+}
+
+/// Unit of the time-of-flight column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum TimeUnit {
+	/// Seconds with one decimal, as the legacy tool writes them.
+	#[default]
+	#[serde(rename = "s")]
+	Seconds,
+
+	/// Whole milliseconds, for close-range autocannon work.  The simulation
+	/// steps 10 ms at a time, so the last digit is always 0.
+	#[serde(rename = "ms")]
+	Milliseconds,
+}
+
+impl FromStr for TimeUnit {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"s" => Ok(Self::Seconds),
+			"ms" => Ok(Self::Milliseconds),
+			_ => Err(format!("unknown time unit `{s}` (expected `s` or `ms`)")),
+		}
+	}
+}
+
+impl fmt::Display for TimeUnit {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::Seconds => "s",
+			Self::Milliseconds => "ms",
+		})
+	}
+}
+
+/// How penetration is rounded to whole millimetres (and to multiples of
+/// [`pen_round`](BallisticConfig::pen_round)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PenRounding {
+	/// To the nearest value, halves away from zero (legacy behaviour).
+	#[default]
+	Nearest,
+
+	/// Down, as some in-game penetration readouts display it.
+	Floor,
+
+	/// Up.
+	Ceil,
+}
+
+impl PenRounding {
+	/// Round `x` to an integer in this mode.
+	#[must_use]
+	pub fn apply(self, x: f64) -> f64 {
+		match self {
+			Self::Nearest => x.round(),
+			Self::Floor => x.floor(),
+			Self::Ceil => x.ceil(),
+		}
+	}
+}
+
+impl FromStr for PenRounding {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"nearest" => Ok(Self::Nearest),
+			"floor" => Ok(Self::Floor),
+			"ceil" => Ok(Self::Ceil),
+			_ => Err(format!(
+				"unknown penetration rounding `{s}` (expected `nearest`, `floor` or `ceil`)"
+			)),
+		}
+	}
+}
+
+impl fmt::Display for PenRounding {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::Nearest => "nearest",
+			Self::Floor => "floor",
+			Self::Ceil => "ceil",
+		})
+	}
+}
+
+/// Armor that effective penetration is measured against.
+///
+/// Base tables are always in RHA; this only scales derived effective
+/// penetration figures, by approximate resistances relative to RHA (the
+/// armor effectiveness values War Thunder uses for these steels).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TargetMaterial {
+	/// Rolled homogeneous armor, the reference (1.0).
+	#[default]
+	Rha,
+
+	/// Cast armor (0.94 of RHA).
+	Cast,
+
+	/// Structural steel (0.45 of RHA).
+	Structural,
+}
+
+impl TargetMaterial {
+	/// Resistance relative to RHA.
+	#[must_use]
+	pub fn resistance(self) -> f64 {
+		match self {
+			Self::Rha => 1.0,
+			Self::Cast => 0.94,
+			Self::Structural => 0.45,
+		}
+	}
+
+	/// Thickness in mm of this material defeated by `rha_mm` of RHA
+	/// penetration.
+	#[must_use]
+	pub fn effective_penetration(self, rha_mm: f64) -> f64 {
+		rha_mm / self.resistance()
+	}
+}
+
+impl FromStr for TargetMaterial {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"rha" => Ok(Self::Rha),
+			"cast" => Ok(Self::Cast),
+			"structural" => Ok(Self::Structural),
+			_ => Err(format!(
+				"unknown target material `{s}` (expected `rha`, `cast` or `structural`)"
+			)),
+		}
+	}
+}
+
+impl fmt::Display for TargetMaterial {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::Rha => "rha",
+			Self::Cast => "cast",
+			Self::Structural => "structural",
+		})
+	}
+}
+
+/// Unit of the launch angles of an elevation table
+/// ([`compute_elevation_table`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum AngleUnit {
+	/// NATO mils, 6400 per circle.  About 2% smaller than the milliradians
+	/// of the scroll step: one 2.8·s² mrad step is 2.852·s² mils.
+	#[default]
+	#[serde(rename = "mils")]
+	Mils,
+
+	/// Degrees.
+	#[serde(rename = "deg")]
+	Degrees,
+}
+
+impl AngleUnit {
+	/// `angle` (rad) in this unit.
+	#[must_use]
+	pub fn from_radians(self, angle: f64) -> f64 {
+		match self {
+			Self::Mils => angle * 3200.0 / PI,
+			Self::Degrees => angle.to_degrees(),
+		}
+	}
+}
+
+impl FromStr for AngleUnit {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"mils" => Ok(Self::Mils),
+			"deg" => Ok(Self::Degrees),
+			_ => Err(format!("unknown angle unit `{s}` (expected `mils` or `deg`)")),
+		}
+	}
+}
+
+impl fmt::Display for AngleUnit {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::Mils => "mils",
+			Self::Degrees => "deg",
+		})
+	}
+}
+
+/// Parameters controlling a ballistic computation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BallisticConfig {
+	/// Mouse sensitivity (0 < s ≤ 1).  Only used by [`RangeSampling::ScrollStep`],
+	/// whose launch-angle step is 2.8·s² mrad: 0.5 gives ~1500 rows per table,
+	/// 1.0 ~375, and anything above 1 is coarser still.
+	pub sensitivity: f64,
+
+	/// Row placement strategy.
+	pub sampling: RangeSampling,
+
+	/// Upper bound on scroll-step launch angles simulated per shell.  At very
+	/// low sensitivities the uncapped count runs into the millions, each a
+	/// full trajectory, so a slow shell could stall a whole batch.
+	pub max_entries_cap: usize,
+
+	/// Gravitational acceleration (m/s²) acting on the projectile.  Only the
+	/// trajectory uses it; the standard atmosphere is unaffected.
+	pub gravity: f64,
+
+	/// Unit of the penetration column.
+	pub pen_unit: PenetrationUnit,
+
+	/// Round the penetration column to the nearest multiple of this many
+	/// millimetres (before any [`pen_unit`](Self::pen_unit) conversion), for
+	/// cleaner sight labels; 1 keeps whole millimetres.  Display only: the
+	/// simulation is unaffected, but the formatted table, and so the cache
+	/// key, is not.
+	pub pen_round: u32,
+
+	/// Rounding of penetration values, both to whole millimetres and to
+	/// [`pen_round`](Self::pen_round) multiples.  Like `pen_round`, part of
+	/// the cache key.
+	pub pen_rounding: PenRounding,
+
+	/// Keep the first row, the 0 m shot at the muzzle.  It anchors sights
+	/// that place marks relative to the barrel, but is a degenerate entry
+	/// for consumers that only look at ranges downrange.
+	pub include_muzzle_row: bool,
+
+	/// Estimate the penetration of APDS-FS rounds without an armor power
+	/// series from their mass, speed and caliber instead of writing zeros;
+	/// see [`estimates_penetration`].
+	pub estimate_missing_pen: bool,
+
+	/// Write `distance\ttime` rows without the penetration column, for
+	/// flight-time and drop analysis.  No penetration model is consulted, so
+	/// every shell that is computed at all gets a full trajectory, whatever
+	/// its type.
+	pub trajectory_only: bool,
+
+	/// Unit of the time column.  Display only, like
+	/// [`pen_unit`](Self::pen_unit).
+	pub time_unit: TimeUnit,
+
+	/// Decimal places of the distance column.  Display only, like
+	/// [`pen_unit`](Self::pen_unit); fewer than the default shrink the
+	/// output of large runs.
+	pub distance_precision: usize,
+
+	/// Range (m) at which a table ends: the scroll-step sweep stops after the
+	/// first row beyond it, fixed-step sampling at the last multiple within
+	/// it.  Above [`DEFAULT_MAX_RANGE`](Self::DEFAULT_MAX_RANGE) a density
+	/// table reaching [`NAVAL_MAX_RANGE`](Self::NAVAL_MAX_RANGE) in altitude
+	/// is used.
+	pub max_range: f64,
+
+	/// Height (m) of the optic above the bore axis.  Elevation tables
+	/// ([`compute_elevation_table`]) then give the angle between the bore and
+	/// the line of sight to a target at launch height, instead of the launch
+	/// angle: `atan(h / d)` more at `d` metres, large at short range and
+	/// vanishing far out.  The distance, time and penetration columns, and so
+	/// the cache key, are unaffected.
+	pub sight_height_m: f64,
+}
+
+impl BallisticConfig {
+	/// Default [`max_entries_cap`](Self::max_entries_cap).  Well above the
+	/// ~1500 entries of the default 0.50 sensitivity.
+	pub const DEFAULT_MAX_ENTRIES_CAP: usize = 10_000;
+
+	/// Default [`gravity`](Self::gravity): standard gravity, as in game.
+	pub const STANDARD_GRAVITY: f64 = G;
+
+	/// Default [`max_range`](Self::max_range), enough for direct-fire tank
+	/// engagements.
+	pub const DEFAULT_MAX_RANGE: f64 = 4500.0;
+
+	/// Default [`distance_precision`](Self::distance_precision), as in the
+	/// reference tables.
+	pub const DEFAULT_DISTANCE_PRECISION: usize = 3;
+
+	/// [`max_range`](Self::max_range) of the naval preset, for ship guns
+	/// that engage far beyond tank ranges.
+	pub const NAVAL_MAX_RANGE: f64 = 20_000.0;
+
+	/// Scroll-step configuration for the given sensitivity.
+	#[must_use]
+	pub fn new(sensitivity: f64) -> Self {
+		Self {
+			sensitivity,
+			sampling: RangeSampling::ScrollStep,
+			max_entries_cap: Self::DEFAULT_MAX_ENTRIES_CAP,
+			gravity: Self::STANDARD_GRAVITY,
+			pen_unit: PenetrationUnit::Millimetres,
+			pen_round: 1,
+			pen_rounding: PenRounding::Nearest,
+			include_muzzle_row: true,
+			estimate_missing_pen: false,
+			trajectory_only: false,
+			time_unit: TimeUnit::Seconds,
+			distance_precision: Self::DEFAULT_DISTANCE_PRECISION,
+			max_range: Self::DEFAULT_MAX_RANGE,
+			sight_height_m: 0.0,
+		}
+	}
+
+	/// This configuration with the naval preset: tables run to
+	/// [`NAVAL_MAX_RANGE`](Self::NAVAL_MAX_RANGE).
+	#[must_use]
+	pub fn naval(self) -> Self {
+		Self {
+			max_range: Self::NAVAL_MAX_RANGE,
+			..self
+		}
+	}
+
+	/// Number of scroll-step launch angles covering the 60° sweep, before
+	/// [`max_entries_cap`](Self::max_entries_cap) is applied.
+	#[must_use]
+	#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+	pub fn scroll_entries(&self) -> usize {
+		(PI / 180.0 * 60.0 * 1000.0 / self.scroll_step()).floor() as usize
+	}
+
+	/// Launch-angle increment in milliradians.
+	fn scroll_step(&self) -> f64 {
+		2.8 * self.sensitivity * self.sensitivity
+	}
+
+	/// Whether the cap cuts the scroll-step sweep short.
+	fn caps_scroll_entries(&self) -> bool {
+		self.sampling == RangeSampling::ScrollStep && self.max_entries_cap < self.scroll_entries()
+	}
+}
+
+impl Default for BallisticConfig {
+	fn default() -> Self {
+		Self::new(0.50)
+	}
+}
+
 // ── Ballistic cache key ────────────────────────────────────────────────────
 
 /// Bit-exact wrapper for `f64` that implements `Hash` and `Eq` via `to_bits()`.
@@ -99,7 +613,7 @@ pub fn should_skip(normalized_type: &str) -> bool {
 /// This is intentional: we want cache hits only when inputs are bit-identical,
 /// since even tiny differences in drag or mass can compound over thousands of
 /// Euler steps.
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 struct F64Key(u64);
 
 impl F64Key {
@@ -115,12 +629,16 @@ impl Hash for F64Key {
 }
 
 /// Cache key capturing every `DataProjectile` field that influences
-/// `compute_ballistic` output, plus the `sensitivity` parameter.
+/// `compute_ballistic` output, plus the [`BallisticConfig`] parameters.
 ///
-/// Fields that are purely metadata (`name`, `bullet_type`, `output_name`)
-/// are excluded — two shells with different names but identical physics
-/// produce identical trajectories and can share a cached result.
-#[derive(Clone, PartialEq, Eq, Hash)]
+/// Fields that are purely metadata (`name`, `bullet_type`, `explosive_type`,
+/// `blast_radius`, the fuze fields, `output_name`) are excluded — two shells with different
+/// names but identical physics produce identical trajectories and can share a
+/// cached result.
+///
+/// The entries cap is only part of the key when it is below the sweep length
+/// for the configured sensitivity; otherwise it cannot change the output.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct BallisticKey {
 	normalized_type: String,
 	mass: F64Key,
@@ -136,12 +654,30 @@ pub struct BallisticKey {
 	demarre_caliber_pow: F64Key,
 	armor_power_table: Vec<(F64Key, F64Key)>,
 	sensitivity: F64Key,
+	fixed_step: Option<F64Key>,
+	entries_cap: Option<usize>,
+	gravity: F64Key,
+	pen_unit: PenetrationUnit,
+	pen_round: u32,
+	pen_rounding: PenRounding,
+	include_muzzle_row: bool,
+	estimate_missing_pen: bool,
+	trajectory_only: bool,
+	time_unit: TimeUnit,
+	distance_precision: usize,
+	max_range: F64Key,
 }
 
 impl BallisticKey {
 	/// Build a cache key from a projectile and the sensitivity parameter.
 	#[must_use]
 	pub fn new(proj: &DataProjectile, sensitivity: f64) -> Self {
+		Self::with_config(proj, &BallisticConfig::new(sensitivity))
+	}
+
+	/// Build a cache key from a projectile and a full [`BallisticConfig`].
+	#[must_use]
+	pub fn with_config(proj: &DataProjectile, cfg: &BallisticConfig) -> Self {
 		Self {
 			normalized_type: proj.normalized_type.clone(),
 			mass: F64Key::new(proj.mass),
@@ -160,7 +696,22 @@ impl BallisticKey {
 				.iter()
 				.map(|&(d, p)| (F64Key::new(d), F64Key::new(p)))
 				.collect(),
-			sensitivity: F64Key::new(sensitivity),
+			sensitivity: F64Key::new(cfg.sensitivity),
+			fixed_step: match cfg.sampling {
+				RangeSampling::ScrollStep => None,
+				RangeSampling::FixedStep(step) => Some(F64Key::new(step)),
+			},
+			entries_cap: cfg.caps_scroll_entries().then_some(cfg.max_entries_cap),
+			gravity: F64Key::new(cfg.gravity),
+			pen_unit: cfg.pen_unit,
+			pen_round: cfg.pen_round,
+			pen_rounding: cfg.pen_rounding,
+			include_muzzle_row: cfg.include_muzzle_row,
+			estimate_missing_pen: cfg.estimate_missing_pen,
+			trajectory_only: cfg.trajectory_only,
+			time_unit: cfg.time_unit,
+			distance_precision: cfg.distance_precision,
+			max_range: F64Key::new(cfg.max_range),
 		}
 	}
 }
@@ -176,6 +727,78 @@ impl BallisticKey {
 /// writers, which is important given the 80% cache-hit rate.
 pub type BallisticCache = DashMap<BallisticKey, Option<String>>;
 
+/// On-disk form of a [`BallisticCache`], written by [`write_cache`].
+///
+/// Tables depend on the simulation code as well as the key, so a file is
+/// only accepted by [`seed_cache`] from the same fcsgen version.
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+	version: String,
+	entries: Vec<(BallisticKey, Option<String>)>,
+}
+
+/// Serialize every entry of `cache` to `path` as JSON.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written.
+pub fn write_cache(cache: &BallisticCache, path: &Path) -> std::io::Result<()> {
+	let mut writer = BufWriter::new(std::fs::File::create(path)?);
+	write_cache_to(cache, &mut writer)?;
+	writer.flush()
+}
+
+/// Serialize every entry of `cache` as JSON to `writer`, e.g. a compressing
+/// stream; see [`write_cache`].
+///
+/// # Errors
+///
+/// Returns an error if writing fails.
+pub fn write_cache_to(cache: &BallisticCache, writer: impl io::Write) -> std::io::Result<()> {
+	let file = CacheFile {
+		version: crate::VERSION.to_owned(),
+		entries: cache
+			.iter()
+			.map(|entry| (entry.key().clone(), entry.value().clone()))
+			.collect(),
+	};
+	serde_json::to_writer(writer, &file).map_err(std::io::Error::other)
+}
+
+/// Insert the entries of a file written by [`write_cache`] into `cache`,
+/// returning how many were read.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or parsed, or was written by
+/// a different fcsgen version.
+pub fn seed_cache(cache: &BallisticCache, path: &Path) -> std::io::Result<usize> {
+	seed_cache_from_reader(cache, BufReader::new(std::fs::File::open(path)?))
+}
+
+/// [`seed_cache`] from the JSON read from `reader`, e.g. a decompressing
+/// stream.
+///
+/// # Errors
+///
+/// Returns an error if the data cannot be read or parsed, or was written by
+/// a different fcsgen version.
+pub fn seed_cache_from_reader(cache: &BallisticCache, reader: impl Read) -> std::io::Result<usize> {
+	let file: CacheFile = serde_json::from_reader(reader)?;
+	if file.version != crate::VERSION {
+		return Err(std::io::Error::other(format!(
+			"written by fcsgen {}, this is {}",
+			file.version,
+			crate::VERSION,
+		)));
+	}
+	let count = file.entries.len();
+	for (key, table) in file.entries {
+		cache.insert(key, table);
+	}
+	Ok(count)
+}
+
 /// Compute the ballistic table for a projectile, using a shared cache to
 /// avoid redundant simulations.
 ///
@@ -193,11 +816,33 @@ pub fn compute_ballistic_cached(
 	sensitivity: f64,
 	cache: &BallisticCache,
 ) -> (Option<String>, bool) {
-	let key = BallisticKey::new(proj, sensitivity);
+	compute_ballistic_cached_with_config(proj, &BallisticConfig::new(sensitivity), cache)
+}
+
+/// Same as [`compute_ballistic_cached`] but with a full [`BallisticConfig`].
+#[must_use]
+pub fn compute_ballistic_cached_with_config(
+	proj: &DataProjectile,
+	cfg: &BallisticConfig,
+	cache: &BallisticCache,
+) -> (Option<String>, bool) {
+	compute_ballistic_cached_with_warnings(proj, cfg, cache, &mut Vec::new())
+}
+
+/// Same as [`compute_ballistic_cached_with_config`], pushing the warnings of
+/// a computed table onto `warnings`.  A cache hit has none: they were
+/// reported when the table was first computed.
+pub fn compute_ballistic_cached_with_warnings(
+	proj: &DataProjectile,
+	cfg: &BallisticConfig,
+	cache: &BallisticCache,
+	warnings: &mut Vec<BallisticWarning>,
+) -> (Option<String>, bool) {
+	let key = BallisticKey::with_config(proj, cfg);
 	if let Some(cached) = cache.get(&key) {
 		return (cached.clone(), true);
 	}
-	let result = compute_ballistic(proj, sensitivity);
+	let result = compute_ballistic_with_warnings(proj, cfg, warnings);
 	cache.insert(key, result.clone());
 	(result, false)
 }
@@ -207,79 +852,566 @@ pub fn compute_ballistic_cached(
 /// Returns the TSV-formatted output string (`distance\ttime\tpenetration\n`
 /// per line), or `None` if the projectile type is skipped.
 #[must_use]
-#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_precision_loss)]
 pub fn compute_ballistic(proj: &DataProjectile, sensitivity: f64) -> Option<String> {
-	if should_skip(&proj.normalized_type) || sensitivity <= 0.0 {
+	compute_ballistic_with_config(proj, &BallisticConfig::new(sensitivity))
+}
+
+/// Compute the ballistic table for a single projectile with a full
+/// [`BallisticConfig`].
+///
+/// Returns `None` if the projectile type is skipped or the sampling
+/// parameters are out of range (non-positive sensitivity or step).
+#[must_use]
+pub fn compute_ballistic_with_config(proj: &DataProjectile, cfg: &BallisticConfig) -> Option<String> {
+	compute_ballistic_with_warnings(proj, cfg, &mut Vec::new())
+}
+
+/// Same as [`compute_ballistic_with_config`], pushing a
+/// [`BallisticWarning`] onto `warnings` for anything the caller should
+/// report: a shell skipped for lack of a caliber, a penetration fallback, or
+/// a truncated table.
+pub fn compute_ballistic_with_warnings(
+	proj: &DataProjectile,
+	cfg: &BallisticConfig,
+	warnings: &mut Vec<BallisticWarning>,
+) -> Option<String> {
+	let mut out = String::new();
+	for row in &table_rows(proj, cfg, warnings)? {
+		write_row(&mut out, row, cfg);
+	}
+	Some(out)
+}
+
+/// Launch angle (rad) of every row of the ballistic table, as
+/// `(distance, angle)` pairs, measured from the line of sight when
+/// [`BallisticConfig::sight_height_m`] is set.
+///
+/// The rows are those of [`compute_ballistic_with_config`] with the same
+/// `cfg`; format them with [`write_elevation_table`].  Returns `None` under
+/// the same conditions.  The 0 m row has no line of sight to a target and
+/// keeps its launch angle.
+#[must_use]
+pub fn compute_elevation_table(proj: &DataProjectile, cfg: &BallisticConfig) -> Option<Vec<(f64, f64)>> {
+	let rows = table_rows(proj, cfg, &mut Vec::new())?;
+	let sight_offset = |distance: f64| {
+		if distance > 0.0 { (cfg.sight_height_m / distance).atan() } else { 0.0 }
+	};
+	Some(rows.iter().map(|row| (row.distance, row.angle + sight_offset(row.distance))).collect())
+}
+
+/// The rows of a ballistic table, before formatting.
+///
+/// The muzzle row is dropped last, after the scroll-step monotonicity
+/// guard, so [`BallisticConfig::include_muzzle_row`] never changes which
+/// downrange rows are kept.
+fn table_rows(
+	proj: &DataProjectile,
+	cfg: &BallisticConfig,
+	warnings: &mut Vec<BallisticWarning>,
+) -> Option<Vec<Row>> {
+	if !is_computed(proj, warnings) {
+		return None;
+	}
+
+	let trajectory = Trajectory::new(proj, cfg);
+	let penetration = PenetrationModel::new(proj, cfg, warnings);
+
+	let rows = match cfg.sampling {
+		RangeSampling::ScrollStep => scroll_step_rows(&trajectory, &penetration, cfg, warnings)?,
+		RangeSampling::FixedStep(step) => {
+			fixed_step_rows(&trajectory, &penetration, step, cfg.max_range, &[])?
+		},
+	};
+	Some(drop_muzzle_row(rows, cfg))
+}
+
+/// Scroll-step and fixed-step ballistic tables of one projectile, from one
+/// shared simulation (`--sampling both`).
+///
+/// The fixed-step solver starts each range from the scroll-step shot just
+/// short of it instead of walking up from the previous range.  Both tables
+/// are formatted as [`compute_ballistic_with_config`] would with `cfg` and
+/// the respective [`RangeSampling`]; `cfg.sampling` itself is ignored.
+/// Returns `None` if either table would be.  Warnings are pushed onto
+/// `warnings` as by [`compute_ballistic_with_warnings`].
+pub fn compute_ballistic_both(
+	proj: &DataProjectile,
+	cfg: &BallisticConfig,
+	step: f64,
+	warnings: &mut Vec<BallisticWarning>,
+) -> Option<(String, String)> {
+	if !is_computed(proj, warnings) {
 		return None;
 	}
 
-	// DeMarre parameters with defaults applied
-	let k = non_zero_or(proj.demarre_k, DEFAULT_K);
-	let speed_pow = non_zero_or(proj.demarre_speed_pow, DEFAULT_SPEED_POW);
-	let mass_pow = non_zero_or(proj.demarre_mass_pow, DEFAULT_MASS_POW);
-	let caliber_pow = non_zero_or(proj.demarre_caliber_pow, DEFAULT_CALIBER_POW);
+	let trajectory = Trajectory::new(proj, cfg);
+	let penetration = PenetrationModel::new(proj, cfg, warnings);
+	let scroll = scroll_step_rows(&trajectory, &penetration, cfg, warnings)?;
+	let fixed = fixed_step_rows(&trajectory, &penetration, step, cfg.max_range, &scroll)?;
+
+	let format = |rows: Vec<Row>| {
+		let mut out = String::new();
+		for row in &drop_muzzle_row(rows, cfg) {
+			write_row(&mut out, row, cfg);
+		}
+		out
+	};
+	Some((format(scroll), format(fixed)))
+}
 
-	let scroll_step = 2.8 * sensitivity * sensitivity;
-	let max_entries = (PI / 180.0 * 60.0 * 1000.0 / scroll_step).floor() as usize;
+/// The rows of a scroll-step table, muzzle row included, or `None` for a
+/// non-positive sensitivity.
+fn scroll_step_rows(
+	trajectory: &Trajectory<'_>,
+	penetration: &PenetrationModel<'_>,
+	cfg: &BallisticConfig,
+	warnings: &mut Vec<BallisticWarning>,
+) -> Option<Vec<Row>> {
+	if cfg.sensitivity <= 0.0 {
+		return None;
+	}
+	let (mut rows, truncated) = sample_scroll_step(trajectory, penetration, cfg);
+	if truncated {
+		warnings.push(BallisticWarning::Truncated {
+			max_entries_cap: cfg.max_entries_cap,
+		});
+	}
 
-	// ── Precomputed constants (hoisted out of inner loop) ──────────────
-	//
-	// Sea-level air density: ρ₀ = P · M / (R · T)   (used only in fallback)
-	let rho_base = P_ATM * M_AIR / R_GAS / (T_GROUND + 273.15);
-	let baro_exp = G * M_AIR / R_GAS / LAPSE_RATE - 1.0;
+	// Keep every row except the last, stopping early on a distance
+	// decrease (monotonicity guard, matches C# output loop).
+	let kept = rows
+		.windows(2)
+		.position(|pair| pair[1].distance < pair[0].distance)
+		.unwrap_or(rows.len().saturating_sub(1));
+	rows.truncate(kept);
+	Some(rows)
+}
+
+/// The rows of a fixed-step table, muzzle row included, or `None` for a
+/// non-positive step.  `seeds` are rows of increasing distance whose angles
+/// the solver may start from (see [`sample_fixed_step`]).
+fn fixed_step_rows(
+	trajectory: &Trajectory<'_>,
+	penetration: &PenetrationModel<'_>,
+	step: f64,
+	max_range: f64,
+	seeds: &[Row],
+) -> Option<Vec<Row>> {
+	if step <= 0.0 || !step.is_finite() {
+		return None;
+	}
+	Some(sample_fixed_step(trajectory, penetration, step, max_range, seeds))
+}
+
+/// Drop the first row unless [`BallisticConfig::include_muzzle_row`]: both
+/// samplings start with the 0 m shot (launch angle 0, or the 0 m grid point).
+fn drop_muzzle_row(mut rows: Vec<Row>, cfg: &BallisticConfig) -> Vec<Row> {
+	if !cfg.include_muzzle_row && !rows.is_empty() {
+		rows.remove(0);
+	}
+	rows
+}
 
-	// Reference the pre-baked density table (initialized once on first use).
-	let density = &*DENSITY_TABLE;
+/// Distance (m) and penetration (mm) where the flattest scroll-step shot
+/// lands: the first launch angle above zero (`i = 1`), i.e. the second row of
+/// the full table (the first is the 0 m row).
+///
+/// A single trajectory instead of a whole sweep, for overviews of a shell's
+/// direct-fire reach.  Returns `None` under the same conditions as
+/// [`compute_ballistic_with_config`] with scroll-step sampling; the
+/// configured [`RangeSampling`] and [`PenetrationUnit`] are ignored.
+#[must_use]
+pub fn compute_max_direct_range(proj: &DataProjectile, cfg: &BallisticConfig) -> Option<(f64, f64)> {
+	// Overview only: the warnings are those of the full table
+	let mut warnings = Vec::new();
+	if !is_computed(proj, &mut warnings) || cfg.sensitivity <= 0.0 {
+		return None;
+	}
 
-	// Drag geometry factor: Cx · π · d² / 8  (cross-section area × Cx)
-	// divided by mass to get acceleration per unit (ρ · v²).
-	let drag_k = proj.cx * PI * proj.ballistic_caliber * proj.ballistic_caliber
-		/ 8.0 / proj.mass;
+	let trajectory = Trajectory::new(proj, cfg);
+	let penetration = PenetrationModel::new(proj, cfg, &mut warnings);
+	let row = trajectory.fire(cfg.scroll_step() / 1000.0).to_row(&penetration);
+	Some((row.distance, row.penetration))
+}
 
-	let ntype = proj.normalized_type.as_str();
-	let is_ap = AP_TYPES.contains(&ntype);
-	let is_aphe = APHE_TYPES.contains(&ntype);
-	let is_subcaliber = ntype == "apcr" || ntype == "apds";
-	let is_apds_fs = ntype == "apds_fs";
+/// Sweep launch angles by the sensitivity-derived scroll step.
+///
+/// Returns the rows and whether the sweep was stopped by
+/// [`BallisticConfig::max_entries_cap`] before reaching
+/// [`BallisticConfig::max_range`].
+#[allow(clippy::cast_precision_loss)]
+fn sample_scroll_step(
+	trajectory: &Trajectory<'_>,
+	penetration: &PenetrationModel<'_>,
+	cfg: &BallisticConfig,
+) -> (Vec<Row>, bool) {
+	let scroll_step = cfg.scroll_step();
+	let max_entries = cfg.scroll_entries();
+	let capped_entries = max_entries.min(cfg.max_entries_cap);
 
-	let mut rows: Vec<Row> = Vec::with_capacity(max_entries.min(512));
+	let mut rows: Vec<Row> = Vec::with_capacity(capped_entries.min(512));
 	let mut last_distance = 0.0_f64;
 
-	for i in 0..max_entries {
-		if last_distance >= MAX_RANGE {
-			break;
+	for i in 0..capped_entries {
+		if last_distance >= cfg.max_range {
+			return (rows, false);
 		}
 
 		let angle = scroll_step * (i as f64) / 1000.0;
-		let mut vx = proj.speed * angle.cos();
-		let mut vy = proj.speed * angle.sin();
-		let (mut x, mut y, mut t) = (0.0_f64, 0.0_f64, 0.0_f64);
-		let (mut x0, mut y0) = (0.0_f64, 0.0_f64);
+		let impact = trajectory.fire(angle);
+		last_distance = impact.distance;
+		rows.push(impact.to_row(penetration));
+	}
 
-		while y >= 0.0 {
-			// Atmospheric density via precomputed lookup table with
-			// linear interpolation.  Falls back to powf for extreme
-			// altitudes beyond the table range (> 500 m).
-			let ro = {
-				let idx_f = y / DENSITY_STEP;
-				let idx = idx_f as usize;
-				if idx + 1 < density.len() {
-					let frac = idx_f - idx as f64;
-					density[idx] + frac * (density[idx + 1] - density[idx])
-				} else {
-					rho_base * (1.0 - LAPSE_RATE * y / T_STD).powf(baro_exp)
-				}
-			};
+	let truncated = capped_entries < max_entries && last_distance < cfg.max_range;
+	(rows, truncated)
+}
 
-			let v_sq = vx * vx + vy * vy;
-			let accel = drag_k * ro * v_sq;
+/// Emit one row per multiple of `step` up to `max_range`, solving for the
+/// launch angle that lands at each range.  Stops at the first range the
+/// projectile cannot reach.
+///
+/// The solve for a range starts from the steeper of the previous solution
+/// and the last of `seeds` (e.g. a scroll-step sweep) landing short of it.
+fn sample_fixed_step(
+	trajectory: &Trajectory<'_>,
+	penetration: &PenetrationModel<'_>,
+	step: f64,
+	max_range: f64,
+	seeds: &[Row],
+) -> Vec<Row> {
+	let mut rows = Vec::new();
+	// Range grows with angle up to the max-range angle, so each solve can
+	// start from the previous solution.
+	let mut lo = 0.0_f64;
 
-			// Decompose drag into x/y components using algebraic
-			// identities instead of transcendental functions:
-			//   cos(atan(vy/vx)) = vx / √(vx²+vy²)
-			//   sin(atan(vy/vx)) = vy / √(vx²+vy²)
-			//
-			// NOTE: vx is updated *first*; the vy update sees the new
+	for n in 0_u32.. {
+		let target = step * f64::from(n);
+		if target > max_range {
+			break;
+		}
+		let short = seeds.partition_point(|row| row.distance <= target);
+		if let Some(seed) = short.checked_sub(1).map(|i| &seeds[i]) {
+			lo = lo.max(seed.angle);
+		}
+		let Some((angle, impact)) = solve_angle(trajectory, target, lo) else {
+			break;
+		};
+		lo = angle;
+		rows.push(impact.to_row(penetration));
+	}
+
+	rows
+}
+
+/// Find the launch angle (≥ `lo`) whose trajectory lands at `target` metres.
+///
+/// Brackets the target by walking the angle upwards, then bisects.  Returns
+/// `None` if the target lies beyond the projectile's maximum range.
+fn solve_angle(trajectory: &Trajectory<'_>, target: f64, lo: f64) -> Option<(f64, Impact)> {
+	let mut lo = lo;
+	let mut lo_impact = trajectory.fire(lo);
+	if (lo_impact.distance - target).abs() <= FIXED_STEP_TOL {
+		return Some((lo, lo_impact));
+	}
+
+	// Bracket: [lo, hi] with d(lo) < target ≤ d(hi).  When a step overshoots
+	// the max-range angle (distance drops), retry with a smaller step.
+	let mut bracket_step = ANGLE_BRACKET_STEP;
+	let (mut hi, mut hi_impact) = loop {
+		let next = lo + bracket_step;
+		if next > MAX_ANGLE {
+			return None;
+		}
+		let impact = trajectory.fire(next);
+		if impact.distance < lo_impact.distance {
+			bracket_step /= 2.0;
+			if bracket_step < 1e-9 {
+				return None;
+			}
+			continue;
+		}
+		if impact.distance >= target {
+			break (next, impact);
+		}
+		lo = next;
+		lo_impact = impact;
+	};
+
+	for _ in 0..MAX_BISECT_ITERS {
+		if (hi_impact.distance - target).abs() <= FIXED_STEP_TOL {
+			break;
+		}
+		let mid = 0.5 * (lo + hi);
+		let impact = trajectory.fire(mid);
+		if impact.distance < target {
+			lo = mid;
+		} else {
+			hi = mid;
+			hi_impact = impact;
+		}
+	}
+
+	Some((hi, hi_impact))
+}
+
+// ── Optional table header ──────────────────────────────────────────────────
+
+/// Leading character of the optional ballistic table header line.
+pub const HEADER_PREFIX: char = '#';
+
+/// Build a one-line header describing the shell and sampling parameters of
+/// a ballistic table, e.g.
+/// `# shell=m735\ttype=apds_fs\tcaliber=0.105\tspeed=1501\tsensitivity=0.5`,
+/// plus `gravity=`, `pen_unit=`, `pen_round=`, `pen_rounding=`, `time_unit=`,
+/// `distance_precision=`, `muzzle_row=`, `max_range=` and `sight_height=`
+/// when they are not the defaults, `pen=estimated` for an [estimated](estimates_penetration)
+/// table and `pen=none` for a trajectory-only one.
+///
+/// Not part of the computed (and cached) table, since shells that share a
+/// trajectory can differ in name.  The legacy sight generator does not
+/// understand it, so it is opt-in.
+#[must_use]
+pub fn table_header(proj: &DataProjectile, cfg: &BallisticConfig) -> String {
+	let sampling = match cfg.sampling {
+		RangeSampling::ScrollStep => format!("sensitivity={}", cfg.sensitivity),
+		RangeSampling::FixedStep(step) => format!("step={step}"),
+	};
+	// Only non-default gravity and units are worth calling out
+	let mut extra = String::new();
+	if (cfg.gravity - G).abs() > f64::EPSILON {
+		write!(extra, "\tgravity={}", cfg.gravity).unwrap();
+	}
+	if cfg.pen_unit != PenetrationUnit::Millimetres {
+		write!(extra, "\tpen_unit={}", cfg.pen_unit).unwrap();
+	}
+	if cfg.pen_round != 1 {
+		write!(extra, "\tpen_round={}", cfg.pen_round).unwrap();
+	}
+	if cfg.pen_rounding != PenRounding::Nearest {
+		write!(extra, "\tpen_rounding={}", cfg.pen_rounding).unwrap();
+	}
+	if cfg.time_unit != TimeUnit::Seconds {
+		write!(extra, "\ttime_unit={}", cfg.time_unit).unwrap();
+	}
+	if cfg.distance_precision != BallisticConfig::DEFAULT_DISTANCE_PRECISION {
+		write!(extra, "\tdistance_precision={}", cfg.distance_precision).unwrap();
+	}
+	if !cfg.include_muzzle_row {
+		extra.push_str("\tmuzzle_row=false");
+	}
+	if (cfg.max_range - BallisticConfig::DEFAULT_MAX_RANGE).abs() > f64::EPSILON {
+		write!(extra, "\tmax_range={}", cfg.max_range).unwrap();
+	}
+	if cfg.sight_height_m.abs() > f64::EPSILON {
+		write!(extra, "\tsight_height={}", cfg.sight_height_m).unwrap();
+	}
+	if cfg.trajectory_only {
+		extra.push_str("\tpen=none");
+	} else if estimates_penetration(proj, cfg) {
+		extra.push_str("\tpen=estimated");
+	}
+	format!(
+		"{HEADER_PREFIX} shell={}\ttype={}\tcaliber={}\tspeed={}\t{sampling}{extra}\n",
+		proj.output_name, proj.normalized_type, proj.ballistic_caliber, proj.speed,
+	)
+}
+
+/// [`table_header`] plus a `marks=` field giving the launch angle each sight
+/// range mark needs, e.g. `marks=400:2.041,800:4.377`: ranges in m, angles
+/// in mils from [`drops_at_standard_ranges`].  Tells the sight generator
+/// which marks to draw and where.  One extra elevation sweep per shell;
+/// without `marks` this is [`table_header`].
+///
+/// Marks beyond the shell's reach (the last row of its table) cannot be
+/// placed, so they are left out; their count is returned with the header.
+#[must_use]
+pub fn table_header_with_marks(
+	proj: &DataProjectile,
+	cfg: &BallisticConfig,
+	marks: &[f64],
+) -> (String, usize) {
+	let mut header = table_header(proj, cfg);
+	if marks.is_empty() {
+		return (header, 0);
+	}
+	let rows = compute_elevation_table(proj, cfg).unwrap_or_default();
+	let reachable: Vec<String> = drops_at_standard_ranges(&rows, marks)
+		.into_iter()
+		.filter_map(|(range, drop)| Some(format!("{range}:{:.3}", drop?)))
+		.collect();
+	header.insert_str(header.len() - 1, &format!("\tmarks={}", reachable.join(",")));
+	(header, marks.len() - reachable.len())
+}
+
+/// Return `table` without its header line, if it has one.
+#[must_use]
+pub fn strip_table_header(table: &str) -> &str {
+	if table.starts_with(HEADER_PREFIX) {
+		table.split_once('\n').map_or("", |(_, rows)| rows)
+	} else {
+		table
+	}
+}
+
+/// Whether every row of `table` has zero penetration (HE, smoke, ...).
+///
+/// The header line, if any, is ignored; `∞` counts as non-zero.  An empty
+/// table is not considered zero-penetration.
+#[must_use]
+pub fn is_zero_penetration(table: &str) -> bool {
+	let mut rows = strip_table_header(table).lines().peekable();
+	rows.peek().is_some()
+		&& rows.all(|row| {
+			row.split('\t')
+				.nth(2)
+				.and_then(|pen| pen.parse::<f64>().ok())
+				.is_some_and(|pen| pen == 0.0)
+		})
+}
+
+/// Format an elevation table as `distance\tangle\n` lines, the angle in
+/// `unit` (three decimals for mils, four for degrees).
+#[must_use]
+pub fn write_elevation_table(rows: &[(f64, f64)], unit: AngleUnit) -> String {
+	rows.iter()
+		.map(|&(distance, angle)| {
+			let angle = unit.from_radians(angle);
+			match unit {
+				AngleUnit::Mils => format!("{distance:.3}\t{angle:.3}\n"),
+				AngleUnit::Degrees => format!("{distance:.3}\t{angle:.4}\n"),
+			}
+		})
+		.collect()
+}
+
+/// Append a single TSV row to `out`, without the penetration column for
+/// [`BallisticConfig::trajectory_only`].
+fn write_row(out: &mut String, row: &Row, cfg: &BallisticConfig) {
+	let precision = cfg.distance_precision;
+	write!(out, "{:.precision$}\t{}", row.distance, fmt_time(row.time, cfg.time_unit)).unwrap();
+	if !cfg.trajectory_only {
+		let pen = fmt_penetration(row.penetration, cfg.pen_unit, cfg.pen_round, cfg.pen_rounding);
+		write!(out, "\t{pen}").unwrap();
+	}
+	out.push('\n');
+}
+
+// ── Trajectory simulation ──────────────────────────────────────────────────
+
+/// Ground impact of a single simulated shot.
+struct Impact {
+	/// Launch angle (rad).
+	angle: f64,
+	/// Interpolated ground-crossing distance (m).
+	distance: f64,
+	/// Time of flight (s), at the end of the step that crossed the ground.
+	time: f64,
+	/// Speed at impact (m/s).
+	speed: f64,
+}
+
+impl Impact {
+	fn to_row(&self, penetration: &PenetrationModel<'_>) -> Row {
+		Row {
+			angle: self.angle,
+			distance: self.distance,
+			time: self.time,
+			penetration: penetration.at(self.speed, self.distance),
+		}
+	}
+}
+
+/// Per-projectile constants for the Euler trajectory integration.
+struct Trajectory<'a> {
+	speed: f64,
+	gravity: f64,
+	/// Drag geometry factor: Cx · π · d² / 8 / mass.
+	drag_k: f64,
+	rho_base: f64,
+	baro_exp: f64,
+	density: &'a [f64],
+	/// Altitude step (m) between `density` entries.
+	density_step: f64,
+}
+
+impl Trajectory<'_> {
+	fn new(proj: &DataProjectile, cfg: &BallisticConfig) -> Self {
+		// ── Precomputed constants (hoisted out of inner loop) ──────────────
+		//
+		// Sea-level air density: ρ₀ = P · M / (R · T)   (used only in fallback)
+		let rho_base = P_ATM * M_AIR / R_GAS / (T_GROUND + 273.15);
+		let baro_exp = G * M_AIR / R_GAS / LAPSE_RATE - 1.0;
+
+		// Drag geometry factor: Cx · π · d² / 8  (cross-section area × Cx)
+		// divided by mass to get acceleration per unit (ρ · v²).
+		let drag_k = proj.cx * PI * proj.ballistic_caliber * proj.ballistic_caliber
+			/ 8.0 / proj.mass;
+
+		// Reference a pre-baked density table (initialized once on first use);
+		// long-range configurations need the one reaching higher.
+		let (density, density_step) = if cfg.max_range > BallisticConfig::DEFAULT_MAX_RANGE {
+			(&*HIGH_DENSITY_TABLE, HIGH_DENSITY_STEP)
+		} else {
+			(&*DENSITY_TABLE, DENSITY_STEP)
+		};
+
+		Self {
+			speed: proj.speed,
+			gravity: cfg.gravity,
+			drag_k,
+			rho_base,
+			baro_exp,
+			density,
+			density_step,
+		}
+	}
+
+	/// Air density (kg/m³) at altitude `y` m above the launch point.
+	///
+	/// Uses the precomputed lookup table with linear interpolation, falling
+	/// back to `powf` for extreme altitudes beyond the table range (> 500 m,
+	/// or the max range for long-range configurations), clamped at vacuum
+	/// (see [`barometric_density`]).  Altitudes below launch height (`y < 0`,
+	/// e.g. just before the ground crossing) get the base density rather than
+	/// indexing off the table.
+	#[inline]
+	#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_precision_loss)]
+	fn air_density(&self, y: f64) -> f64 {
+		let y = y.max(0.0);
+		let idx_f = y / self.density_step;
+		let idx = idx_f as usize;
+		if idx + 1 < self.density.len() {
+			let frac = idx_f - idx as f64;
+			self.density[idx] + frac * (self.density[idx + 1] - self.density[idx])
+		} else {
+			self.rho_base * (1.0 - LAPSE_RATE * y / T_STD).max(0.0).powf(self.baro_exp)
+		}
+	}
+
+	/// Simulate a shot at `angle` radians above horizontal until it returns
+	/// to launch height.
+	fn fire(&self, angle: f64) -> Impact {
+		let drag_k = self.drag_k;
+		let gravity = self.gravity;
+
+		let mut vx = self.speed * angle.cos();
+		let mut vy = self.speed * angle.sin();
+		let (mut x, mut y, mut t) = (0.0_f64, 0.0_f64, 0.0_f64);
+		let (mut x0, mut y0) = (0.0_f64, 0.0_f64);
+
+		while y >= 0.0 {
+			let ro = self.air_density(y);
+
+			let v_sq = vx * vx + vy * vy;
+			let accel = drag_k * ro * v_sq;
+
+			// Decompose drag into x/y components using algebraic
+			// identities instead of transcendental functions:
+			//   cos(atan(vy/vx)) = vx / √(vx²+vy²)
+			//   sin(atan(vy/vx)) = vy / √(vx²+vy²)
+			//
+			// NOTE: vx is updated *first*; the vy update sees the new
 			// vx, matching the C# evaluation order.
 			let v_mag = v_sq.sqrt();
 			let accel_per_v = accel / v_mag;
@@ -288,7 +1420,7 @@ pub fn compute_ballistic(proj: &DataProjectile, sensitivity: f64) -> Option<Stri
 			// Recompute |v| with updated vx (preserves the C#
 			// sequential-update semantics where a2 = atan(vy/new_vx)).
 			let v_mag2 = (vx * vx + vy * vy).sqrt();
-			vy += (-G - accel / v_mag2 * vy) * DT;
+			vy += (-gravity - accel / v_mag2 * vy) * DT;
 
 			t += DT;
 			x0 = x;
@@ -299,66 +1431,189 @@ pub fn compute_ballistic(proj: &DataProjectile, sensitivity: f64) -> Option<Stri
 
 		// Interpolate the ground-crossing distance.
 		let distance = x0 + (x - x0) / (y - y0) * (-y0);
-		last_distance = distance;
 
-		let time = (t * 10.0).round() / 10.0; // 1-decimal, away-from-zero
-		let v_impact = (vx * vx + vy * vy).sqrt();
+		Impact {
+			angle,
+			distance,
+			time: t,
+			speed: (vx * vx + vy * vy).sqrt(),
+		}
+	}
+}
+
+// ── Penetration model ──────────────────────────────────────────────────────
+
+/// Penetration formula selected from the normalized shell type.
+#[derive(Clone, Copy)]
+enum PenetrationKind {
+	/// Full-caliber AP (`DeMarre`), with the APHE filler penalty if `aphe`.
+	FullCaliber { aphe: bool },
+	/// APCR/APDS (`DeMarre` on the effective sub-caliber mass), with the
+	/// core's mass (kg) and caliber (m).
+	Subcaliber { core_mass: f64, core_caliber: f64 },
+	/// APDS-FS (interpolated armor power table).
+	ApdsFs,
+	/// Everything else (HE, HEAT, smoke, …) reports 0.
+	None,
+}
+
+/// [`PenetrationKind::Subcaliber`] for an APCR/APDS round.
+///
+/// A round without `DamageCaliber` would divide the `DeMarre` formula by zero
+/// and print `∞` down the whole table.  It is computed as a full-caliber solid
+/// shot instead (ballistic caliber, and the full mass if `DamageMass` is
+/// missing too), with a [`BallisticWarning::NoDamageCaliber`].
+fn subcaliber_kind(proj: &DataProjectile, warnings: &mut Vec<BallisticWarning>) -> PenetrationKind {
+	if proj.damage_caliber > 0.0 {
+		return PenetrationKind::Subcaliber {
+			core_mass: proj.damage_mass,
+			core_caliber: proj.damage_caliber,
+		};
+	}
+
+	warnings.push(BallisticWarning::NoDamageCaliber);
+	PenetrationKind::Subcaliber {
+		core_mass: non_zero_or(proj.damage_mass, proj.mass),
+		core_caliber: proj.ballistic_caliber,
+	}
+}
+
+/// Whether the penetration of `proj` is estimated: an APDS-FS round without
+/// an armor power series (it is not in the datamine), with
+/// [`BallisticConfig::estimate_missing_pen`].
+///
+/// Such a round is computed as a full-caliber solid shot of its own mass and
+/// ballistic caliber, a rough stand-in for the series.  Without the option
+/// its table is all zeros.
+#[must_use]
+pub fn estimates_penetration(proj: &DataProjectile, cfg: &BallisticConfig) -> bool {
+	cfg.estimate_missing_pen
+		&& !cfg.trajectory_only
+		&& proj.normalized_type == "apds_fs"
+		&& proj.armor_power_table.is_empty()
+}
 
-		let penetration = if is_ap {
-			let mut pen = k
-				* (v_impact / DEMARRE_REF_V).powf(speed_pow)
-				* proj.mass.powf(mass_pow)
-				/ (proj.ballistic_caliber * 10.0).powf(caliber_pow)
-				* 100.0;
+/// Per-projectile penetration parameters with `DeMarre` defaults applied.
+struct PenetrationModel<'a> {
+	proj: &'a DataProjectile,
+	kind: PenetrationKind,
+	rounding: PenRounding,
+	k: f64,
+	speed_pow: f64,
+	mass_pow: f64,
+	caliber_pow: f64,
+}
 
-			if is_aphe {
-				pen *= aphe_penalty(proj.explosive_mass / proj.mass);
+impl<'a> PenetrationModel<'a> {
+	fn new(
+		proj: &'a DataProjectile,
+		cfg: &BallisticConfig,
+		warnings: &mut Vec<BallisticWarning>,
+	) -> Self {
+		let ntype = proj.normalized_type.as_str();
+		let kind = if cfg.trajectory_only {
+			PenetrationKind::None
+		} else if AP_TYPES.contains(&ntype) {
+			PenetrationKind::FullCaliber {
+				aphe: APHE_TYPES.contains(&ntype),
 			}
-			pen.round()
-		} else if is_subcaliber {
-			let ratio = proj.damage_mass / proj.mass;
-			let sub_k = interpolate_table(&PEN_BY_SUBCALIBER, ratio);
-			let effective_mass =
-				(proj.mass - proj.damage_mass) * sub_k + proj.damage_mass;
-
-			(k * (v_impact / DEMARRE_REF_V).powf(speed_pow)
-				* effective_mass.powf(mass_pow)
-				/ (proj.damage_caliber * 10.0).powf(caliber_pow)
-				* 100.0)
-				.round()
-		} else if is_apds_fs {
-			interpolate_armor_power(&proj.armor_power_table, distance).round()
+		} else if ntype == "apcr" || ntype == "apds" {
+			subcaliber_kind(proj, warnings)
+		} else if estimates_penetration(proj, cfg) {
+			warnings.push(BallisticWarning::EstimatedPenetration);
+			PenetrationKind::FullCaliber { aphe: false }
+		} else if ntype == "apds_fs" {
+			PenetrationKind::ApdsFs
 		} else {
-			0.0
+			PenetrationKind::None
 		};
 
-		rows.push(Row {
-			distance,
-			time,
-			penetration,
-		});
+		Self {
+			proj,
+			kind,
+			rounding: cfg.pen_rounding,
+			// DeMarre parameters with defaults applied
+			k: non_zero_or(proj.demarre_k, DEFAULT_K),
+			speed_pow: non_zero_or(proj.demarre_speed_pow, DEFAULT_SPEED_POW),
+			mass_pow: non_zero_or(proj.demarre_mass_pow, DEFAULT_MASS_POW),
+			caliber_pow: non_zero_or(proj.demarre_caliber_pow, DEFAULT_CALIBER_POW),
+		}
 	}
 
-	// Emit TSV.  Output every row except the last, stopping early on a
-	// distance decrease (monotonicity guard, matches C# output loop).
-	let mut out = String::new();
-	if rows.len() >= 2 {
-		for i in 0..rows.len() - 1 {
-			if rows[i + 1].distance < rows[i].distance {
-				break;
-			}
-			writeln!(
-				out,
-				"{:.3}\t{}\t{}",
-				rows[i].distance,
-				fmt_time(rows[i].time),
-				fmt_penetration(rows[i].penetration),
-			)
-			.unwrap();
+	/// Penetration (mm, rounded) for a hit at `v_impact` m/s and `distance` m.
+	fn at(&self, v_impact: f64, distance: f64) -> f64 {
+		let proj = self.proj;
+		let round = |pen| self.rounding.apply(pen);
+		match self.kind {
+			PenetrationKind::FullCaliber { aphe } => {
+				let pen = self.demarre(v_impact, proj.mass, proj.ballistic_caliber);
+				if aphe {
+					round(aphe_adjusted_penetration(pen, proj.explosive_mass, proj.mass))
+				} else {
+					round(pen)
+				}
+			},
+			PenetrationKind::Subcaliber {
+				core_mass,
+				core_caliber,
+			} => {
+				let ratio = core_mass / proj.mass;
+				let sub_k = interpolate_table(&PEN_BY_SUBCALIBER, ratio);
+				let effective_mass = (proj.mass - core_mass) * sub_k + core_mass;
+
+				round(self.demarre(v_impact, effective_mass, core_caliber))
+			},
+			PenetrationKind::ApdsFs => {
+				round(interpolate_armor_power(&proj.armor_power_table, distance))
+			},
+			PenetrationKind::None => 0.0,
 		}
 	}
 
-	Some(out)
+	/// [`demarre_penetration`] with this projectile's coefficients.
+	fn demarre(&self, v_impact: f64, mass: f64, caliber: f64) -> f64 {
+		demarre_penetration(
+			v_impact,
+			mass,
+			caliber,
+			self.k,
+			self.speed_pow,
+			self.mass_pow,
+			self.caliber_pow,
+		)
+	}
+}
+
+/// `DeMarre` armour penetration (mm, unrounded) for a hit at `v_impact` m/s.
+///
+/// `mass` is in kg and `caliber` in metres; the coefficients are the
+/// datamine `demarre*` values (callers substitute the defaults for zeros).
+/// Velocity is normalised to 1900 m/s and caliber to decimetres, as in the
+/// C# `Ballistic()` method.  The `* 10.0` below is that metres → decimetres
+/// step, not a conversion to centimetres or millimetres (see
+/// [`crate::parser::data::caliber_mm`] for the latter).
+#[must_use]
+pub fn demarre_penetration(
+	v_impact: f64,
+	mass: f64,
+	caliber: f64,
+	k: f64,
+	speed_pow: f64,
+	mass_pow: f64,
+	caliber_pow: f64,
+) -> f64 {
+	k * (v_impact / DEMARRE_REF_V).powf(speed_pow) * mass.powf(mass_pow)
+		/ (caliber * 10.0).powf(caliber_pow)
+		* 100.0
+}
+
+/// Apply the APHE filler penalty to a full-caliber `DeMarre` penetration.
+///
+/// The multiplier is interpolated from the explosive-to-shell mass ratio:
+/// 1.0 up to 0.65 % filler, falling to 0.75 at 4 % and above.
+#[must_use]
+pub fn aphe_adjusted_penetration(penetration: f64, explosive_mass: f64, mass: f64) -> f64 {
+	penetration * aphe_penalty(explosive_mass / mass)
 }
 
 // ── Helpers ────────────────────────────────────────────────────────────────
@@ -387,45 +1642,67 @@ fn interpolate_table(table: &[(f64, f64)], k: f64) -> f64 {
 	table[table.len() - 1].1
 }
 
-/// Linear interpolation of the APDS-FS armor-power table.
+/// Linear interpolation of the APDS-FS armor-power table, which is sorted
+/// by distance.
 ///
+/// The bracketing interval `d0 ≤ distance < d1` is found by binary search.
 /// Returns 0 when the distance falls outside all intervals (this includes
 /// the case where the table is empty — i.e. no APDS series was present in
 /// the data file).
 fn interpolate_armor_power(table: &[(f64, f64)], distance: f64) -> f64 {
-	for window in table.windows(2) {
-		let (d0, p0) = window[0];
-		let (d1, p1) = window[1];
-		if distance >= d0 && distance < d1 {
-			return p0 + (distance - d0) / (d1 - d0) * (p1 - p0);
-		}
+	// First entry beyond `distance`; the interval ends there
+	let end = table.partition_point(|&(d, _)| d <= distance);
+	if end == 0 || end == table.len() {
+		return 0.0;
 	}
-	0.0
+	let (d0, p0) = table[end - 1];
+	let (d1, p1) = table[end];
+	p0 + (distance - d0) / (d1 - d0) * (p1 - p0)
 }
 
 /// Format time for TSV output, matching C# `double.ToString()` behaviour.
 ///
-/// Whole-second values have no decimal point: `"0"`, `"1"`, `"10"`.
-/// Fractional values get exactly one decimal: `"0.1"`, `"3.5"`.
+/// Seconds are rounded to one decimal (away from zero).  Whole-second values
+/// have no decimal point: `"0"`, `"1"`, `"10"`.  Fractional values get
+/// exactly one decimal: `"0.1"`, `"3.5"`.  Milliseconds are rounded from the
+/// unrounded time and written as integers: `"350"`.
 #[allow(clippy::cast_possible_truncation)]
-fn fmt_time(t: f64) -> String {
-	if t.fract().abs() < 1e-9 {
-		format!("{}", t as i64)
-	} else {
-		format!("{t:.1}")
+fn fmt_time(t: f64, unit: TimeUnit) -> String {
+	match unit {
+		TimeUnit::Seconds => {
+			let t = (t * 10.0).round() / 10.0;
+			if t.fract().abs() < 1e-9 {
+				format!("{}", t as i64)
+			} else {
+				format!("{t:.1}")
+			}
+		},
+		TimeUnit::Milliseconds => format!("{}", (t * 1000.0).round() as i64),
 	}
 }
 
 /// Format penetration for TSV output, matching C# `double.ToString()`.
 ///
-/// Finite values are integers: `"138"`, `"0"`.
+/// Finite values are rounded to a multiple of `round_to` mm in `rounding`
+/// mode, then written as integers: `"138"`, `"0"`, or inches with one decimal (`"5.4"`)
+/// under [`PenetrationUnit::Inches`].
 /// Infinite values are the infinity symbol: `"∞"` (matches C# behaviour).
 #[allow(clippy::cast_possible_truncation)]
-fn fmt_penetration(p: f64) -> String {
+fn fmt_penetration(
+	p: f64,
+	unit: PenetrationUnit,
+	round_to: u32,
+	rounding: PenRounding,
+) -> String {
 	if p.is_infinite() || p.is_nan() {
 		"\u{221E}".to_owned() // ∞
 	} else {
-		format!("{}", p as i64)
+		let step = f64::from(round_to.max(1));
+		let p = rounding.apply(p / step) * step;
+		match unit {
+			PenetrationUnit::Millimetres => format!("{}", p as i64),
+			PenetrationUnit::Inches => format!("{:.1}", p / MM_PER_INCH),
+		}
 	}
 }
 
@@ -468,6 +1745,7 @@ mod tests {
 
 	#[test]
 	fn test_fmt_time() {
+		let fmt_time = |t| fmt_time(t, TimeUnit::Seconds);
 		assert_eq!(fmt_time(0.0), "0");
 		assert_eq!(fmt_time(1.0), "1");
 		assert_eq!(fmt_time(10.0), "10");
@@ -475,6 +1753,127 @@ mod tests {
 		assert_eq!(fmt_time(3.5), "3.5");
 	}
 
+	#[test]
+	fn test_time_unit_ms_from_unrounded_time() {
+		assert_eq!(fmt_time(0.35, TimeUnit::Milliseconds), "350");
+		assert_eq!(fmt_time(0.0, TimeUnit::Milliseconds), "0");
+		assert_eq!(fmt_time(12.34, TimeUnit::Milliseconds), "12340");
+		assert_eq!("ms".parse(), Ok(TimeUnit::Milliseconds));
+		assert!("min".parse::<TimeUnit>().is_err());
+
+		// Same rows, finer time column
+		let proj = sample_projectile();
+		let ms_cfg = BallisticConfig {
+			time_unit: TimeUnit::Milliseconds,
+			..BallisticConfig::default()
+		};
+		let seconds = compute_ballistic_with_config(&proj, &BallisticConfig::default()).unwrap();
+		let ms = compute_ballistic_with_config(&proj, &ms_cfg).unwrap();
+		assert_eq!(seconds.lines().count(), ms.lines().count());
+		for (s_row, ms_row) in seconds.lines().zip(ms.lines()).step_by(100) {
+			let time = |row: &str| row.split('\t').nth(1).unwrap().parse::<f64>().unwrap();
+			assert!((time(s_row) - time(ms_row) / 1000.0).abs() <= 0.05, "{s_row} {ms_row}");
+		}
+	}
+
+	#[test]
+	fn test_distance_precision_one_decimal() {
+		use crate::compare::{DeltaStats, Tolerances, compare_ballistic_fuzzy};
+
+		let proj = sample_projectile();
+		let cfg = BallisticConfig {
+			distance_precision: 1,
+			..BallisticConfig::default()
+		};
+		let reference = compute_ballistic_with_config(&proj, &BallisticConfig::default()).unwrap();
+		let table = compute_ballistic_with_config(&proj, &cfg).unwrap();
+		assert!(table.len() < reference.len());
+		for row in table.lines() {
+			let distance = row.split('\t').next().unwrap();
+			let (_, decimals) = distance.split_once('.').unwrap();
+			assert_eq!(decimals.len(), 1, "{row}");
+		}
+		assert!(table_header(&proj, &cfg).contains("\tdistance_precision=1"));
+		let default_key = BallisticKey::with_config(&proj, &BallisticConfig::default());
+		assert!(BallisticKey::with_config(&proj, &cfg) != default_key);
+
+		// Still within the corpus tolerances, once widened for the rounding
+		let compare = |tol| {
+			let mut stats = DeltaStats::default();
+			compare_ballistic_fuzzy("test", "shell", &table, &reference, &tol, &mut stats)
+		};
+		let tol = Tolerances::default();
+		assert!(compare(tol.for_distance_precision(1)).is_ok());
+		assert!(compare(tol).is_err());
+	}
+
+	#[test]
+	fn test_fmt_penetration() {
+		let fmt_penetration = |p, unit, round_to| {
+			fmt_penetration(p, unit, round_to, PenRounding::Nearest)
+		};
+		assert_eq!(fmt_penetration(138.0, PenetrationUnit::Millimetres, 1), "138");
+		assert_eq!(fmt_penetration(138.0, PenetrationUnit::Inches, 1), "5.4");
+		assert_eq!(fmt_penetration(0.0, PenetrationUnit::Inches, 1), "0.0");
+		assert_eq!(fmt_penetration(f64::INFINITY, PenetrationUnit::Inches, 1), "\u{221E}");
+
+		// --pen-round 5
+		assert_eq!(fmt_penetration(138.0, PenetrationUnit::Millimetres, 5), "140");
+		assert_eq!(fmt_penetration(132.0, PenetrationUnit::Millimetres, 5), "130");
+		assert_eq!(fmt_penetration(0.0, PenetrationUnit::Millimetres, 5), "0");
+		assert_eq!(fmt_penetration(138.0, PenetrationUnit::Inches, 5), "5.5");
+		assert_eq!(fmt_penetration(f64::INFINITY, PenetrationUnit::Millimetres, 5), "\u{221E}");
+		assert_eq!("in".parse(), Ok(PenetrationUnit::Inches));
+		assert!("cm".parse::<PenetrationUnit>().is_err());
+	}
+
+	#[test]
+	fn test_pen_rounding_modes() {
+		let mm = PenetrationUnit::Millimetres;
+		let fmt = |rounding| fmt_penetration(138.6, mm, 1, rounding);
+		assert_eq!(fmt(PenRounding::Nearest), "139");
+		assert_eq!(fmt(PenRounding::Floor), "138");
+		assert_eq!(fmt(PenRounding::Ceil), "139");
+		assert_eq!(fmt_penetration(138.4, mm, 1, PenRounding::Ceil), "139");
+		assert_eq!(fmt_penetration(138.0, mm, 5, PenRounding::Floor), "135");
+
+		// Applied to the computed penetration, and part of the cache key
+		let proj = DataProjectile {
+			normalized_type: "apds_fs".to_owned(),
+			armor_power_table: vec![(0.0, 138.6), (1000.0, 138.6)],
+			..sample_projectile()
+		};
+		let pen = |pen_rounding| {
+			let cfg = BallisticConfig {
+				pen_rounding,
+				..BallisticConfig::default()
+			};
+			PenetrationModel::new(&proj, &cfg, &mut Vec::new()).at(1500.0, 500.0)
+		};
+		assert!((pen(PenRounding::Floor) - 138.0).abs() < f64::EPSILON);
+		assert!((pen(PenRounding::Nearest) - 139.0).abs() < f64::EPSILON);
+		assert!((pen(PenRounding::Ceil) - 139.0).abs() < f64::EPSILON);
+		let floor = BallisticConfig {
+			pen_rounding: PenRounding::Floor,
+			..BallisticConfig::default()
+		};
+		assert!(
+			BallisticKey::with_config(&proj, &floor)
+				!= BallisticKey::with_config(&proj, &BallisticConfig::default())
+		);
+		assert_eq!("floor".parse(), Ok(PenRounding::Floor));
+		assert!("down".parse::<PenRounding>().is_err());
+	}
+
+	#[test]
+	fn test_is_zero_penetration() {
+		assert!(is_zero_penetration("0.000\t0\t0\n100.000\t0.1\t0\n"));
+		assert!(is_zero_penetration("# shell=x\ttype=he\n0.000\t0\t0.0\n"));
+		assert!(!is_zero_penetration("0.000\t0\t12\n100.000\t0.1\t0\n"));
+		assert!(!is_zero_penetration("0.000\t0\t\u{221E}\n"));
+		assert!(!is_zero_penetration(""));
+	}
+
 	#[test]
 	fn test_should_skip() {
 		assert!(should_skip("atgm"));
@@ -486,9 +1885,612 @@ mod tests {
 		assert!(!should_skip("apds_fs"));
 	}
 
+	#[test]
+	fn test_is_unsupported_type() {
+		assert!(is_unsupported_type("snowball"));
+		assert!(is_unsupported_type("practice"));
+		assert!(!is_unsupported_type("apcbc"));
+		assert!(!is_unsupported_type("apds_fs"));
+		assert!(!is_unsupported_type("smoke"));
+		assert!(!is_unsupported_type("atgm"));
+	}
+
+	/// 75 mm APCBC round (`PzGr 39`-like) for end-to-end simulation tests.
+	fn sample_projectile() -> DataProjectile {
+		DataProjectile {
+			name: "75mm_pzgr_39".to_owned(),
+			bullet_type: "apcbc_tank".to_owned(),
+			normalized_type: "apcbc".to_owned(),
+			is_tracer: false,
+			is_incendiary: false,
+			mass: 6.8,
+			ballistic_caliber: 0.075,
+			speed: 740.0,
+			cx: 0.4,
+			explosive_mass: 0.017,
+			explosive_type: Some("h10".to_owned()),
+			blast_radius: None,
+			fuze_delay_m: None,
+			fuze_sensitivity: None,
+			damage_mass: 0.0,
+			damage_caliber: 0.0,
+			demarre_k: 1.0,
+			demarre_speed_pow: 1.43,
+			demarre_mass_pow: 0.71,
+			demarre_caliber_pow: 1.07,
+			armor_power_table: Vec::new(),
+			output_name: "pzgr_39".to_owned(),
+		}
+	}
+
+	/// Parse the distance column of a TSV ballistic table.
+	fn distances(table: &str) -> Vec<f64> {
+		table
+			.lines()
+			.map(|l| l.split('\t').next().unwrap().parse().unwrap())
+			.collect()
+	}
+
+	#[test]
+	fn test_fixed_step_rows_on_grid() {
+		let cfg = BallisticConfig {
+			sampling: RangeSampling::FixedStep(100.0),
+			..BallisticConfig::default()
+		};
+		let table = compute_ballistic_with_config(&sample_projectile(), &cfg).unwrap();
+		let dists = distances(&table);
+
+		assert_eq!(dists.len(), 46, "0..=4500 m in 100 m steps");
+		for (i, d) in (0_u32..).zip(&dists) {
+			let target = 100.0 * f64::from(i);
+			assert!((d - target).abs() < 0.01, "row {i}: {d} vs {target}");
+		}
+	}
+
+	#[test]
+	fn test_fixed_step_ignores_sensitivity() {
+		let proj = sample_projectile();
+		let a = BallisticConfig {
+			sensitivity: 0.3,
+			sampling: RangeSampling::FixedStep(250.0),
+			..BallisticConfig::default()
+		};
+		let b = BallisticConfig { sensitivity: 0.9, ..a };
+		assert_eq!(
+			compute_ballistic_with_config(&proj, &a),
+			compute_ballistic_with_config(&proj, &b),
+		);
+		assert!(compute_ballistic_with_config(&proj, &BallisticConfig {
+			sampling: RangeSampling::FixedStep(0.0),
+			..a
+		})
+		.is_none());
+	}
+
+	#[test]
+	fn test_both_samplings_from_one_simulation() {
+		const DIST_TOL: f64 = 0.01;
+
+		let proj = sample_projectile();
+		let cfg = BallisticConfig::default();
+		let (scroll, fixed) = compute_ballistic_both(&proj, &cfg, 100.0, &mut Vec::new()).unwrap();
+		assert_eq!(Some(scroll), compute_ballistic_with_config(&proj, &cfg));
+
+		let alone = compute_ballistic_with_config(&proj, &BallisticConfig {
+			sampling: RangeSampling::FixedStep(100.0),
+			..cfg
+		})
+		.unwrap();
+		let (fixed, alone) = (crate::table::parse_table(&fixed), crate::table::parse_table(&alone));
+		assert_eq!(fixed.len(), alone.len());
+		for (a, b) in fixed.iter().zip(&alone) {
+			assert!((a.distance - b.distance).abs() < DIST_TOL, "{} vs {}", a.distance, b.distance);
+			assert!((a.time - b.time).abs() < 0.15, "{} vs {}", a.time, b.time);
+			assert!((a.penetration - b.penetration).abs() <= 1.0, "at {} m", a.distance);
+		}
+
+		assert!(compute_ballistic_both(&proj, &cfg, 0.0, &mut Vec::new()).is_none());
+	}
+
+	#[test]
+	fn test_max_entries_cap_bounds_sweep() {
+		let proj = sample_projectile();
+		let cfg = BallisticConfig {
+			max_entries_cap: 200,
+			..BallisticConfig::new(0.01)
+		};
+		assert!(cfg.scroll_entries() > 1_000_000);
+
+		// The last simulated row is dropped by the output loop
+		let mut warnings = Vec::new();
+		let table = compute_ballistic_with_warnings(&proj, &cfg, &mut warnings).unwrap();
+		assert_eq!(table.lines().count(), 199);
+		assert_eq!(warnings, [BallisticWarning::Truncated { max_entries_cap: 200 }]);
+
+		// A binding cap is part of the cache key, a slack one is not
+		let uncapped = BallisticConfig::new(0.01);
+		assert!(BallisticKey::with_config(&proj, &cfg) != BallisticKey::with_config(&proj, &uncapped));
+		let slack = BallisticConfig {
+			max_entries_cap: 1_000_000,
+			..BallisticConfig::default()
+		};
+		assert!(
+			BallisticKey::with_config(&proj, &slack)
+				== BallisticKey::with_config(&proj, &BallisticConfig::default())
+		);
+	}
+
+	#[test]
+	fn test_include_muzzle_row_toggles_zero_row() {
+		let proj = sample_projectile();
+		let without = |cfg: BallisticConfig| BallisticConfig {
+			include_muzzle_row: false,
+			..cfg
+		};
+
+		let rows = |cfg: &BallisticConfig| {
+			distances(&compute_ballistic_with_config(&proj, cfg).unwrap())
+		};
+
+		for cfg in [
+			BallisticConfig::default(),
+			BallisticConfig {
+				sampling: RangeSampling::FixedStep(100.0),
+				..BallisticConfig::default()
+			},
+		] {
+			let with_rows = rows(&cfg);
+			assert!(with_rows[0].abs() < 0.01, "first row is the muzzle");
+			assert_eq!(rows(&without(cfg)), with_rows[1..]);
+
+			let key = BallisticKey::with_config(&proj, &cfg);
+			assert!(key != BallisticKey::with_config(&proj, &without(cfg)));
+		}
+	}
+
+	#[test]
+	fn test_apds_fs_without_series_estimated_on_request() {
+		let proj = DataProjectile {
+			name: "105mm_m735".to_owned(),
+			bullet_type: "apds_fs_long_tank".to_owned(),
+			normalized_type: "apds_fs".to_owned(),
+			mass: 3.7,
+			ballistic_caliber: 0.038,
+			speed: 1501.0,
+			cx: 0.2,
+			explosive_mass: 0.0,
+			explosive_type: None,
+			blast_radius: None,
+			fuze_delay_m: None,
+			fuze_sensitivity: None,
+			output_name: "m735".to_owned(),
+			..sample_projectile()
+		};
+		let cfg = BallisticConfig::default();
+		let estimated = BallisticConfig {
+			estimate_missing_pen: true,
+			..cfg
+		};
+
+		let zeros = compute_ballistic_with_config(&proj, &cfg).unwrap();
+		assert!(is_zero_penetration(&zeros));
+		assert!(!estimates_penetration(&proj, &cfg));
+
+		let mut warnings = Vec::new();
+		let table = compute_ballistic_with_warnings(&proj, &estimated, &mut warnings).unwrap();
+		assert!(!is_zero_penetration(&table));
+		assert_eq!(warnings, [BallisticWarning::EstimatedPenetration]);
+		assert!(estimates_penetration(&proj, &estimated));
+		assert!(table_header(&proj, &estimated).contains("\tpen=estimated"));
+		assert!(!table_header(&proj, &cfg).contains("pen="));
+		assert!(
+			BallisticKey::with_config(&proj, &cfg) != BallisticKey::with_config(&proj, &estimated)
+		);
+
+		// A round with its series keeps it
+		let with_series = DataProjectile {
+			armor_power_table: vec![(0.0, 400.0), (10_000.0, 300.0)],
+			..proj
+		};
+		assert!(!estimates_penetration(&with_series, &estimated));
+	}
+
+	#[test]
+	fn test_trajectory_only_smoke_round() {
+		let smoke = DataProjectile {
+			name: "75mm_smoke".to_owned(),
+			bullet_type: "smoke_tank".to_owned(),
+			normalized_type: "smoke".to_owned(),
+			output_name: "smoke".to_owned(),
+			..sample_projectile()
+		};
+		let cfg = BallisticConfig {
+			trajectory_only: true,
+			..BallisticConfig::default()
+		};
+
+		let table = compute_ballistic_with_config(&smoke, &cfg).unwrap();
+		assert!(table.lines().count() > 10);
+		assert!(table.lines().all(|row| row.split('\t').count() == 2), "{table}");
+		assert!(!is_zero_penetration(&table));
+		assert!(table_header(&smoke, &cfg).contains("\tpen=none"));
+
+		// Same trajectory as the full table, minus the last column
+		let full = compute_ballistic_with_config(&smoke, &BallisticConfig::default()).unwrap();
+		let trimmed: Vec<_> = full.lines().map(|row| row.rsplit_once('\t').unwrap().0).collect();
+		assert!(table.lines().eq(trimmed));
+	}
+
+	#[test]
+	fn test_lower_gravity_extends_range() {
+		let proj = sample_projectile();
+		let earth = BallisticConfig::default();
+		let moon = BallisticConfig {
+			gravity: 1.62,
+			..earth
+		};
+
+		// Same launch angle per row, so every shot lands further out
+		let earth_dists = distances(&compute_ballistic_with_config(&proj, &earth).unwrap());
+		let moon_dists = distances(&compute_ballistic_with_config(&proj, &moon).unwrap());
+		assert!(moon_dists.len() < earth_dists.len(), "reaches max_range in fewer steps");
+		for (i, (m, e)) in moon_dists.iter().zip(&earth_dists).enumerate().skip(1) {
+			assert!(m > e, "row {i}: {m} <= {e}");
+		}
+
+		assert!(BallisticKey::with_config(&proj, &moon) != BallisticKey::with_config(&proj, &earth));
+	}
+
+	#[test]
+	fn test_naval_preset_extends_tables_past_tank_range() {
+		// 406 mm Mk 8 APC, lobbed well beyond any tank engagement
+		let proj = DataProjectile {
+			name: "406mm_mk8_apc".to_owned(),
+			mass: 1225.0,
+			ballistic_caliber: 0.406,
+			speed: 762.0,
+			cx: 0.3,
+			explosive_mass: 0.0,
+			output_name: "mk8_apc".to_owned(),
+			..sample_projectile()
+		};
+		let tank = BallisticConfig::default();
+		let naval = tank.naval();
+
+		let tank_dists = distances(&compute_ballistic_with_config(&proj, &tank).unwrap());
+		assert!(tank_dists.last().unwrap() < &BallisticConfig::DEFAULT_MAX_RANGE);
+
+		let table = compute_ballistic_with_config(&proj, &naval).unwrap();
+		let rows: Vec<Vec<f64>> = table
+			.lines()
+			.map(|l| l.split('\t').map(|f| f.parse().unwrap()).collect())
+			.collect();
+		let last = rows.last().unwrap();
+		assert!(last[0] > 19_000.0, "ends at {} m", last[0]);
+		assert!(rows.len() > tank_dists.len());
+		for pair in rows.windows(2) {
+			assert!(pair[1][0] > pair[0][0] && pair[1][1] >= pair[0][1], "{pair:?}");
+		}
+		// Penetration stays finite and positive, but well below the muzzle
+		for row in &rows {
+			assert!(row[2].is_finite() && row[2] > 0.0, "{row:?}");
+		}
+		assert!(last[2] < 0.8 * rows[0][2], "{} vs {}", last[2], rows[0][2]);
+
+		assert!(
+			BallisticKey::with_config(&proj, &naval) != BallisticKey::with_config(&proj, &tank)
+		);
+		assert!(table_header(&proj, &naval).contains("\tmax_range=20000"));
+	}
+
+	#[test]
+	fn test_max_direct_range_matches_first_row() {
+		const DIST_TOL: f64 = 0.01;
+
+		let proj = sample_projectile();
+		for cfg in [BallisticConfig::default(), BallisticConfig::new(1.0)] {
+			let table = compute_ballistic_with_config(&proj, &cfg).unwrap();
+			let rows = crate::table::parse_table(&table);
+			let (distance, pen) = compute_max_direct_range(&proj, &cfg).unwrap();
+
+			assert!(rows[0].distance.abs() < DIST_TOL, "table starts at 0 m");
+			assert!((distance - rows[1].distance).abs() < DIST_TOL, "{distance} vs {}", rows[1].distance);
+			assert!((pen - rows[1].penetration).abs() < f64::EPSILON);
+		}
+
+		let rocket = DataProjectile {
+			normalized_type: "atgm".to_owned(),
+			..proj
+		};
+		assert_eq!(compute_max_direct_range(&rocket, &BallisticConfig::default()), None);
+	}
+
+	#[test]
+	fn test_elevation_table_degrees_and_mils() {
+		let proj = sample_projectile();
+		let cfg = BallisticConfig::default();
+		let elevation = compute_elevation_table(&proj, &cfg).unwrap();
+		let table = crate::table::parse_table(&compute_ballistic_with_config(&proj, &cfg).unwrap());
+		assert_eq!(elevation.len(), table.len(), "one angle per table row");
+
+		let mils = write_elevation_table(&elevation, AngleUnit::Mils);
+		let deg = write_elevation_table(&elevation, AngleUnit::Degrees);
+		let angle = |line: &str| -> f64 { line.split('\t').nth(1).unwrap().parse().unwrap() };
+		let (mils_row, deg_row) = (mils.lines().nth(10).unwrap(), deg.lines().nth(10).unwrap());
+		assert_eq!(mils_row.split('\t').next(), deg_row.split('\t').next());
+
+		// Row i is i scroll steps up: 10 · 2.8 · 0.5² mrad, in 6400ths of a turn
+		let nato_mils = 7.0e-3 * 6400.0 / (2.0 * PI);
+		assert!((angle(mils_row) - nato_mils).abs() < 1e-3, "{mils_row}");
+		let mils_per_degree = 6400.0 / 360.0;
+		assert!((angle(mils_row) / mils_per_degree - angle(deg_row)).abs() < 1e-4, "{deg_row}");
+	}
+
+	#[test]
+	fn test_sight_height_adds_line_of_sight_angle() {
+		let proj = sample_projectile();
+		let bore = compute_elevation_table(&proj, &BallisticConfig::default()).unwrap();
+		let cfg = BallisticConfig {
+			sight_height_m: 0.5,
+			..BallisticConfig::default()
+		};
+		let sight = compute_elevation_table(&proj, &cfg).unwrap();
+		assert_eq!(sight.len(), bore.len());
+		assert!(sight.iter().zip(&bore).all(|(s, b)| (s.0 - b.0).abs() < f64::EPSILON));
+		assert!((sight[0].1 - bore[0].1).abs() < f64::EPSILON, "0 m row unchanged");
+
+		// 0.5 m over the first few hundred metres: several mrad
+		let (distance, angle) = sight[1];
+		assert!(distance > 0.0 && distance < 500.0, "{distance}");
+		let offset = angle - bore[1].1;
+		assert!((offset - (0.5 / distance).atan()).abs() < 1e-12, "{offset}");
+		assert!(offset > 1e-3, "{offset}");
+		// ... but a fraction of that at the far end
+		let far_offset = sight[sight.len() - 1].1 - bore[bore.len() - 1].1;
+		assert!(far_offset < offset / 10.0, "{far_offset}");
+
+		// The ballistic table itself is relative to the bore
+		assert_eq!(
+			compute_ballistic_with_config(&proj, &cfg),
+			compute_ballistic_with_config(&proj, &BallisticConfig::default()),
+		);
+		assert!(table_header(&proj, &cfg).contains("\tsight_height=0.5"));
+	}
+
+	#[test]
+	fn test_caliber_only_shell_has_drag_and_zero_caliber_is_skipped() {
+		let weapon = serde_json::json!({
+			"bullet": {
+				"bulletName": "30mm_ap",
+				"bulletType": "ap_t",
+				"mass": 0.4,
+				"caliber": 0.03,
+				"speed": 1000.0,
+				"Cx": 0.35
+			}
+		});
+		let projectiles = crate::parser::parse_weapon_module(&weapon, None).unwrap();
+		let proj = crate::parser::data::from_projectile(&projectiles[0]);
+		assert!((proj.ballistic_caliber - 0.03).abs() < f64::EPSILON);
+
+		// Same launch angle, shorter reach than without drag
+		let cfg = BallisticConfig::default();
+		let with_drag = distances(&compute_ballistic_with_config(&proj, &cfg).unwrap());
+		let vacuum = DataProjectile {
+			cx: 0.0,
+			..proj.clone()
+		};
+		let without_drag = distances(&compute_ballistic_with_config(&vacuum, &cfg).unwrap());
+		assert!(with_drag[20] < without_drag[20], "{} vs {}", with_drag[20], without_drag[20]);
+
+		let no_caliber = DataProjectile {
+			ballistic_caliber: 0.0,
+			..proj
+		};
+		assert_eq!(compute_ballistic_with_config(&no_caliber, &cfg), None);
+		assert_eq!(compute_max_direct_range(&no_caliber, &cfg), None);
+		let mut warnings = Vec::new();
+		assert_eq!(compute_ballistic_both(&no_caliber, &cfg, 100.0, &mut warnings), None);
+		assert_eq!(warnings, [BallisticWarning::NoBallisticCaliber]);
+	}
+
+	#[test]
+	fn test_header_range_marks_at_requested_ranges() {
+		let proj = sample_projectile();
+		let cfg = BallisticConfig::default();
+		let marks = [250.0, 500.0, 750.0];
+		let (header, unreachable) = table_header_with_marks(&proj, &cfg, &marks);
+		assert_eq!(unreachable, 0);
+		assert!(header.starts_with(table_header(&proj, &cfg).trim_end()), "{header}");
+		assert!(header.ends_with('\n'));
+
+		let field = header.trim_end().rsplit_once("\tmarks=").unwrap().1;
+		let written = parse_marks(field);
+		let elevation = compute_elevation_table(&proj, &cfg).unwrap();
+		let expected = drops_at_standard_ranges(&elevation, &marks);
+		assert!(written.iter().map(|&(range, _)| range).eq(marks));
+		for ((_, mils), (_, drop)) in written.iter().zip(&expected) {
+			assert!((mils - drop.unwrap()).abs() < 1e-3, "{field}");
+		}
+		assert_eq!(table_header_with_marks(&proj, &cfg, &[]), (table_header(&proj, &cfg), 0));
+	}
+
+	/// `(range, mils)` pairs of a header `marks=` field.
+	fn parse_marks(field: &str) -> Vec<(f64, f64)> {
+		field
+			.split(',')
+			.map(|mark| {
+				let (range, mils) = mark.split_once(':').unwrap();
+				(range.parse().unwrap(), mils.parse().unwrap())
+			})
+			.collect()
+	}
+
+	#[test]
+	fn test_slow_gun_leaves_out_unreachable_mark() {
+		// 150 m/s: under 2.3 km even in vacuum
+		let proj = DataProjectile {
+			speed: 150.0,
+			..sample_projectile()
+		};
+		let cfg = BallisticConfig::default();
+		let table = crate::table::parse_table(&compute_ballistic_with_config(&proj, &cfg).unwrap());
+		let reach = table.last().unwrap().distance;
+		assert!(reach > 800.0 && reach < 2500.0, "{reach}");
+
+		let (header, unreachable) = table_header_with_marks(&proj, &cfg, &[400.0, 800.0, 2500.0]);
+		assert_eq!(unreachable, 1);
+		let field = header.trim_end().rsplit_once("\tmarks=").unwrap().1;
+		let ranges: Vec<f64> = parse_marks(field).into_iter().map(|(range, _)| range).collect();
+		assert_eq!(ranges, [400.0, 800.0], "{field}");
+
+		// Nothing in reach: an empty field rather than no field
+		let (header, unreachable) = table_header_with_marks(&proj, &cfg, &[2500.0, 3000.0]);
+		assert_eq!(unreachable, 2);
+		assert!(header.ends_with("\tmarks=\n"), "{header}");
+	}
+
+	#[test]
+	fn test_seeded_cache_hits_on_first_lookup() {
+		let dir = std::env::temp_dir().join(format!("fcsgen-seed-cache-{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+		let path = dir.join("cache.json");
+
+		let proj = sample_projectile();
+		let cfg = BallisticConfig::default();
+		let key = BallisticKey::with_config(&proj, &cfg);
+		let warm = BallisticCache::new();
+		warm.insert(key, Some("0.000\t0\t123\n".to_owned()));
+		write_cache(&warm, &path).unwrap();
+
+		let cache = BallisticCache::new();
+		assert_eq!(seed_cache(&cache, &path).unwrap(), 1);
+		let (table, hit) = compute_ballistic_cached_with_config(&proj, &cfg, &cache);
+		assert!(hit, "seeded key is a hit on first encounter");
+		assert_eq!(table.as_deref(), Some("0.000\t0\t123\n"));
+
+		// Other physics still miss
+		let other = BallisticConfig { gravity: 1.62, ..cfg };
+		assert!(!compute_ballistic_cached_with_config(&proj, &other, &cache).1);
+
+		std::fs::write(&path, r#"{"version":"0.0.0-old","entries":[]}"#).unwrap();
+		assert!(seed_cache(&cache, &path).is_err());
+
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn test_subcaliber_without_damage_caliber_is_finite() {
+		// Type 4 APCR-like round whose Data block lacks DamageMass/DamageCaliber
+		let proj = DataProjectile {
+			name: "75mm_type_4_apcr".to_owned(),
+			bullet_type: "apcr_tank".to_owned(),
+			normalized_type: "apcr".to_owned(),
+			mass: 4.15,
+			speed: 930.0,
+			cx: 0.38,
+			explosive_mass: 0.0,
+			explosive_type: None,
+			blast_radius: None,
+			fuze_delay_m: None,
+			fuze_sensitivity: None,
+			demarre_k: 0.0,
+			output_name: "type_4_apcr".to_owned(),
+			..sample_projectile()
+		};
+		let mut warnings = Vec::new();
+		let cfg = BallisticConfig::new(0.5);
+		let table = compute_ballistic_with_warnings(&proj, &cfg, &mut warnings).unwrap();
+		assert_eq!(warnings, [BallisticWarning::NoDamageCaliber]);
+
+		assert!(!table.contains('\u{221E}'));
+		let muzzle = table.lines().next().unwrap();
+		let pen: f64 = muzzle.split('\t').nth(2).unwrap().parse().unwrap();
+		assert!(pen > 0.0);
+	}
+
+	#[test]
+	fn test_air_density_below_launch_height() {
+		let trajectory = Trajectory::new(&sample_projectile(), &BallisticConfig::default());
+		let ground = trajectory.air_density(0.0);
+		assert!((ground - DENSITY_TABLE[0]).abs() < f64::EPSILON);
+
+		// Slightly below launch height, as just before a ground crossing
+		for y in [-1e-9, -0.05, -3.0] {
+			let rho = trajectory.air_density(y);
+			assert!((rho - ground).abs() < f64::EPSILON, "y={y}: {rho}");
+		}
+		// Density still falls with altitude above it
+		assert!(trajectory.air_density(100.0) < ground);
+		assert!(trajectory.air_density(600.0) < trajectory.air_density(100.0));
+	}
+
+	#[test]
+	fn test_demarre_penetration_worked_examples() {
+		// At the reference velocity with unit mass and a 1 dm caliber every
+		// power term is 1, leaving k · 100.
+		let pen = demarre_penetration(1900.0, 1.0, 0.1, 0.9, 1.43, 0.71, 1.07);
+		assert!((pen - 90.0).abs() < 1e-9, "{pen}");
+
+		// 75 mm, 6.8 kg at 950 m/s:
+		// 0.5^1.43 · 6.8^0.71 / 0.75^1.07 · 100 ≈ 0.3712 · 3.900 / 0.7351 · 100
+		let pen = demarre_penetration(950.0, 6.8, 0.075, 1.0, 1.43, 0.71, 1.07);
+		assert!((pen - 196.921).abs() < 1e-3, "{pen}");
+	}
+
+	#[test]
+	fn test_aphe_adjusted_penetration() {
+		// 0.25 % filler: below the first threshold, no penalty
+		assert!((aphe_adjusted_penetration(100.0, 0.017, 6.8) - 100.0).abs() < 1e-9);
+		// 1.6 % filler: exactly on a table entry
+		assert!((aphe_adjusted_penetration(100.0, 0.016, 1.0) - 93.0).abs() < 1e-9);
+		// 2.5 % filler: halfway between 0.9 and 0.85
+		assert!((aphe_adjusted_penetration(100.0, 0.025, 1.0) - 87.5).abs() < 1e-9);
+		// 10 % filler: clamped to the last entry
+		assert!((aphe_adjusted_penetration(100.0, 0.1, 1.0) - 75.0).abs() < 1e-9);
+	}
+
 	#[test]
 	fn test_non_zero_or() {
 		assert!((non_zero_or(0.0, 0.9) - 0.9).abs() < f64::EPSILON);
 		assert!((non_zero_or(1.0, 0.9) - 1.0).abs() < f64::EPSILON);
 	}
+
+	#[test]
+	fn test_armor_power_binary_search_matches_linear_scan() {
+		fn linear(table: &[(f64, f64)], distance: f64) -> f64 {
+			for window in table.windows(2) {
+				let (d0, p0) = window[0];
+				let (d1, p1) = window[1];
+				if distance >= d0 && distance < d1 {
+					return p0 + (distance - d0) / (d1 - d0) * (p1 - p0);
+				}
+			}
+			0.0
+		}
+
+		let m735 = [
+			(0.0, 292.4),
+			(100.0, 290.8),
+			(500.0, 284.6),
+			(1000.0, 276.9),
+			(1500.0, 269.2),
+			(2000.0, 261.5),
+			(2500.0, 253.8),
+			(3000.0, 246.2),
+			(3500.0, 238.5),
+			(10000.0, 138.5),
+		];
+		// Repeated distances give an empty interval, skipped by both
+		let repeated = [(0.0, 300.0), (1000.0, 250.0), (1000.0, 240.0), (2000.0, 200.0)];
+		for table in [&m735[..], &repeated, &m735[..1], &[]] {
+			for i in -20..=2200 {
+				let distance = f64::from(i) * 5.0 + 0.25 * f64::from(i % 4);
+				let fast = interpolate_armor_power(table, distance);
+				let slow = linear(table, distance);
+				assert!(fast.to_bits() == slow.to_bits(), "{distance}: {fast} != {slow}");
+			}
+			assert!(interpolate_armor_power(table, f64::NAN).abs() < f64::EPSILON);
+		}
+	}
 }
@@ -0,0 +1,192 @@
+//! Fuzzy comparison of ballistic tables against a reference.
+//!
+//! Shared by the corpus integration tests and `fcsgen compare`.  Rows are
+//! matched positionally and each column is checked against its own absolute
+//! [`Tolerances`] value, which absorbs the minor floating-point differences of
+//! the optimised trajectory engine (algebraic identity replacements and
+//! Taylor-expanded atmospheric density).
+
+use crate::ballistic::strip_table_header;
+
+/// Maximum acceptable differences between a computed and a reference table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerances {
+	/// Distance column (metres).
+	pub dist: f64,
+	/// Time column (seconds).
+	pub time: f64,
+	/// Penetration column (mm).
+	pub pen: f64,
+	/// Row count (extra/missing rows at the end).
+	pub row_count: usize,
+}
+
+impl Default for Tolerances {
+	/// The tolerances the reference corpus is held to.
+	fn default() -> Self {
+		Self {
+			dist: 0.01,
+			time: 0.1,
+			pen: 1.0,
+			row_count: 5,
+		}
+	}
+}
+
+impl Tolerances {
+	/// These tolerances for distances written with `precision` decimals
+	/// (`--distance-precision`): rounding can move a distance by half a unit
+	/// in the last place, on top of the engine differences.
+	#[must_use]
+	#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+	pub fn for_distance_precision(self, precision: usize) -> Self {
+		Self {
+			dist: self.dist + 0.5 * 10f64.powi(-(precision as i32)),
+			..self
+		}
+	}
+}
+
+/// Worst-case deltas accumulated across many comparisons.
+#[derive(Debug, Default)]
+pub struct DeltaStats {
+	pub max_dist: f64,
+	pub max_time: f64,
+	pub max_pen: f64,
+	pub max_row_diff: usize,
+	pub worst_dist_shell: String,
+	pub worst_pen_shell: String,
+	pub worst_row_shell: String,
+}
+
+/// Parse a ballistic TSV row into `(distance, time, penetration)`.
+///
+/// Penetration may be `∞`, which parses as [`f64::INFINITY`].  Returns `None`
+/// for rows with fewer than three columns or non-numeric values.
+#[must_use]
+pub fn parse_row(line: &str) -> Option<(f64, f64, f64)> {
+	let parts: Vec<&str> = line.split('\t').collect();
+	if parts.len() < 3 {
+		return None;
+	}
+	let dist = parts[0].parse::<f64>().ok()?;
+	let time = parts[1].parse::<f64>().ok()?;
+	let pen = if parts[2] == "\u{221E}" {
+		f64::INFINITY
+	} else {
+		parts[2].parse::<f64>().ok()?
+	};
+	Some((dist, time, pen))
+}
+
+/// Compare a computed ballistic TSV against the expected reference using
+/// fuzzy numeric matching.
+///
+/// An optional leading header line (`--with-header`) is skipped on both sides.
+/// `stats` is updated with the deltas seen, even on failure.
+///
+/// # Errors
+///
+/// Returns a description of the first tolerance violation.
+pub fn compare_ballistic_fuzzy(
+	vehicle: &str,
+	shell: &str,
+	computed: &str,
+	expected: &str,
+	tol: &Tolerances,
+	stats: &mut DeltaStats,
+) -> Result<(), String> {
+	let computed = computed.replace("\r\n", "\n");
+	let expected = expected.replace("\r\n", "\n");
+
+	let comp_lines: Vec<&str> = strip_table_header(&computed).lines().collect();
+	let exp_lines: Vec<&str> = strip_table_header(&expected).lines().collect();
+
+	let row_diff = comp_lines.len().abs_diff(exp_lines.len());
+	if row_diff > stats.max_row_diff {
+		stats.max_row_diff = row_diff;
+		stats.worst_row_shell = format!("{vehicle}/{shell}");
+	}
+	if row_diff > tol.row_count {
+		return Err(format!(
+			"{vehicle}/{shell}: row count diff {row_diff} exceeds tolerance {} \
+			 (expected {}, got {})",
+			tol.row_count,
+			exp_lines.len(),
+			comp_lines.len(),
+		));
+	}
+
+	// Compare the overlapping rows
+	for (i, (comp_line, exp_line)) in comp_lines.iter().zip(&exp_lines).enumerate() {
+		let (Some(comp), Some(exp)) = (parse_row(comp_line), parse_row(exp_line)) else {
+			continue;
+		};
+
+		let dd = (comp.0 - exp.0).abs();
+		let dt = (comp.1 - exp.1).abs();
+		let dp = if comp.2.is_infinite() && exp.2.is_infinite() {
+			0.0
+		} else {
+			(comp.2 - exp.2).abs()
+		};
+
+		if dd > stats.max_dist {
+			stats.max_dist = dd;
+			stats.worst_dist_shell = format!("{vehicle}/{shell}");
+		}
+		if dt > stats.max_time {
+			stats.max_time = dt;
+		}
+		if dp > stats.max_pen {
+			stats.max_pen = dp;
+			stats.worst_pen_shell = format!("{vehicle}/{shell}");
+		}
+
+		if dd > tol.dist || dt > tol.time || dp > tol.pen {
+			return Err(format!(
+				"{vehicle}/{shell} line {}: delta dist={dd:.4} time={dt:.2} pen={dp:.1} \
+				 (tol: dist={} time={} pen={})",
+				i + 1,
+				tol.dist,
+				tol.time,
+				tol.pen,
+			));
+		}
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_row() {
+		assert_eq!(parse_row("100\t0.13\t250.5"), Some((100.0, 0.13, 250.5)));
+		assert_eq!(parse_row("0\t0\t\u{221E}"), Some((0.0, 0.0, f64::INFINITY)));
+		assert_eq!(parse_row("100\t0.13"), None);
+		assert_eq!(parse_row("# shell=x\ttype=y\tcaliber=z"), None);
+	}
+
+	#[test]
+	fn test_row_count_tolerance() {
+		let expected = "0\t0\t100\n10\t0.1\t99\n20\t0.2\t98\n";
+		let computed = "0\t0\t100\n";
+		let mut stats = DeltaStats::default();
+
+		let tol = Tolerances {
+			row_count: 2,
+			..Tolerances::default()
+		};
+		assert!(compare_ballistic_fuzzy("v", "s", computed, expected, &tol, &mut stats).is_ok());
+		let tol = Tolerances {
+			row_count: 1,
+			..Tolerances::default()
+		};
+		assert!(compare_ballistic_fuzzy("v", "s", computed, expected, &tol, &mut stats).is_err());
+		assert_eq!(stats.max_row_diff, 2);
+		assert_eq!(stats.worst_row_shell, "v/s");
+	}
+}
@@ -5,10 +5,26 @@
 use std::fmt::Write;
 
 use crate::model::VehicleData;
+use crate::parser::data::DefaultCx;
+
+/// Largest magnitude written to a Data file.  Far beyond any physical
+/// value, but keeps a corrupt datamine entry from printing a 300-digit number.
+const MAX_FLOAT_MAGNITUDE: f64 = 1e12;
 
 /// Format a float value, ensuring it always has a decimal point.
 /// E.g., 960 -> "960.0", 960.5 -> "960.5", 0.389 -> "0.389"
+///
+/// The legacy reader cannot parse exponents, so the output is always
+/// fixed-point: `Display` for `f64` never uses scientific notation and
+/// prints the shortest digits that round-trip (1e-7 -> "0.0000001").
+/// Magnitudes are clamped to [`MAX_FLOAT_MAGNITUDE`], and NaN (which the
+/// reader cannot parse either) is written as 0.
 fn fmt_float(v: f64) -> String {
+	let v = if v.is_nan() {
+		0.0
+	} else {
+		v.clamp(-MAX_FLOAT_MAGNITUDE, MAX_FLOAT_MAGNITUDE)
+	};
 	let s = v.to_string();
 	if s.contains('.') { s } else { format!("{s}.0") }
 }
@@ -22,12 +38,16 @@ fn fmt_float(v: f64) -> String {
 /// ZoomIn:{value}
 /// ZoomOut:{value}
 /// HasLaser  (optional, presence-only flag)
+/// TraverseSpeed:{deg/s}  (optional)
+/// ElevationSpeed:{deg/s}  (optional)
+/// RateOfFire:{rounds/min}  (optional)
 ///
 /// Name:{name}
 /// Type:{type}
 /// BulletMass:{value}
 /// ...
 /// ```
+#[allow(clippy::too_many_lines)]
 pub fn emit_legacy_txt(data: &VehicleData) -> String {
 	let mut out = String::new();
 
@@ -52,6 +72,18 @@ pub fn emit_legacy_txt(data: &VehicleData) -> String {
 		writeln!(out, "HasLaser").unwrap();
 	}
 
+	if let Some(ts) = data.traverse_speed_deg_s {
+		writeln!(out, "TraverseSpeed:{}", fmt_float(ts)).unwrap();
+	}
+
+	if let Some(es) = data.elevation_speed_deg_s {
+		writeln!(out, "ElevationSpeed:{}", fmt_float(es)).unwrap();
+	}
+
+	if let Some(rpm) = data.rate_of_fire_rpm {
+		writeln!(out, "RateOfFire:{}", fmt_float(rpm)).unwrap();
+	}
+
 	// Projectiles
 	for proj in &data.projectiles {
 		writeln!(out).unwrap(); // Blank line before each projectile block
@@ -71,8 +103,8 @@ pub fn emit_legacy_txt(data: &VehicleData) -> String {
 			writeln!(out, "Speed:{}", fmt_float(s)).unwrap();
 		}
 
-		// Cx with default of 0.38 (legacy behavior for rockets/ATGMs without Cx)
-		let cx = proj.cx.unwrap_or(0.38);
+		// Rockets/ATGMs without Cx get a per-caliber default (legacy: 0.38)
+		let cx = proj.cx.unwrap_or_else(|| DefaultCx::default().cx(proj.ballistic_caliber));
 		writeln!(out, "Cx:{}", fmt_float(cx)).unwrap();
 
 		if let Some(em) = proj.explosive_mass {
@@ -83,6 +115,18 @@ pub fn emit_legacy_txt(data: &VehicleData) -> String {
 			writeln!(out, "ExplosiveType:{et}").unwrap();
 		}
 
+		if let Some(r) = proj.blast_radius {
+			writeln!(out, "BlastRadius:{}", fmt_float(r)).unwrap();
+		}
+
+		if let Some(d) = proj.fuze_delay_m {
+			writeln!(out, "FuzeDelay:{}", fmt_float(d)).unwrap();
+		}
+
+		if let Some(s) = proj.fuze_sensitivity {
+			writeln!(out, "FuzeSensitivity:{}", fmt_float(s)).unwrap();
+		}
+
 		if let Some(dm) = proj.damage_mass {
 			writeln!(out, "DamageMass:{}", fmt_float(dm)).unwrap();
 		}
@@ -161,15 +205,25 @@ mod tests {
 			zoom_in: Some(6.0),
 			zoom_out: Some(30.0),
 			has_laser: true,
+			traverse_speed_deg_s: None,
+			elevation_speed_deg_s: None,
+			barrels: 1,
+			rate_of_fire_rpm: None,
 			projectiles: vec![Projectile {
 				name: "test_shell".to_string(),
 				bullet_type: "ap_t".to_string(),
+				is_tracer: true,
+				is_incendiary: false,
+				hull_break: false,
 				mass: Some(10.0),
 				ballistic_caliber: Some(0.1),
 				speed: Some(800.0),
 				cx: Some(0.3),
 				explosive_mass: None,
 				explosive_type: None,
+				blast_radius: None,
+				fuze_delay_m: None,
+				fuze_sensitivity: None,
 				damage_mass: None,
 				damage_caliber: None,
 				demarre: Some(DemarreParams {
@@ -180,6 +234,7 @@ mod tests {
 				}),
 				armor_power: None,
 				armor_power_series: None,
+				unlocked_by_default: true,
 			}],
 		};
 
@@ -202,4 +257,19 @@ mod tests {
 		assert_eq!(fmt_float(0.389), "0.389");
 		assert_eq!(fmt_float(1.0), "1.0");
 	}
+
+	#[test]
+	fn test_fmt_float_is_fixed_point() {
+		assert_eq!(fmt_float(1e-7), "0.0000001");
+		for v in [1e-7, 3.3e-9, 0.000_017, -2.5e-12, 123_456_789.125, 9.9e11] {
+			let s = fmt_float(v);
+			assert!(!s.contains(['e', 'E']), "{v}: {s}");
+			let back: f64 = s.parse().unwrap();
+			assert!((back - v).abs() <= v.abs() * 1e-12, "{v}: {s}");
+		}
+
+		assert_eq!(fmt_float(1e300), "1000000000000.0");
+		assert_eq!(fmt_float(f64::NEG_INFINITY), "-1000000000000.0");
+		assert_eq!(fmt_float(f64::NAN), "0.0");
+	}
 }
@@ -32,6 +32,11 @@ pub enum ParseError {
 	/// Invalid data format.
 	#[error("invalid data format in {context}: {message}")]
 	InvalidFormat { context: String, message: String },
+
+	/// Structural problem in a weapon module (`path` is the JSON path within
+	/// the module, e.g. `30mm_HE/bullet`).
+	#[error("weapon module {path}: {reason}")]
+	WeaponModule { path: String, reason: String },
 }
 
 impl ParseError {
@@ -50,4 +55,12 @@ impl ParseError {
 			source,
 		}
 	}
+
+	/// Create a weapon module error at a JSON path within the module.
+	pub fn weapon_module(path: impl Into<String>, reason: impl Into<String>) -> Self {
+		Self::WeaponModule {
+			path: path.into(),
+			reason: reason.into(),
+		}
+	}
 }
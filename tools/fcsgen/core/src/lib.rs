@@ -8,20 +8,93 @@
 //! See the CLI crate (`fcsgen`) for the command-line interface.
 
 pub mod ballistic;
+pub mod compare;
 pub mod emit;
 pub mod error;
 pub mod model;
 pub mod parser;
+pub mod table;
 
-pub use ballistic::{BallisticCache, BallisticKey, compute_ballistic, compute_ballistic_cached};
+pub use ballistic::{
+	AngleUnit,
+	BallisticCache,
+	BallisticConfig,
+	BallisticKey,
+	BallisticWarning,
+	PenRounding,
+	PenetrationUnit,
+	RangeSampling,
+	TargetMaterial,
+	TimeUnit,
+	compute_ballistic,
+	compute_ballistic_both,
+	compute_ballistic_cached,
+	compute_ballistic_cached_with_config,
+	compute_ballistic_cached_with_warnings,
+	compute_ballistic_with_config,
+	compute_ballistic_with_warnings,
+	compute_elevation_table,
+	compute_max_direct_range,
+	estimates_penetration,
+	is_zero_penetration,
+	seed_cache,
+	seed_cache_from_reader,
+	strip_table_header,
+	table_header,
+	table_header_with_marks,
+	write_cache,
+	write_cache_to,
+	write_elevation_table,
+};
+pub use compare::{DeltaStats, Tolerances, compare_ballistic_fuzzy};
 pub use emit::emit_legacy_txt;
 pub use error::{ParseError, Result};
-pub use model::{Projectile, VehicleData};
-pub use parser::data::{from_projectile, parse_data_file, parse_data_text};
-pub use parser::{UnittagsMap, lookup_vehicle_id, parse_unittags, parse_unittags_str, parse_vehicle, parse_weapon_module};
+pub use model::{Projectile, RangefinderKind, VehicleData, VehicleSummary};
+pub use parser::data::{
+	DefaultCx,
+	UNIFORM_CX,
+	caliber_m_from_mm,
+	caliber_mm,
+	fill_default_cx,
+	from_projectile,
+	is_hull_break_capable,
+	parse_data_file,
+	parse_data_text,
+};
+pub use parser::{
+	BeltBullets,
+	CxArrayPolicy,
+	LangTable,
+	ParseOptions,
+	RankMap,
+	UnittagsMap,
+	VehicleRank,
+	battle_rating,
+	count_weapon_module_shells,
+	lookup_vehicle_id,
+	parse_unittags,
+	parse_unittags_str,
+	parse_vehicle,
+	parse_weapon_module,
+	parse_weapon_module_with_options,
+	parse_weapon_module_with_warnings,
+	parse_wpcost,
+	parse_wpcost_str,
+	rate_of_fire_rpm,
+	vehicle_nation,
+};
+pub use table::{
+	BallisticRow,
+	drops_at_standard_ranges,
+	parse_table,
+	penetration_at,
+	penetration_delta,
+	suggest_zero_distance,
+	time_at,
+};
 
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub const VERSION: &str = env!("PROJECT_VERSION");
 
@@ -41,6 +114,20 @@ pub type Datamine = HashMap<String, String>;
 /// # Returns
 /// A `VehicleData` struct with all header and projectile data.
 pub fn convert_vehicle(vehicle_path: &Path, datamine_root: &Path) -> Result<VehicleData> {
+	convert_vehicle_with_warnings(vehicle_path, datamine_root, &mut Vec::new())
+}
+
+/// Same as [`convert_vehicle`], additionally pushing onto `warnings` every
+/// malformed weapon module entry that was dropped; see
+/// [`parse_weapon_module_with_warnings`].
+///
+/// # Errors
+/// Same as [`convert_vehicle`].
+pub fn convert_vehicle_with_warnings(
+	vehicle_path: &Path,
+	datamine_root: &Path,
+	warnings: &mut Vec<ParseError>,
+) -> Result<VehicleData> {
 	// Parse vehicle file
 	let vehicle_json = read_json_file(vehicle_path)?;
 	let vehicle_id = vehicle_path
@@ -48,16 +135,91 @@ pub fn convert_vehicle(vehicle_path: &Path, datamine_root: &Path) -> Result<Vehi
 		.and_then(|s| s.to_str())
 		.unwrap_or("unknown");
 
-	convert_vehicle_impl(vehicle_id, &vehicle_json, &|weapon_path| {
+	let resolve_related = |weapon_path: &str| {
 		let full_path = resolve_weapon_path(datamine_root, weapon_path);
 		if full_path.exists() {
 			std::fs::read_to_string(&full_path).ok()
 		} else {
 			None
 		}
+	};
+	convert_vehicle_impl(vehicle_id, &vehicle_json, &resolve_related, warnings)
+}
+
+/// Summarize a vehicle without converting it.
+///
+/// Reads the same weapon and rocket modules as [`convert_vehicle`], but only
+/// counts their shells instead of extracting each one's physics.  Meant for
+/// vehicle pickers that list the whole datamine.
+///
+/// # Arguments
+/// * `vehicle_json` - The parsed JSON content of the vehicle file
+/// * `vehicle_id` - The vehicle identifier (basename without extension)
+/// * `datamine_root` - Root path of the datamine (contains `aces.vromfs.bin_u/`)
+///
+/// # Errors
+/// If the vehicle or one of its modules is not valid JSON.
+pub fn summarize_vehicle(
+	vehicle_json: &serde_json::Value,
+	vehicle_id: &str,
+	datamine_root: &Path,
+) -> Result<VehicleSummary> {
+	let data = parse_vehicle(vehicle_json, vehicle_id)?;
+
+	let mut shell_count = 0;
+	for module_path in data.weapon_path.iter().chain(&data.rocket_paths) {
+		let full_path = resolve_weapon_path(datamine_root, module_path);
+		let Ok(content) = std::fs::read_to_string(&full_path) else {
+			continue;
+		};
+		let module_json: serde_json::Value = serde_json::from_str(&content)
+			.map_err(|e| ParseError::json(module_path.as_str(), e))?;
+		shell_count += count_weapon_module_shells(&module_json, Some(vehicle_json));
+	}
+
+	Ok(VehicleSummary {
+		id: vehicle_id.to_owned(),
+		armed: shell_count > 0,
+		shell_count,
+		has_laser: data.has_laser,
+		nation: vehicle_nation(vehicle_id).map(str::to_owned),
 	})
 }
 
+/// Weapon and rocket module files that [`convert_vehicle`] would read for a
+/// vehicle, resolved against `datamine_root`.  They need not exist.
+///
+/// # Errors
+/// If the vehicle file cannot be read or parsed.
+pub fn vehicle_module_paths(vehicle_path: &Path, datamine_root: &Path) -> Result<Vec<PathBuf>> {
+	let vehicle_json = read_json_file(vehicle_path)?;
+	let data = parse_vehicle(&vehicle_json, "")?;
+	Ok(data
+		.weapon_path
+		.iter()
+		.chain(&data.rocket_paths)
+		.map(|path| resolve_weapon_path(datamine_root, path))
+		.collect())
+}
+
+/// [`Datamine`] keys of the weapon and rocket modules that
+/// [`convert_vehicle_in_memory`] would read for a vehicle.  They need not be
+/// present.
+///
+/// # Errors
+/// If `vehicle_content` is not a valid vehicle file.
+pub fn vehicle_module_keys(vehicle_content: &str) -> Result<Vec<String>> {
+	let vehicle_json: serde_json::Value = serde_json::from_str(vehicle_content)
+		.map_err(|e| ParseError::json("<memory>", e))?;
+	let data = parse_vehicle(&vehicle_json, "")?;
+	Ok(data
+		.weapon_path
+		.iter()
+		.chain(&data.rocket_paths)
+		.map(|path| datamine_key(path))
+		.collect())
+}
+
 /// Convert a vehicle from in-memory datamine data.
 ///
 /// Same as [`convert_vehicle`] but reads all related files from an in-memory
@@ -71,24 +233,66 @@ pub fn convert_vehicle_in_memory(
 	vehicle_id: &str,
 	vehicle_content: &str,
 	datamine: &Datamine,
+) -> Result<VehicleData> {
+	convert_vehicle_in_memory_with_warnings(vehicle_id, vehicle_content, datamine, &mut Vec::new())
+}
+
+/// Same as [`convert_vehicle_in_memory`], additionally pushing onto
+/// `warnings` every malformed weapon module entry that was dropped; see
+/// [`parse_weapon_module_with_warnings`].
+///
+/// # Errors
+/// Same as [`convert_vehicle_in_memory`].
+pub fn convert_vehicle_in_memory_with_warnings(
+	vehicle_id: &str,
+	vehicle_content: &str,
+	datamine: &Datamine,
+	warnings: &mut Vec<ParseError>,
 ) -> Result<VehicleData> {
 	let vehicle_json: serde_json::Value = serde_json::from_str(vehicle_content)
 		.map_err(|e| ParseError::json(format!("<memory>/{vehicle_id}.blkx"), e))?;
 
-	convert_vehicle_impl(vehicle_id, &vehicle_json, &|weapon_path| {
-		let key = weapon_path.replace('\\', "/").to_lowercase();
-		datamine.get(&key).cloned()
+	let resolve_related =
+		|weapon_path: &str| datamine.get(&datamine_key(weapon_path)).cloned();
+	convert_vehicle_impl(vehicle_id, &vehicle_json, &resolve_related, warnings)
+}
+
+/// Every `(vehicle_id, projectile)` pair of a [`Datamine`], for analysis
+/// across all vehicles.
+///
+/// Vehicles are the top-level tankmodels, in key order.  Each is converted
+/// with [`convert_vehicle_in_memory`] only when the iterator reaches it.
+/// Unarmed vehicles yield nothing, and vehicles that fail to convert are
+/// skipped; convert them directly to see the error.
+pub fn iter_all_projectiles(datamine: &Datamine) -> impl Iterator<Item = (String, Projectile)> {
+	let mut vehicles: Vec<(&str, &str)> = datamine
+		.iter()
+		.filter_map(|(key, content)| {
+			let file = key.strip_prefix("gamedata/units/tankmodels/")?;
+			let id = file.strip_suffix(".blkx").filter(|id| !id.contains('/'))?;
+			Some((id, content.as_str()))
+		})
+		.collect();
+	vehicles.sort_unstable_by_key(|&(id, _)| id);
+
+	vehicles.into_iter().flat_map(|(id, content)| {
+		let projectiles = convert_vehicle_in_memory(id, content, datamine)
+			.map(|data| data.projectiles)
+			.unwrap_or_default();
+		projectiles.into_iter().map(move |proj| (id.to_owned(), proj))
 	})
 }
 
 /// Shared implementation for vehicle conversion.
 ///
 /// `resolve_related` looks up a weapon/rocket .blkx path and returns its JSON
-/// content string, or `None` if the file is not found.
+/// content string, or `None` if the file is not found.  Entries the module
+/// parser drops as malformed are pushed onto `warnings`.
 fn convert_vehicle_impl(
 	vehicle_id: &str,
 	vehicle_json: &serde_json::Value,
 	resolve_related: &dyn Fn(&str) -> Option<String>,
+	warnings: &mut Vec<ParseError>,
 ) -> Result<VehicleData> {
 	let mut data = parse_vehicle(vehicle_json, vehicle_id)?;
 
@@ -98,8 +302,15 @@ fn convert_vehicle_impl(
 	{
 		let weapon_json: serde_json::Value = serde_json::from_str(&content)
 			.map_err(|e| ParseError::json(weapon_path.as_str(), e))?;
-		let projectiles = parse_weapon_module(&weapon_json, Some(vehicle_json))?;
+		let projectiles = parse_weapon_module_with_warnings(
+			&weapon_json,
+			Some(vehicle_json),
+			ParseOptions::default(),
+			warnings,
+		)?;
 		data.projectiles.extend(projectiles);
+		data.rate_of_fire_rpm =
+			rate_of_fire_rpm(&weapon_json).map(|rpm| rpm * f64::from(data.barrels));
 	}
 
 	// Parse rocket modules and collect projectiles
@@ -107,7 +318,12 @@ fn convert_vehicle_impl(
 		if let Some(content) = resolve_related(&rocket_path) {
 			let rocket_json: serde_json::Value = serde_json::from_str(&content)
 				.map_err(|e| ParseError::json(rocket_path.as_str(), e))?;
-			let projectiles = parse_weapon_module(&rocket_json, Some(vehicle_json))?;
+			let projectiles = parse_weapon_module_with_warnings(
+				&rocket_json,
+				Some(vehicle_json),
+				ParseOptions::default(),
+				warnings,
+			)?;
 			data.projectiles.extend(projectiles);
 		}
 	}
@@ -121,14 +337,30 @@ fn read_json_file(path: &Path) -> Result<serde_json::Value> {
 	serde_json::from_str(&content).map_err(|e| ParseError::json(path, e))
 }
 
+/// Canonical [`Datamine`] key of a module path: lowercase, `/`-separated and
+/// rooted at `gamedata/`.
+///
+/// Vehicle files mostly name modules `gameData/Weapons/...`, but some give
+/// them relative to `gameData` (`Weapons/...`) or with a leading slash; all
+/// forms map to the same key.
+#[must_use]
+pub fn datamine_key(path: &str) -> String {
+	let path = path.replace('\\', "/").to_lowercase();
+	let path = path.trim_start_matches("./").trim_start_matches('/');
+	if path.starts_with("gamedata/") {
+		path.to_owned()
+	} else {
+		format!("gamedata/{path}")
+	}
+}
+
 /// Resolve a weapon path relative to the datamine root.
 ///
 /// Weapon paths in vehicle files look like "gameData/Weapons/..."
-/// and need to be resolved relative to the aces.vromfs.bin_u directory.
-fn resolve_weapon_path(datamine_root: &Path, weapon_path: &str) -> std::path::PathBuf {
-	// Normalize path separators and case for cross-platform compatibility
-	let normalized = weapon_path.replace('\\', "/").to_lowercase();
-	datamine_root.join("aces.vromfs.bin_u").join(&normalized)
+/// and need to be resolved relative to the aces.vromfs.bin_u directory;
+/// see [`datamine_key`] for the other forms.
+fn resolve_weapon_path(datamine_root: &Path, weapon_path: &str) -> PathBuf {
+	datamine_root.join("aces.vromfs.bin_u").join(datamine_key(weapon_path))
 }
 
 #[cfg(test)]
@@ -139,4 +371,89 @@ mod tests {
 	fn version_is_set() {
 		assert!(!VERSION.is_empty());
 	}
+
+	#[test]
+	fn test_twin_mount_doubles_rate_of_fire() {
+		let weapon = r#"{
+			"shotFreq": 0.1,
+			"bullet": {"bulletName": "57mm_br_281", "bulletType": "apcbc_tank"}
+		}"#;
+		let datamine: Datamine = [(
+			"gamedata/weapons/groundmodels_weapons/57mm_s60_user_cannon.blkx".to_owned(),
+			weapon.to_owned(),
+		)]
+		.into();
+		let mount = |entries: usize| {
+			let entry = serde_json::json!({
+				"trigger": "gunner0",
+				"blk": "gameData/Weapons/groundModels_weapons/57mm_s60_user_cannon.blk"
+			});
+			let vehicle = serde_json::json!({"commonWeapons": {"Weapon": vec![entry; entries]}});
+			convert_vehicle_in_memory("test", &vehicle.to_string(), &datamine).unwrap()
+		};
+
+		let single = mount(1);
+		let twin = mount(2);
+		assert_eq!((single.barrels, twin.barrels), (1, 2));
+		let single_rpm = single.rate_of_fire_rpm.unwrap();
+		let twin_rpm = twin.rate_of_fire_rpm.unwrap();
+		assert!((single_rpm - 6.0).abs() < 1e-9, "{single_rpm}");
+		assert!((twin_rpm / single_rpm - 2.0).abs() < 1e-9, "{twin_rpm}");
+		assert!(emit_legacy_txt(&twin).contains("RateOfFire:12.0\n"));
+	}
+
+	#[test]
+	fn test_conversion_returns_weapon_module_warnings() {
+		let weapon = r#"{
+			"bullet": {"bulletName": "75mm_pzgr_39", "bulletType": "apcbc_tank"},
+			"75mm_empty_belt": {"shotFreq": 5.0}
+		}"#;
+		let key = "gamedata/weapons/groundmodels_weapons/75mm_kwk40.blkx";
+		let datamine: Datamine = [(key.to_owned(), weapon.to_owned())].into();
+		// Belts are read only if the vehicle names them
+		let vehicle = serde_json::json!({
+			"commonWeapons": {"Weapon": {
+				"blk": "gameData/Weapons/groundModels_weapons/75mm_kwk40.blk"
+			}},
+			"modifications": {"75mm_empty_belt": {}}
+		});
+
+		let mut warnings = Vec::new();
+		let data = convert_vehicle_in_memory_with_warnings(
+			"test",
+			&vehicle.to_string(),
+			&datamine,
+			&mut warnings,
+		)
+		.unwrap();
+		assert_eq!(data.projectiles.len(), 1);
+		let messages: Vec<String> = warnings.iter().map(ToString::to_string).collect();
+		assert_eq!(messages, ["weapon module 75mm_empty_belt: belt has no bullet"]);
+	}
+
+	#[test]
+	fn test_rooted_and_relative_weapon_paths_share_a_key() {
+		let key = "gamedata/weapons/groundmodels_weapons/75mm_kwk40.blkx";
+		for path in [
+			"gameData/Weapons/groundModels_weapons/75mm_kwk40.blkx",
+			"/gameData/Weapons/groundModels_weapons/75mm_kwk40.blkx",
+			"Weapons/groundModels_weapons/75mm_kwk40.blkx",
+			"weapons\\groundmodels_weapons\\75mm_kwk40.blkx",
+		] {
+			assert_eq!(datamine_key(path), key, "{path}");
+		}
+
+		let weapon = r#"{"bullet": {"bulletName": "75mm_pzgr_39", "bulletType": "apcbc_tank"}}"#;
+		let datamine: Datamine = [(key.to_owned(), weapon.to_owned())].into();
+		for blk in [
+			"gameData/Weapons/groundModels_weapons/75mm_kwk40.blk",
+			"Weapons/groundModels_weapons/75mm_kwk40.blk",
+		] {
+			let vehicle = serde_json::json!({"commonWeapons": {"Weapon": {"blk": blk}}});
+			let content = vehicle.to_string();
+			let data = convert_vehicle_in_memory("test", &content, &datamine).unwrap();
+			assert_eq!(data.projectiles.len(), 1, "{blk}");
+			assert_eq!(vehicle_module_keys(&content).unwrap(), [key]);
+		}
+	}
 }
@@ -3,8 +3,14 @@
 //! These structs represent the intermediate data extracted from datamine files,
 //! used for ballistic computation and sight generation.
 
+use std::fmt;
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
 
+use crate::ballistic::should_skip;
+use crate::parser::data::normalize_shell_type;
+
 /// Complete vehicle data extracted from datamine, ready for emission.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VehicleData {
@@ -27,12 +33,47 @@ pub struct VehicleData {
 	/// Whether the vehicle has a laser rangefinder.
 	pub has_laser: bool,
 
+	/// Turret traverse speed of the primary weapon (°/s).
+	pub traverse_speed_deg_s: Option<f64>,
+
+	/// Gun elevation speed of the primary weapon (°/s).
+	pub elevation_speed_deg_s: Option<f64>,
+
+	/// Barrels of the primary weapon fired by one trigger pull (1 for a
+	/// single gun, 2 for twin mounts such as the ZSU-57-2).
+	pub barrels: u32,
+
+	/// Effective rate of fire of the primary weapon (rounds/min, all
+	/// barrels); metadata only.
+	pub rate_of_fire_rpm: Option<f64>,
+
 	/// Projectiles from all weapon modules.
 	pub projectiles: Vec<Projectile>,
 }
 
+/// Cheap overview of a vehicle, for listing vehicles without converting them
+/// (see [`summarize_vehicle`](crate::summarize_vehicle)).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VehicleSummary {
+	/// Vehicle identifier, as passed in.
+	pub id: String,
+
+	/// Whether [`VehicleData::is_armed`] would hold after conversion.
+	pub armed: bool,
+
+	/// Number of projectiles conversion would produce.
+	pub shell_count: usize,
+
+	/// Whether the vehicle has a laser rangefinder.
+	pub has_laser: bool,
+
+	/// Nation from the vehicle ID prefix (e.g. `"ussr"`), if known.
+	pub nation: Option<String>,
+}
+
 /// A single projectile (bullet, shell, or rocket/missile).
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)] // independent per-shell flags
 pub struct Projectile {
 	/// Projectile name (e.g. "30mm_UBR6").
 	pub name: String,
@@ -40,6 +81,16 @@ pub struct Projectile {
 	/// Projectile type (e.g. "ap_t", "he_frag_i", "apds_fs_long_tank").
 	pub bullet_type: String,
 
+	/// Tracer round (`t` segment in `bullet_type`, e.g. `ap_t`).
+	pub is_tracer: bool,
+
+	/// Incendiary round (`i` segment in `bullet_type`, e.g. `he_frag_i`).
+	pub is_incendiary: bool,
+
+	/// Likely to hull-break light vehicles; a heuristic, see
+	/// [`is_hull_break_capable`](crate::parser::data::is_hull_break_capable).
+	pub hull_break: bool,
+
 	/// Mass in kg.
 	pub mass: Option<f64>,
 
@@ -58,6 +109,15 @@ pub struct Projectile {
 	/// Explosive type (e.g. "a_ix_2", "ocfol").
 	pub explosive_type: Option<String>,
 
+	/// Blast radius of the explosive in meters (`damage.explosive.radius`).
+	pub blast_radius: Option<f64>,
+
+	/// Fuze delay in meters travelled after impact (`fuseDelayDist`).
+	pub fuze_delay_m: Option<f64>,
+
+	/// Armor thickness in mm that triggers the fuze (`explodeTreshold`).
+	pub fuze_sensitivity: Option<f64>,
+
 	/// Damage mass for sub-caliber rounds.
 	pub damage_mass: Option<f64>,
 
@@ -72,6 +132,12 @@ pub struct Projectile {
 
 	/// Armor power series for APDS/APFSDS (distance -> penetration).
 	pub armor_power_series: Option<ArmorPowerSeries>,
+
+	/// Whether the round is available on a stock vehicle.
+	///
+	/// `false` for belts gated behind an entry in the vehicle's
+	/// `modifications` block (i.e. ammo that must be researched).
+	pub unlocked_by_default: bool,
 }
 
 /// DeMarre penetration formula parameters.
@@ -111,6 +177,10 @@ impl VehicleData {
 			zoom_in: None,
 			zoom_out: None,
 			has_laser: false,
+			traverse_speed_deg_s: None,
+			elevation_speed_deg_s: None,
+			barrels: 1,
+			rate_of_fire_rpm: None,
 			projectiles: Vec::new(),
 		}
 	}
@@ -123,4 +193,69 @@ impl VehicleData {
 	pub fn is_armed(&self) -> bool {
 		!self.projectiles.is_empty()
 	}
+
+	/// How the gunner finds the range; see [`RangefinderKind::detect`].
+	#[must_use]
+	pub fn rangefinder_kind(&self) -> RangefinderKind {
+		let types: Vec<String> =
+			self.projectiles.iter().map(|p| normalize_shell_type(&p.bullet_type)).collect();
+		RangefinderKind::detect(self.has_laser, types.iter().map(String::as_str))
+	}
+}
+
+/// How a vehicle's gunner finds the range, which decides the in-game sight
+/// setup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RangefinderKind {
+	/// Laser rangefinder (`HasLaser`).
+	Laser,
+	/// No laser: the range is set by scrolling the sight.
+	Scroll,
+	/// Nothing to range: every shell is a skipped type (ATGM/SAM carriers).
+	None,
+}
+
+impl RangefinderKind {
+	/// Classify a vehicle from its laser flag and the normalized types of its
+	/// shells.  A vehicle without a single computed shell is
+	/// [`None`](Self::None), laser or not.
+	#[must_use]
+	pub fn detect<'a>(
+		has_laser: bool,
+		normalized_types: impl IntoIterator<Item = &'a str>,
+	) -> Self {
+		if normalized_types.into_iter().all(should_skip) {
+			Self::None
+		} else if has_laser {
+			Self::Laser
+		} else {
+			Self::Scroll
+		}
+	}
+}
+
+impl FromStr for RangefinderKind {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"laser" => Ok(Self::Laser),
+			"scroll" => Ok(Self::Scroll),
+			"none" => Ok(Self::None),
+			_ => Err(format!(
+				"unknown rangefinder kind `{s}` (expected `laser`, `scroll` or `none`)"
+			)),
+		}
+	}
+}
+
+impl fmt::Display for RangefinderKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::Laser => "laser",
+			Self::Scroll => "scroll",
+			Self::None => "none",
+		})
+	}
 }
@@ -6,7 +6,7 @@
 use std::collections::HashMap;
 use std::path::Path;
 
-use crate::model::{ArmorPowerSeries, Projectile};
+use crate::model::{ArmorPowerSeries, Projectile, RangefinderKind};
 
 /// Parsed vehicle data from a `Data/{vehicle}.txt` file.
 #[derive(Debug, Clone)]
@@ -29,10 +29,28 @@ pub struct DataFile {
 	/// Whether the vehicle has a laser rangefinder.
 	pub has_laser: bool,
 
+	/// Turret traverse speed (°/s).
+	pub traverse_speed_deg_s: Option<f64>,
+
+	/// Gun elevation speed (°/s).
+	pub elevation_speed_deg_s: Option<f64>,
+
+	/// Effective rate of fire of the primary weapon (rounds/min).
+	pub rate_of_fire_rpm: Option<f64>,
+
 	/// Parsed projectile blocks.
 	pub projectiles: Vec<DataProjectile>,
 }
 
+impl DataFile {
+	/// How the gunner finds the range; see [`RangefinderKind::detect`].
+	#[must_use]
+	pub fn rangefinder_kind(&self) -> RangefinderKind {
+		let types = self.projectiles.iter().map(|p| p.normalized_type.as_str());
+		RangefinderKind::detect(self.has_laser, types)
+	}
+}
+
 /// A single projectile block from a `Data/{vehicle}.txt` file.
 #[derive(Debug, Clone)]
 pub struct DataProjectile {
@@ -48,6 +66,12 @@ pub struct DataProjectile {
 	/// Otherwise it is the first `_`-delimited segment (e.g. "apcbc", "he", "heat").
 	pub normalized_type: String,
 
+	/// Tracer round; see [`is_tracer_type`].
+	pub is_tracer: bool,
+
+	/// Incendiary round; see [`is_incendiary_type`].
+	pub is_incendiary: bool,
+
 	/// Projectile mass in kg.
 	pub mass: f64,
 
@@ -63,6 +87,26 @@ pub struct DataProjectile {
 	/// Explosive filler mass in kg.
 	pub explosive_mass: f64,
 
+	/// Explosive type (e.g. `a_ix_2`, `ocfol`), if the shell has a filler.
+	///
+	/// Not used by the trajectory simulation and therefore not part of
+	/// [`BallisticKey`](crate::ballistic::BallisticKey).
+	pub explosive_type: Option<String>,
+
+	/// Blast radius of the explosive in meters, if known.
+	///
+	/// Metadata for a future HE splash-ring sight element; like
+	/// `explosive_type` it is not part of the `BallisticKey`.
+	pub blast_radius: Option<f64>,
+
+	/// Fuze delay in meters after impact, if known.
+	///
+	/// APHE behavior notes for players; not part of the `BallisticKey`.
+	pub fuze_delay_m: Option<f64>,
+
+	/// Armor thickness in mm that arms the fuze, if known.
+	pub fuze_sensitivity: Option<f64>,
+
 	/// Sub-caliber core mass in kg (APCR/APDS).
 	pub damage_mass: f64,
 
@@ -83,8 +127,8 @@ pub struct DataProjectile {
 
 	/// APDS-FS armor power lookup table: `(distance_m, penetration_mm)` pairs.
 	///
-	/// Populated from `APDS{distance}:{penetration}` lines in the data file.
-	/// Empty for non-APDS-FS types.
+	/// Populated from `APDS{distance}:{penetration}` lines in the data file,
+	/// sorted by distance.  Empty for non-APDS-FS types.
 	pub armor_power_table: Vec<(f64, f64)>,
 
 	/// Shell name cleaned for output filename.
@@ -119,6 +163,9 @@ pub fn parse_data_text(content: &str, vehicle_id: &str) -> DataFile {
 	let mut zoom_in = None;
 	let mut zoom_out = None;
 	let mut has_laser = false;
+	let mut traverse_speed_deg_s = None;
+	let mut elevation_speed_deg_s = None;
+	let mut rate_of_fire_rpm = None;
 	let mut projectiles = Vec::new();
 
 	// Split into sections by blank lines
@@ -134,6 +181,9 @@ pub fn parse_data_text(content: &str, vehicle_id: &str) -> DataFile {
 					"RocketPath" => rocket_paths.push(value.to_owned()),
 					"ZoomIn" => zoom_in = value.parse().ok(),
 					"ZoomOut" => zoom_out = value.parse().ok(),
+					"TraverseSpeed" => traverse_speed_deg_s = value.parse().ok(),
+					"ElevationSpeed" => elevation_speed_deg_s = value.parse().ok(),
+					"RateOfFire" => rate_of_fire_rpm = value.parse().ok(),
 					_ => {},
 				}
 			} else if line == "HasLaser" {
@@ -156,6 +206,9 @@ pub fn parse_data_text(content: &str, vehicle_id: &str) -> DataFile {
 		zoom_in,
 		zoom_out,
 		has_laser,
+		traverse_speed_deg_s,
+		elevation_speed_deg_s,
+		rate_of_fire_rpm,
 		projectiles,
 	}
 }
@@ -196,6 +249,10 @@ fn parse_projectile_block(block: &str) -> Option<DataProjectile> {
 	let speed = parse_f64(fields.get("Speed").copied());
 	let cx = parse_f64(fields.get("Cx").copied());
 	let explosive_mass = parse_f64(fields.get("ExplosiveMass").copied());
+	let explosive_type = fields.get("ExplosiveType").map(|&v| v.to_owned());
+	let blast_radius = fields.get("BlastRadius").and_then(|v| v.parse().ok());
+	let fuze_delay_m = fields.get("FuzeDelay").and_then(|v| v.parse().ok());
+	let fuze_sensitivity = fields.get("FuzeSensitivity").and_then(|v| v.parse().ok());
 	let damage_mass = parse_f64(fields.get("DamageMass").copied());
 	let damage_caliber = parse_f64(fields.get("DamageCaliber").copied());
 	let demarre_k = parse_f64(fields.get("demarrePenetrationK").copied());
@@ -211,6 +268,8 @@ fn parse_projectile_block(block: &str) -> Option<DataProjectile> {
 
 	Some(DataProjectile {
 		name,
+		is_tracer: is_tracer_type(&bullet_type),
+		is_incendiary: is_incendiary_type(&bullet_type),
 		bullet_type,
 		normalized_type,
 		mass,
@@ -218,6 +277,10 @@ fn parse_projectile_block(block: &str) -> Option<DataProjectile> {
 		speed,
 		cx,
 		explosive_mass,
+		explosive_type,
+		blast_radius,
+		fuze_delay_m,
+		fuze_sensitivity,
 		damage_mass,
 		damage_caliber,
 		demarre_k,
@@ -246,6 +309,41 @@ pub fn normalize_shell_type(raw_type: &str) -> String {
 		.to_owned()
 }
 
+/// Whether a raw type string marks a tracer round (`t` segment, e.g.
+/// `ap_t`, `he_frag_i_t`).  `_tank` suffixes do not count.
+#[must_use]
+pub fn is_tracer_type(raw_type: &str) -> bool {
+	raw_type.split('_').any(|segment| segment == "t")
+}
+
+/// Whether a raw type string marks an incendiary round (`i` segment, e.g.
+/// `he_frag_i`).
+#[must_use]
+pub fn is_incendiary_type(raw_type: &str) -> bool {
+	raw_type.split('_').any(|segment| segment == "i")
+}
+
+/// Largest ballistic caliber (m) of [`is_hull_break_capable`] rounds.
+const HULL_BREAK_MAX_CALIBER: f64 = 0.040;
+
+/// Smallest explosive filler (kg) of [`is_hull_break_capable`] rounds.
+const HULL_BREAK_MIN_FILLER: f64 = 0.010;
+
+/// Whether a round is likely to hull-break light vehicles: an HE round
+/// (`he`, `he_frag_i`, ..., not HEAT) of autocannon caliber, at most 40 mm,
+/// with at least 10 g of filler.
+///
+/// A heuristic on the type, caliber (m) and filler mass (kg) only: the game
+/// also weighs the target's armor, which a shell cannot know.  Metadata for
+/// notes in shell listings; the ballistics do not use it.
+#[must_use]
+pub fn is_hull_break_capable(raw_type: &str, caliber: f64, explosive_mass: f64) -> bool {
+	raw_type.split('_').next() == Some("he")
+		&& caliber > 0.0
+		&& caliber <= HULL_BREAK_MAX_CALIBER
+		&& explosive_mass >= HULL_BREAK_MIN_FILLER
+}
+
 /// Clean a shell name for use as an output filename.
 ///
 /// Strips the caliber prefix (everything up to and including "mm_")
@@ -263,16 +361,78 @@ pub fn clean_shell_name(name: &str) -> String {
 	}
 }
 
+/// Millimetres per metre, for converting datamine calibers.
+const MM_PER_M: f64 = 1000.0;
+
+/// Ballistic caliber of `proj` in millimetres (e.g. 105.0 for a 0.105 m
+/// round).  Unrounded; callers that group by caliber round it themselves.
+#[must_use]
+pub fn caliber_mm(proj: &DataProjectile) -> f64 {
+	proj.ballistic_caliber * MM_PER_M
+}
+
+/// Convert a caliber in millimetres to the metres used by the datamine.
+#[must_use]
+pub fn caliber_m_from_mm(mm: f64) -> f64 {
+	mm / MM_PER_M
+}
+
 /// Parse a string as f64, returning 0.0 on failure or None.
 fn parse_f64(s: Option<&str>) -> f64 {
 	s.and_then(|v| v.parse().ok()).unwrap_or(0.0)
 }
 
-/// Default Cx value for projectiles without an explicit drag coefficient.
+/// Cx of the legacy emitter for every projectile without an explicit drag
+/// coefficient (rockets/ATGMs and other shells missing a Cx field).
+pub const UNIFORM_CX: f64 = 0.38;
+
+/// Approximate Cx of shells without one, by ballistic caliber: `(upper
+/// bound in m, Cx)`, first match wins; [`CX_ABOVE_TABLE`] beyond the last.
+///
+/// A rough approximation, not fitted to the datamine: small boat-tailed
+/// bullets are sleeker than autocannon rounds, and large blunt HE shells
+/// drag the most.
+const CX_BY_CALIBER: [(f64, f64); 4] =
+	[(0.015, 0.30), (0.040, 0.35), (0.090, 0.38), (0.130, 0.42)];
+
+/// Cx for calibers above the last [`CX_BY_CALIBER`] entry.
+const CX_ABOVE_TABLE: f64 = 0.45;
+
+/// How a missing `Cx` is filled in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DefaultCx {
+	/// Per caliber, from a small table of approximations.
+	#[default]
+	ByCaliber,
+	/// [`UNIFORM_CX`] for every shell (`--uniform-cx`), as the legacy tool
+	/// and the reference corpus do.
+	Uniform,
+}
+
+impl DefaultCx {
+	/// Cx for a shell of `caliber` (m) without one.  Shells of unknown
+	/// caliber get [`UNIFORM_CX`].
+	#[must_use]
+	pub fn cx(self, caliber: Option<f64>) -> f64 {
+		match (self, caliber) {
+			(Self::ByCaliber, Some(caliber)) if caliber > 0.0 => CX_BY_CALIBER
+				.iter()
+				.find(|&&(bound, _)| caliber < bound)
+				.map_or(CX_ABOVE_TABLE, |&(_, cx)| cx),
+			_ => UNIFORM_CX,
+		}
+	}
+}
+
+/// Give every projectile without a `cx` the default of `policy`.
 ///
-/// Matches the legacy emitter behaviour: rockets/ATGMs and other shells
-/// missing a Cx field get 0.38 written into `Data/*.txt`.
-const DEFAULT_CX: f64 = 0.38;
+/// [`from_projectile`] and the Data emitter fall back to
+/// [`DefaultCx::ByCaliber`]; call this first for another policy.
+pub fn fill_default_cx(projectiles: &mut [Projectile], policy: DefaultCx) {
+	for proj in projectiles.iter_mut().filter(|p| p.cx.is_none()) {
+		proj.cx = Some(policy.cx(proj.ballistic_caliber));
+	}
+}
 
 /// Fixed distance steps for the `ArmorPowerSeries` → table conversion.
 #[allow(clippy::type_complexity)]
@@ -331,11 +491,17 @@ pub fn from_projectile(proj: &Projectile) -> DataProjectile {
 		name: proj.name.clone(),
 		bullet_type: proj.bullet_type.clone(),
 		normalized_type,
+		is_tracer: proj.is_tracer,
+		is_incendiary: proj.is_incendiary,
 		mass: proj.mass.unwrap_or(0.0),
 		ballistic_caliber: proj.ballistic_caliber.unwrap_or(0.0),
 		speed: proj.speed.unwrap_or(0.0),
-		cx: proj.cx.unwrap_or(DEFAULT_CX),
+		cx: proj.cx.unwrap_or_else(|| DefaultCx::default().cx(proj.ballistic_caliber)),
 		explosive_mass: proj.explosive_mass.unwrap_or(0.0),
+		explosive_type: proj.explosive_type.clone(),
+		blast_radius: proj.blast_radius,
+		fuze_delay_m: proj.fuze_delay_m,
+		fuze_sensitivity: proj.fuze_sensitivity,
 		damage_mass: proj.damage_mass.unwrap_or(0.0),
 		damage_caliber: proj.damage_caliber.unwrap_or(0.0),
 		demarre_k,
@@ -364,6 +530,33 @@ mod tests {
 		assert_eq!(normalize_shell_type("smoke_tank"), "smoke");
 	}
 
+	#[test]
+	fn test_caliber_mm_round_trip() {
+		let data = parse_data_text(
+			"WeaponPath:x\n\nName:105mm_m735\nType:apds_fs_tank\nBallisticCaliber:0.105\n",
+			"test",
+		);
+		let mm = caliber_mm(&data.projectiles[0]);
+		assert!((mm - 105.0).abs() < 1e-9, "{mm}");
+		for mm in [7.62, 20.0, 105.0, 125.0] {
+			assert!((caliber_m_from_mm(mm) * MM_PER_M - mm).abs() < 1e-9, "{mm}");
+		}
+	}
+
+	#[test]
+	fn test_tracer_and_incendiary_flags() {
+		let flags = |t: &str| (is_tracer_type(t), is_incendiary_type(t));
+		assert_eq!(flags("ap_t"), (true, false));
+		assert_eq!(flags("he_frag_i"), (false, true));
+		assert_eq!(flags("he_frag_i_t"), (true, true));
+		assert_eq!(flags("apcbc"), (false, false));
+		assert_eq!(flags("apcbc_tank"), (false, false));
+
+		let parsed = parse_data_text("WeaponPath:w.blkx\n\nName:20mm_ap_t\nType:ap_t\n", "v");
+		assert!(parsed.projectiles[0].is_tracer);
+		assert!(!parsed.projectiles[0].is_incendiary);
+	}
+
 	#[test]
 	fn test_clean_shell_name() {
 		assert_eq!(clean_shell_name("75mm_pzgr_39"), "pzgr_39");
@@ -433,5 +626,91 @@ demarreCaliberPow:1.07";
 		assert_eq!(pzgr.normalized_type, "apcbc");
 		assert!((pzgr.demarre_k - 1.0).abs() < f64::EPSILON);
 		assert!((pzgr.explosive_mass - 0.017).abs() < f64::EPSILON);
+		assert_eq!(pzgr.explosive_type.as_deref(), Some("h10"));
+		assert_eq!(m735.explosive_type, None);
+	}
+
+	#[test]
+	fn test_missing_cx_defaults_by_caliber_unless_uniform() {
+		let autocannon = Projectile {
+			name: "20mm_ap".to_owned(),
+			bullet_type: "ap_t".to_owned(),
+			is_tracer: true,
+			is_incendiary: false,
+			hull_break: false,
+			mass: Some(0.12),
+			ballistic_caliber: Some(0.02),
+			speed: Some(1000.0),
+			cx: None,
+			explosive_mass: None,
+			explosive_type: None,
+			blast_radius: None,
+			fuze_delay_m: None,
+			fuze_sensitivity: None,
+			damage_mass: None,
+			damage_caliber: None,
+			demarre: None,
+			armor_power: None,
+			armor_power_series: None,
+			unlocked_by_default: true,
+		};
+		let howitzer = Projectile {
+			name: "152mm_he".to_owned(),
+			bullet_type: "he_frag_tank".to_owned(),
+			mass: Some(43.5),
+			ballistic_caliber: Some(0.152),
+			speed: Some(650.0),
+			..autocannon.clone()
+		};
+		let mut projectiles = vec![autocannon, howitzer];
+
+		let cx: Vec<f64> = projectiles.iter().map(|p| from_projectile(p).cx).collect();
+		assert!(cx[0] < cx[1], "{cx:?}");
+
+		fill_default_cx(&mut projectiles, DefaultCx::Uniform);
+		for proj in &projectiles {
+			assert_eq!(proj.cx, Some(UNIFORM_CX));
+			assert!((from_projectile(proj).cx - UNIFORM_CX).abs() < f64::EPSILON);
+		}
+	}
+
+	#[test]
+	fn test_explosive_type_survives_bridge_and_roundtrip() {
+		use crate::emit::emit_legacy_txt;
+		use crate::model::VehicleData;
+
+		let mut vehicle = VehicleData::new("test_vehicle");
+		vehicle.weapon_path = Some("gameData/Weapons/test.blkx".to_owned());
+		vehicle.projectiles.push(Projectile {
+			name: "75mm_pzgr_39".to_owned(),
+			bullet_type: "apcbc_tank".to_owned(),
+			is_tracer: false,
+			is_incendiary: false,
+			hull_break: false,
+			mass: Some(6.8),
+			ballistic_caliber: Some(0.075),
+			speed: Some(740.0),
+			cx: Some(0.4),
+			explosive_mass: Some(0.017),
+			explosive_type: Some("h10".to_owned()),
+			blast_radius: None,
+			fuze_delay_m: None,
+			fuze_sensitivity: None,
+			damage_mass: None,
+			damage_caliber: None,
+			demarre: None,
+			armor_power: None,
+			armor_power_series: None,
+			unlocked_by_default: true,
+		});
+
+		// In-memory bridge
+		let bridged = from_projectile(&vehicle.projectiles[0]);
+		assert_eq!(bridged.explosive_type.as_deref(), Some("h10"));
+
+		// Text roundtrip
+		let text = emit_legacy_txt(&vehicle);
+		let parsed = parse_data_text(&text, "test_vehicle");
+		assert_eq!(parsed.projectiles[0].explosive_type.as_deref(), Some("h10"));
 	}
 }
@@ -0,0 +1,216 @@
+//! Parser for the localization CSVs of `lang.vromfs.bin` (`lang/units.csv`,
+//! `lang/units_weaponry.csv`).
+//!
+//! Each row is a localization key followed by one column per language,
+//! separated by `;`.  The first row names the columns (`"<ID|readonly|noverify>"`,
+//! `"<English>"`, ...).  Fields are quoted, and a quoted field may contain
+//! `;`, line breaks and `""` (an escaped `"`), so rows cannot be split on raw
+//! `;`.  Some files start with a UTF-8 BOM.
+
+use std::collections::HashMap;
+
+/// A localization table: key → one string per language.
+///
+/// Keys are matched case-insensitively, since vehicle IDs in the datamine
+/// and in the CSVs do not always agree on casing.
+#[derive(Debug, Clone, Default)]
+pub struct LangTable {
+	/// Language names from the header row, without the angle brackets
+	/// (e.g. `English`), in column order after the key.
+	languages: Vec<String>,
+	/// Lowercase key → fields after the key.
+	rows: HashMap<String, Vec<String>>,
+}
+
+impl LangTable {
+	/// Parse the contents of a localization CSV.
+	///
+	/// Rows without a key are ignored; on duplicate keys the first row wins.
+	#[must_use]
+	pub fn parse(text: &str) -> Self {
+		let mut records = parse_records(text).into_iter();
+		let languages = records
+			.next()
+			.map(|header| {
+				header
+					.into_iter()
+					.skip(1)
+					.map(|name| name.trim_start_matches('<').trim_end_matches('>').to_owned())
+					.collect()
+			})
+			.unwrap_or_default();
+
+		let mut rows = HashMap::new();
+		for mut record in records {
+			if record.first().is_none_or(String::is_empty) {
+				continue;
+			}
+			let key = record.remove(0).to_lowercase();
+			rows.entry(key).or_insert(record);
+		}
+
+		Self { languages, rows }
+	}
+
+	/// The `language` string (e.g. `English`) for `key`, if present and
+	/// non-empty.
+	#[must_use]
+	pub fn get(&self, key: &str, language: &str) -> Option<&str> {
+		let column = self.languages.iter().position(|l| l == language)?;
+		self.rows
+			.get(&key.to_lowercase())?
+			.get(column)
+			.map(String::as_str)
+			.filter(|s| !s.is_empty())
+	}
+
+	/// English display name of a vehicle, from its `{vehicle_id}_shop` key in
+	/// `units.csv`.
+	#[must_use]
+	pub fn vehicle_name(&self, vehicle_id: &str) -> Option<&str> {
+		self.get(&format!("{vehicle_id}_shop"), "English")
+	}
+
+	/// English in-game name of a shell, from `units_weaponry.csv`.
+	///
+	/// `name` is the full projectile name with its caliber prefix
+	/// (`75mm_pzgr_39`), which is how the CSV keys most shells; the
+	/// `{name}/name` and `weapons/{name}/short` keys some use instead are
+	/// tried next.
+	#[must_use]
+	pub fn shell_name(&self, name: &str) -> Option<&str> {
+		[name.to_owned(), format!("{name}/name"), format!("weapons/{name}/short")]
+			.iter()
+			.find_map(|key| self.get(key, "English"))
+	}
+
+	/// [`shell_name`](Self::shell_name), or `output_name` (the file stem
+	/// without the caliber prefix, e.g. `pzgr_39`) for a shell that is not
+	/// localized.
+	#[must_use]
+	pub fn shell_display_name<'a>(&'a self, name: &str, output_name: &'a str) -> &'a str {
+		self.shell_name(name).unwrap_or(output_name)
+	}
+
+	/// Number of keyed rows.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.rows.len()
+	}
+
+	/// Whether the table has no keyed rows.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.rows.is_empty()
+	}
+}
+
+/// Split CSV text into records of unquoted fields.
+///
+/// A `"` opens a quoted field only at the start of a field; elsewhere it is
+/// kept as-is.  Blank lines are skipped.
+fn parse_records(text: &str) -> Vec<Vec<String>> {
+	let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+
+	let mut records = Vec::new();
+	let mut record = Vec::new();
+	let mut field = String::new();
+	let mut quoted = false;
+	// Whether the current field has started (so an empty `""` field counts)
+	let mut started = false;
+	let mut chars = text.chars().peekable();
+
+	while let Some(c) = chars.next() {
+		if quoted {
+			match c {
+				'"' if chars.peek() == Some(&'"') => {
+					chars.next();
+					field.push('"');
+				},
+				'"' => quoted = false,
+				_ => field.push(c),
+			}
+			continue;
+		}
+
+		match c {
+			'"' if !started => {
+				quoted = true;
+				started = true;
+			},
+			';' => {
+				record.push(std::mem::take(&mut field));
+				started = false;
+			},
+			'\r' if chars.peek() == Some(&'\n') => {},
+			'\n' => {
+				if started || !record.is_empty() {
+					record.push(std::mem::take(&mut field));
+					records.push(std::mem::take(&mut record));
+				}
+				started = false;
+			},
+			_ => {
+				field.push(c);
+				started = true;
+			},
+		}
+	}
+	if started || !record.is_empty() {
+		record.push(field);
+		records.push(record);
+	}
+
+	records
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// BOM, `;` inside quotes, doubled quotes, a line break inside quotes and
+	/// CRLF line endings.
+	const TRICKY_CSV: &str = "\u{feff}\"<ID|readonly|noverify>\";\"<English>\";\"<French>\"\r\n\
+\"us_m1_abrams_shop\";\"M1 Abrams\";\"M1 Abrams\"\r\n\
+\"germ_leopard_2k_shop\";\"Leopard 2K; prototype\";\"Leopard 2K\"\r\n\
+\"ussr_t_80u_shop\";\"T-80U \"\"Ural\"\"\";\"\"\r\n\
+\"jp_type_90_shop\";\"Type 90\nB\";\"Type 90\"\r\n";
+
+	#[test]
+	fn test_tricky_rows_keep_display_name() {
+		let table = LangTable::parse(TRICKY_CSV);
+		assert_eq!(table.len(), 4);
+
+		// The BOM does not end up in the header
+		assert_eq!(table.vehicle_name("us_m1_abrams"), Some("M1 Abrams"));
+		assert_eq!(table.vehicle_name("germ_leopard_2k"), Some("Leopard 2K; prototype"));
+		assert_eq!(table.get("germ_leopard_2k_shop", "French"), Some("Leopard 2K"));
+		assert_eq!(table.vehicle_name("ussr_t_80u"), Some("T-80U \"Ural\""));
+		assert_eq!(table.get("ussr_t_80u_shop", "French"), None);
+		assert_eq!(table.vehicle_name("jp_type_90"), Some("Type 90\nB"));
+	}
+
+	#[test]
+	fn test_shell_display_name_by_caliber_prefixed_key() {
+		let table = LangTable::parse(
+			"\"<ID|readonly|noverify>\";\"<English>\"\n\
+			 \"75mm_pzgr_39\";\"PzGr 39\"\n\
+			 \"13mm_hotckiss_AP/name\";\"13.2 mm AP-T\"\n\
+			 \"weapons/380mm_sprgr4581/short\";\"38 cm R Spgr.4581\"\n",
+		);
+		assert_eq!(table.shell_name("75mm_pzgr_39"), Some("PzGr 39"));
+		assert_eq!(table.shell_name("13mm_hotckiss_ap"), Some("13.2 mm AP-T"));
+		assert_eq!(table.shell_name("380mm_sprgr4581"), Some("38 cm R Spgr.4581"));
+
+		assert_eq!(table.shell_display_name("75mm_pzgr_39", "pzgr_39"), "PzGr 39");
+		assert_eq!(table.shell_display_name("75mm_pzgr_40", "pzgr_40"), "pzgr_40");
+	}
+
+	#[test]
+	fn test_keys_case_insensitive() {
+		let table = LangTable::parse(TRICKY_CSV);
+		assert_eq!(table.vehicle_name("US_M1_Abrams"), Some("M1 Abrams"));
+		assert_eq!(table.vehicle_name("us_m1a1_abrams"), None);
+		assert_eq!(table.get("us_m1_abrams_shop", "Klingon"), None);
+	}
+}
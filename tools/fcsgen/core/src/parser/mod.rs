@@ -1,10 +1,30 @@
 //! Parser for War Thunder datamine files.
 
 pub mod data;
+pub mod lang;
 pub mod unittags;
 pub mod vehicle;
 pub mod weapon;
+pub mod wpcost;
 
-pub use unittags::{UnittagsMap, lookup_vehicle_id, parse_unittags, parse_unittags_str};
+pub use lang::LangTable;
+pub use unittags::{
+	NATIONS,
+	UnittagsMap,
+	lookup_vehicle_id,
+	parse_unittags,
+	parse_unittags_str,
+	vehicle_nation,
+};
 pub use vehicle::parse_vehicle;
-pub use weapon::parse_weapon_module;
+pub use weapon::{
+	BeltBullets,
+	CxArrayPolicy,
+	ParseOptions,
+	count_weapon_module_shells,
+	parse_weapon_module,
+	parse_weapon_module_with_options,
+	parse_weapon_module_with_warnings,
+	rate_of_fire_rpm,
+};
+pub use wpcost::{RankMap, VehicleRank, battle_rating, parse_wpcost, parse_wpcost_str};
@@ -60,6 +60,21 @@ pub fn lookup_vehicle_id<'a>(map: &'a UnittagsMap, id: &'a str) -> &'a str {
 		.unwrap_or(id)
 }
 
+/// Nation prefixes of ground vehicle IDs (`us_m1_abrams`, `germ_pzkpfw_…`).
+pub const NATIONS: &[&str] = &["us", "germ", "ussr", "uk", "jp", "cn", "it", "fr", "sw", "il"];
+
+/// Nation of a vehicle, from the prefix of its ID (case-insensitive).
+///
+/// Returns `None` when the ID does not start with one of [`NATIONS`]
+/// followed by `_`.
+pub fn vehicle_nation(id: &str) -> Option<&'static str> {
+	let (prefix, _) = id.split_once('_')?;
+	NATIONS
+		.iter()
+		.copied()
+		.find(|nation| nation.eq_ignore_ascii_case(prefix))
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -131,4 +146,13 @@ mod tests {
 	fn test_parse_unittags_str_invalid_json() {
 		assert!(parse_unittags_str("invalid json").is_none());
 	}
+
+	#[test]
+	fn test_vehicle_nation() {
+		assert_eq!(vehicle_nation("us_m1a2_sep_abrams"), Some("us"));
+		assert_eq!(vehicle_nation("germ_pzkpfw_VI_ausf_h1_tiger"), Some("germ"));
+		assert_eq!(vehicle_nation("USSR_t_34_1941"), Some("ussr"));
+		assert_eq!(vehicle_nation("uss_something"), None);
+		assert_eq!(vehicle_nation("tiger"), None);
+	}
 }
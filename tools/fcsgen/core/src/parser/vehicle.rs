@@ -13,6 +13,10 @@ pub struct WeaponEntry {
 	pub blk_path: String,
 	pub trigger: Option<String>,
 	pub trigger_group: Option<String>,
+	/// Turret traverse speed (°/s).
+	pub speed_yaw: Option<f64>,
+	/// Gun elevation speed (°/s).
+	pub speed_pitch: Option<f64>,
 }
 
 /// Parse a vehicle .blkx file and extract header information.
@@ -58,6 +62,11 @@ pub fn parse_vehicle(json: &Value, vehicle_id: &str) -> Result<VehicleData> {
 }
 
 /// Extract zoom values from the cockpit object.
+///
+/// Of an array of cockpits, the primary optic is the one with the highest
+/// zoom-in magnification (narrowest `zoomInFov`), then the narrowest
+/// `zoomOutFov`; cockpits without a FOV come last.  The array order is not
+/// used, since it differs between datamine versions for some vehicles.
 fn extract_zoom_values(cockpit: &Value, data: &mut VehicleData) {
 	// Handle both single cockpit object and array of cockpits
 	match cockpit {
@@ -66,10 +75,20 @@ fn extract_zoom_values(cockpit: &Value, data: &mut VehicleData) {
 			data.zoom_out = extract_fov_value(obj.get("zoomOutFov"));
 		},
 		Value::Array(arr) => {
-			// First cockpit is primary
-			if let Some(Value::Object(obj)) = arr.first() {
-				data.zoom_in = extract_fov_value(obj.get("zoomInFov"));
-				data.zoom_out = extract_fov_value(obj.get("zoomOutFov"));
+			let fov = |fov: Option<f64>| fov.unwrap_or(f64::INFINITY);
+			let primary = arr
+				.iter()
+				.filter_map(Value::as_object)
+				.map(|obj| {
+					let zoom_in = extract_fov_value(obj.get("zoomInFov"));
+					(zoom_in, extract_fov_value(obj.get("zoomOutFov")))
+				})
+				.min_by(|a, b| {
+					fov(a.0).total_cmp(&fov(b.0)).then_with(|| fov(a.1).total_cmp(&fov(b.1)))
+				});
+			if let Some((zoom_in, zoom_out)) = primary {
+				data.zoom_in = zoom_in;
+				data.zoom_out = zoom_out;
 			}
 		},
 		// Note: secondary cockpit optics (previously zoom_in_2/zoom_out_2 for
@@ -130,6 +149,8 @@ fn extract_weapon_entries(common_weapons: &Value) -> Vec<WeaponEntry> {
 					.get("triggerGroup")
 					.and_then(Value::as_str)
 					.map(String::from),
+				speed_yaw: weapon.get("speedYaw").and_then(Value::as_f64),
+				speed_pitch: weapon.get("speedPitch").and_then(Value::as_f64),
 			});
 		}
 	}
@@ -151,15 +172,34 @@ fn normalize_blk_path(path: &str) -> String {
 /// Legacy behavior:
 /// - First weapon with "groundModels_weapons" in path becomes weapon_path
 /// - Weapons with triggerGroup "special" become rocket_paths (up to 2 unique)
+///
+/// The primary weapon's `speedYaw`/`speedPitch` are the turret traverse and
+/// gun elevation speeds.  Twin and quad mounts list one entry per barrel, so
+/// the entries sharing the primary's module and trigger give its barrel count.
+///
+/// Unlike legacy, a main-gun weapon (see [`is_main_gun`]) is preferred over an
+/// earlier coaxial/machine gun; the first match is only the fallback.
 fn classify_weapons(weapons: &[WeaponEntry], data: &mut VehicleData) {
-	// Find primary weapon (first one with groundModels_weapons that isn't special)
-	for weapon in weapons {
-		if weapon.blk_path.contains("groundModels_weapons")
-			&& weapon.trigger_group.as_deref() != Some("special")
-			&& data.weapon_path.is_none()
+	// Find primary weapon (groundModels_weapons that isn't special)
+	if data.weapon_path.is_none() {
+		let candidates = || {
+			weapons.iter().filter(|w| {
+				w.blk_path.contains("groundModels_weapons")
+					&& w.trigger_group.as_deref() != Some("special")
+			})
+		};
+		if let Some(primary) = candidates()
+			.find(|w| is_main_gun(w))
+			.or_else(|| candidates().next())
 		{
-			data.weapon_path = Some(weapon.blk_path.clone());
-			break;
+			data.weapon_path = Some(primary.blk_path.clone());
+			data.traverse_speed_deg_s = primary.speed_yaw;
+			data.elevation_speed_deg_s = primary.speed_pitch;
+			let barrels = weapons
+				.iter()
+				.filter(|w| w.blk_path == primary.blk_path && w.trigger == primary.trigger)
+				.count();
+			data.barrels = u32::try_from(barrels).unwrap_or(u32::MAX);
 		}
 	}
 
@@ -173,6 +213,15 @@ fn classify_weapons(weapons: &[WeaponEntry], data: &mut VehicleData) {
 	}
 }
 
+/// Whether a weapon is fired by the main-gun trigger.
+///
+/// `trigger` is `gunnerN` for every weapon, so only the group tells the main
+/// gun (`primary`, or no group at all) from `coaxial`, `machinegun`,
+/// `commander`, etc.
+fn is_main_gun(weapon: &WeaponEntry) -> bool {
+	matches!(weapon.trigger_group.as_deref(), None | Some("primary"))
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -205,6 +254,124 @@ mod tests {
 		assert!((data.zoom_out.unwrap() - 30.0).abs() < 0.001);
 	}
 
+	#[test]
+	fn test_primary_cockpit_independent_of_order() {
+		let gunner = json!({"zoomInFov": 5.0, "zoomOutFov": 20.0});
+		let commander = json!({"zoomInFov": 12.0, "zoomOutFov": 20.0});
+		let periscope = json!({"zoomOutFov": 60.0});
+
+		for cockpit in [
+			json!([gunner, commander, periscope]),
+			json!([periscope, commander, gunner]),
+		] {
+			let mut data = VehicleData::new("test");
+			extract_zoom_values(&cockpit, &mut data);
+			assert!((data.zoom_in.unwrap() - 5.0).abs() < 0.001);
+			assert!((data.zoom_out.unwrap() - 20.0).abs() < 0.001);
+		}
+	}
+
+	#[test]
+	fn test_main_gun_preferred_over_earlier_coaxial() {
+		let vehicle = json!({
+			"commonWeapons": {
+				"Weapon": [
+					{
+						"trigger": "gunner0",
+						"triggerGroup": "coaxial",
+						"blk": "gameData/Weapons/groundModels_weapons/7_62mm_pkt_user_machinegun.blk"
+					},
+					{
+						"trigger": "gunner0",
+						"triggerGroup": "primary",
+						"blk": "gameData/Weapons/groundModels_weapons/125mm_2a46m_user_cannon.blk"
+					},
+					{
+						"trigger": "gunner0",
+						"triggerGroup": "special",
+						"blk": "gameData/Weapons/groundModels_weapons/9m119_launcher.blk"
+					}
+				]
+			}
+		});
+		let data = parse_vehicle(&vehicle, "test").unwrap();
+
+		assert_eq!(
+			data.weapon_path.as_deref(),
+			Some("gameData/Weapons/groundModels_weapons/125mm_2a46m_user_cannon.blkx")
+		);
+		assert_eq!(data.rocket_paths, ["gameData/Weapons/groundModels_weapons/9m119_launcher.blkx"]);
+
+		// Machine guns only: fall back to the first listed weapon
+		let mg_only = json!({
+			"commonWeapons": {
+				"Weapon": [
+					{
+						"trigger": "gunner0",
+						"triggerGroup": "machinegun",
+						"blk": "gameData/Weapons/groundModels_weapons/12_7mm_dshk.blk"
+					},
+					{
+						"trigger": "gunner1",
+						"triggerGroup": "commander",
+						"blk": "gameData/Weapons/groundModels_weapons/7_62mm_pkt.blk"
+					}
+				]
+			}
+		});
+		let data = parse_vehicle(&mg_only, "test").unwrap();
+		assert_eq!(
+			data.weapon_path.as_deref(),
+			Some("gameData/Weapons/groundModels_weapons/12_7mm_dshk.blkx")
+		);
+	}
+
+	#[test]
+	fn test_turret_speeds_extracted_and_roundtrip() {
+		use crate::emit::emit_legacy_txt;
+		use crate::parser::data::parse_data_text;
+
+		let vehicle = json!({
+			"commonWeapons": {
+				"Weapon": [
+					{
+						"trigger": "gunner0",
+						"blk": "gameData/Weapons/groundModels_weapons/125mm_ZPT_98_user_cannon.blk",
+						"speedYaw": 30.0,
+						"speedPitch": 5.0
+					},
+					{
+						"trigger": "gunner0",
+						"triggerGroup": "coaxial",
+						"blk": "gameData/Weapons/groundModels_weapons/7_62mm_Type86_user_machinegun.blk",
+						"speedYaw": 7.0,
+						"speedPitch": 4.0
+					}
+				]
+			}
+		});
+		let data = parse_vehicle(&vehicle, "test").unwrap();
+		assert_eq!(data.traverse_speed_deg_s, Some(30.0));
+		assert_eq!(data.elevation_speed_deg_s, Some(5.0));
+
+		let text = emit_legacy_txt(&data);
+		assert!(text.contains("TraverseSpeed:30.0\nElevationSpeed:5.0"));
+		let parsed = parse_data_text(&text, "test");
+		assert_eq!(parsed.traverse_speed_deg_s, Some(30.0));
+		assert_eq!(parsed.elevation_speed_deg_s, Some(5.0));
+
+		// Absent speeds stay absent
+		let bare = json!({
+			"commonWeapons": {
+				"Weapon": { "blk": "gameData/Weapons/groundModels_weapons/test_cannon.blk" }
+			}
+		});
+		let data = parse_vehicle(&bare, "test").unwrap();
+		assert_eq!(data.traverse_speed_deg_s, None);
+		let parsed = parse_data_text(&emit_legacy_txt(&data), "test");
+		assert_eq!(parsed.elevation_speed_deg_s, None);
+	}
+
 	#[test]
 	fn test_normalize_blk_path() {
 		assert_eq!(
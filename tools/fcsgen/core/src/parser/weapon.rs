@@ -15,14 +15,76 @@
 //!
 //! This explains why the top-level bullet array [UBR6, UOF8] outputs UOF8 (last-wins),
 //! while belt arrays like AP [UBR6, UBR6, UBR6, UOR6] output UBR6 (first only).
+//! [`BeltBullets::All`] instead keeps every distinct round of a belt, e.g. both
+//! halves of an alternating AP/HE autocannon belt.
 //!
 //! Belt sections (like "30mm_2a42_HE") are filtered based on whether that belt
 //! name exists in the vehicle data. Top-level bullets are always included.
+//!
+//! # Stock vs. researched ammo
+//!
+//! Top-level bullets are the gun's stock ammunition. A belt is considered
+//! modification-gated (not `unlocked_by_default`) when its name, or its
+//! nation-stripped form, is a key of the vehicle's `modifications` block.
+//!
+//! # Diagnostics
+//!
+//! Malformed entries (a belt with no bullet, an empty bullet array, an
+//! unreadable `Cx`, ...) are dropped rather than failing the whole module.
+//! [`parse_weapon_module_with_warnings`] reports each one as a
+//! [`ParseError::WeaponModule`] so callers can surface them.
 
-use serde_json::Value;
+use serde_json::{Map, Value};
 
-use crate::error::Result;
+use crate::error::{ParseError, Result};
 use crate::model::{ArmorPowerSeries, DemarreParams, Projectile};
+use crate::parser::data::{is_hull_break_capable, is_incendiary_type, is_tracer_type};
+use crate::parser::unittags::NATIONS;
+
+/// Speed of sound at sea level, 15 °C (m/s), for muzzle Mach lookups.
+const SPEED_OF_SOUND: f64 = 340.29;
+
+/// How a `Cx` array is reduced to the single drag coefficient used by the
+/// constant-Cx trajectory model.
+///
+/// Scalar `Cx` values are always used as-is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CxArrayPolicy {
+	/// Use the first entry.
+	First,
+
+	/// Average all entries, rounded to 4 decimal places (legacy behaviour,
+	/// reproduces the reference corpus).
+	#[default]
+	Mean,
+
+	/// Use the entry for the shell's muzzle Mach number, assuming entry `i`
+	/// covers Mach `[i, i+1)`; the last entry covers everything above.
+	AtMuzzleMach,
+}
+
+/// Which rounds of a belt's `bullet` array become projectiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BeltBullets {
+	/// Only the first round (legacy behaviour, reproduces the reference
+	/// corpus).
+	#[default]
+	First,
+
+	/// Every distinct round, in belt order.  Repeats (same name and type)
+	/// are dropped; a round sharing its name with an earlier round of another
+	/// type gets `_{bulletType}` appended, so each keeps its own table.
+	All,
+}
+
+/// Options for weapon module parsing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseOptions {
+	/// Reduction applied to `Cx` arrays.
+	pub cx_array_policy: CxArrayPolicy,
+	/// Rounds taken from each belt.
+	pub belt_bullets: BeltBullets,
+}
 
 /// Parse a weapon module .blkx file and extract projectile data.
 ///
@@ -33,101 +95,257 @@ use crate::model::{ArmorPowerSeries, DemarreParams, Projectile};
 /// # Returns
 /// A vector of `Projectile` structs extracted from the module.
 pub fn parse_weapon_module(json: &Value, vehicle_json: Option<&Value>) -> Result<Vec<Projectile>> {
+	parse_weapon_module_with_options(json, vehicle_json, ParseOptions::default())
+}
+
+/// Same as [`parse_weapon_module`] but with explicit [`ParseOptions`].
+///
+/// # Errors
+///
+/// Returns [`ParseError::WeaponModule`] if the module root is not an object.
+pub fn parse_weapon_module_with_options(
+	json: &Value,
+	vehicle_json: Option<&Value>,
+	options: ParseOptions,
+) -> Result<Vec<Projectile>> {
+	parse_weapon_module_with_warnings(json, vehicle_json, options, &mut Vec::new())
+}
+
+/// Same as [`parse_weapon_module_with_options`], additionally pushing a
+/// [`ParseError::WeaponModule`] onto `warnings` for every entry that was
+/// dropped as malformed.
+///
+/// # Errors
+///
+/// Returns [`ParseError::WeaponModule`] if the module root is not an object.
+pub fn parse_weapon_module_with_warnings(
+	json: &Value,
+	vehicle_json: Option<&Value>,
+	options: ParseOptions,
+	warnings: &mut Vec<ParseError>,
+) -> Result<Vec<Projectile>> {
+	let Value::Object(obj) = json else {
+		return Err(ParseError::weapon_module("$", "root is not an object"));
+	};
+
 	let mut projectiles = Vec::new();
 
 	// Convert vehicle JSON to string for belt membership checks (matches legacy behavior)
 	let vehicle_str = vehicle_json.map(|v| v.to_string());
+	let modifications = vehicle_json.and_then(|v| v.get("modifications"));
 
 	// Process the JSON object in insertion order (serde_json preserves order)
-	if let Value::Object(obj) = json {
-		for (key, value) in obj {
-			match key.as_str() {
-				"bullet" => {
-					// Top-level bullet - merge all array elements (last-wins)
-					if let Some(proj) = collect_bullet_merged(value) {
-						projectiles.push(proj);
-					}
-				},
-				"rocket" => {
-					// Top-level rocket - merge all array elements (last-wins)
-					if let Some(proj) = collect_bullet_merged(value) {
+	for (key, value) in obj {
+		match key.as_str() {
+			"bullet" | "rocket" => {
+				// Top-level bullet/rocket - merge all array elements (last-wins)
+				if let Some(proj) = collect_bullet_merged(value, options, key, warnings) {
+					projectiles.push(proj);
+				}
+			},
+			_ => {
+				// Could be a belt section - check if it should be included
+				// Belts with rocket/ATGM data are always included (they're not
+				// modification-gated); regular ammo belts are filtered by vehicle data.
+				let Some(belt) = belt_section(value) else {
+					continue;
+				};
+				let include =
+					belt_has_rocket(belt) || should_include_belt(key, vehicle_str.as_deref());
+				if !include {
+					continue;
+				}
+				let unlocked_by_default = !is_modification_gated(key, modifications);
+				// Look for bullet/rocket within this belt section
+				if !belt.contains_key("bullet") && !belt.contains_key("rocket") {
+					warnings.push(ParseError::weapon_module(
+						key.as_str(),
+						"belt has no bullet",
+					));
+				}
+				for kind in ["bullet", "rocket"] {
+					let Some(entry) = belt.get(kind) else {
+						continue;
+					};
+					let path = format!("{key}/{kind}");
+					let belt_projectiles = match options.belt_bullets {
+						// Only first element (legacy bracket behavior)
+						BeltBullets::First => {
+							collect_bullet_first(entry, options, &path, warnings)
+								.into_iter()
+								.collect()
+						},
+						BeltBullets::All => collect_belt_all(entry, options, &path, warnings),
+					};
+					for mut proj in belt_projectiles {
+						proj.unlocked_by_default = unlocked_by_default;
 						projectiles.push(proj);
 					}
-				},
-				_ => {
-					// Could be a belt section - check if it should be included
-					// Belts with rocket/ATGM data are always included (they're not
-					// modification-gated); regular ammo belts are filtered by vehicle data.
-					let include = belt_has_rocket(value)
-						|| (value.is_object()
-							&& should_include_belt(key, vehicle_str.as_deref()));
-					if include {
-						// Look for bullet/rocket within this belt section
-						if let Value::Object(belt) = value {
-							if let Some(bullets) = belt.get("bullet") {
-								// Belt bullets - only first element (legacy bracket behavior)
-								if let Some(proj) = collect_bullet_first(bullets) {
-									projectiles.push(proj);
-								}
-							}
-							if let Some(rockets) = belt.get("rocket") {
-								// Belt rockets - only first element
-								if let Some(proj) = collect_bullet_first(rockets) {
-									projectiles.push(proj);
-								}
-							}
-						}
-					}
-				},
-			}
+				}
+			},
 		}
 	}
 
 	Ok(projectiles)
 }
 
+/// Number of projectiles [`parse_weapon_module`] returns for a module, without
+/// extracting their physics.
+///
+/// Applies the same belt filtering and first-round/merged rules; entries that
+/// the parser would drop for lacking a name or type are not counted.
+#[must_use]
+pub fn count_weapon_module_shells(json: &Value, vehicle_json: Option<&Value>) -> usize {
+	let Value::Object(obj) = json else {
+		return 0;
+	};
+	let vehicle_str = vehicle_json.map(ToString::to_string);
+
+	obj.iter()
+		.map(|(key, value)| match key.as_str() {
+			"bullet" | "rocket" => usize::from(has_name_and_type(rounds(value, false))),
+			_ => {
+				let Some(belt) = belt_section(value) else {
+					return 0;
+				};
+				if !belt_has_rocket(belt) && !should_include_belt(key, vehicle_str.as_deref()) {
+					return 0;
+				}
+				["bullet", "rocket"]
+					.into_iter()
+					.filter_map(|kind| belt.get(kind))
+					.filter(|entry| has_name_and_type(rounds(entry, true)))
+					.count()
+			},
+		})
+		.sum()
+}
+
+/// Rate of fire of one barrel of a weapon module in rounds/min, from its
+/// top-level `shotFreq` (shots per second).
+#[must_use]
+pub fn rate_of_fire_rpm(json: &Value) -> Option<f64> {
+	json.get("shotFreq")
+		.and_then(Value::as_f64)
+		.filter(|&freq| freq > 0.0)
+		.map(|freq| freq * 60.0)
+}
+
+/// The rounds of a `bullet`/`rocket` value: all of an array, or only its
+/// first element if `first_only`.
+fn rounds(value: &Value, first_only: bool) -> &[Value] {
+	match value {
+		Value::Array(arr) if first_only => &arr[..arr.len().min(1)],
+		Value::Array(arr) => arr,
+		Value::Object(_) => std::slice::from_ref(value),
+		_ => &[],
+	}
+}
+
+/// Whether `rounds` merge into a bullet with a name and type, i.e. one that
+/// [`MergedBullet::into_projectile`] keeps.
+fn has_name_and_type(rounds: &[Value]) -> bool {
+	let mut merged = MergedBullet::default();
+	for round in rounds {
+		merged.merge(round);
+	}
+	merged.bullet_name.is_some() && merged.bullet_type.is_some()
+}
+
 /// Collect ONE bullet from an array, merging ALL elements with last-wins semantics.
 /// Used for top-level bullets where legacy bracket counting reads the entire array.
-fn collect_bullet_merged(value: &Value) -> Option<Projectile> {
+fn collect_bullet_merged(
+	value: &Value,
+	options: ParseOptions,
+	path: &str,
+	warnings: &mut Vec<ParseError>,
+) -> Option<Projectile> {
+	let mut merged = MergedBullet::default();
 	match value {
-		Value::Array(arr) => {
+		Value::Array(arr) if !arr.is_empty() => {
 			// Merge all array entries, last wins
-			let mut merged = MergedBullet::default();
 			for bullet in arr {
 				merged.merge(bullet);
 			}
-			merged.to_projectile()
 		},
-		Value::Object(_) => {
-			let mut merged = MergedBullet::default();
-			merged.merge(value);
-			merged.to_projectile()
+		Value::Object(_) => merged.merge(value),
+		_ => {
+			warnings.push(malformed_bullet(path, value));
+			return None;
 		},
-		_ => None,
 	}
+	merged.into_projectile(options, path, warnings)
 }
 
 /// Collect ONE bullet from an array, using ONLY the first element.
 /// Used for belt bullets where legacy bracket counting exits after first bullet.
-fn collect_bullet_first(value: &Value) -> Option<Projectile> {
+fn collect_bullet_first(
+	value: &Value,
+	options: ParseOptions,
+	path: &str,
+	warnings: &mut Vec<ParseError>,
+) -> Option<Projectile> {
+	let mut merged = MergedBullet::default();
 	match value {
-		Value::Array(arr) => {
-			// Take ONLY the first element (legacy behavior for belts)
-			if let Some(first) = arr.first() {
-				let mut merged = MergedBullet::default();
-				merged.merge(first);
-				merged.to_projectile()
-			} else {
-				None
-			}
+		// Take ONLY the first element (legacy behavior for belts)
+		Value::Array(arr) if !arr.is_empty() => merged.merge(&arr[0]),
+		Value::Object(_) => merged.merge(value),
+		_ => {
+			warnings.push(malformed_bullet(path, value));
+			return None;
 		},
-		Value::Object(_) => {
-			let mut merged = MergedBullet::default();
-			merged.merge(value);
-			merged.to_projectile()
+	}
+	merged.into_projectile(options, path, warnings)
+}
+
+/// Collect every distinct round of a belt ([`BeltBullets::All`]).
+fn collect_belt_all(
+	value: &Value,
+	options: ParseOptions,
+	path: &str,
+	warnings: &mut Vec<ParseError>,
+) -> Vec<Projectile> {
+	let rounds: Vec<(String, &Value)> = match value {
+		Value::Array(arr) if !arr.is_empty() => arr
+			.iter()
+			.enumerate()
+			.map(|(i, round)| (format!("{path}[{i}]"), round))
+			.collect(),
+		Value::Object(_) => vec![(path.to_owned(), value)],
+		_ => {
+			warnings.push(malformed_bullet(path, value));
+			return Vec::new();
 		},
-		_ => None,
+	};
+
+	let mut projectiles: Vec<Projectile> = Vec::new();
+	for (path, round) in rounds {
+		let mut merged = MergedBullet::default();
+		merged.merge(round);
+		let Some(mut proj) = merged.into_projectile(options, &path, warnings) else {
+			continue;
+		};
+		let taken = |proj: &Projectile| projectiles.iter().find(|p| p.name == proj.name);
+		match taken(&proj) {
+			Some(p) if p.bullet_type == proj.bullet_type => continue,
+			Some(_) => proj.name = format!("{}_{}", proj.name, proj.bullet_type),
+			None => {},
+		}
+		if taken(&proj).is_none() {
+			projectiles.push(proj);
+		}
 	}
+	projectiles
+}
+
+/// Warning for a `bullet`/`rocket` value that is neither an object nor a
+/// non-empty array.
+fn malformed_bullet(path: &str, value: &Value) -> ParseError {
+	let reason = match value {
+		Value::Array(_) => "empty bullet array".to_string(),
+		other => format!("expected an object or array, found {other}"),
+	};
+	ParseError::weapon_module(path, reason)
 }
 
 /// Helper struct for merging bullet values with last-wins semantics.
@@ -138,9 +356,14 @@ struct MergedBullet {
 	mass: Option<f64>,
 	caliber: Option<f64>,
 	speed: Option<f64>,
-	cx: Option<f64>,
+	cx: Option<CxSource>,
+	/// `Cx` was present in some merged entry but had no numeric value.
+	cx_unreadable: bool,
 	explosive_mass: Option<f64>,
 	explosive_type: Option<String>,
+	blast_radius: Option<f64>,
+	fuze_delay_m: Option<f64>,
+	fuze_sensitivity: Option<f64>,
 	damage_mass: Option<f64>,
 	damage_caliber: Option<f64>,
 	demarre_k: Option<f64>,
@@ -155,7 +378,7 @@ impl MergedBullet {
 	/// Merge values from a bullet object, overwriting any existing values.
 	fn merge(&mut self, bullet: &Value) {
 		// For rockets/ATGMs, data may be nested under "rocket"
-		let rocket = bullet.get("rocket");
+		let rocket = rocket_section(bullet);
 		let data_source = rocket.unwrap_or(bullet);
 
 		// Extract bullet name (can be string or array)
@@ -167,8 +390,7 @@ impl MergedBullet {
 			self.bullet_type = Some(bt.to_string());
 		}
 		// Legacy scans line-by-line with last-wins, so rocket.bulletType overwrites outer
-		if let Some(bt) = bullet
-			.get("rocket")
+		if let Some(bt) = rocket
 			.and_then(|r| r.get("bulletType"))
 			.and_then(Value::as_str)
 		{
@@ -182,13 +404,16 @@ impl MergedBullet {
 			self.mass = Some(v);
 		}
 
-		// Caliber - prefer ballisticCaliber, then caliber
-		if let Some(v) = data_source.get("ballisticCaliber").and_then(Value::as_f64) {
-			self.caliber = Some(v);
-		} else if let Some(v) = data_source.get("caliber").and_then(Value::as_f64) {
-			self.caliber = Some(v);
-		} else if let Some(v) = bullet.get("caliber").and_then(Value::as_f64) {
-			self.caliber = Some(v);
+		// Caliber - prefer ballisticCaliber, then caliber, rocket section
+		// first; a shell with only `caliber` must not end up caliber-less
+		let caliber = [data_source, bullet].into_iter().find_map(|source| {
+			source
+				.get("ballisticCaliber")
+				.or_else(|| source.get("caliber"))
+				.and_then(Value::as_f64)
+		});
+		if caliber.is_some() {
+			self.caliber = caliber;
 		}
 
 		// Speed - prefer endSpeed, then speed
@@ -198,9 +423,11 @@ impl MergedBullet {
 			self.speed = Some(v);
 		}
 
-		// Cx
-		if let Some(cx) = extract_cx(data_source) {
+		// Cx (reduced to a scalar per `CxArrayPolicy` once speed is known)
+		if let Some(cx) = read_cx(data_source) {
 			self.cx = Some(cx);
+		} else if data_source.get("Cx").is_some() {
+			self.cx_unreadable = true;
 		}
 
 		// Explosive
@@ -210,6 +437,22 @@ impl MergedBullet {
 		if let Some(v) = data_source.get("explosiveType").and_then(Value::as_str) {
 			self.explosive_type = Some(v.to_string());
 		}
+		if let Some(v) = data_source
+			.get("damage")
+			.and_then(|d| d.get("explosive"))
+			.and_then(|e| e.get("radius"))
+			.and_then(Value::as_f64)
+		{
+			self.blast_radius = Some(v);
+		}
+
+		// Fuze (`explodeTreshold` is the datamine's spelling)
+		if let Some(v) = data_source.get("fuseDelayDist").and_then(Value::as_f64) {
+			self.fuze_delay_m = Some(v);
+		}
+		if let Some(v) = data_source.get("explodeTreshold").and_then(Value::as_f64) {
+			self.fuze_sensitivity = Some(v);
+		}
 
 		// Damage
 		if let Some(v) = data_source.get("damageMass").and_then(Value::as_f64) {
@@ -236,8 +479,7 @@ impl MergedBullet {
 	}
 
 	fn merge_demarre(&mut self, bullet: &Value) {
-		let rocket_damage_kinetic = bullet
-			.get("rocket")
+		let rocket_damage_kinetic = rocket_section(bullet)
 			.and_then(|r| r.get("damage"))
 			.and_then(|d| d.get("kinetic"));
 		let bullet_damage_kinetic = bullet
@@ -266,10 +508,20 @@ impl MergedBullet {
 		}
 	}
 
-	fn to_projectile(self) -> Option<Projectile> {
+	fn into_projectile(
+		self,
+		options: ParseOptions,
+		path: &str,
+		warnings: &mut Vec<ParseError>,
+	) -> Option<Projectile> {
 		// Name and type are required
-		let name = self.bullet_name?;
-		let bullet_type = self.bullet_type?;
+		let (Some(name), Some(bullet_type)) = (self.bullet_name, self.bullet_type) else {
+			warnings.push(ParseError::weapon_module(path, "bullet has no bulletType"));
+			return None;
+		};
+		if self.cx.is_none() && self.cx_unreadable {
+			warnings.push(ParseError::weapon_module(path, "Cx has no numeric value"));
+		}
 
 		let demarre = if self.demarre_k.is_some()
 			|| self.demarre_speed_pow.is_some()
@@ -297,49 +549,72 @@ impl MergedBullet {
 
 		Some(Projectile {
 			name,
+			is_tracer: is_tracer_type(&bullet_type),
+			is_incendiary: is_incendiary_type(&bullet_type),
+			hull_break: is_hull_break_capable(
+				&bullet_type,
+				self.caliber.unwrap_or(0.0),
+				self.explosive_mass.unwrap_or(0.0),
+			),
 			bullet_type,
 			mass: self.mass,
 			ballistic_caliber: self.caliber,
 			speed: self.speed,
-			cx: self.cx,
+			cx: self
+				.cx
+				.map(|cx| cx.resolve(options.cx_array_policy, self.speed)),
 			explosive_mass: self.explosive_mass,
 			explosive_type: self.explosive_type,
+			blast_radius: self.blast_radius,
+			fuze_delay_m: self.fuze_delay_m,
+			fuze_sensitivity: self.fuze_sensitivity,
 			damage_mass: self.damage_mass,
 			damage_caliber: self.damage_caliber,
 			demarre,
 			armor_power: self.armor_power,
 			armor_power_series,
+			unlocked_by_default: true,
 		})
 	}
 }
 
+/// The belt section stored under a non-bullet key of a weapon module.
+///
+/// Usually an object holding `bullet` (an object, or an array of them).  A
+/// belt block repeated in the blk deserializes to an array of such objects;
+/// like belt bullets, only the first counts.  An array is only taken for a
+/// belt when that first object holds a `bullet` or `rocket`, so unrelated
+/// array-valued keys stay ignored.
+fn belt_section(value: &Value) -> Option<&Map<String, Value>> {
+	match value {
+		Value::Object(belt) => Some(belt),
+		Value::Array(sections) => sections
+			.first()
+			.and_then(Value::as_object)
+			.filter(|belt| belt.contains_key("bullet") || belt.contains_key("rocket")),
+		_ => None,
+	}
+}
+
 /// Check if a belt section contains rocket/ATGM data (nested `rocket` inside `bullet`).
 /// Such belts represent gun-launched ATGMs or missiles and should always be included
 /// regardless of belt filtering, since they're not modification-gated ammo belts.
-fn belt_has_rocket(value: &Value) -> bool {
-	if let Value::Object(belt) = value {
-		// Check bullet sub-objects for a nested "rocket" section
-		if let Some(bullets) = belt.get("bullet") {
-			let bullet_iter: Box<dyn Iterator<Item = &Value>> = match bullets {
-				Value::Array(arr) => Box::new(arr.iter()),
-				obj @ Value::Object(_) => Box::new(std::iter::once(obj)),
-				_ => return false,
-			};
-			for bullet in bullet_iter {
-				if bullet.get("rocket").is_some() {
-					return true;
-				}
-			}
-		}
-		// Check for direct "rocket" key in belt (standalone rocket sections)
-		if let Some(rockets) = belt.get("rocket") {
-			match rockets {
-				Value::Object(_) | Value::Array(_) => return true,
-				_ => {}
+fn belt_has_rocket(belt: &Map<String, Value>) -> bool {
+	// Check bullet sub-objects for a nested "rocket" section
+	if let Some(bullets) = belt.get("bullet") {
+		let bullet_iter: Box<dyn Iterator<Item = &Value>> = match bullets {
+			Value::Array(arr) => Box::new(arr.iter()),
+			obj @ Value::Object(_) => Box::new(std::iter::once(obj)),
+			_ => return false,
+		};
+		for bullet in bullet_iter {
+			if bullet.get("rocket").is_some() {
+				return true;
 			}
 		}
 	}
-	false
+	// Check for direct "rocket" key in belt (standalone rocket sections)
+	matches!(belt.get("rocket"), Some(Value::Object(_) | Value::Array(_)))
 }
 
 /// Check if a belt should be included based on vehicle data.
@@ -363,17 +638,30 @@ fn should_include_belt(belt_name: &str, vehicle_str: Option<&str>) -> bool {
 	false
 }
 
-/// Strip nation prefix from belt name (legacy behavior).
-fn strip_nation_prefix(name: &str) -> String {
-	let prefixes = [
-		"_cn_", "_fr_", "_germ_", "_il_", "_it_", "_jp_", "_sw_", "_uk_", "_us_", "_ussr_",
-	];
+/// Check whether a belt is unlocked through a vehicle modification.
+///
+/// `modifications` is the vehicle's `modifications` object; a belt is gated
+/// when it (or its nation-stripped name) appears as one of its keys.
+fn is_modification_gated(belt_name: &str, modifications: Option<&Value>) -> bool {
+	let Some(Value::Object(mods)) = modifications else {
+		return false;
+	};
+	mods.contains_key(belt_name) || mods.contains_key(&strip_nation_prefix(belt_name))
+}
 
-	let mut result = name.to_string();
-	for prefix in prefixes {
-		result = result.replace(prefix, "_");
+/// Strip the nation segment from a belt name (legacy behavior).
+///
+/// The nation follows the caliber (`120mm_us_m829a2` → `120mm_m829a2`), so
+/// only the second segment is stripped; a nation token later in the name
+/// (`76mm_apcbc_us_m62`) is part of the belt's own name and kept.
+fn strip_nation_prefix(name: &str) -> String {
+	let Some((head, rest)) = name.split_once('_') else {
+		return name.to_owned();
+	};
+	match rest.split_once('_') {
+		Some((nation, tail)) if NATIONS.contains(&nation) => format!("{head}_{tail}"),
+		_ => name.to_owned(),
 	}
-	result
 }
 
 /// Extract bullet name, handling both scalar and array cases.
@@ -392,20 +680,51 @@ fn extract_bullet_name(bullet: &Value) -> Option<String> {
 	}
 }
 
-/// Extract Cx drag coefficient, handling both scalar and array cases.
-/// For arrays, legacy tool averages all values and rounds to 4 decimal places.
-fn extract_cx(obj: &Value) -> Option<f64> {
+/// Raw `Cx` field: a scalar or a per-Mach-band array.
+#[derive(Debug, Clone)]
+enum CxSource {
+	Scalar(f64),
+	Array(Vec<f64>),
+}
+
+impl CxSource {
+	/// Reduce to a single drag coefficient.
+	///
+	/// `speed` is the muzzle velocity, used by [`CxArrayPolicy::AtMuzzleMach`]
+	/// (falls back to the first entry when unknown).
+	#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::cast_precision_loss)]
+	fn resolve(&self, policy: CxArrayPolicy, speed: Option<f64>) -> f64 {
+		let values = match self {
+			Self::Scalar(v) => return *v,
+			Self::Array(values) => values,
+		};
+		match policy {
+			CxArrayPolicy::First => values[0],
+			CxArrayPolicy::Mean => {
+				// Legacy behaviour: average all array values, round to 4 decimal places
+				let avg = values.iter().sum::<f64>() / values.len() as f64;
+				// Math.Round(average, 4) in C#
+				(avg * 10000.0).round() / 10000.0
+			},
+			CxArrayPolicy::AtMuzzleMach => {
+				let mach = speed.unwrap_or(0.0).max(0.0) / SPEED_OF_SOUND;
+				values[(mach.floor() as usize).min(values.len() - 1)]
+			},
+		}
+	}
+}
+
+/// Read the Cx drag coefficient, handling both scalar and array cases.
+/// Arrays with no numeric entries are treated as missing.
+fn read_cx(obj: &Value) -> Option<CxSource> {
 	match obj.get("Cx") {
-		Some(Value::Number(n)) => n.as_f64(),
+		Some(Value::Number(n)) => n.as_f64().map(CxSource::Scalar),
 		Some(Value::Array(arr)) => {
-			// Legacy behavior: average all array values, round to 4 decimal places
 			let values: Vec<f64> = arr.iter().filter_map(Value::as_f64).collect();
 			if values.is_empty() {
 				None
 			} else {
-				let avg = values.iter().sum::<f64>() / values.len() as f64;
-				// Math.Round(average, 4) in C#
-				Some((avg * 10000.0).round() / 10000.0)
+				Some(CxSource::Array(values))
 			}
 		},
 		_ => None,
@@ -422,12 +741,42 @@ fn extract_f64_or_first(obj: &Value, key: &str) -> Option<f64> {
 	}
 }
 
+/// Keys that mark the object holding a rocket's flight physics.
+const ROCKET_PHYSICS_KEYS: [&str; 3] = ["speed", "endSpeed", "Cx"];
+
+/// Levels below `bullet.rocket` searched for the rocket physics.
+const ROCKET_NESTING_DEPTH: usize = 2;
+
+/// The `rocket` section of a bullet that holds its physics.
+///
+/// Usually that is `bullet.rocket` itself, but some unguided rocket modules
+/// nest it once more, as `rocket.rocket`, or under a launcher wrapper object
+/// (`rocket.<launcher>`); up to [`ROCKET_NESTING_DEPTH`] levels are searched,
+/// a nested `rocket` first.  Without physics at any level, `bullet.rocket`
+/// is returned as is.
+fn rocket_section(bullet: &Value) -> Option<&Value> {
+	fn find(section: &Value, depth: usize) -> Option<&Value> {
+		if ROCKET_PHYSICS_KEYS.iter().any(|key| section.get(key).is_some()) {
+			return Some(section);
+		}
+		if depth == 0 {
+			return None;
+		}
+		let nested = section.get("rocket").into_iter();
+		let wrappers = section.as_object().into_iter().flat_map(|obj| obj.values());
+		nested.chain(wrappers).filter(|v| v.is_object()).find_map(|v| find(v, depth - 1))
+	}
+
+	let rocket = bullet.get("rocket")?;
+	find(rocket, ROCKET_NESTING_DEPTH).or(Some(rocket))
+}
+
 /// Extract armor power for ATGMs/rockets.
 fn extract_armor_power(bullet: &Value) -> Option<f64> {
 	// Check multiple possible locations
 	let sources = [
 		bullet.get("cumulativeDamage"),
-		bullet.get("rocket").and_then(|r| r.get("cumulativeDamage")),
+		rocket_section(bullet).and_then(|r| r.get("cumulativeDamage")),
 		Some(bullet),
 	];
 
@@ -474,20 +823,61 @@ mod tests {
 	use super::*;
 	use serde_json::json;
 
+	/// Reduce an object's `Cx` with the given policy.
+	fn extract_cx(obj: &Value, policy: CxArrayPolicy, speed: Option<f64>) -> Option<f64> {
+		read_cx(obj).map(|cx| cx.resolve(policy, speed))
+	}
+
 	#[test]
 	fn test_extract_cx_array() {
 		let obj = json!({ "Cx": [0.3, 0.4, 0.5] });
-		let cx = extract_cx(&obj);
+		let cx = extract_cx(&obj, CxArrayPolicy::default(), None);
 		// Should average all values: (0.3 + 0.4 + 0.5) / 3 = 0.4, rounded to 4 decimal places
 		assert!((cx.unwrap() - 0.4).abs() < 0.00001);
 
 		// Test rounding to 4 decimal places
 		let obj2 = json!({ "Cx": [0.276611, 0.33] });
-		let cx2 = extract_cx(&obj2);
+		let cx2 = extract_cx(&obj2, CxArrayPolicy::default(), None);
 		// Average = 0.303305... rounds to 0.3033
 		assert!((cx2.unwrap() - 0.3033).abs() < 0.00001);
 	}
 
+	#[test]
+	fn test_cx_array_policies() {
+		let weapon = json!({
+			"bullet": {
+				"bulletName": "90mm_m82",
+				"bulletType": "apcbc_tank",
+				"speed": 853.44,
+				"Cx": [0.276_611, 0.33, 0.35]
+			}
+		});
+		let cx_with = |policy| {
+			let options = ParseOptions {
+				cx_array_policy: policy,
+				..ParseOptions::default()
+			};
+			parse_weapon_module_with_options(&weapon, None, options).unwrap()[0]
+				.cx
+				.unwrap()
+		};
+
+		let first = cx_with(CxArrayPolicy::First);
+		let mean = cx_with(CxArrayPolicy::Mean);
+		assert!((first - 0.276_611).abs() < 1e-9);
+		assert!((mean - 0.3189).abs() < 1e-9);
+		assert!(first < mean);
+
+		// 853.44 m/s ≈ Mach 2.5 → third band
+		assert!((cx_with(CxArrayPolicy::AtMuzzleMach) - 0.35).abs() < 1e-9);
+
+		// Scalars are never reduced
+		let scalar = json!({ "Cx": 0.276_611 });
+		for policy in [CxArrayPolicy::First, CxArrayPolicy::Mean, CxArrayPolicy::AtMuzzleMach] {
+			assert_eq!(extract_cx(&scalar, policy, Some(853.44)), Some(0.276_611));
+		}
+	}
+
 	#[test]
 	fn test_extract_bullet_name_array() {
 		let bullet = json!({
@@ -529,6 +919,116 @@ mod tests {
 		assert_eq!(result[0].name, "top_level");
 	}
 
+	#[test]
+	fn test_strip_nation_prefix_only_after_caliber() {
+		assert_eq!(strip_nation_prefix("120mm_us_m829a2"), "120mm_m829a2");
+		assert_eq!(strip_nation_prefix("125mm_ussr_3bm42"), "125mm_3bm42");
+		// Not a nation prefix: left intact
+		assert_eq!(strip_nation_prefix("76mm_apcbc_us_m62"), "76mm_apcbc_us_m62");
+		assert_eq!(strip_nation_prefix("75mm_ger_kwk40_HE"), "75mm_ger_kwk40_HE");
+		assert_eq!(strip_nation_prefix("us_belt"), "us_belt");
+
+		// Inclusion still matches the stripped name, but no longer a name
+		// corrupted mid-way
+		let vehicle = r#"{"preset": "120mm_m829a2", "other": "76mm_apcbc_m62"}"#;
+		assert!(should_include_belt("120mm_us_m829a2", Some(vehicle)));
+		assert!(!should_include_belt("76mm_apcbc_us_m62", Some(vehicle)));
+		assert!(should_include_belt("76mm_apcbc_m62", Some(vehicle)));
+	}
+
+	#[test]
+	fn test_unlocked_by_default() {
+		let weapon = json!({
+			"bullet": {
+				"bulletName": "75mm_pzgr_39",
+				"bulletType": "apcbc_tank"
+			},
+			"75mm_ger_kwk40_APCR": {
+				"bullet": {
+					"bulletName": "75mm_pzgr_40",
+					"bulletType": "apcr_tank"
+				}
+			},
+			"75mm_ger_kwk40_HE": {
+				"bullet": {
+					"bulletName": "75mm_sprgr_34",
+					"bulletType": "he_frag_tank"
+				}
+			}
+		});
+
+		// APCR belt is behind a modification; HE belt is referenced elsewhere
+		let vehicle = json!({
+			"weapon_presets": {"preset": "75mm_ger_kwk40_HE"},
+			"modifications": {
+				"tank_new_gun": {},
+				"75mm_ger_kwk40_APCR": {}
+			}
+		});
+		let result = parse_weapon_module(&weapon, Some(&vehicle)).unwrap();
+		assert_eq!(result.len(), 3);
+
+		assert_eq!(result[0].name, "75mm_pzgr_39");
+		assert!(result[0].unlocked_by_default, "top-level bullet is stock");
+		assert_eq!(result[1].name, "75mm_pzgr_40");
+		assert!(!result[1].unlocked_by_default, "APCR belt is modification-gated");
+		assert_eq!(result[2].name, "75mm_sprgr_34");
+		assert!(result[2].unlocked_by_default, "HE belt is not a modification");
+	}
+
+	#[test]
+	fn test_malformed_belts_produce_warnings() {
+		let weapon = json!({
+			"bullet": {
+				"bulletName": "top_level",
+				"bulletType": "ap",
+				"Cx": ["n/a"]
+			},
+			"30mm_empty": {
+				"bullet": []
+			},
+			"30mm_nobullet": {
+				"shotFreq": 5.0
+			},
+			"30mm_notype": {
+				"bullet": {"bulletName": "orphan"}
+			},
+			"30mm_HE": {
+				"bullet": {
+					"bulletName": "belt_bullet",
+					"bulletType": "he"
+				}
+			}
+		});
+
+		let mut warnings = Vec::new();
+		let result = parse_weapon_module_with_warnings(
+			&weapon,
+			None,
+			ParseOptions::default(),
+			&mut warnings,
+		)
+		.unwrap();
+
+		// Well-formed entries still come through
+		let names: Vec<&str> = result.iter().map(|p| p.name.as_str()).collect();
+		assert_eq!(names, ["top_level", "belt_bullet"]);
+
+		let messages: Vec<String> = warnings.iter().map(ToString::to_string).collect();
+		assert_eq!(
+			messages,
+			[
+				"weapon module bullet: Cx has no numeric value",
+				"weapon module 30mm_empty/bullet: empty bullet array",
+				"weapon module 30mm_nobullet: belt has no bullet",
+				"weapon module 30mm_notype/bullet: bullet has no bulletType",
+			]
+		);
+
+		let err = parse_weapon_module(&json!([]), None).unwrap_err();
+		assert!(matches!(err, ParseError::WeaponModule { .. }));
+	}
+
 	#[test]
 	fn test_belt_with_rocket_always_included() {
 		let weapon = json!({
@@ -559,4 +1059,228 @@ mod tests {
 		assert_eq!(result.len(), 2, "ATGM belt should be included: {:?}", result);
 		assert_eq!(result[1].bullet_type, "atgm_tandem_tank");
 	}
+
+	#[test]
+	fn test_doubly_nested_rocket_physics() {
+		let rocket = |rocket: serde_json::Value| {
+			let weapon = json!({
+				"bullet": {
+					"bulletName": "70mm_hydra_m151",
+					"bulletType": "rocket_tank",
+					"rocket": rocket
+				}
+			});
+			parse_weapon_module(&weapon, None).unwrap().remove(0)
+		};
+		let physics = json!({
+			"mass": 6.2,
+			"caliber": 0.07,
+			"endSpeed": 740.0,
+			"Cx": 0.45,
+			"cumulativeDamage": {"armorPower": 20.0}
+		});
+
+		// `rocket.rocket.rocket` and `rocket.<launcher>.rocket`
+		let nested = rocket(json!({"rocket": {"rocket": physics.clone()}}));
+		let wrapped = rocket(json!({"launcher": {"rocket": physics}}));
+		for proj in [nested, wrapped] {
+			assert_eq!(proj.speed, Some(740.0));
+			assert_eq!(proj.cx, Some(0.45));
+			assert_eq!(proj.ballistic_caliber, Some(0.07));
+			assert_eq!(proj.armor_power, Some(20.0));
+		}
+	}
+
+	#[test]
+	fn test_belt_shapes() {
+		let weapon = json!({
+			// `bullet` is a single object, not an array
+			"75mm_ger_kwk40_HE": {
+				"bullet": {
+					"bulletName": "75mm_sprgr_34",
+					"bulletType": "he_frag_tank"
+				}
+			},
+			// Repeated belt block: first section only, like belt bullets
+			"75mm_ger_kwk40_APHE": [
+				{"bullet": {"bulletName": "75mm_pzgr_39", "bulletType": "apcbc_tank"}},
+				{"bullet": {"bulletName": "ignored", "bulletType": "ap_tank"}}
+			],
+			// Array-valued keys that are not belts
+			"75mm_ger_kwk40_other": [{"shotFreq": 0.1}],
+			"75mm_ger_kwk40_APCR": {
+				"bullet": {"bulletName": "75mm_pzgr_40", "bulletType": "apcr_tank"}
+			}
+		});
+
+		// The include rules still apply to both shapes
+		let vehicle = json!({
+			"weapon_presets": {"preset": ["75mm_ger_kwk40_HE", "75mm_ger_kwk40_APHE"]}
+		});
+		let mut warnings = Vec::new();
+		let result = parse_weapon_module_with_warnings(
+			&weapon,
+			Some(&vehicle),
+			ParseOptions::default(),
+			&mut warnings,
+		)
+		.unwrap();
+		let names: Vec<&str> = result.iter().map(|p| p.name.as_str()).collect();
+		assert_eq!(names, ["75mm_sprgr_34", "75mm_pzgr_39"]);
+		assert!(warnings.is_empty(), "{warnings:?}");
+
+		let all = parse_weapon_module(&weapon, None).unwrap();
+		assert_eq!(all.len(), 3);
+	}
+
+	#[test]
+	fn test_belt_all_keeps_alternating_rounds() {
+		// Alternating AP/HE belt whose rounds share one bulletName
+		let round = |bullet_type: &str, mass: f64| {
+			json!({
+				"bulletName": "20mm_hs404",
+				"bulletType": bullet_type,
+				"mass": mass,
+				"caliber": 0.02,
+				"speed": 880.0,
+				"Cx": 0.35
+			})
+		};
+		let weapon = json!({
+			"20mm_hs404_default": {
+				"bullet": [
+					round("ap_t", 0.13),
+					round("he_frag_i", 0.12),
+					round("ap_t", 0.13),
+					round("he_frag_i", 0.12)
+				]
+			}
+		});
+
+		let first = parse_weapon_module(&weapon, None).unwrap();
+		assert_eq!(first.len(), 1, "legacy: first round only");
+
+		let options = ParseOptions {
+			belt_bullets: BeltBullets::All,
+			..ParseOptions::default()
+		};
+		let all = parse_weapon_module_with_options(&weapon, None, options).unwrap();
+		let rounds: Vec<(&str, &str)> =
+			all.iter().map(|p| (p.name.as_str(), p.bullet_type.as_str())).collect();
+		assert_eq!(
+			rounds,
+			[("20mm_hs404", "ap_t"), ("20mm_hs404_he_frag_i", "he_frag_i")]
+		);
+
+		// Two file names, two different tables
+		let shells: Vec<_> = all.iter().map(crate::parser::data::from_projectile).collect();
+		assert_ne!(shells[0].output_name, shells[1].output_name);
+		let tables: Vec<_> = shells
+			.iter()
+			.map(|shell| crate::ballistic::compute_ballistic(shell, 1.0).unwrap())
+			.collect();
+		assert_ne!(tables[0], tables[1]);
+	}
+
+	#[test]
+	fn test_blast_radius_extracted_and_roundtrips() {
+		use crate::emit::emit_legacy_txt;
+		use crate::model::VehicleData;
+		use crate::parser::data::parse_data_text;
+
+		let shell = |damage: Value| {
+			json!({
+				"bullet": {
+					"bulletName": "120mm_dm11",
+					"bulletType": "he_frag_fs_tank",
+					"mass": 19.0,
+					"caliber": 0.12,
+					"speed": 1000.0,
+					"Cx": 0.3,
+					"explosiveMass": 2.1,
+					"explosiveType": "a_ix_1",
+					"damage": damage
+				}
+			})
+		};
+		let projectiles = parse_weapon_module(
+			&shell(json!({ "explosive": { "radius": 0.3, "offset": 0.0 } })),
+			None,
+		)
+		.unwrap();
+		assert_eq!(projectiles[0].blast_radius, Some(0.3));
+		let without = parse_weapon_module(&shell(json!({ "kinetic": {} })), None).unwrap();
+		assert_eq!(without[0].blast_radius, None);
+
+		let mut vehicle = VehicleData::new("test_vehicle");
+		vehicle.weapon_path = Some("gameData/Weapons/test.blkx".to_owned());
+		vehicle.projectiles = projectiles;
+		let text = emit_legacy_txt(&vehicle);
+		assert!(text.contains("\nBlastRadius:0.3"), "{text}");
+
+		let parsed = parse_data_text(&text, "test_vehicle");
+		assert_eq!(parsed.projectiles[0].blast_radius, Some(0.3));
+	}
+
+	#[test]
+	fn test_aphe_fuze_extracted_and_roundtrips() {
+		use crate::emit::emit_legacy_txt;
+		use crate::model::VehicleData;
+		use crate::parser::data::parse_data_text;
+
+		let module = json!({
+			"bullet": {
+				"bulletName": "75mm_pzgr_39",
+				"bulletType": "apcbc_tank",
+				"mass": 6.8,
+				"caliber": 0.075,
+				"speed": 740.0,
+				"Cx": 0.4,
+				"explosiveMass": 0.0288,
+				"fuseDelayDist": 1.2,
+				"explodeTreshold": 15.0
+			}
+		});
+		let projectiles = parse_weapon_module(&module, None).unwrap();
+		assert_eq!(projectiles[0].fuze_delay_m, Some(1.2));
+		assert_eq!(projectiles[0].fuze_sensitivity, Some(15.0));
+
+		let mut vehicle = VehicleData::new("test_vehicle");
+		vehicle.weapon_path = Some("gameData/Weapons/test.blkx".to_owned());
+		vehicle.projectiles = projectiles;
+		let text = emit_legacy_txt(&vehicle);
+		assert!(text.contains("\nFuzeDelay:1.2\nFuzeSensitivity:15.0"), "{text}");
+
+		let parsed = parse_data_text(&text, "test_vehicle");
+		assert_eq!(parsed.projectiles[0].fuze_delay_m, Some(1.2));
+		assert_eq!(parsed.projectiles[0].fuze_sensitivity, Some(15.0));
+	}
+
+	#[test]
+	fn test_hull_break_flag() {
+		let hull_break = |bullet: serde_json::Value| {
+			let projectiles = parse_weapon_module(&json!({ "bullet": bullet }), None).unwrap();
+			projectiles[0].hull_break
+		};
+		assert!(hull_break(json!({
+			"bulletName": "30mm_oerlikon_hei",
+			"bulletType": "he_frag_i_t",
+			"mass": 0.36,
+			"caliber": 0.03,
+			"speed": 1080.0,
+			"explosiveMass": 0.049
+		})));
+		assert!(!hull_break(json!({
+			"bulletName": "120mm_l11_apds",
+			"bulletType": "apds_tank",
+			"mass": 6.4,
+			"caliber": 0.12,
+			"speed": 1370.0
+		})));
+
+		// HEAT and large HE rounds are not hull-breakers by this heuristic
+		assert!(!is_hull_break_capable("heat_fs_tank", 0.03, 0.05));
+		assert!(!is_hull_break_capable("he_frag_tank", 0.152, 6.0));
+		assert!(!is_hull_break_capable("he_i", 0.02, 0.002));
+	}
 }
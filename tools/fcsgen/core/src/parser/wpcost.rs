@@ -0,0 +1,109 @@
+//! Parser for wpcost.blkx — per-vehicle rank and battle rating.
+//!
+//! War Thunder's `char.vromfs.bin/config/wpcost.blkx` holds the economy
+//! settings of every unit as a JSON object keyed by unit ID:
+//! ```json
+//! {
+//!     "economicRankMax": 38,
+//!     "us_m1_abrams": { "rank": 6, "economicRankHistorical": 28, ... },
+//!     ...
+//! }
+//! ```
+//! `rank` is the research tier (1 = I).  The battle rating is encoded as an
+//! economic rank per game mode, three steps per whole BR starting at 1.0;
+//! see [`battle_rating`].
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+/// Tier and battle rating of one vehicle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VehicleRank {
+	/// Research tier (1 = I, 8 = VIII).
+	pub rank: u32,
+	/// Realistic battles BR, from `economicRankHistorical`.
+	pub battle_rating: f64,
+}
+
+/// A map from lowercase vehicle ID to its rank and battle rating.
+pub type RankMap = HashMap<String, VehicleRank>;
+
+/// Battle rating of an economic rank: `1.0 + rank / 3`, to one decimal
+/// (0 → 1.0, 1 → 1.3, 2 → 1.7, 28 → 10.3).
+#[must_use]
+pub fn battle_rating(economic_rank: u32) -> f64 {
+	(10.0 + f64::from(economic_rank) * 10.0 / 3.0).round() / 10.0
+}
+
+/// Parse wpcost.blkx JSON into a rank map.
+///
+/// Entries without both `rank` and `economicRankHistorical` (scalar
+/// settings, units without a realistic BR) are skipped.
+#[must_use]
+pub fn parse_wpcost(json: &Value) -> RankMap {
+	let mut map = HashMap::new();
+
+	if let Value::Object(obj) = json {
+		for (key, unit) in obj {
+			let field = |name| {
+				unit.get(name)
+					.and_then(Value::as_u64)
+					.and_then(|v| u32::try_from(v).ok())
+			};
+			let (Some(rank), Some(economic_rank)) = (field("rank"), field("economicRankHistorical"))
+			else {
+				continue;
+			};
+			map.insert(
+				key.to_lowercase(),
+				VehicleRank {
+					rank,
+					battle_rating: battle_rating(economic_rank),
+				},
+			);
+		}
+	}
+
+	map
+}
+
+/// Parse wpcost from a JSON string.
+///
+/// Returns `None` if the JSON is invalid.
+#[must_use]
+pub fn parse_wpcost_str(content: &str) -> Option<RankMap> {
+	let json: Value = serde_json::from_str(content).ok()?;
+	Some(parse_wpcost(&json))
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+
+	#[test]
+	fn test_parse_wpcost() {
+		let map = parse_wpcost(&json!({
+			"economicRankMax": 38,
+			"us_M1_abrams": { "rank": 6, "economicRankHistorical": 28 },
+			"germ_pzkpfw_IV_ausf_F": { "rank": 2, "economicRankHistorical": 3 },
+			"us_no_br": { "rank": 1 }
+		}));
+
+		assert_eq!(map.len(), 2);
+		let abrams = map["us_m1_abrams"];
+		assert_eq!(abrams.rank, 6);
+		assert!((abrams.battle_rating - 10.3).abs() < 1e-9);
+		assert!((map["germ_pzkpfw_iv_ausf_f"].battle_rating - 2.0).abs() < 1e-9);
+	}
+
+	#[test]
+	fn test_battle_rating_steps() {
+		let brs: Vec<_> = [0, 1, 2, 3, 37].into_iter().map(battle_rating).collect();
+		for (br, expected) in brs.into_iter().zip([1.0, 1.3, 1.7, 2.0, 13.3]) {
+			assert!((br - expected).abs() < 1e-9, "{br} != {expected}");
+		}
+	}
+}
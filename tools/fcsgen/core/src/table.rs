@@ -0,0 +1,294 @@
+//! Structured access to written ballistic tables.
+//!
+//! For tools that consume `Ballistic/{vehicle}/{shell}.txt` rather than
+//! produce it: [`parse_table`] turns the TSV back into rows, and
+//! [`penetration_at`] / [`time_at`] interpolate between the irregular
+//! scroll-step distances.  [`drops_at_standard_ranges`] does the same for
+//! the launch angles of an elevation table, at the round ranges a sight
+//! reticle marks, and [`suggest_zero_distance`] picks a sight zero from
+//! them.  [`penetration_delta`] compares two shells' tables.
+
+use crate::ballistic::strip_table_header;
+
+/// One row of a ballistic table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BallisticRow {
+	/// Distance along the line of fire (m).
+	pub distance: f64,
+	/// Time of flight to `distance` (s).
+	pub time: f64,
+	/// Penetration at `distance`, in the table's unit; `∞` is
+	/// [`f64::INFINITY`].
+	pub penetration: f64,
+}
+
+impl BallisticRow {
+	/// Parse a `distance\ttime\tpenetration` line.
+	///
+	/// Returns `None` for lines with fewer than three columns or non-numeric
+	/// values.
+	#[must_use]
+	pub fn parse(line: &str) -> Option<Self> {
+		let mut cols = line.split('\t');
+		let distance = cols.next()?.parse().ok()?;
+		let time = cols.next()?.parse().ok()?;
+		let penetration = match cols.next()? {
+			"\u{221E}" => f64::INFINITY,
+			pen => pen.parse().ok()?,
+		};
+		Some(Self {
+			distance,
+			time,
+			penetration,
+		})
+	}
+}
+
+/// Parse a ballistic table into rows, skipping the optional header line and
+/// any line that is not a row.
+#[must_use]
+pub fn parse_table(table: &str) -> Vec<BallisticRow> {
+	strip_table_header(table)
+		.lines()
+		.filter_map(|line| BallisticRow::parse(line.trim_end_matches('\r')))
+		.collect()
+}
+
+/// Penetration at `distance`, linearly interpolated between the bracketing
+/// rows.
+///
+/// `rows` must be sorted by distance, as written.  Returns `None` when
+/// `distance` lies outside the table.  Between an `∞` row and a finite one
+/// the result is `∞`.
+#[must_use]
+pub fn penetration_at(rows: &[BallisticRow], distance: f64) -> Option<f64> {
+	interpolate(rows, distance, |row| row.distance, |row| row.penetration)
+}
+
+/// Time of flight to `distance`, linearly interpolated between the
+/// bracketing rows.  See [`penetration_at`].
+#[must_use]
+pub fn time_at(rows: &[BallisticRow], distance: f64) -> Option<f64> {
+	interpolate(rows, distance, |row| row.distance, |row| row.time)
+}
+
+/// Penetration of `rows` minus that of `reference` at every distance of
+/// either table where both are defined, e.g. for a sight comparing a shell
+/// to the stock round.
+///
+/// Both tables are interpolated with [`penetration_at`] onto the union of
+/// their row distances within the range they share, so the deltas are in
+/// the tables' unit (mm by default).  A positive delta means `rows`
+/// penetrates more.  Empty when the tables do not overlap.
+#[must_use]
+pub fn penetration_delta(rows: &[BallisticRow], reference: &[BallisticRow]) -> Vec<(f64, f64)> {
+	let mut grid: Vec<f64> = rows.iter().chain(reference).map(|row| row.distance).collect();
+	grid.sort_by(f64::total_cmp);
+	grid.dedup();
+	grid
+		.into_iter()
+		.filter_map(|distance| {
+			let delta = penetration_at(rows, distance)? - penetration_at(reference, distance)?;
+			Some((distance, delta))
+		})
+		.collect()
+}
+
+/// Launch angle (milliradians, the unit of the scroll step and of
+/// `--range-marks`) needed to reach each of `ranges`, linearly interpolated
+/// in an elevation table.
+///
+/// `rows` are `(distance, angle)` pairs as returned by
+/// [`compute_elevation_table`](crate::compute_elevation_table), angles in
+/// radians.  Ranges beyond the shell's reach (past the last row) yield
+/// `None`.
+#[must_use]
+pub fn drops_at_standard_ranges(rows: &[(f64, f64)], ranges: &[f64]) -> Vec<(f64, Option<f64>)> {
+	ranges
+		.iter()
+		.map(|&range| {
+			let angle = interpolate(rows, range, |&(distance, _)| distance, |&(_, angle)| angle);
+			(range, angle.map(|angle| angle * 1000.0))
+		})
+		.collect()
+}
+
+/// End (m) of the engagement band searched by [`suggest_zero_distance`].
+pub const ZERO_BAND: f64 = 1000.0;
+
+/// Largest rise (m) above the line of sight that [`suggest_zero_distance`]
+/// accepts short of the zero: about half the height of a tank.
+pub const ZERO_TOLERANCE: f64 = 0.5;
+
+/// Spacing (m) of the ranges [`suggest_zero_distance`] checks.
+const ZERO_STEP: f64 = 10.0;
+
+/// Suggested sight zero (m) for a shell, from its elevation table.
+///
+/// A sight zeroed at `z` fires at the launch angle for `z`, so a target at
+/// `d` is hit `d · (θ(z) − θ(d))` above the point of aim: high before the
+/// zero, low beyond it.  The suggestion is the farthest zero, in steps of
+/// 10 m up to [`ZERO_BAND`] (or the shell's reach), whose shots never pass
+/// more than [`ZERO_TOLERANCE`] above the line of sight on the way — the
+/// flattest useful trajectory, so fast rounds get a longer zero than slow
+/// ones.  `rows` are `(distance, angle)` pairs as for
+/// [`drops_at_standard_ranges`], angles in radians.  Returns 0 for an empty
+/// table.
+#[must_use]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn suggest_zero_distance(rows: &[(f64, f64)]) -> f64 {
+	let angle_at = |range: f64| {
+		interpolate(rows, range, |&(distance, _)| distance, |&(_, angle)| angle)
+	};
+	let band_end = rows.last().map_or(0.0, |&(distance, _)| distance.min(ZERO_BAND));
+	let ranges: Vec<(f64, f64)> = (1..=(ZERO_BAND / ZERO_STEP) as u32)
+		.map(|i| f64::from(i) * ZERO_STEP)
+		.take_while(|&range| range <= band_end)
+		.filter_map(|range| Some((range, angle_at(range)?)))
+		.collect();
+
+	let mut zero = 0.0;
+	for (i, &(candidate, zero_angle)) in ranges.iter().enumerate() {
+		let rise = ranges[..i]
+			.iter()
+			.map(|&(range, angle)| range * (zero_angle - angle))
+			.fold(0.0, f64::max);
+		if rise > ZERO_TOLERANCE {
+			break;
+		}
+		zero = candidate;
+	}
+	zero
+}
+
+/// Interpolate the column selected by `value` at `distance`, with rows
+/// sorted by `key`.
+fn interpolate<R>(
+	rows: &[R],
+	distance: f64,
+	key: impl Fn(&R) -> f64,
+	value: impl Fn(&R) -> f64,
+) -> Option<f64> {
+	let (first, last) = (rows.first()?, rows.last()?);
+	if !(key(first)..=key(last)).contains(&distance) {
+		return None;
+	}
+
+	// First row at or beyond `distance`; exists since `distance <= last`
+	let i = rows.partition_point(|row| key(row) < distance);
+	let hi = &rows[i];
+	if i == 0 || distance >= key(hi) {
+		return Some(value(hi));
+	}
+
+	let lo = &rows[i - 1];
+	let t = (distance - key(lo)) / (key(hi) - key(lo));
+	Some(value(lo) * (1.0 - t) + value(hi) * t)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TABLE: &str = "\
+# shell=test\ttype=apcbc\tcaliber=0.075\tspeed=740\tsensitivity=0.5
+0.000\t0\t150
+100.000\t0.1\t140
+300.000\t0.4\t120
+";
+
+	fn approx(actual: Option<f64>, expected: f64) {
+		let actual = actual.expect("in range");
+		assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+	}
+
+	#[test]
+	fn test_parse_table_skips_header() {
+		let rows = parse_table(TABLE);
+		assert_eq!(rows.len(), 3);
+		assert_eq!(
+			rows[1],
+			BallisticRow {
+				distance: 100.0,
+				time: 0.1,
+				penetration: 140.0,
+			}
+		);
+		assert!(BallisticRow::parse("0\t0\t\u{221E}").unwrap().penetration.is_infinite());
+	}
+
+	#[test]
+	fn test_exact_row() {
+		let rows = parse_table(TABLE);
+		approx(penetration_at(&rows, 0.0), 150.0);
+		approx(penetration_at(&rows, 100.0), 140.0);
+		approx(penetration_at(&rows, 300.0), 120.0);
+		approx(time_at(&rows, 300.0), 0.4);
+	}
+
+	#[test]
+	fn test_between_rows() {
+		let rows = parse_table(TABLE);
+		approx(penetration_at(&rows, 50.0), 145.0);
+		approx(penetration_at(&rows, 250.0), 125.0);
+		approx(time_at(&rows, 200.0), 0.25);
+	}
+
+	#[test]
+	fn test_out_of_range() {
+		let rows = parse_table(TABLE);
+		assert_eq!(penetration_at(&rows, -1.0), None);
+		assert_eq!(penetration_at(&rows, 300.5), None);
+		assert_eq!(time_at(&rows, f64::NAN), None);
+		assert_eq!(penetration_at(&[], 0.0), None);
+	}
+
+	#[test]
+	fn test_penetration_delta_on_common_grid() {
+		// APDS: 200 mm at the muzzle, losing 10 mm per 100 m out to 400 m
+		let apds = parse_table("0\t0\t200\n200\t0.2\t180\n400\t0.4\t160\n");
+		let ap = parse_table(TABLE);
+
+		let delta = penetration_delta(&apds, &ap);
+		// Union of both tables' distances, cut at the shorter table's end
+		assert!(delta.iter().map(|&(range, _)| range).eq([0.0, 100.0, 200.0, 300.0]));
+		assert!(delta.iter().all(|&(_, d)| d > 0.0), "{delta:?}");
+		approx(Some(delta[1].1), 190.0 - 140.0);
+		approx(Some(delta[3].1), 170.0 - 120.0);
+
+		let reversed = penetration_delta(&ap, &apds);
+		assert!(reversed.iter().all(|&(_, d)| d < 0.0), "{reversed:?}");
+		assert!(penetration_delta(&apds, &[]).is_empty());
+	}
+
+	#[test]
+	fn test_fast_round_zeroes_further_than_slow() {
+		let zero = |speed: f64| {
+			let data = format!(
+				"WeaponPath:w.blkx\n\nName:75mm_test\nType:apcbc_tank\nBulletMass:6.8\n\
+				 BallisticCaliber:0.075\nSpeed:{speed}\nCx:0.4\n"
+			);
+			let proj = &crate::parse_data_text(&data, "test_tank").projectiles[0];
+			let rows = crate::compute_elevation_table(proj, &crate::BallisticConfig::new(0.5));
+			suggest_zero_distance(&rows.unwrap())
+		};
+
+		let (fast, slow) = (zero(1000.0), zero(300.0));
+		assert!(fast > slow, "{fast} <= {slow}");
+		assert!(slow > 0.0 && fast <= ZERO_BAND);
+		assert!(suggest_zero_distance(&[]).abs() < f64::EPSILON);
+	}
+
+	#[test]
+	fn test_drops_at_standard_ranges() {
+		// Elevation table: 0 m flat, 400 m at 2 mrad, 900 m at 6 mrad
+		let rows = [(0.0, 0.0), (400.0, 0.002), (900.0, 0.006)];
+		let drops = drops_at_standard_ranges(&rows, &[400.0, 800.0, 1200.0]);
+
+		assert_eq!(drops.len(), 3);
+		assert!(drops.iter().map(|&(range, _)| range).eq([400.0, 800.0, 1200.0]));
+		approx(drops[0].1, 2.0);
+		approx(drops[1].1, 5.2);
+		assert_eq!(drops[2].1, None, "beyond the shell's reach");
+	}
+}
@@ -8,7 +8,16 @@
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
-use fcsgen_core::{convert_vehicle, emit_legacy_txt};
+use fcsgen_core::{
+	Datamine,
+	DefaultCx,
+	convert_vehicle,
+	emit_legacy_txt,
+	fill_default_cx,
+	iter_all_projectiles,
+	summarize_vehicle,
+	vehicle_module_paths,
+};
 
 /// Parsed representation of a legacy .txt output file.
 #[allow(dead_code)]
@@ -78,6 +87,10 @@ fn block_to_canonical(block: &HashMap<String, String>) -> String {
 	pairs.iter().map(|(k, v)| format!("{k}:{v}")).collect::<Vec<_>>().join("\n")
 }
 
+/// Ammo keys fcsgen writes that the legacy tool never did.  Stripped from
+/// our output before comparing, so the rest still has to match exactly.
+const NON_LEGACY_AMMO_KEYS: &[&str] = &["BlastRadius", "FuzeDelay", "FuzeSensitivity"];
+
 /// Get the path to the test_data directory.
 fn test_data_dir() -> PathBuf {
 	PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -103,9 +116,10 @@ fn check_vehicle(vehicle_name: &str) -> Result<(), String> {
 	let expected_data_dir = test_data_dir().join("expected").join("data");
 	let expected_path = expected_data_dir.join(format!("{vehicle_name}.txt"));
 
-	// Convert
-	let data = convert_vehicle(&vehicle_path, &test_data_dir().join("datamine"))
+	// Convert, with the legacy default Cx of the reference
+	let mut data = convert_vehicle(&vehicle_path, &test_data_dir().join("datamine"))
 		.map_err(|e| format!("conversion error: {e}"))?;
+	fill_default_cx(&mut data.projectiles, DefaultCx::Uniform);
 	let output = emit_legacy_txt(&data);
 
 	// Load expected
@@ -113,7 +127,10 @@ fn check_vehicle(vehicle_name: &str) -> Result<(), String> {
 		.map_err(|e| format!("cannot read expected: {e}"))?;
 
 	let exp = parse_legacy_txt(&expected);
-	let out = parse_legacy_txt(&output);
+	let mut out = parse_legacy_txt(&output);
+	for block in &mut out.ammo_blocks {
+		block.retain(|key, _| !NON_LEGACY_AMMO_KEYS.contains(&key.as_str()));
+	}
 
 	let mut diffs = Vec::new();
 
@@ -180,6 +197,89 @@ fn check_vehicle(vehicle_name: &str) -> Result<(), String> {
 	}
 }
 
+/// The summary counts exactly the shells that conversion extracts.
+#[test]
+fn test_summary_matches_conversion() {
+	let datamine = test_data_dir().join("datamine");
+	let vehicles_path = datamine
+		.join("aces.vromfs.bin_u")
+		.join("gamedata")
+		.join("units")
+		.join("tankmodels");
+	if !vehicles_path.exists() {
+		eprintln!("Skipping summary test: examples not present");
+		return;
+	}
+
+	let summarize = |path: &std::path::Path| {
+		let id = path.file_stem().unwrap().to_string_lossy().to_string();
+		let json = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+		summarize_vehicle(&json, &id, &datamine).unwrap()
+	};
+
+	let bmp = vehicles_path.join("ussr_bmp_2m.blkx");
+	let summary = summarize(&bmp);
+	let data = convert_vehicle(&bmp, &datamine).unwrap();
+	assert!(summary.armed);
+	assert_eq!(summary.shell_count, data.projectiles.len());
+	assert_eq!(summary.has_laser, data.has_laser);
+	assert_eq!(summary.nation.as_deref(), Some("ussr"));
+
+	for entry in std::fs::read_dir(&vehicles_path).unwrap() {
+		let path = entry.unwrap().path();
+		let Ok(data) = convert_vehicle(&path, &datamine) else {
+			continue;
+		};
+		let summary = summarize(&path);
+		assert_eq!(summary.shell_count, data.projectiles.len(), "{}", summary.id);
+		assert_eq!(summary.armed, data.is_armed(), "{}", summary.id);
+	}
+}
+
+#[test]
+fn test_iter_all_projectiles_attaches_vehicle_ids() {
+	let datamine_root = test_data_dir().join("datamine");
+	let aces = datamine_root.join("aces.vromfs.bin_u");
+	let tankmodels = aces.join("gamedata/units/tankmodels");
+	let vehicles = ["ussr_bmp_2m", "cn_bt_5"];
+	if vehicles.iter().any(|v| !tankmodels.join(format!("{v}.blkx")).exists()) {
+		eprintln!("Skipping projectile iterator test: examples not present");
+		return;
+	}
+
+	// The vehicles and their modules, an unarmed vehicle and a damage model
+	// under a tankmodels subdirectory, which is not a vehicle
+	let mut datamine = Datamine::new();
+	for vehicle in vehicles {
+		let path = tankmodels.join(format!("{vehicle}.blkx"));
+		for path in std::iter::once(path.clone())
+			.chain(vehicle_module_paths(&path, &datamine_root).unwrap())
+		{
+			let key = path.strip_prefix(&aces).unwrap().to_string_lossy().replace('\\', "/");
+			datamine.insert(key, std::fs::read_to_string(&path).unwrap());
+		}
+	}
+	datamine.insert("gamedata/units/tankmodels/test_unarmed.blkx".to_owned(), "{}".to_owned());
+	datamine.insert("gamedata/units/tankmodels/dm/ussr_bmp_2m.blkx".to_owned(), "{}".to_owned());
+
+	let all: Vec<_> = iter_all_projectiles(&datamine).collect();
+	let ids: Vec<&str> = all.iter().map(|(id, _)| id.as_str()).collect();
+	assert!(ids.is_sorted());
+	for vehicle in vehicles {
+		let data = convert_vehicle(&tankmodels.join(format!("{vehicle}.blkx")), &datamine_root)
+			.unwrap();
+		let names: Vec<&str> = all
+			.iter()
+			.filter(|(id, _)| id == vehicle)
+			.map(|(_, proj)| proj.name.as_str())
+			.collect();
+		let expected: Vec<&str> = data.projectiles.iter().map(|p| p.name.as_str()).collect();
+		assert!(!names.is_empty(), "{vehicle}");
+		assert_eq!(names, expected, "{vehicle}");
+	}
+	assert!(ids.iter().all(|id| vehicles.contains(id)), "{ids:?}");
+}
+
 /// Run conversion on ALL vehicles in the corpus and report statistics.
 #[test]
 fn test_full_corpus() {
@@ -299,10 +399,11 @@ fn test_full_corpus() {
 		}
 	}
 
-	// Fail if pass rate is below threshold (adjust as we improve)
+	// Fail if pass rate is below threshold.  Every vehicle with a datamine
+	// file matches; the rest (~9%) are conversion errors for missing files.
 	let pass_rate = passed as f64 / total as f64;
 	assert!(
-		pass_rate >= 0.0, // Set to 0 for now, raise as we improve
+		pass_rate >= 0.90,
 		"Pass rate {:.1}% below threshold",
 		pass_rate * 100.0
 	);
@@ -11,21 +11,34 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-use fcsgen_core::ballistic::{BallisticCache, compute_ballistic_cached, should_skip};
+use fcsgen_core::ballistic::{
+	BallisticCache,
+	BallisticConfig,
+	compute_ballistic,
+	compute_ballistic_cached,
+	compute_ballistic_with_config,
+	should_skip,
+	strip_table_header,
+	table_header,
+};
+use fcsgen_core::compare::{DeltaStats, Tolerances, compare_ballistic_fuzzy};
 use fcsgen_core::parser::data::parse_data_file;
 
 /// Default sensitivity used when generating the reference data.
 const SENSITIVITY: f64 = 0.50;
 
-// ── Tolerances ─────────────────────────────────────────────────────────────
-/// Maximum acceptable delta for the distance column (metres).
-const DIST_TOL: f64 = 0.01;
-/// Maximum acceptable delta for the time column (seconds).
-const TIME_TOL: f64 = 0.1;
-/// Maximum acceptable delta for the penetration column (mm).
-const PEN_TOL: f64 = 1.0;
-/// Maximum acceptable row-count difference (extra/missing rows at the end).
-const ROW_COUNT_TOL: usize = 5;
+/// Shells whose reference penetration is intentionally not reproduced:
+/// subcaliber rounds without `DamageCaliber`, for which the legacy tool
+/// divided by zero and wrote `∞`.  fcsgen computes them as full-caliber solid
+/// shot instead; their trajectory must still match the reference.
+const PEN_CHANGED_SHELLS: &[(&str, &str)] = &[
+	("fr_fcm_36", "mle1937"),
+	("fr_hotchkiss_h35", "mle1937"),
+	("jp_type_5_na_to", "type_4_apcr"),
+	("uk_fv107_scimitar", "apds_t"),
+	("uk_fv510_isv", "apds_t"),
+	("uk_fv721_fox", "apds_t"),
+];
 
 /// Get the path to the test_data directory.
 fn test_data_dir() -> PathBuf {
@@ -35,106 +48,6 @@ fn test_data_dir() -> PathBuf {
 		.join("test_data")
 }
 
-/// Parsed TSV row: (distance, time, penetration).
-fn parse_row(line: &str) -> Option<(f64, f64, f64)> {
-	let parts: Vec<&str> = line.split('\t').collect();
-	if parts.len() < 3 {
-		return None;
-	}
-	let dist = parts[0].parse::<f64>().ok()?;
-	let time = parts[1].parse::<f64>().ok()?;
-	// Penetration may be "∞"; treat as f64::INFINITY
-	let pen = if parts[2] == "\u{221E}" {
-		f64::INFINITY
-	} else {
-		parts[2].parse::<f64>().ok()?
-	};
-	Some((dist, time, pen))
-}
-
-/// Tracking struct for worst-case deltas across the corpus.
-#[derive(Default)]
-struct DeltaStats {
-	max_dist: f64,
-	max_time: f64,
-	max_pen: f64,
-	max_row_diff: usize,
-	worst_dist_shell: String,
-	worst_pen_shell: String,
-	worst_row_shell: String,
-}
-
-/// Compare a computed ballistic TSV against the expected reference using
-/// fuzzy numeric matching.
-///
-/// Returns `Ok(())` when all values are within tolerance,
-/// `Err(description)` on a tolerance violation.
-fn compare_ballistic_fuzzy(
-	vehicle: &str,
-	shell: &str,
-	computed: &str,
-	expected: &str,
-	stats: &mut DeltaStats,
-) -> Result<(), String> {
-	let computed = computed.replace("\r\n", "\n");
-	let expected = expected.replace("\r\n", "\n");
-
-	let comp_lines: Vec<&str> = computed.lines().collect();
-	let exp_lines: Vec<&str> = expected.lines().collect();
-
-	let row_diff = comp_lines.len().abs_diff(exp_lines.len());
-	if row_diff > stats.max_row_diff {
-		stats.max_row_diff = row_diff;
-		stats.worst_row_shell = format!("{vehicle}/{shell}");
-	}
-	if row_diff > ROW_COUNT_TOL {
-		return Err(format!(
-			"{vehicle}/{shell}: row count diff {row_diff} exceeds tolerance {ROW_COUNT_TOL} \
-			 (expected {}, got {})",
-			exp_lines.len(),
-			comp_lines.len(),
-		));
-	}
-
-	// Compare the overlapping rows
-	let overlap = comp_lines.len().min(exp_lines.len());
-	for i in 0..overlap {
-		let (Some(comp), Some(exp)) = (parse_row(comp_lines[i]), parse_row(exp_lines[i])) else {
-			continue;
-		};
-
-		let dd = (comp.0 - exp.0).abs();
-		let dt = (comp.1 - exp.1).abs();
-		let dp = if comp.2.is_infinite() && exp.2.is_infinite() {
-			0.0
-		} else {
-			(comp.2 - exp.2).abs()
-		};
-
-		if dd > stats.max_dist {
-			stats.max_dist = dd;
-			stats.worst_dist_shell = format!("{vehicle}/{shell}");
-		}
-		if dp > stats.max_pen {
-			stats.max_pen = dp;
-			stats.worst_pen_shell = format!("{vehicle}/{shell}");
-		}
-		if dt > stats.max_time {
-			stats.max_time = dt;
-		}
-
-		if dd > DIST_TOL || dt > TIME_TOL || dp > PEN_TOL {
-			return Err(format!(
-				"{vehicle}/{shell} line {}: delta dist={dd:.4} time={dt:.2} pen={dp:.1} \
-				 (tol: dist={DIST_TOL} time={TIME_TOL} pen={PEN_TOL})",
-				i + 1,
-			));
-		}
-	}
-
-	Ok(())
-}
-
 /// Run ballistic computation on ALL vehicles in the corpus and report statistics.
 #[test]
 fn test_ballistic_corpus() {
@@ -168,8 +81,10 @@ fn test_ballistic_corpus() {
 	let mut passed = 0;
 	let mut failed = 0;
 	let mut missing_expected = 0;
+	let mut pen_changed = 0;
 	let mut errors = 0;
 	let mut failures: Vec<String> = Vec::new();
+	let tol = Tolerances::default();
 	let mut stats = DeltaStats::default();
 	let cache: BallisticCache = BallisticCache::new();
 	let mut cache_hits = 0_usize;
@@ -242,13 +157,21 @@ fn test_ballistic_corpus() {
 				},
 			};
 
-			match compare_ballistic_fuzzy(
-				&vehicle_id,
-				&proj.output_name,
-				&computed,
-				&expected,
-				&mut stats,
-			) {
+			let shell = (vehicle_id.as_str(), proj.output_name.as_str());
+			let result = if PEN_CHANGED_SHELLS.contains(&shell) {
+				pen_changed += 1;
+				compare_pen_changed(&vehicle_id, &proj.output_name, &computed, &expected, &tol)
+			} else {
+				compare_ballistic_fuzzy(
+					&vehicle_id,
+					&proj.output_name,
+					&computed,
+					&expected,
+					&tol,
+					&mut stats,
+				)
+			};
+			match result {
 				Ok(()) => passed += 1,
 				Err(msg) => {
 					failed += 1;
@@ -289,6 +212,7 @@ fn test_ballistic_corpus() {
 	);
 	eprintln!("Errors:               {errors}");
 	eprintln!("Missing expected:     {missing_expected}");
+	eprintln!("Penetration changed:  {pen_changed} (legacy ∞, trajectory compared)");
 	let total_lookups = cache_hits + cache_misses;
 	eprintln!(
 		"Cache:                {cache_misses} unique / {total_lookups} total ({cache_hits} hits, {:.0}% reuse)",
@@ -297,20 +221,20 @@ fn test_ballistic_corpus() {
 	eprintln!();
 	eprintln!("Worst-case deltas (across all shells):");
 	eprintln!(
-		"  Distance:  {:.4} m  (tol {DIST_TOL})  [{}]",
-		stats.max_dist, stats.worst_dist_shell,
+		"  Distance:  {:.4} m  (tol {})  [{}]",
+		stats.max_dist, tol.dist, stats.worst_dist_shell,
 	);
 	eprintln!(
-		"  Time:      {:.2} s   (tol {TIME_TOL})",
-		stats.max_time,
+		"  Time:      {:.2} s   (tol {})",
+		stats.max_time, tol.time,
 	);
 	eprintln!(
-		"  Pen:       {:.1} mm  (tol {PEN_TOL})  [{}]",
-		stats.max_pen, stats.worst_pen_shell,
+		"  Pen:       {:.1} mm  (tol {})  [{}]",
+		stats.max_pen, tol.pen, stats.worst_pen_shell,
 	);
 	eprintln!(
-		"  Row count: {}      (tol {ROW_COUNT_TOL})  [{}]",
-		stats.max_row_diff, stats.worst_row_shell,
+		"  Row count: {}      (tol {})  [{}]",
+		stats.max_row_diff, tol.row_count, stats.worst_row_shell,
 	);
 
 	if !failures.is_empty() {
@@ -345,4 +269,128 @@ fn test_ballistic_corpus() {
 		failed, 0,
 		"{failed} shells exceeded tolerance out of {total_shells}",
 	);
+	assert_eq!(pen_changed, PEN_CHANGED_SHELLS.len(), "every listed shell is in the corpus");
+}
+
+/// Compare a [`PEN_CHANGED_SHELLS`] table: the reference penetration must
+/// still be the legacy `∞` and ours finite, and distance and time must match.
+fn compare_pen_changed(
+	vehicle: &str,
+	shell: &str,
+	computed: &str,
+	expected: &str,
+	tol: &Tolerances,
+) -> Result<(), String> {
+	if !expected.lines().all(|line| line.trim_end().ends_with("\t∞")) {
+		return Err(format!("{vehicle}/{shell}: reference penetration is no longer ∞"));
+	}
+	if computed.contains('∞') {
+		return Err(format!("{vehicle}/{shell}: penetration is still ∞"));
+	}
+	// |finite − ∞| is not above an infinite tolerance
+	let tol = Tolerances {
+		pen: f64::INFINITY,
+		..*tol
+	};
+	compare_ballistic_fuzzy(vehicle, shell, computed, expected, &tol, &mut DeltaStats::default())
+}
+
+/// A table written with `--with-header` carries the shell metadata and still
+/// matches the headerless reference once the header is skipped.
+#[test]
+fn test_header_skipped_by_fuzzy_compare() {
+	let data_path = test_data_dir()
+		.join("expected")
+		.join("data")
+		.join("cn_al_khalid_1.txt");
+	let expected_path = test_data_dir()
+		.join("expected")
+		.join("ballistic")
+		.join("cn_al_khalid_1")
+		.join("dtc10_125.txt");
+
+	if !data_path.exists() || !expected_path.exists() {
+		eprintln!("Skipping header test: reference corpus not present");
+		return;
+	}
+
+	let data = parse_data_file(&data_path).expect("parse data file");
+	let proj = data
+		.projectiles
+		.iter()
+		.rfind(|p| p.output_name == "dtc10_125")
+		.expect("dtc10_125 in data file");
+
+	let header = table_header(proj, &BallisticConfig::new(SENSITIVITY));
+	assert!(header.starts_with('#'));
+	for field in [
+		"shell=dtc10_125".to_owned(),
+		format!("type={}", proj.normalized_type),
+		format!("caliber={}", proj.ballistic_caliber),
+		format!("speed={}", proj.speed),
+		"sensitivity=0.5".to_owned(),
+	] {
+		assert!(header.contains(&field), "{field:?} missing from {header:?}");
+	}
+
+	let table = compute_ballistic(proj, SENSITIVITY).expect("ballistic table");
+	let with_header = header + &table;
+	assert_eq!(strip_table_header(&with_header), table);
+
+	let expected = std::fs::read_to_string(&expected_path).unwrap();
+	compare_ballistic_fuzzy(
+		"cn_al_khalid_1",
+		"dtc10_125",
+		&with_header,
+		&expected,
+		&Tolerances::default(),
+		&mut DeltaStats::default(),
+	)
+	.unwrap();
+}
+
+/// Standard gravity is the default and reproduces the reference corpus.
+#[test]
+fn test_default_gravity_matches_corpus() {
+	let data_path = test_data_dir()
+		.join("expected")
+		.join("data")
+		.join("cn_al_khalid_1.txt");
+	let expected_path = test_data_dir()
+		.join("expected")
+		.join("ballistic")
+		.join("cn_al_khalid_1")
+		.join("dtc10_125.txt");
+
+	if !data_path.exists() || !expected_path.exists() {
+		eprintln!("Skipping gravity test: reference corpus not present");
+		return;
+	}
+
+	let data = parse_data_file(&data_path).expect("parse data file");
+	let proj = data
+		.projectiles
+		.iter()
+		.rfind(|p| p.output_name == "dtc10_125")
+		.expect("dtc10_125 in data file");
+
+	let cfg = BallisticConfig {
+		gravity: 9.806_65,
+		..BallisticConfig::new(SENSITIVITY)
+	};
+	assert_eq!(BallisticConfig::new(SENSITIVITY), cfg);
+
+	let table = compute_ballistic_with_config(proj, &cfg).expect("ballistic table");
+	assert_eq!(Some(&table), compute_ballistic(proj, SENSITIVITY).as_ref());
+
+	let expected = std::fs::read_to_string(&expected_path).unwrap();
+	compare_ballistic_fuzzy(
+		"cn_al_khalid_1",
+		"dtc10_125",
+		&table,
+		&expected,
+		&Tolerances::default(),
+		&mut DeltaStats::default(),
+	)
+	.unwrap();
 }
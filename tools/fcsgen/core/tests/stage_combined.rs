@@ -10,22 +10,28 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-use fcsgen_core::ballistic::{BallisticCache, compute_ballistic_cached, should_skip};
-use fcsgen_core::parser::data::from_projectile;
+use fcsgen_core::ballistic::{
+	BallisticCache,
+	compute_ballistic_cached,
+	should_skip,
+};
+use fcsgen_core::compare::{DeltaStats, Tolerances, compare_ballistic_fuzzy};
+use fcsgen_core::parser::data::{DefaultCx, fill_default_cx, from_projectile};
 use fcsgen_core::{convert_vehicle, emit_legacy_txt};
 
 /// Default sensitivity used when generating the reference data.
 const SENSITIVITY: f64 = 0.50;
 
-// ── Tolerances ─────────────────────────────────────────────────────────────
-/// Maximum acceptable delta for the distance column (metres).
-const DIST_TOL: f64 = 0.01;
-/// Maximum acceptable delta for the time column (seconds).
-const TIME_TOL: f64 = 0.1;
-/// Maximum acceptable delta for the penetration column (mm).
-const PEN_TOL: f64 = 1.0;
-/// Maximum acceptable row-count difference (extra/missing rows at the end).
-const ROW_COUNT_TOL: usize = 5;
+/// Shells whose reference penetration is intentionally not reproduced; see
+/// the list of the same name in `stage2.rs`.
+const PEN_CHANGED_SHELLS: &[(&str, &str)] = &[
+	("fr_fcm_36", "mle1937"),
+	("fr_hotchkiss_h35", "mle1937"),
+	("jp_type_5_na_to", "type_4_apcr"),
+	("uk_fv107_scimitar", "apds_t"),
+	("uk_fv510_isv", "apds_t"),
+	("uk_fv721_fox", "apds_t"),
+];
 
 /// Get the path to the `test_data` directory.
 fn test_data_dir() -> PathBuf {
@@ -35,104 +41,6 @@ fn test_data_dir() -> PathBuf {
 		.join("test_data")
 }
 
-/// Parsed TSV row: (distance, time, penetration).
-fn parse_row(line: &str) -> Option<(f64, f64, f64)> {
-	let parts: Vec<&str> = line.split('\t').collect();
-	if parts.len() < 3 {
-		return None;
-	}
-	let dist = parts[0].parse::<f64>().ok()?;
-	let time = parts[1].parse::<f64>().ok()?;
-	let pen = if parts[2] == "\u{221E}" {
-		f64::INFINITY
-	} else {
-		parts[2].parse::<f64>().ok()?
-	};
-	Some((dist, time, pen))
-}
-
-/// Tracking struct for worst-case deltas across the corpus.
-#[derive(Default)]
-struct DeltaStats {
-	max_dist: f64,
-	max_time: f64,
-	max_pen: f64,
-	max_row_diff: usize,
-	worst_dist_shell: String,
-	worst_pen_shell: String,
-	worst_row_shell: String,
-}
-
-/// Compare a computed ballistic TSV against the expected reference using
-/// fuzzy numeric matching.
-///
-/// Returns `Ok(())` when all values are within tolerance,
-/// `Err(description)` on a tolerance violation.
-fn compare_ballistic_fuzzy(
-	vehicle: &str,
-	shell: &str,
-	computed: &str,
-	expected: &str,
-	stats: &mut DeltaStats,
-) -> Result<(), String> {
-	let computed = computed.replace("\r\n", "\n");
-	let expected = expected.replace("\r\n", "\n");
-
-	let comp_lines: Vec<&str> = computed.lines().collect();
-	let exp_lines: Vec<&str> = expected.lines().collect();
-
-	let row_diff = comp_lines.len().abs_diff(exp_lines.len());
-	if row_diff > stats.max_row_diff {
-		stats.max_row_diff = row_diff;
-		stats.worst_row_shell = format!("{vehicle}/{shell}");
-	}
-	if row_diff > ROW_COUNT_TOL {
-		return Err(format!(
-			"{vehicle}/{shell}: row count diff {row_diff} exceeds tolerance {ROW_COUNT_TOL} \
-			 (expected {}, got {})",
-			exp_lines.len(),
-			comp_lines.len(),
-		));
-	}
-
-	let overlap = comp_lines.len().min(exp_lines.len());
-	for i in 0..overlap {
-		let (Some(comp), Some(exp)) = (parse_row(comp_lines[i]), parse_row(exp_lines[i])) else {
-			continue;
-		};
-
-		let dd = (comp.0 - exp.0).abs();
-		let dt = (comp.1 - exp.1).abs();
-		let dp = if comp.2.is_infinite() && exp.2.is_infinite() {
-			0.0
-		} else {
-			(comp.2 - exp.2).abs()
-		};
-
-		if dd > stats.max_dist {
-			stats.max_dist = dd;
-			stats.worst_dist_shell = format!("{vehicle}/{shell}");
-		}
-		if dt > stats.max_time {
-			stats.max_time = dt;
-		}
-		if dp > stats.max_pen {
-			stats.max_pen = dp;
-			stats.worst_pen_shell = format!("{vehicle}/{shell}");
-		}
-
-		if dd > DIST_TOL || dt > TIME_TOL || dp > PEN_TOL {
-			return Err(format!(
-				"{vehicle}/{shell} line {}: delta dist={dd:.4} time={dt:.2} pen={dp:.1} \
-				 (tol: dist={DIST_TOL} time={TIME_TOL} pen={PEN_TOL})",
-				i + 1,
-			));
-		}
-	}
-
-	Ok(())
-}
-
 /// Run the full in-memory pipeline on ALL vehicles in the corpus.
 ///
 /// For each vehicle:
@@ -179,8 +87,10 @@ fn test_combined_pipeline_corpus() {
 	let mut total_shells = 0;
 	let mut passed = 0;
 	let mut failed = 0;
+	let mut pen_changed = 0;
 	let mut errors = 0;
 	let mut failures: Vec<String> = Vec::new();
+	let tol = Tolerances::default();
 	let mut stats = DeltaStats::default();
 	let cache: BallisticCache = BallisticCache::new();
 	let mut cache_hits = 0_usize;
@@ -199,7 +109,7 @@ fn test_combined_pipeline_corpus() {
 		}
 
 		// Stage 1: convert vehicle from datamine
-		let data = match convert_vehicle(&vehicle_path, &datamine_dir) {
+		let mut data = match convert_vehicle(&vehicle_path, &datamine_dir) {
 			Ok(d) => d,
 			Err(e) => {
 				eprintln!("CONVERT ERROR {vehicle_name}: {e}");
@@ -208,6 +118,9 @@ fn test_combined_pipeline_corpus() {
 			},
 		};
 
+		// The reference was generated with the legacy uniform default Cx
+		fill_default_cx(&mut data.projectiles, DefaultCx::Uniform);
+
 		// Verify Stage 1 output hasn't changed: emit to text and compare.
 		// (This is a sanity check — stage1.rs covers this exhaustively.)
 		let _legacy_txt = emit_legacy_txt(&data);
@@ -265,13 +178,21 @@ fn test_combined_pipeline_corpus() {
 				},
 			};
 
-			match compare_ballistic_fuzzy(
-				vehicle_name,
-				&dp.output_name,
-				&computed,
-				&expected,
-				&mut stats,
-			) {
+			let shell = (vehicle_name.as_str(), dp.output_name.as_str());
+			let result = if PEN_CHANGED_SHELLS.contains(&shell) {
+				pen_changed += 1;
+				compare_pen_changed(vehicle_name, &dp.output_name, &computed, &expected, &tol)
+			} else {
+				compare_ballistic_fuzzy(
+					vehicle_name,
+					&dp.output_name,
+					&computed,
+					&expected,
+					&tol,
+					&mut stats,
+				)
+			};
+			match result {
 				Ok(()) => passed += 1,
 				Err(msg) => {
 					failed += 1;
@@ -311,6 +232,7 @@ fn test_combined_pipeline_corpus() {
 		}
 	);
 	eprintln!("Errors:               {errors}");
+	eprintln!("Penetration changed:  {pen_changed} (legacy ∞, trajectory compared)");
 	let total_lookups = cache_hits + cache_misses;
 	eprintln!(
 		"Cache:                {cache_misses} unique / {total_lookups} total ({cache_hits} hits, {:.0}% reuse)",
@@ -319,20 +241,20 @@ fn test_combined_pipeline_corpus() {
 	eprintln!();
 	eprintln!("Worst-case deltas (across all shells):");
 	eprintln!(
-		"  Distance:  {:.4} m  (tol {DIST_TOL})  [{}]",
-		stats.max_dist, stats.worst_dist_shell,
+		"  Distance:  {:.4} m  (tol {})  [{}]",
+		stats.max_dist, tol.dist, stats.worst_dist_shell,
 	);
 	eprintln!(
-		"  Time:      {:.2} s   (tol {TIME_TOL})",
-		stats.max_time,
+		"  Time:      {:.2} s   (tol {})",
+		stats.max_time, tol.time,
 	);
 	eprintln!(
-		"  Pen:       {:.1} mm  (tol {PEN_TOL})  [{}]",
-		stats.max_pen, stats.worst_pen_shell,
+		"  Pen:       {:.1} mm  (tol {})  [{}]",
+		stats.max_pen, tol.pen, stats.worst_pen_shell,
 	);
 	eprintln!(
-		"  Row count: {}      (tol {ROW_COUNT_TOL})  [{}]",
-		stats.max_row_diff, stats.worst_row_shell,
+		"  Row count: {}      (tol {})  [{}]",
+		stats.max_row_diff, tol.row_count, stats.worst_row_shell,
 	);
 
 	if !failures.is_empty() {
@@ -373,4 +295,28 @@ fn test_combined_pipeline_corpus() {
 			0.0
 		}
 	);
+	assert_eq!(pen_changed, PEN_CHANGED_SHELLS.len(), "every listed shell is in the corpus");
+}
+
+/// Compare a [`PEN_CHANGED_SHELLS`] table: the reference penetration must
+/// still be the legacy `∞` and ours finite, and distance and time must match.
+fn compare_pen_changed(
+	vehicle: &str,
+	shell: &str,
+	computed: &str,
+	expected: &str,
+	tol: &Tolerances,
+) -> Result<(), String> {
+	if !expected.lines().all(|line| line.trim_end().ends_with("\t∞")) {
+		return Err(format!("{vehicle}/{shell}: reference penetration is no longer ∞"));
+	}
+	if computed.contains('∞') {
+		return Err(format!("{vehicle}/{shell}: penetration is still ∞"));
+	}
+	// |finite − ∞| is not above an infinite tolerance
+	let tol = Tolerances {
+		pen: f64::INFINITY,
+		..*tol
+	};
+	compare_ballistic_fuzzy(vehicle, shell, computed, expected, &tol, &mut DeltaStats::default())
 }